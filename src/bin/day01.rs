@@ -1,84 +1,41 @@
-#[derive(Default, Debug, Clone, Copy)]
-struct Elf {
-    pub index: usize,
-    pub count: u32,
+use advent_of_code_2022::{
+    cli::CommonOpts,
+    day01::{top_elves, Day01, Options, PART1_DATA, SAMPLE},
+    solution,
+};
+use anyhow::Error;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "day01", about = "Calorie Counting")]
+struct Opt {
+    #[structopt(flatten)]
+    common: CommonOpts,
+
+    /// List the elves carrying the most calories, this many of them,
+    /// instead of solving both parts
+    #[structopt(long)]
+    top: Option<usize>,
 }
 
-type ElfList = Vec<Elf>;
+fn main() -> Result<(), Error> {
+    let opt = Opt::from_args();
+    opt.common.init();
 
-fn parse_input(value: &str) -> Vec<u32> {
-    value
-        .lines()
-        .map(|s| s.parse::<u32>().unwrap_or_default())
-        .collect()
-}
-
-fn make_elves(input_data: &str) -> ElfList {
-    let values: Vec<_> = parse_input(input_data);
-
-    let acc = vec![Vec::new()];
-    let value_lists: Vec<Vec<u32>> = values.into_iter().fold(acc, |mut acc, x| {
-        if x == 0 {
-            acc.push(Vec::new());
-        } else {
-            acc.last_mut().unwrap().push(x);
+    let input = opt.common.input(SAMPLE, PART1_DATA)?;
+    if let Some(k) = opt.top {
+        let elves = top_elves(&input, k)?;
+        for elf in &elves {
+            println!("elf {}: {}", elf.index.0, elf.count);
         }
-        acc
-    });
-    let mut counts: Vec<_> = value_lists
-        .into_iter()
-        .enumerate()
-        .map(|(index, list)| Elf {
-            index: index + 1,
-            count: list.into_iter().sum::<u32>(),
-        })
-        .collect();
-    counts.sort_by(|a, b| b.count.cmp(&a.count));
-    counts
-}
-
-const PART1_DATA: &str = include_str!("../../data/day01.txt");
-
-fn main() {
-    let elves = make_elves(PART1_DATA);
-    println!("best elf = {} cal {}", elves[0].index, elves[0].count);
-
-    let top_3: u32 = elves[0..3].iter().map(|e| e.count).sum();
-    println!("top 3 = {top_3}");
-}
-
-#[cfg(test)]
-mod test {
-    use super::*;
-
-    const SAMPLE: &str = r#"1000
-2000
-3000
-
-4000
-
-5000
-6000
-
-7000
-8000
-9000
-
-10000"#;
-
-    #[test]
-    fn test_parse() {
-        dbg!(SAMPLE);
-        let values: Vec<_> = parse_input(SAMPLE);
-        assert_eq!(values.len(), 14);
-        dbg!(&values);
-        assert_eq!(values[0], 1000);
-        assert_eq!(values[13], 10000);
+        println!(
+            "top {k} total = {}",
+            elves.iter().map(|e| e.count).sum::<u64>()
+        );
+        return Ok(());
     }
 
-    #[test]
-    fn test_sum() {
-        let elves = make_elves(SAMPLE);
-        assert_eq!(elves[0].index, 4);
-    }
+    solution::parse_and_run(&opt.common, || Day01::new(&input, &Options))?;
+
+    Ok(())
 }
@@ -1,71 +1,48 @@
-use std::collections::{HashSet, VecDeque};
-
-const DATA: &str = include_str!("../../data/day06.txt");
-
-#[derive(Debug, Default)]
-struct Scanner<const N: usize> {
-    buffer: VecDeque<char>,
-    received: usize,
+use advent_of_code_2022::{
+    cli::CommonOpts,
+    day06::{Day06, Options, Scanner, DATA, SAMPLE},
+    solution,
+};
+use anyhow::{bail, Context, Error};
+use std::fs::File;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "day06", about = "Tuning Trouble")]
+struct Opt {
+    #[structopt(flatten)]
+    common: CommonOpts,
+
+    /// Find the first marker this many bytes long instead of solving both
+    /// parts. An --input file is streamed rather than read in whole
+    #[structopt(long)]
+    window: Option<usize>,
 }
 
-impl<const N: usize> Scanner<N> {
-    pub fn received(&mut self, c: char) {
-        if self.buffer.len() >= N {
-            self.buffer.pop_front();
-        }
-        self.buffer.push_back(c);
-        self.received += 1;
-    }
+fn main() -> Result<(), Error> {
+    let opt = Opt::from_args();
+    opt.common.init();
 
-    pub fn unique_count(&self) -> usize {
-        let set: HashSet<char> = self.buffer.iter().copied().collect();
-        set.len()
-    }
-
-    pub fn received_count(&self) -> usize {
-        self.received
-    }
-
-    pub fn run_scanner(data: &str) -> Option<usize> {
-        let mut scanner = Scanner::<N>::default();
-        for c in data.chars() {
-            scanner.received(c);
-            if scanner.unique_count() == N {
-                return Some(scanner.received_count());
-            }
+    if let Some(window) = opt.window {
+        if window == 0 {
+            bail!("--window has to be at least 1");
         }
-        None
-    }
-}
-
-fn main() {
-    let received_count = Scanner::<4>::run_scanner(DATA);
-    println!("characters processed = {received_count:?}");
-
-    let received_count = Scanner::<14>::run_scanner(DATA);
-    println!("characters processed = {received_count:?}");
-}
-
-#[cfg(test)]
-mod test {
-    use super::*;
-
-    fn test_scanner_for_data<const N: usize>(expected: usize, data: &str) {
-        let received_count = Scanner::<N>::run_scanner(data);
-        assert_eq!(received_count, Some(expected));
+        let marker = match &opt.common.input {
+            Some(path) => Scanner::scan_reader(
+                File::open(path).with_context(|| format!("opening {path:?}"))?,
+                window,
+            )?,
+            None => Scanner::run_scanner(&opt.common.input(SAMPLE, DATA)?, window),
+        };
+        match marker {
+            Some(received) => println!("marker of {window} after {received}"),
+            None => bail!("no marker of {window}"),
+        }
+        return Ok(());
     }
 
-    #[test]
-    fn test_scanner() {
-        test_scanner_for_data::<4>(7, "mjqjpqmgbljsphdztnvjfqwrcgsmlb");
-        test_scanner_for_data::<4>(5, "bvwbjplbgvbhsrlpgdmjqwftvncz");
-        test_scanner_for_data::<4>(10, "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg");
-        test_scanner_for_data::<4>(11, "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw");
+    let input = opt.common.input(SAMPLE, DATA)?;
+    solution::parse_and_run(&opt.common, || Day06::new(&input, &Options))?;
 
-        test_scanner_for_data::<14>(19, "mjqjpqmgbljsphdztnvjfqwrcgsmlb");
-        test_scanner_for_data::<14>(23, "bvwbjplbgvbhsrlpgdmjqwftvncz");
-        test_scanner_for_data::<14>(23, "nppdvjthqldpwncqszvftbrmjlhg");
-        test_scanner_for_data::<14>(29, "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg");
-        test_scanner_for_data::<14>(26, "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw");
-    }
+    Ok(())
 }
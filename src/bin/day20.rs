@@ -1,82 +1,60 @@
+use advent_of_code_2022::{
+    cli::CommonOpts,
+    day20::{decrypt, Day20, Options, DATA, SAMPLE},
+    solution,
+};
 use anyhow::Error;
 use structopt::StructOpt;
 
-const DATA: &str = include_str!("../../data/day20.txt");
-const SAMPLE: &str = r#"1
-2
--3
-3
--2
-0
-4"#;
-
 #[derive(Debug, StructOpt)]
 #[structopt(name = "day20", about = "Grove Positioning System")]
 struct Opt {
-    /// Use puzzle input instead of the sample
-    #[structopt(short, long)]
-    puzzle_input: bool,
-}
-
-type Record = (usize, isize);
-type List = Vec<Record>;
-
-fn parse(s: &str, key: usize) -> Vec<Record> {
-    s.lines()
-        .map(|s| s.parse::<isize>().unwrap() * key as isize)
-        .enumerate()
-        .collect()
-}
-
-fn solve(mut data_list: List, count: usize) -> isize {
-    let data_len = data_list.len() as isize;
-
-    for _ in 0..count {
-        for original_index in 0..data_list.len() {
-            let index = data_list
-                .iter()
-                .position(|x| x.0 == original_index)
-                .unwrap();
-            let value = data_list[index].1;
-            let new_index = index as isize + value;
-            let new_index = new_index.rem_euclid(data_list.len() as isize - 1);
-            let tmp = data_list.remove(index);
-            data_list.insert(new_index as usize, tmp);
-        }
-    }
-
-    let tests = [1000, 2000, 3000];
-
-    let zero_position = data_list
-        .iter()
-        .copied()
-        .position(|val| val.1 == 0)
-        .expect("position");
-
-    let mut sum = 0;
-    for t in tests {
-        let i = (zero_position + t) % (data_len as usize);
-        let v = data_list[i];
-        sum += v.1;
-    }
-    sum
+    #[structopt(flatten)]
+    common: CommonOpts,
+
+    /// Decryption key for part 2
+    #[structopt(long, default_value = "811589153")]
+    key: isize,
+
+    /// Rounds of mixing for part 2
+    #[structopt(long, default_value = "10")]
+    rounds: usize,
+
+    /// Places after the zero whose numbers are added up, separated by commas
+    #[structopt(
+        long,
+        require_delimiter = true,
+        allow_hyphen_values = true,
+        default_value = "1000,2000,3000"
+    )]
+    offsets: Vec<isize>,
+
+    /// Instead of solving, decrypt with each of these keys, mixing --rounds
+    /// times, and print the number at each offset and their sum
+    #[structopt(long, require_delimiter = true, allow_hyphen_values = true)]
+    keys: Vec<isize>,
 }
 
 fn main() -> Result<(), Error> {
     let opt = Opt::from_args();
+    opt.common.init();
+
+    let input = opt.common.input(SAMPLE, DATA)?;
+    if !opt.keys.is_empty() {
+        for key in &opt.keys {
+            let coordinates = decrypt(&input, *key, opt.rounds, &opt.offsets)?;
+            let values: Vec<_> = coordinates.values.iter().map(isize::to_string).collect();
+            println!("key {key}: {} = {}", values.join(" + "), coordinates.sum());
+        }
+        return Ok(());
+    }
 
-    let file_contents = parse(if opt.puzzle_input { DATA } else { SAMPLE }, 1);
-
-    let sum = solve(file_contents, 1);
-
-    println!("sum = {sum}");
-
-    let file_contents = parse(if opt.puzzle_input { DATA } else { SAMPLE }, 811589153);
-    let sum = solve(file_contents, 10);
-
-    println!("sum = {sum}");
-
-    // You guessed 8920 too high
+    let options = Options {
+        key: opt.key,
+        rounds: opt.rounds,
+        offsets: opt.offsets,
+    };
+    solution::parse_and_run(&opt.common, || Day20::new(&input, &options))?;
 
     Ok(())
 }
@@ -85,43 +63,20 @@ fn main() -> Result<(), Error> {
 mod test {
     use super::*;
 
-    const _EXPECTED: &[[isize; 7]] = &[
-        // Initial arrangement:
-        [1, 2, -3, 3, -2, 0, 4],
-        // 1 moves between 2 and -3:
-        [2, 1, -3, 3, -2, 0, 4],
-        // 2 moves between -3 and 3:
-        [1, -3, 2, 3, -2, 0, 4],
-        // -3 moves between -2 and 0:
-        [1, 2, 3, -2, -3, 0, 4],
-        // 3 moves between 0 and 4:
-        [1, 2, -2, -3, 0, 3, 4],
-        // -2 moves between 4 and 1:
-        [1, 2, -3, 0, 3, 4, -2],
-        // 0 does not move:
-        [1, 2, -3, 0, 3, 4, -2],
-        // 4 moves between -3 and 0:
-        [1, 2, -3, 4, 0, 3, -2],
-    ];
-
-    #[test]
-    fn test_parse() {
-        let file_contents = parse(SAMPLE, 1);
-        dbg!(&file_contents);
-        assert_eq!(file_contents.len(), 7);
-    }
-
-    #[test]
-    fn test_part_1() {
-        let data = parse(SAMPLE, 1);
-        let sum = solve(data, 1);
-        assert_eq!(sum, 3);
+    fn opt(args: &[&str]) -> Opt {
+        Opt::from_iter_safe(std::iter::once("day20").chain(args.iter().copied())).unwrap()
     }
 
     #[test]
-    fn test_part_2() {
-        let data = parse(SAMPLE, 811589153);
-        let sum = solve(data, 10);
-        assert_eq!(sum, 1623178306);
+    fn test_lists() {
+        let parsed = opt(&["--offsets", "1000", "-p"]);
+        assert_eq!(parsed.offsets, [1000]);
+        assert!(parsed.common.puzzle_input);
+
+        let parsed = opt(&["--keys", "-1,2", "--rounds", "1"]);
+        assert_eq!(parsed.keys, [-1, 2]);
+        assert_eq!(parsed.rounds, 1);
+        assert_eq!(opt(&["--keys", "-5", "-p"]).keys, [-5]);
+        assert_eq!(opt(&[]).offsets, [1000, 2000, 3000]);
     }
 }
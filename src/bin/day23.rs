@@ -1,416 +1,119 @@
-use anyhow::Error;
-use enum_iterator::{cardinality, Sequence};
-use euclid::{point2, size2, vec2};
-use std::{
-    cmp::Ordering,
-    collections::{BTreeSet, HashMap},
+use advent_of_code_2022::{
+    cli::CommonOpts,
+    day23::{parse, solve_part_2, Day23, Options, DATA, SAMPLE},
+    solution, viz,
 };
+use anyhow::{bail, Context, Error};
+use std::{fs, path::PathBuf};
 use structopt::StructOpt;
 
-type Coord = i64;
-type Point = euclid::default::Point2D<Coord>;
-type Box = euclid::default::Box2D<Coord>;
-type Vector = euclid::default::Vector2D<Coord>;
-type Rect = euclid::default::Rect<Coord>;
-
-const DATA: &str = include_str!("../../data/day23.txt");
-const SAMPLE: &str = r#"....#..
-..###.#
-#...#.#
-.#...##
-#.###..
-##.#.##
-.#..#.."#;
-
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Sequence)]
-#[repr(usize)]
-enum Direction {
-    North,
-    South,
-    West,
-    East,
-}
-
-const NORTH_ADJ_V: [Vector; 3] = [vec2(-1, -1), vec2(0, -1), vec2(1, -1)];
-const SOUTH_ADJ_V: [Vector; 3] = [vec2(-1, 1), vec2(0, 1), vec2(1, 1)];
-const WEST_ADJ_V: [Vector; 3] = [vec2(-1, -1), vec2(-1, 0), vec2(-1, 1)];
-const EAST_ADJ_V: [Vector; 3] = [vec2(1, -1), vec2(1, 0), vec2(1, 1)];
-
-impl Direction {
-    fn adjacents(&self, p: Point) -> [Point; 3] {
-        match self {
-            Direction::North => [p + NORTH_ADJ_V[0], p + NORTH_ADJ_V[1], p + NORTH_ADJ_V[2]],
-            Direction::South => [p + SOUTH_ADJ_V[0], p + SOUTH_ADJ_V[1], p + SOUTH_ADJ_V[2]],
-            Direction::West => [p + WEST_ADJ_V[0], p + WEST_ADJ_V[1], p + WEST_ADJ_V[2]],
-            Direction::East => [p + EAST_ADJ_V[0], p + EAST_ADJ_V[1], p + EAST_ADJ_V[2]],
-        }
-    }
+#[derive(Debug, StructOpt)]
+#[structopt(name = "day23", about = "Unstable Diffusion")]
+struct Opt {
+    #[structopt(flatten)]
+    common: CommonOpts,
 
-    fn as_char(&self) -> char {
-        (*self).into()
-    }
-}
+    /// Time each part three ways: checking every elf for neighbors, looking
+    /// them up, and looking them up with --parallel. On the puzzle input the
+    /// check is about 25 times slower than the lookup, taking close to a
+    /// minute for part 2, so --part 1 is quicker
+    #[structopt(long)]
+    bench: bool,
 
-impl From<Direction> for Vector {
-    fn from(val: Direction) -> Self {
-        match val {
-            Direction::North => vec2(0, -1),
-            Direction::East => vec2(1, 0),
-            Direction::South => vec2(0, 1),
-            Direction::West => vec2(-1, 0),
-        }
-    }
-}
+    /// Spread each round's work across threads. On the puzzle input this is
+    /// 20 to 40% slower than the default, since a round has too little work
+    /// to pay for the threads
+    #[structopt(long)]
+    parallel: bool,
 
-impl From<Direction> for char {
-    fn from(val: Direction) -> Self {
-        match val {
-            Direction::North => '^',
-            Direction::East => '>',
-            Direction::South => 'v',
-            Direction::West => '<',
-        }
-    }
-}
+    /// Show the elves spreading out round by round instead of solving
+    #[structopt(long)]
+    animate: bool,
 
-impl From<usize> for Direction {
-    fn from(v: usize) -> Self {
-        match v {
-            0 => Direction::North,
-            1 => Direction::South,
-            2 => Direction::West,
-            3 => Direction::East,
-            _ => panic!("illegal direction"),
-        }
-    }
-}
+    /// Frames per second for --animate
+    #[structopt(long, default_value = "10")]
+    fps: f64,
 
-const DIRECTION_COUNT: usize = cardinality::<Direction>();
+    /// Instead of solving, save a PNG of the elves every this many rounds,
+    /// and after the last, to the --out directory
+    #[structopt(long)]
+    snapshot_every: Option<usize>,
 
-type Proposal = Option<Direction>;
-type ProposalList = Vec<Proposal>;
-type LocationMap = HashMap<Point, usize>;
+    /// Directory for --snapshot-every
+    #[structopt(long, parse(from_os_str))]
+    out: Option<PathBuf>,
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
-struct Elf {
-    position: Point,
+    /// Instead of solving, print how many elves moved in each round until
+    /// none do, then where they ended up
+    #[structopt(long)]
+    stats: bool,
 }
 
-impl Elf {
-    fn propose(&self, world: &World) -> Proposal {
-        let surrounds = Rect::new(self.position - vec2(1, 1), size2(3, 3));
-        if world.elf_in_rect(&self.position, &surrounds) {
-            'direction: for direction_index in world.time..world.time + DIRECTION_COUNT {
-                let direction: Direction = (direction_index % DIRECTION_COUNT).into();
-                for p in direction.adjacents(self.position) {
-                    if world.elf_at(p) {
-                        continue 'direction;
-                    }
-                }
-                return Some(direction);
-            }
-        }
-        None
-    }
-
-    fn apply_proposal(&mut self, proposal: Proposal, locations_map: &LocationMap) {
-        if let Some(direction) = proposal {
-            let delta: Vector = direction.into();
-            let new_position = self.position + delta;
-            if locations_map
-                .get(&new_position)
-                .copied()
-                .unwrap_or_default()
-                <= 1
-            {
-                self.position = new_position;
-            } else {
-                // println!("collision at {new_position:?}");
+fn main() -> Result<(), Error> {
+    let opt = Opt::from_args();
+    opt.common.init();
+
+    let input = opt.common.input(SAMPLE, DATA)?;
+    let mut world = parse(&input);
+    world.parallel = opt.parallel;
+
+    if opt.animate || opt.snapshot_every.is_some() {
+        let snapshots = match (opt.snapshot_every, &opt.out) {
+            (Some(0), _) => bail!("--snapshot-every has to be at least 1"),
+            (Some(every), Some(dir)) => {
+                fs::create_dir_all(dir).with_context(|| format!("creating {dir:?}"))?;
+                Some((every, dir.as_path()))
             }
-        }
-    }
-
-    fn calculate_proposal(&self, proposal: Proposal) -> Point {
-        proposal
-            .map(|direction| {
-                let delta: Vector = direction.into();
-                self.position + delta
-            })
-            .unwrap_or(self.position)
-    }
-}
-
-impl PartialOrd for Elf {
-    fn partial_cmp(&self, o: &Elf) -> Option<Ordering> {
-        Some(self.cmp(o))
-    }
-}
-
-impl Ord for Elf {
-    fn cmp(&self, o: &Elf) -> Ordering {
-        let x_ord = self.position.x.cmp(&o.position.x);
-        match x_ord {
-            Ordering::Equal => self.position.y.cmp(&o.position.y),
-            _ => x_ord,
-        }
-    }
-}
-
-fn direction_list(time: usize) -> String {
-    (time..time + DIRECTION_COUNT)
-        .map(|direction_index| {
-            let direction: Direction = (direction_index % DIRECTION_COUNT).into();
-            let c: char = direction.into();
-            c
-        })
-        .collect::<String>()
-}
-
-#[derive(Debug, PartialEq, Eq, Clone)]
-struct World {
-    elves: Vec<Elf>,
-    time: usize,
-}
-
-impl World {
-    fn elf_at(&self, p: Point) -> bool {
-        let is_elf = self.elves.iter().any(|elf| elf.position == p);
-        // println!("elf_at {p:?} {is_elf}");
-        is_elf
-    }
-
-    fn elf_in_rect(&self, ignore: &Point, r: &Rect) -> bool {
-        self.elves
-            .iter()
-            .any(|elf| elf.position != *ignore && r.contains(elf.position))
-    }
-
-    fn proposals(&self) -> ProposalList {
-        self.elves.iter().map(|e| e.propose(self)).collect()
-    }
-
-    fn apply_proposals(&mut self, proposals: ProposalList) {
-        let new_locations: Vec<Point> = self
-            .elves
-            .iter()
-            .zip(proposals.iter().copied())
-            .map(|(e, p)| e.calculate_proposal(p))
-            .collect();
-        let mut locations_map: LocationMap = HashMap::new();
-        for p in new_locations {
-            let entry = locations_map.entry(p).or_default();
-            *entry += 1;
-        }
-        self.elves
-            .iter_mut()
-            .zip(proposals.iter().copied())
-            .for_each(|(e, p)| e.apply_proposal(p, &locations_map));
-    }
-
-    fn step(&mut self) {
-        self.time += 1;
-    }
-
-    fn empty_spaces(&self) -> usize {
-        let bbox_size = self.bounding_box().size().to_usize();
-        (bbox_size.width + 1) * (bbox_size.height + 1) - self.elves.len()
-    }
-
-    fn render(&self) {
-        let empty_proposals = vec![None; self.elves.len()];
-        self.render_with_proposals(&empty_proposals);
-    }
-
-    fn render_with_proposals(&self, proposals: &ProposalList) {
+            (Some(_), None) => bail!("--snapshot-every needs an --out directory"),
+            (None, _) => None,
+        };
+        let fps = opt.animate.then_some(opt.fps);
+        let rounds = viz::day23::show_rounds(&mut world, fps, snapshots)?;
+        println!("settled after {rounds} rounds");
+        return Ok(());
+    }
+
+    if opt.stats {
+        let rounds = solve_part_2(&mut world, &opt.common.cancel())?;
+        let stats = world.stats();
+        for (round, moved) in (1..).zip(&stats.moves_per_round) {
+            println!("round {round:4}: {moved} moved");
+        }
+        println!("round {rounds:4}: 0 moved");
         println!(
-            "~~~ time = {:2} ~~~ {}",
-            self.time,
-            direction_list(self.time)
+            "{} elves in {:?}..={:?} with {} empty spaces",
+            stats.elves, stats.bounding_box.min, stats.bounding_box.max, stats.empty_spaces
         );
-        render_elves(&self.elves, proposals);
-    }
-
-    fn bounding_box(&self) -> Box {
-        Box::from_points(self.elves.iter().map(|e| e.position))
-    }
-}
-
-fn render_elves(elves: &[Elf], proposals: &ProposalList) {
-    let bbox = Box::from_points(elves.iter().map(|e| e.position));
-    let elf_map: HashMap<_, _> = elves
-        .iter()
-        .zip(proposals.iter())
-        .map(|(e, p)| (e.position, (e, p)))
-        .collect();
-    for y in bbox.min.y - 2..bbox.max.y + 2 {
-        let mut s = format!("{y:04}");
-        for x in bbox.min.x - 2..bbox.max.x + 2 {
-            let elf_at = elf_map.get(&point2(x, y));
-            let c = if let Some((_e, p)) = elf_at {
-                if let Some(d) = p {
-                    d.as_char()
-                } else {
-                    '#'
-                }
-            } else {
-                '.'
-            };
-            s.push(c);
-        }
-        println!("{s}");
-    }
-}
-
-#[derive(Debug, StructOpt)]
-#[structopt(name = "day23", about = "Unstable Diffusion")]
-struct Opt {
-    /// Use puzzle input instead of the sample
-    #[structopt(short, long)]
-    puzzle_input: bool,
-}
-
-fn maybe_elf(x: isize, y: isize, c: char) -> Option<Elf> {
-    (c == '#').then_some(Elf {
-        position: point2(x, y).to_i64(),
-    })
-}
-
-fn handle_line((y, line): (isize, &str), delta_x: isize) -> Vec<Elf> {
-    line.chars()
-        .enumerate()
-        .filter_map(|(x, c)| maybe_elf(x as isize - delta_x, y, c))
-        .collect()
-}
-
-fn parse(s: &str) -> World {
-    let elves: Vec<Elf> = s
-        .lines()
-        .enumerate()
-        .flat_map(|(y, s)| handle_line((y as isize, s), 0))
-        .collect();
-    World { elves, time: 0 }
-}
-
-fn solve_part_1(world: &mut World, expected: Option<&Vec<Vec<Elf>>>, print: bool) -> usize {
-    let empty_proposals = vec![None; world.elves.len()];
-    for i in 0..10 {
-        let time = i + 1;
-        if print {
-            println!("~~~ Before Round {time}");
-            world.render();
-        }
-        let proposals = world.proposals();
-        if print {
-            world.render_with_proposals(&proposals);
-        }
-        if proposals.iter().any(Option::is_some) {
-            world.apply_proposals(proposals);
-        } else {
-            break;
-        }
-        world.step();
-        if print {
-            println!("~~~ After Round {time}");
-            world.render();
-        }
-        if let Some(expected) = expected.as_ref() {
-            if expected.len() > i {
-                let e_set: BTreeSet<_> = expected[i].iter().collect();
-                let w_set: BTreeSet<_> = world.elves.iter().collect();
-                println!("~~~ expected");
-                render_elves(&expected[i], &empty_proposals);
-                itertools::assert_equal(e_set.iter(), w_set.iter());
+        return Ok(());
+    }
+
+    if opt.bench {
+        let ways = [
+            ("scan", true, false),
+            ("lookup", false, false),
+            ("lookup, parallel", false, true),
+        ];
+        for (name, scan, parallel) in ways {
+            let day = Day23::new(&input, &Options { parallel, scan })?;
+            for part in [1, 2].into_iter().filter(|part| opt.common.part(*part)) {
+                let common = CommonOpts {
+                    part: Some(part),
+                    force: true,
+                    ..opt.common.clone()
+                };
+                let report = solution::run(&day, &common)?;
+                println!("{name}: part {part} took {:.1}ms", report.elapsed_ms);
             }
         }
+        return Ok(());
     }
-    world.empty_spaces()
-}
-
-fn solve_part_2(world: &mut World) -> usize {
-    loop {
-        let proposals = world.proposals();
-        if proposals.iter().any(Option::is_some) {
-            world.apply_proposals(proposals);
-        } else {
-            return world.time + 1;
-        }
-        world.step();
-    }
-}
-
-fn main() -> Result<(), Error> {
-    let opt = Opt::from_args();
-
-    let mut world = parse(if opt.puzzle_input { DATA } else { SAMPLE });
-
-    let mut world2 = world.clone();
 
-    let p1 = solve_part_1(&mut world, None, false);
-    println!("part 1 password = {p1}");
-
-    println!("part 2 password = {}", solve_part_2(&mut world2));
+    let options = Options {
+        parallel: opt.parallel,
+        scan: false,
+    };
+    let day = Day23::new(&input, &options)?;
+    solution::run(&day, &opt.common)?;
 
     Ok(())
 }
-
-#[cfg(test)]
-mod test {
-    use super::*;
-
-    const EXPECTED_5: &str = include_str!("../../data/day23_ex.txt");
-    const EXPECTED_10: &str = r#"xxx
-	.......#......
-	...........#..
-	..#.#..#......
-	......#.......
-	...#.....#..#.
-	.#......##....
-	.....##.......
-	..#........#..
-	....#.#..#....
-	..............
-	....#..#..#...
-	..............
-
-	"#;
-
-    fn parse_expected(s: &str) -> Vec<Vec<Elf>> {
-        let mut exp = vec![];
-        for chunk in s.split("\n\n") {
-            let mut elves = vec![];
-            for (y, line) in chunk.lines().enumerate().skip(1) {
-                elves.extend(handle_line((y as isize - 3, line), 3));
-            }
-            exp.push(elves);
-        }
-        exp
-    }
-
-    #[test]
-    fn test_parse() {
-        let world = parse(SAMPLE);
-        assert_eq!(world.time, 0);
-        assert_eq!(world.elves.len(), 22);
-        assert_eq!(world.elves[0].position, point2(4, 0));
-
-        let expected = parse_expected(EXPECTED_5);
-        dbg!(&expected);
-        assert_eq!(expected.len(), 5);
-    }
-
-    #[test]
-    fn test_part_1() {
-        let mut world = parse(SAMPLE);
-        let expected = parse_expected(EXPECTED_5);
-        let empty_spaces = solve_part_1(&mut world, Some(&expected), true);
-        println!("### final expected\n{EXPECTED_10}");
-        assert_eq!(empty_spaces, 110);
-    }
-
-    #[test]
-    fn test_part_2() {
-        let mut world = parse(SAMPLE);
-        let rounds = solve_part_2(&mut world);
-        assert_eq!(rounds, 20);
-    }
-}
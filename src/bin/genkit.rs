@@ -0,0 +1,191 @@
+use anyhow::{bail, Context, Error};
+use std::{
+    fmt::Write as _,
+    fs,
+    io::{self, Write as _},
+    path::PathBuf,
+};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "genkit",
+    about = "Generate large inputs for days 1, 4, 6, 8 and 20, for timing"
+)]
+struct Opt {
+    /// Day to generate an input for
+    day: u8,
+
+    /// How big to make it: elves for days 1 and 4, characters for day 6, the
+    /// side of the grid for day 8 and numbers for day 20
+    #[structopt(long, default_value = "10000")]
+    size: usize,
+
+    /// The same seed gives the same input
+    #[structopt(long, default_value = "2022")]
+    seed: u64,
+
+    /// Write the input here instead of to stdout
+    #[structopt(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// SplitMix64, which is plenty for test inputs and gives the same numbers for
+/// a seed on every platform and build.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number from `low` to `high`, inclusive.
+    fn range(&mut self, low: u64, high: u64) -> u64 {
+        low + self.next() % (high - low + 1)
+    }
+}
+
+/// `size` elves carrying up to 15 snacks each.
+fn calories(rng: &mut Rng, size: usize) -> String {
+    let mut s = String::new();
+    for elf in 0..size {
+        if elf > 0 {
+            s.push('\n');
+        }
+        for _ in 0..rng.range(1, 15) {
+            writeln!(s, "{}", rng.range(1000, 70000)).unwrap();
+        }
+    }
+    s
+}
+
+/// `size` pairs of elves, with sections numbered up to the number of pairs.
+fn assignments(rng: &mut Rng, size: usize) -> String {
+    let top = size.max(10) as u64;
+    let mut s = String::new();
+    for _ in 0..size {
+        let mut range = || {
+            let start = rng.range(1, top);
+            (start, rng.range(start, top))
+        };
+        let (a, b) = (range(), range());
+        writeln!(s, "{}-{},{}-{}", a.0, a.1, b.0, b.1).unwrap();
+    }
+    s
+}
+
+/// A stream of `size` characters whose markers are both in its last few
+/// characters, so a scan has to read nearly all of it. The three letters
+/// before them can't make a marker, and the doubled `d` keeps the message
+/// marker from starting early.
+fn datastream(rng: &mut Rng, size: usize) -> String {
+    const MARKER: &str = "ddefghijklmnopq";
+    let mut s: String = (0..size.saturating_sub(MARKER.len()))
+        .map(|_| (b'a' + rng.range(0, 2) as u8) as char)
+        .collect();
+    s.push_str(MARKER);
+    s.push('\n');
+    s
+}
+
+/// A `size` by `size` grid of tree heights.
+fn trees(rng: &mut Rng, size: usize) -> String {
+    let mut s = String::with_capacity(size * (size + 1));
+    for _ in 0..size {
+        s.extend((0..size).map(|_| (b'0' + rng.range(0, 9) as u8) as char));
+        s.push('\n');
+    }
+    s
+}
+
+/// `size` numbers with exactly one zero among them.
+fn encrypted(rng: &mut Rng, size: usize) -> String {
+    let zero = rng.range(0, size as u64 - 1) as usize;
+    let mut s = String::new();
+    for i in 0..size {
+        let value = match i == zero {
+            true => 0,
+            false => match rng.range(0, 20000) as i64 - 10000 {
+                0 => 1,
+                value => value,
+            },
+        };
+        writeln!(s, "{value}").unwrap();
+    }
+    s
+}
+
+fn generate(day: u8, size: usize, seed: u64) -> Result<String, Error> {
+    if size == 0 {
+        bail!("size must be at least 1");
+    }
+    let mut rng = Rng(seed);
+    Ok(match day {
+        1 => calories(&mut rng, size),
+        4 => assignments(&mut rng, size),
+        6 => datastream(&mut rng, size),
+        8 => trees(&mut rng, size),
+        20 => encrypted(&mut rng, size),
+        _ => bail!("there's no generator for day {day}"),
+    })
+}
+
+fn main() -> Result<(), Error> {
+    let opt = Opt::from_args();
+
+    let input = generate(opt.day, opt.size, opt.seed)?;
+    match &opt.output {
+        Some(path) => fs::write(path, input).with_context(|| format!("writing {path:?}"))?,
+        None => io::stdout().write_all(input.as_bytes())?,
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use advent_of_code_2022::{day01, day04, day06, day08, day20, solution::Answer};
+
+    #[test]
+    fn test_seeded() {
+        for day in [1, 4, 6, 8, 20] {
+            let input = generate(day, 50, 7).unwrap();
+            assert_eq!(input, generate(day, 50, 7).unwrap(), "day {day}");
+            assert_ne!(input, generate(day, 50, 8).unwrap(), "day {day}");
+        }
+        assert!(generate(2, 50, 7).is_err());
+        assert!(generate(1, 0, 7).is_err());
+    }
+
+    #[test]
+    fn test_solvable() {
+        let input = generate(1, 200, 1).unwrap();
+        assert_eq!(input.split("\n\n").count(), 200);
+        assert!(day01::solve(&input, &day01::Options).is_ok());
+
+        let input = generate(4, 200, 1).unwrap();
+        assert_eq!(input.lines().count(), 200);
+        assert!(day04::solve(&input, &day04::Options).is_ok());
+
+        let answers = day06::solve(&generate(6, 1000, 1).unwrap(), &day06::Options).unwrap();
+        let Answer::Number(start) = answers.part1 else {
+            panic!("no packet marker");
+        };
+        assert!((986..=990).contains(&start), "{start}");
+        assert_eq!(answers.part2, Answer::from(1000));
+
+        let input = generate(8, 30, 1).unwrap();
+        assert_eq!(input.lines().count(), 30);
+        assert!(input.lines().all(|line| line.len() == 30));
+        assert!(day08::solve(&input, &day08::Options).is_ok());
+
+        let input = generate(20, 200, 1).unwrap();
+        assert_eq!(input.lines().filter(|line| *line == "0").count(), 1);
+        assert!(day20::solve(&input, &day20::Options::default()).is_ok());
+    }
+}
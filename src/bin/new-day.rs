@@ -0,0 +1,250 @@
+use anyhow::{bail, ensure, Context, Error};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+
+/// A day's module in the library, wired up the way the others are: a
+/// `Solution` for the parsed input, `solve` for callers, and tests for the
+/// sample.
+const MODULE_TEMPLATE: &str = r##"//! Day {DAY}: {TITLE}.
+
+use crate::{
+    parse::parse_lines,
+    solution::{self, Answer, Answers, Solution},
+};
+use anyhow::Error;
+use tracing::debug;
+
+pub const DATA: &str = include_str!("../data/day{NN}.txt");
+pub const SAMPLE: &str = include_str!("../data/day{NN}_sample.txt");
+
+fn parse(s: &str) -> Result<Vec<String>, Error> {
+    parse_lines(s)
+}
+
+/// Day {DAY} has no options.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options;
+
+pub struct Day{NN} {
+    lines: Vec<String>,
+}
+
+impl Day{NN} {
+    pub fn new(input: &str, _options: &Options) -> Result<Self, Error> {
+        Ok(Self {
+            lines: parse(input)?,
+        })
+    }
+}
+
+impl Solution for Day{NN} {
+    const DAY: u8 = {DAY};
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        debug!(lines = self.lines.len(), "input");
+        Ok(Answer::Unsolved)
+    }
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day{NN}::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert!(parse(SAMPLE).is_ok());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_part_1() {
+        let day = Day{NN}::new(SAMPLE, &Options).unwrap();
+        assert_eq!(day.part_1().unwrap(), Answer::Unsolved);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_part_2() {
+        let day = Day{NN}::new(SAMPLE, &Options).unwrap();
+        assert_eq!(day.part_2().unwrap(), Answer::Unsolved);
+    }
+}
+"##;
+
+/// The day's binary, which reads the common options and runs the module.
+const BIN_TEMPLATE: &str = r##"use advent_of_code_2022::{
+    cli::CommonOpts,
+    day{NN}::{Day{NN}, Options, DATA, SAMPLE},
+    solution,
+};
+use anyhow::Error;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "day{NN}", about = "{TITLE}")]
+struct Opt {
+    #[structopt(flatten)]
+    common: CommonOpts,
+}
+
+fn main() -> Result<(), Error> {
+    let opt = Opt::from_args();
+    opt.common.init();
+
+    let input = opt.common.input(SAMPLE, DATA)?;
+    solution::parse_and_run(&opt.common, || Day{NN}::new(&input, &Options))?;
+
+    Ok(())
+}
+"##;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "new-day",
+    about = "Start a new day's module, binary and input file from the templates"
+)]
+struct Opt {
+    /// Day of December, written zero-padded in the file names
+    day: u8,
+
+    /// The puzzle's title, for --help
+    #[structopt(long)]
+    title: Option<String>,
+}
+
+/// `template` filled in for `day`.
+fn render(template: &str, day: u8, title: &str) -> String {
+    template
+        .replace("{NN}", &format!("{day:02}"))
+        .replace("{DAY}", &day.to_string())
+        .replace("{TITLE}", title)
+}
+
+/// `lib` with a `pub mod` line for `module` among the others, which are
+/// kept in order.
+fn add_module(lib: &str, module: &str) -> String {
+    let line = format!("pub mod {module};");
+    let mut lines: Vec<&str> = lib.lines().collect();
+    let at = lines
+        .iter()
+        .position(|l| l.starts_with("pub mod ") && *l > line.as_str())
+        .unwrap_or(lines.len());
+    lines.insert(at, &line);
+    lines.join("\n") + "\n"
+}
+
+/// Write `src/dayNN.rs` and `src/bin/dayNN.rs`, and empty `data/dayNN.txt`
+/// and `data/dayNN_sample.txt`, under `root`, and declare the module in
+/// `src/lib.rs`, returning the files created. None are overwritten if they
+/// exist.
+fn create_day(root: &Path, day: u8, title: &str) -> Result<Vec<PathBuf>, Error> {
+    ensure!((1..=99).contains(&day), "day {day} isn't between 1 and 99");
+    let module = root.join(format!("src/day{day:02}.rs"));
+    let source = root.join(format!("src/bin/day{day:02}.rs"));
+    let data = root.join(format!("data/day{day:02}.txt"));
+    let sample = root.join(format!("data/day{day:02}_sample.txt"));
+    for path in [&module, &source, &data, &sample] {
+        if path.exists() {
+            bail!("{path:?} already exists");
+        }
+    }
+    let lib = root.join("src/lib.rs");
+    let lib_source = fs::read_to_string(&lib).with_context(|| format!("reading {lib:?}"))?;
+
+    fs::write(&module, render(MODULE_TEMPLATE, day, title))
+        .with_context(|| format!("writing {module:?}"))?;
+    fs::write(&source, render(BIN_TEMPLATE, day, title))
+        .with_context(|| format!("writing {source:?}"))?;
+    for path in [&data, &sample] {
+        fs::write(path, "").with_context(|| format!("writing {path:?}"))?;
+    }
+    fs::write(&lib, add_module(&lib_source, &format!("day{day:02}")))
+        .with_context(|| format!("writing {lib:?}"))?;
+    Ok(vec![module, source, data, sample])
+}
+
+fn main() -> Result<(), Error> {
+    let opt = Opt::from_args();
+
+    let title = opt.title.unwrap_or_else(|| format!("Day {}", opt.day));
+    for path in create_day(Path::new(env!("CARGO_MANIFEST_DIR")), opt.day, &title)? {
+        println!("created {}", path.display());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_render() {
+        let module = render(MODULE_TEMPLATE, 7, "No Space Left On Device");
+        assert!(module.starts_with("//! Day 7: No Space Left On Device."));
+        assert!(module.contains(r#"include_str!("../data/day07.txt")"#));
+        assert!(module.contains(r#"include_str!("../data/day07_sample.txt")"#));
+        assert!(module.contains("pub struct Day07 {"));
+        assert!(module.contains("const DAY: u8 = 7;"));
+        assert!(!module.contains("{NN}") && !module.contains("{DAY}"));
+
+        let source = render(BIN_TEMPLATE, 7, "No Space Left On Device");
+        assert!(source.contains("day07::{Day07, Options, DATA, SAMPLE}"));
+        assert!(source.contains(r#"name = "day07", about = "No Space Left On Device""#));
+    }
+
+    #[test]
+    fn test_add_module() {
+        let lib = "pub mod cli;\npub mod day25;\npub mod grid;\n";
+        assert_eq!(
+            add_module(lib, "day26"),
+            "pub mod cli;\npub mod day25;\npub mod day26;\npub mod grid;\n"
+        );
+        assert_eq!(
+            add_module("pub mod cli;\n", "vm"),
+            "pub mod cli;\npub mod vm;\n"
+        );
+    }
+
+    #[test]
+    fn test_create_day() {
+        let root = env::temp_dir().join(format!("new-day-{}", std::process::id()));
+        fs::create_dir_all(root.join("src/bin")).unwrap();
+        fs::create_dir_all(root.join("data")).unwrap();
+        fs::write(root.join("src/lib.rs"), "pub mod cli;\npub mod day25;\n").unwrap();
+
+        let created = create_day(&root, 26, "Day 26").unwrap();
+        assert_eq!(
+            created,
+            [
+                root.join("src/day26.rs"),
+                root.join("src/bin/day26.rs"),
+                root.join("data/day26.txt"),
+                root.join("data/day26_sample.txt")
+            ]
+        );
+        assert_eq!(fs::read_to_string(&created[2]).unwrap(), "");
+        assert_eq!(fs::read_to_string(&created[3]).unwrap(), "");
+        assert_eq!(
+            fs::read_to_string(root.join("src/lib.rs")).unwrap(),
+            "pub mod cli;\npub mod day25;\npub mod day26;\n"
+        );
+
+        // A second run mustn't clobber the day that's now being worked on.
+        fs::write(&created[0], "work in progress").unwrap();
+        assert!(create_day(&root, 26, "Day 26").is_err());
+        assert_eq!(fs::read_to_string(&created[0]).unwrap(), "work in progress");
+        assert!(create_day(&root, 0, "Day 0").is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
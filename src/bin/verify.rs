@@ -0,0 +1,349 @@
+use advent_of_code_2022::solution::Timings;
+use anyhow::{anyhow, bail, ensure, Context, Error};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    env,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+use structopt::StructOpt;
+
+const ANSWERS: &str = include_str!("../../answers.toml");
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "verify",
+    about = "Check every day against the recorded answers"
+)]
+struct Opt {
+    /// Only check these days, as 5 or 05
+    #[structopt(parse(try_from_str = parse_day))]
+    days: Vec<usize>,
+
+    /// Have each day time its stages, and say which was slowest at the end
+    #[structopt(long)]
+    time: bool,
+
+    /// Accept each day's cached answers instead of having it solve again
+    #[structopt(long)]
+    cached: bool,
+}
+
+impl Opt {
+    /// Whether `day` is one of the days to check.
+    fn checks(&self, day: usize) -> bool {
+        self.days.is_empty() || self.days.contains(&day)
+    }
+}
+
+/// A day number, with or without a leading zero.
+fn parse_day(s: &str) -> Result<usize, Error> {
+    let day: usize = s
+        .parse()
+        .with_context(|| format!("{s:?} isn't a day number"))?;
+    ensure!(
+        (1..=25).contains(&day),
+        "there's no day {day}, days run from 1 to 25"
+    );
+    Ok(day)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Answer {
+    day: usize,
+    part: usize,
+    value: String,
+    args: Vec<String>,
+}
+
+fn parse_answer(entry: &toml::Value) -> Result<Answer, Error> {
+    let int = |key: &str| {
+        entry
+            .get(key)
+            .and_then(toml::Value::as_integer)
+            .and_then(|i| usize::try_from(i).ok())
+            .ok_or_else(|| anyhow!("missing or invalid {key}"))
+    };
+    let value = entry
+        .get("value")
+        .and_then(toml::Value::as_str)
+        .ok_or_else(|| anyhow!("missing or invalid value"))?
+        .to_string();
+    let args = match entry.get("args") {
+        None => vec![],
+        Some(args) => args
+            .as_array()
+            .ok_or_else(|| anyhow!("args must be an array"))?
+            .iter()
+            .map(|arg| {
+                arg.as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| anyhow!("args must be strings"))
+            })
+            .collect::<Result<_, _>>()?,
+    };
+    Ok(Answer {
+        day: int("day")?,
+        part: int("part")?,
+        value,
+        args,
+    })
+}
+
+fn parse(s: &str) -> Result<Vec<Answer>, Error> {
+    let doc: toml::Value = s.parse().context("answers.toml")?;
+    let Some(entries) = doc.get("answer").and_then(toml::Value::as_array) else {
+        bail!("answers.toml has no [[answer]] entries");
+    };
+    entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| parse_answer(entry).with_context(|| format!("answer #{}", i + 1)))
+        .collect()
+}
+
+/// The report a day printed with `--output json`, which is the last line
+/// that's a JSON object.
+fn parse_report(output: &str) -> Option<Value> {
+    let json = output.lines().rfind(|line| line.starts_with('{'))?;
+    serde_json::from_str(json).ok()
+}
+
+/// One part's answer from a report, written the way `answers.toml` does,
+/// or `None` if the day didn't get as far as that part.
+fn report_answer(report: &Value, part: usize) -> Option<String> {
+    match report.get(format!("part{part}"))? {
+        Value::String(s) => Some(s.clone()),
+        Value::Null => Some("unsolved".to_string()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// The stages a day timed with `--time`, with their timings.
+fn stage_times(report: &Value) -> Vec<(&'static str, f64, String)> {
+    let timings = report
+        .get("timings")
+        .and_then(|timings| serde_json::from_value::<Timings>(timings.clone()).ok())
+        .unwrap_or_default();
+    timings
+        .stages()
+        .map(|(stage, timing)| (stage, timing.wall_ms, timing.to_string()))
+        .collect()
+}
+
+/// The day binaries are built next to this one.
+fn bin_dir() -> Result<PathBuf, Error> {
+    let exe = env::current_exe()?;
+    exe.parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| anyhow!("no parent directory for {exe:?}"))
+}
+
+/// The binary for `day` in `dir`; they're all zero-padded.
+fn day_bin(dir: &Path, day: usize) -> PathBuf {
+    dir.join(format!("day{day:02}"))
+}
+
+/// Run a day and capture what it printed. The JSON report only comes once
+/// both parts are done, so a day that fails or panics part way has neither
+/// part checked, and its exit status is shown with the mismatch.
+fn run_day(dir: &Path, day: usize, args: &[String]) -> Result<Output, Error> {
+    let bin = day_bin(dir, day);
+    Command::new(&bin)
+        .args(args)
+        .output()
+        .with_context(|| format!("running {bin:?}"))
+}
+
+/// What to run a day with to check `answer`. Days report in JSON, and
+/// solve again unless `--cached` was given.
+fn day_args(opt: &Opt, answer: &Answer) -> Vec<String> {
+    let mut args = answer.args.clone();
+    args.extend(["--output".to_string(), "json".to_string()]);
+    if opt.time {
+        args.push("--time".to_string());
+    }
+    if !opt.cached {
+        args.push("--force".to_string());
+    }
+    args
+}
+
+fn main() -> Result<(), Error> {
+    let opt = Opt::from_args();
+
+    let answers = parse(ANSWERS)?;
+    let dir = bin_dir()?;
+
+    // Days often print both parts in one run, so only run each distinct
+    // command line once.
+    let mut outputs: BTreeMap<(usize, Vec<String>), Output> = BTreeMap::new();
+    let mut failures = 0;
+    for answer in answers.iter().filter(|a| opt.checks(a.day)) {
+        let key = (answer.day, day_args(&opt, answer));
+        if !outputs.contains_key(&key) {
+            outputs.insert(key.clone(), run_day(&dir, answer.day, &key.1)?);
+        }
+        let output = &outputs[&key];
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        let got = parse_report(&stdout).and_then(|report| report_answer(&report, answer.part));
+        let ok = got.as_ref() == Some(&answer.value);
+        println!(
+            "day {:2} part {}: {:8} expected {}",
+            answer.day,
+            answer.part,
+            if ok { "ok" } else { "MISMATCH" },
+            answer.value
+        );
+        if !ok {
+            failures += 1;
+            match got {
+                Some(got) => println!("    got {got}"),
+                None => println!("    no answer reported"),
+            }
+            if !output.status.success() {
+                println!("    day{:02} exited with {}", answer.day, output.status);
+            }
+        }
+    }
+
+    for day in (1..=25).filter(|day| opt.checks(*day)) {
+        for part in 1..=2 {
+            if !answers.iter().any(|a| a.day == day && a.part == part) {
+                println!("day {day:2} part {part}: MISSING  no answer in answers.toml");
+                failures += 1;
+            }
+        }
+    }
+
+    if opt.time {
+        let slowest = outputs
+            .iter()
+            .filter_map(|((day, _), output)| {
+                Some((
+                    *day,
+                    parse_report(&String::from_utf8_lossy(&output.stdout))?,
+                ))
+            })
+            .flat_map(|(day, report)| {
+                stage_times(&report)
+                    .into_iter()
+                    .map(move |(stage, wall_ms, timing)| (wall_ms, day, stage, timing))
+            })
+            .max_by(|a, b| a.0.total_cmp(&b.0));
+        if let Some((_, day, stage, timing)) = slowest {
+            println!("slowest stage: day {day} {stage}, {timing}");
+        }
+    }
+
+    if failures > 0 {
+        bail!("{failures} answers didn't match");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let answers = parse(ANSWERS).expect("parse");
+        assert!(answers.iter().all(|a| (1..=25).contains(&a.day)));
+        assert!(answers.iter().all(|a| a.part == 1 || a.part == 2));
+        let day14 = answers
+            .iter()
+            .find(|a| a.day == 14 && a.part == 1)
+            .expect("day 14");
+        assert_eq!(day14.args[0], "--puzzle-input");
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = parse("[[answer]]\nday = 1\nvalue = \"5\"\n").unwrap_err();
+        assert!(format!("{err:#}").contains("part"));
+    }
+
+    #[test]
+    fn test_day_selection() {
+        // Binaries are all zero-padded, but days can be given either way.
+        let opt = Opt::from_iter(["verify", "5", "05", "12"]);
+        assert_eq!(opt.days, [5, 5, 12]);
+        let checked: Vec<_> = parse(ANSWERS)
+            .unwrap()
+            .into_iter()
+            .filter(|a| opt.checks(a.day))
+            .map(|a| (a.day, a.part))
+            .collect();
+        assert_eq!(checked, [(5, 1), (5, 2), (12, 1), (12, 2)]);
+        assert_eq!(
+            day_bin(Path::new("target/release"), 5),
+            Path::new("target/release/day05")
+        );
+        assert!((1..=25).all(|day| Opt::from_iter(["verify"]).checks(day)));
+
+        for (day, error) in [
+            ("0", "there's no day 0, days run from 1 to 25"),
+            ("26", "there's no day 26, days run from 1 to 25"),
+            ("five", "\"five\" isn't a day number"),
+        ] {
+            let message = Opt::from_iter_safe(["verify", day]).unwrap_err().message;
+            assert!(message.contains(error), "{message}");
+        }
+    }
+
+    #[test]
+    fn test_day_args() {
+        let answer = Answer {
+            day: 1,
+            part: 1,
+            value: "64929".to_string(),
+            args: vec!["--puzzle-input".to_string()],
+        };
+        let args = |opt: &[&str]| day_args(&Opt::from_iter(opt), &answer);
+        assert_eq!(
+            args(&["verify"]),
+            ["--puzzle-input", "--output", "json", "--force"]
+        );
+        assert_eq!(
+            args(&["verify", "--cached"]),
+            ["--puzzle-input", "--output", "json"]
+        );
+        assert_eq!(
+            args(&["verify", "--time"]),
+            ["--puzzle-input", "--output", "json", "--time", "--force"]
+        );
+    }
+
+    #[test]
+    fn test_stage_times() {
+        let output = "debug output\n{\"day\":1,\"part1\":24,\"elapsed_ms\":12.6,\
+                      \"timings\":{\"parse\":{\"wall_ms\":0.125,\"cpu_ms\":0.12},\
+                      \"part1\":{\"wall_ms\":12.5,\"cpu_ms\":null}}}\n";
+        assert_eq!(
+            stage_times(&parse_report(output).unwrap()),
+            [
+                ("parse", 0.125, "0.125ms wall, 0.120ms cpu".to_string()),
+                ("part 1", 12.5, "12.500ms wall".to_string())
+            ]
+        );
+        assert_eq!(stage_times(&parse_report("{}").unwrap()), []);
+    }
+
+    #[test]
+    fn test_report_answer() {
+        let output = "part 1 = 11603\n{\"day\":2,\"part1\":11603,\"part2\":null}\n";
+        let report = parse_report(output).unwrap();
+        assert_eq!(report_answer(&report, 1).as_deref(), Some("11603"));
+        assert_eq!(report_answer(&report, 2).as_deref(), Some("unsolved"));
+
+        // Only the part asked for, and letters rather than numbers.
+        let report = parse_report(r#"{"day":10,"part2":"PGPHBEAB"}"#).unwrap();
+        assert_eq!(report_answer(&report, 1), None);
+        assert_eq!(report_answer(&report, 2).as_deref(), Some("PGPHBEAB"));
+        assert_eq!(parse_report("part 1 = 11603\n"), None);
+    }
+}
@@ -0,0 +1,225 @@
+//! Answers from earlier runs, so a day whose puzzle input hasn't changed
+//! needn't be solved again. Each day's answers are kept in
+//! `.cache/dayNN.json`, keyed on the part, the input's SHA-256 and the
+//! options that can change an answer, along with how long they took.
+
+use crate::solution::Answer;
+use anyhow::{Context, Error};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fmt::Write as _,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+/// Hex SHA-256 of `input`.
+pub fn digest(input: &str) -> String {
+    Sha256::digest(input.as_bytes())
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            write!(hex, "{byte:02x}").unwrap();
+            hex
+        })
+}
+
+/// The command line arguments that can change a day's answers: everything
+/// but the common options for choosing the input and part, for how long to
+/// try, and for how the answers are printed.
+pub fn settings(args: impl IntoIterator<Item = String>) -> Vec<String> {
+    const FLAGS: &[&str] = &[
+        "--puzzle-input",
+        "--time",
+        "--verbose",
+        "--force",
+        "--submit",
+    ];
+    const WITH_VALUES: &[&str] = &["--input", "--output", "--part", "--timeout"];
+    let mut args = args.into_iter();
+    let mut kept = vec![];
+    while let Some(arg) = args.next() {
+        let name = arg.split('=').next().unwrap_or_default();
+        if WITH_VALUES.contains(&name) {
+            if !arg.contains('=') {
+                args.next();
+            }
+            continue;
+        }
+        // -p and -v, which can be run together as in -pvv.
+        let short =
+            arg.len() > 1 && arg.starts_with('-') && arg[1..].chars().all(|c| "pv".contains(c));
+        if !short && !FLAGS.contains(&name) {
+            kept.push(arg);
+        }
+    }
+    kept
+}
+
+/// What a cached answer is for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Key {
+    /// [`digest`] of the input.
+    pub input: String,
+    /// [`settings`] the day was run with.
+    pub settings: Vec<String>,
+}
+
+/// One part's answer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub part: u8,
+    pub input: String,
+    pub settings: Vec<String>,
+    pub answer: Answer,
+    pub elapsed_ms: f64,
+}
+
+impl Entry {
+    fn is_for(&self, part: u8, key: &Key) -> bool {
+        self.part == part && self.input == key.input && self.settings == key.settings
+    }
+}
+
+/// A day's cached answers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cache {
+    path: PathBuf,
+    entries: Vec<Entry>,
+}
+
+impl Cache {
+    /// `.cache` at the top of the repository.
+    pub fn dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join(".cache")
+    }
+
+    /// The answers cached for `day` in `dir`, which there are none of if its
+    /// file hasn't been written yet.
+    pub fn open(dir: &Path, day: u8) -> Result<Self, Error> {
+        let path = dir.join(format!("day{day:02}.json"));
+        let entries = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).with_context(|| format!("reading {path:?}"))?,
+            Err(e) if e.kind() == ErrorKind::NotFound => vec![],
+            Err(e) => return Err(e).with_context(|| format!("reading {path:?}")),
+        };
+        Ok(Self { path, entries })
+    }
+
+    pub fn get(&self, part: u8, key: &Key) -> Option<&Entry> {
+        self.entries.iter().find(|entry| entry.is_for(part, key))
+    }
+
+    /// Record `answer` for `part`, returning the answer it replaces if that
+    /// was different.
+    pub fn insert(
+        &mut self,
+        part: u8,
+        key: &Key,
+        answer: Answer,
+        elapsed_ms: f64,
+    ) -> Option<Answer> {
+        let entry = Entry {
+            part,
+            input: key.input.clone(),
+            settings: key.settings.clone(),
+            answer,
+            elapsed_ms,
+        };
+        match self.entries.iter_mut().find(|old| old.is_for(part, key)) {
+            Some(old) => {
+                let previous = std::mem::replace(old, entry);
+                (previous.answer != old.answer).then_some(previous.answer)
+            }
+            None => {
+                self.entries.push(entry);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("creating {dir:?}"))?;
+        }
+        let json = serde_json::to_string_pretty(&self.entries)?;
+        fs::write(&self.path, json + "\n").with_context(|| format!("writing {:?}", self.path))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::env;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_digest() {
+        assert_eq!(
+            digest("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_settings() {
+        let kept = settings(args(&[
+            "-p",
+            "--input",
+            "big.txt",
+            "--output=json",
+            "--part",
+            "2",
+            "-vv",
+            "--time",
+            "--force",
+            "--time-limit",
+            "32",
+            "--solver",
+            "skip-ahead",
+        ]));
+        assert_eq!(kept, ["--time-limit", "32", "--solver", "skip-ahead"]);
+        assert_eq!(
+            settings(args(&["-pv", "--no-normalize"])),
+            ["--no-normalize"]
+        );
+    }
+
+    #[test]
+    fn test_cache() {
+        let dir = env::temp_dir().join(format!("cache-{}", std::process::id()));
+        let key = Key {
+            input: digest("1\n2\n"),
+            settings: vec![],
+        };
+        let other = Key {
+            settings: args(&["--fast"]),
+            ..key.clone()
+        };
+
+        let mut cache = Cache::open(&dir, 19).unwrap();
+        assert_eq!(cache.get(1, &key), None);
+        assert_eq!(cache.insert(1, &key, Answer::Number(3), 1.5), None);
+        assert_eq!(cache.insert(2, &key, Answer::from("ABC"), 2.0), None);
+        cache.save().unwrap();
+
+        let mut cache = Cache::open(&dir, 19).unwrap();
+        assert_eq!(cache.get(1, &key).unwrap().answer, Answer::Number(3));
+        assert_eq!(cache.get(2, &key).unwrap().elapsed_ms, 2.0);
+        assert_eq!(cache.get(1, &other), None);
+        assert_eq!(cache.insert(1, &key, Answer::Number(3), 1.0), None);
+        assert_eq!(
+            cache.insert(1, &key, Answer::Number(4), 1.0),
+            Some(Answer::Number(3))
+        );
+        assert!(Cache::open(&dir, 20).unwrap().entries.is_empty());
+
+        fs::write(dir.join("day21.json"), "not json").unwrap();
+        assert!(Cache::open(&dir, 21).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,102 @@
+//! Stopping long searches early. The runner hands each part a [`Cancel`],
+//! and days whose searches can run for a long time check it between
+//! rounds, giving up with [`Cancelled`] once it's been cancelled or its time
+//! is up.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+
+/// Why a search stopped before finishing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("cancelled after {after:?}")]
+pub struct Cancelled {
+    /// The time limit, or how long the search had been running if it was
+    /// cancelled some other way.
+    pub after: Duration,
+}
+
+/// A flag shared between clones, and an optional deadline. The default is
+/// never cancelled.
+#[derive(Debug, Clone)]
+pub struct Cancel {
+    started: Instant,
+    limit: Option<Duration>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Default for Cancel {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            limit: None,
+            cancelled: Arc::default(),
+        }
+    }
+}
+
+impl Cancel {
+    /// Cancelled once `limit` has passed.
+    pub fn after(limit: Duration) -> Self {
+        Self {
+            limit: Some(limit),
+            ..Self::default()
+        }
+    }
+
+    /// Cancel this and every clone of it.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self
+                .limit
+                .is_some_and(|limit| self.started.elapsed() >= limit)
+    }
+
+    /// A check point for a search: `Err` if it's time to stop.
+    pub fn check(&self) -> Result<(), Cancelled> {
+        match self.is_cancelled() {
+            true => Err(Cancelled {
+                after: self.limit.unwrap_or_else(|| self.started.elapsed()),
+            }),
+            false => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cancel() {
+        let never = Cancel::default();
+        assert_eq!(never.check(), Ok(()));
+
+        let clone = never.clone();
+        never.cancel();
+        assert!(clone.is_cancelled());
+        assert!(clone.check().is_err());
+
+        let limit = Duration::from_millis(20);
+        let timed = Cancel::after(limit);
+        assert!(!timed.is_cancelled());
+        std::thread::sleep(limit);
+        assert_eq!(timed.check(), Err(Cancelled { after: limit }));
+        assert_eq!(
+            Cancel::after(Duration::ZERO)
+                .check()
+                .unwrap_err()
+                .to_string(),
+            "cancelled after 0ns"
+        );
+    }
+}
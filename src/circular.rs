@@ -0,0 +1,274 @@
+//! A list whose ends join up, where elements can be moved around the
+//! circle, as in day 20's mixing.
+
+use std::cmp::Ordering;
+
+const NIL: usize = usize::MAX;
+
+#[derive(Debug, Clone)]
+struct Node<T> {
+    value: T,
+    priority: u64,
+    left: usize,
+    right: usize,
+    parent: usize,
+    size: usize,
+}
+
+/// A circular sequence stored as an implicit treap, giving O(log n)
+/// moves and position lookups. Elements are identified by their index in
+/// the order they were first given, however far they've moved since.
+///
+/// Positions count from an arbitrary starting point. Only the order
+/// around the circle matters, so `[1, 2, 3]` and `[2, 3, 1]` are the same
+/// circle.
+#[derive(Debug, Clone)]
+pub struct CircularList<T> {
+    nodes: Vec<Node<T>>,
+    root: usize,
+}
+
+impl<T> CircularList<T> {
+    pub fn new(values: impl IntoIterator<Item = T>) -> Self {
+        let mut list = Self {
+            nodes: Vec::new(),
+            root: NIL,
+        };
+        let mut priority = 0x2545_f491_4f6c_dd1d_u64;
+        for value in values {
+            // xorshift, so the tree shape is deterministic
+            priority ^= priority << 13;
+            priority ^= priority >> 7;
+            priority ^= priority << 17;
+            let id = list.nodes.len();
+            list.nodes.push(Node {
+                value,
+                priority,
+                left: NIL,
+                right: NIL,
+                parent: NIL,
+                size: 1,
+            });
+            let root = list.merge(list.root, id);
+            list.set_root(root);
+        }
+        list
+    }
+
+    pub fn len(&self) -> usize {
+        self.size(self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root == NIL
+    }
+
+    /// Where element `id` is now.
+    pub fn position_of(&self, id: usize) -> usize {
+        let mut position = self.size(self.nodes[id].left);
+        let mut n = id;
+        while self.nodes[n].parent != NIL {
+            let parent = self.nodes[n].parent;
+            if self.nodes[parent].right == n {
+                position += self.size(self.nodes[parent].left) + 1;
+            }
+            n = parent;
+        }
+        position
+    }
+
+    /// Move element `id` `by` places around the circle, forwards if `by`
+    /// is positive. While it moves the element isn't part of the circle,
+    /// so it passes `len() - 1` others on each trip around: moving by
+    /// `len() - 1` puts it back between the same two neighbors.
+    pub fn rotate_element(&mut self, id: usize, by: isize) {
+        let others = self.len() as isize - 1;
+        if others < 1 {
+            return;
+        }
+        let position = self.remove(id);
+        let position = (position as isize + by).rem_euclid(others);
+        self.insert(id, position as usize);
+    }
+
+    /// The element at `position`, which must be less than `len()`.
+    pub fn get(&self, mut position: usize) -> &T {
+        let mut n = self.root;
+        loop {
+            let left_size = self.size(self.nodes[n].left);
+            match position.cmp(&left_size) {
+                Ordering::Less => n = self.nodes[n].left,
+                Ordering::Equal => return &self.nodes[n].value,
+                Ordering::Greater => {
+                    position -= left_size + 1;
+                    n = self.nodes[n].right;
+                }
+            }
+        }
+    }
+
+    /// The element at `position`, counting round the circle as often as
+    /// it takes, backwards if `position` is negative.
+    pub fn get_wrapping(&self, position: isize) -> &T {
+        self.get(position.rem_euclid(self.len() as isize) as usize)
+    }
+
+    /// The elements in order, starting from position zero.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        (0..self.len()).map(|position| self.get(position))
+    }
+
+    fn size(&self, n: usize) -> usize {
+        if n == NIL {
+            0
+        } else {
+            self.nodes[n].size
+        }
+    }
+
+    fn update(&mut self, n: usize) {
+        let (left, right) = (self.nodes[n].left, self.nodes[n].right);
+        self.nodes[n].size = 1 + self.size(left) + self.size(right);
+        if left != NIL {
+            self.nodes[left].parent = n;
+        }
+        if right != NIL {
+            self.nodes[right].parent = n;
+        }
+    }
+
+    fn set_root(&mut self, n: usize) {
+        self.root = n;
+        if n != NIL {
+            self.nodes[n].parent = NIL;
+        }
+    }
+
+    fn merge(&mut self, a: usize, b: usize) -> usize {
+        if a == NIL {
+            return b;
+        }
+        if b == NIL {
+            return a;
+        }
+        if self.nodes[a].priority > self.nodes[b].priority {
+            let right = self.nodes[a].right;
+            self.nodes[a].right = self.merge(right, b);
+            self.update(a);
+            a
+        } else {
+            let left = self.nodes[b].left;
+            self.nodes[b].left = self.merge(a, left);
+            self.update(b);
+            b
+        }
+    }
+
+    /// Split into the first `k` elements and the rest.
+    fn split(&mut self, n: usize, k: usize) -> (usize, usize) {
+        if n == NIL {
+            return (NIL, NIL);
+        }
+        let left_size = self.size(self.nodes[n].left);
+        if k <= left_size {
+            let left = self.nodes[n].left;
+            let (a, b) = self.split(left, k);
+            self.nodes[n].left = b;
+            self.update(n);
+            (a, n)
+        } else {
+            let right = self.nodes[n].right;
+            let (a, b) = self.split(right, k - left_size - 1);
+            self.nodes[n].right = a;
+            self.update(n);
+            (n, b)
+        }
+    }
+
+    /// Take element `id` out of the sequence, returning where it was.
+    fn remove(&mut self, id: usize) -> usize {
+        let position = self.position_of(id);
+        let (before, rest) = self.split(self.root, position);
+        let (_, after) = self.split(rest, 1);
+        let root = self.merge(before, after);
+        self.set_root(root);
+        position
+    }
+
+    /// Put a removed element `id` back so that it ends up at `position`.
+    fn insert(&mut self, id: usize, position: usize) {
+        let (before, after) = self.split(self.root, position);
+        let root = self.merge(before, id);
+        let root = self.merge(root, after);
+        self.set_root(root);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn values(list: &CircularList<i32>) -> Vec<i32> {
+        list.iter().copied().collect()
+    }
+
+    /// The order around the circle starting from `first`, so that lists
+    /// that only differ in where they start compare equal.
+    fn from(list: &CircularList<i32>, first: i32) -> Vec<i32> {
+        let mut values = values(list);
+        let start = values.iter().position(|v| *v == first).unwrap();
+        values.rotate_left(start);
+        values
+    }
+
+    #[test]
+    fn test_positions() {
+        let list = CircularList::new([10, 20, 30, 40]);
+        assert_eq!(values(&list), [10, 20, 30, 40]);
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.position_of(2), 2);
+        assert_eq!(*list.get(3), 40);
+        assert_eq!(*list.get_wrapping(5), 20);
+        assert_eq!(*list.get_wrapping(-1), 40);
+        assert_eq!(*list.get_wrapping(-9), 40);
+        assert!(CircularList::<i32>::new([]).is_empty());
+    }
+
+    #[test]
+    fn test_rotate_element() {
+        let mut list = CircularList::new([0, 1, 2, 3, 4]);
+        list.rotate_element(1, 2);
+        assert_eq!(from(&list, 0), [0, 2, 3, 1, 4]);
+        list.rotate_element(1, -2);
+        assert_eq!(from(&list, 0), [0, 1, 2, 3, 4]);
+
+        // Moving past the end comes back round from the start.
+        list.rotate_element(3, 2);
+        assert_eq!(from(&list, 0), [0, 3, 1, 2, 4]);
+        list.rotate_element(0, -1);
+        assert_eq!(from(&list, 1), [1, 2, 0, 4, 3]);
+    }
+
+    #[test]
+    fn test_rotate_wraps_modulo_len_minus_one() {
+        // With n elements, a trip round the circle passes the n - 1 others,
+        // so moving by any multiple of n - 1 changes nothing.
+        for by in [0, 4, -4, 8, 4 * 1_000_000_007] {
+            let mut list = CircularList::new([0, 1, 2, 3, 4]);
+            list.rotate_element(2, by);
+            assert_eq!(from(&list, 0), [0, 1, 2, 3, 4], "{by}");
+        }
+        // One more than a trip is the same as moving by one.
+        let mut list = CircularList::new([0, 1, 2, 3, 4]);
+        list.rotate_element(2, 5);
+        assert_eq!(from(&list, 0), [0, 1, 3, 2, 4]);
+        let mut list = CircularList::new([0, 1, 2, 3, 4]);
+        list.rotate_element(2, -5);
+        assert_eq!(from(&list, 0), [0, 2, 1, 3, 4]);
+
+        // Nothing can move in a circle of one.
+        let mut list = CircularList::new([7]);
+        list.rotate_element(0, 3);
+        assert_eq!(values(&list), [7]);
+    }
+}
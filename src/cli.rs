@@ -0,0 +1,275 @@
+//! Command line options shared between the days.
+
+use crate::{
+    cache::{self, Key},
+    cancel::Cancel,
+};
+use anyhow::{bail, Error};
+use std::{env, fs, path::PathBuf, str::FromStr, sync::OnceLock, time::Duration};
+use structopt::StructOpt;
+use tracing::Level;
+
+// Options every day accepts. Days add `#[structopt(flatten)] common:
+// CommonOpts` to their own `Opt` so these are spelled the same everywhere.
+//
+// This and `Verbosity` have plain comments rather than doc comments, since
+// structopt would make a flattened struct's doc comment the `about` text of
+// every day's binary.
+#[derive(Debug, Clone, Default, StructOpt)]
+pub struct CommonOpts {
+    /// Use puzzle input instead of the sample
+    #[structopt(short, long)]
+    pub puzzle_input: bool,
+
+    /// Read the input from this file instead of the built-in sample or puzzle input
+    #[structopt(long, parse(from_os_str))]
+    pub input: Option<PathBuf>,
+
+    /// Only solve this part
+    #[structopt(long, possible_values = &["1", "2"])]
+    pub part: Option<u8>,
+
+    /// Minutes the puzzle allows, for days that have a time limit
+    #[structopt(long)]
+    pub time_limit: Option<usize>,
+
+    /// How to print the answers: text, or json for scripts
+    #[structopt(long, default_value = "text", possible_values = &["text", "json"])]
+    pub output: OutputFormat,
+
+    /// Time parsing and each part, by the clock and in CPU time
+    #[structopt(long)]
+    pub time: bool,
+
+    /// Give up after this many seconds of solving, for the days whose
+    /// searches can stop early
+    #[structopt(long, parse(try_from_str = parse_seconds))]
+    pub timeout: Option<Duration>,
+
+    /// Solve again even if the answers for this input are cached
+    #[structopt(long)]
+    pub force: bool,
+
+    /// Submit the answers to adventofcode.com
+    #[structopt(long, requires = "puzzle-input", conflicts_with = "input")]
+    pub submit: bool,
+
+    #[structopt(flatten)]
+    pub verbosity: Verbosity,
+
+    /// The digest of the puzzle input, once [`input`](Self::input) has read
+    /// it, which is what lets the runner use the cache. Samples and
+    /// `--input` files aren't cached.
+    #[structopt(skip)]
+    pub input_digest: OnceLock<String>,
+}
+
+impl CommonOpts {
+    /// Set up logging; call this first thing in `main`.
+    pub fn init(&self) {
+        self.verbosity.init();
+    }
+
+    /// The text to solve: `--input` if given, otherwise the puzzle input or
+    /// the sample.
+    pub fn input(&self, sample: &str, data: &str) -> Result<String, crate::Error> {
+        let input = if let Some(path) = &self.input {
+            fs::read_to_string(path).map_err(|source| crate::Error::Io {
+                path: path.clone(),
+                source,
+            })?
+        } else if self.puzzle_input {
+            let _ = self.input_digest.set(cache::digest(data));
+            data.to_string()
+        } else {
+            sample.to_string()
+        };
+        Ok(input)
+    }
+
+    /// What this run's answers are cached under, once the input has been
+    /// read.
+    pub fn cache_key(&self) -> Option<Key> {
+        Some(Key {
+            input: self.input_digest.get()?.clone(),
+            settings: cache::settings(env::args().skip(1)),
+        })
+    }
+
+    /// Whether to solve `part`, which is all of them unless `--part` was given.
+    pub fn part(&self, part: u8) -> bool {
+        self.part.is_none() || self.part == Some(part)
+    }
+
+    /// A cancel for the solvers, which runs out after `--timeout` if there
+    /// is one.
+    pub fn cancel(&self) -> Cancel {
+        self.timeout.map_or_else(Cancel::default, Cancel::after)
+    }
+
+    /// The puzzle's time limit, unless overridden with `--time-limit`.
+    pub fn time_limit(&self, default: usize) -> usize {
+        self.time_limit.unwrap_or(default)
+    }
+}
+
+/// A non-negative number of seconds, which can have a fraction.
+fn parse_seconds(s: &str) -> Result<Duration, Error> {
+    Ok(Duration::try_from_secs_f64(s.parse()?)?)
+}
+
+/// How a day reports its answers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    /// One line of JSON with both answers and the time taken
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "text" => Self::Text,
+            "json" => Self::Json,
+            _ => bail!("unknown output format {s:?}"),
+        })
+    }
+}
+
+// How much solver tracing to log to stderr.
+#[derive(Debug, Clone, Default, StructOpt)]
+pub struct Verbosity {
+    /// Log solver progress; -v for debug events, -vv to trace every step
+    #[structopt(short, long, parse(from_occurrences))]
+    pub verbose: u8,
+}
+
+impl Verbosity {
+    pub fn level(&self) -> Level {
+        match self.verbose {
+            0 => Level::WARN,
+            1 => Level::DEBUG,
+            _ => Level::TRACE,
+        }
+    }
+
+    /// Install a subscriber that writes events at or above the chosen level.
+    pub fn init(&self) {
+        tracing_subscriber::fmt()
+            .with_max_level(self.level())
+            .with_writer(std::io::stderr)
+            .init();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, StructOpt)]
+    #[structopt(name = "day", about = "A day's own description")]
+    struct Opt {
+        #[structopt(flatten)]
+        common: CommonOpts,
+
+        /// A day's own option
+        #[structopt(long)]
+        rounds: Option<usize>,
+    }
+
+    fn opt(args: &[&str]) -> Opt {
+        Opt::from_iter(std::iter::once("day").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn test_help() {
+        let mut help = vec![];
+        Opt::clap().write_help(&mut help).unwrap();
+        let help = String::from_utf8(help).unwrap();
+        assert_eq!(help.lines().nth(1), Some("A day's own description"));
+    }
+
+    #[test]
+    fn test_input() {
+        assert_eq!(opt(&[]).common.input("sample", "data").unwrap(), "sample");
+        assert_eq!(opt(&["-p"]).common.input("sample", "data").unwrap(), "data");
+        assert!(opt(&["--input", "/nonexistent/input.txt"])
+            .common
+            .input("sample", "data")
+            .is_err());
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let common = opt(&["-p"]).common;
+        assert_eq!(common.cache_key(), None);
+        common.input("sample", "data").unwrap();
+        assert_eq!(common.cache_key().unwrap().input, cache::digest("data"));
+
+        let sample = opt(&[]).common;
+        sample.input("sample", "data").unwrap();
+        assert_eq!(sample.cache_key(), None);
+
+        let path = env::temp_dir().join(format!("cli-input-{}.txt", std::process::id()));
+        fs::write(&path, "data").unwrap();
+        let file = opt(&["-p", "--input", path.to_str().unwrap()]).common;
+        assert_eq!(file.input("sample", "data").unwrap(), "data");
+        assert_eq!(file.cache_key(), None);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_part() {
+        let all = opt(&[]);
+        assert!(all.common.part(1) && all.common.part(2));
+        let second = opt(&["--part", "2", "--rounds", "5"]);
+        assert!(!second.common.part(1) && second.common.part(2));
+        assert_eq!(second.rounds, Some(5));
+        assert!(Opt::from_iter_safe(["day", "--part", "3"]).is_err());
+    }
+
+    #[test]
+    fn test_output() {
+        assert_eq!(opt(&[]).common.output, OutputFormat::Text);
+        assert_eq!(opt(&["--output", "json"]).common.output, OutputFormat::Json);
+        assert!(Opt::from_iter_safe(["day", "--output", "yaml"]).is_err());
+    }
+
+    #[test]
+    fn test_timeout() {
+        assert!(!opt(&[]).common.cancel().is_cancelled());
+        assert!(opt(&["--timeout", "0"]).common.cancel().is_cancelled());
+        assert!(!opt(&["--timeout", "60"]).common.cancel().is_cancelled());
+        assert_eq!(
+            opt(&["--timeout", "1.5"]).common.timeout,
+            Some(Duration::from_millis(1500))
+        );
+        assert!(Opt::from_iter_safe(["day", "--timeout", "-1"]).is_err());
+    }
+
+    #[test]
+    fn test_submit() {
+        assert!(!opt(&["-p"]).common.submit);
+        assert!(opt(&["-p", "--submit", "--part", "2"]).common.submit);
+        assert!(Opt::from_iter_safe(["day", "--submit"]).is_err());
+        assert!(Opt::from_iter_safe(["day", "-p", "--submit", "--input", "x.txt"]).is_err());
+    }
+
+    #[test]
+    fn test_time_limit() {
+        assert_eq!(opt(&[]).common.time_limit(24), 24);
+        assert_eq!(opt(&["--time-limit", "32"]).common.time_limit(24), 32);
+    }
+
+    #[test]
+    fn test_verbosity() {
+        let level = |args: &[&str]| opt(&args[1..]).common.verbosity.level();
+        assert_eq!(level(&["day"]), Level::WARN);
+        assert_eq!(level(&["day", "-v"]), Level::DEBUG);
+        assert_eq!(level(&["day", "-vv"]), Level::TRACE);
+        assert_eq!(level(&["day", "--verbose", "-vvv"]), Level::TRACE);
+    }
+}
@@ -0,0 +1,141 @@
+//! Day 1: Calorie Counting.
+
+use crate::solution::{self, Answer, Answers, Solution};
+use anyhow::{anyhow, bail, Context, Error};
+use std::{cmp::Reverse, collections::BinaryHeap};
+use tracing::debug;
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Elf {
+    pub count: u64,
+    /// One-based position in the input. Earlier elves win ties.
+    pub index: Reverse<usize>,
+}
+
+pub type ElfList = Vec<Elf>;
+
+/// The `k` elves carrying the most calories, most first, found in one pass
+/// over the lines while holding at most `k` elves. Any run of blank lines
+/// separates elves.
+pub fn top_elves(input_data: &str, k: usize) -> Result<ElfList, Error> {
+    let mut top = BinaryHeap::with_capacity(k + 1);
+    let mut keep = |elf: Elf| {
+        top.push(Reverse(elf));
+        if top.len() > k {
+            top.pop();
+        }
+    };
+
+    let mut current: Option<u64> = None;
+    let mut elf_count = 0;
+    for (line_index, line) in input_data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            if let Some(count) = current.take() {
+                elf_count += 1;
+                keep(Elf {
+                    count,
+                    index: Reverse(elf_count),
+                });
+            }
+            continue;
+        }
+        let calories: u64 = line
+            .parse()
+            .with_context(|| format!("line {}: {line:?}", line_index + 1))?;
+        *current.get_or_insert(0) += calories;
+    }
+    if let Some(count) = current {
+        keep(Elf {
+            count,
+            index: Reverse(elf_count + 1),
+        });
+    }
+
+    Ok(top
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(elf)| elf)
+        .collect())
+}
+
+pub const PART1_DATA: &str = include_str!("../data/day01.txt");
+pub const SAMPLE: &str = include_str!("../data/day01_sample.txt");
+
+/// Day 1 has no options.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options;
+
+pub struct Day01 {
+    elves: ElfList,
+}
+
+impl Day01 {
+    pub fn new(input: &str, _options: &Options) -> Result<Self, Error> {
+        Ok(Self {
+            elves: top_elves(input, 3)?,
+        })
+    }
+}
+
+impl Solution for Day01 {
+    const DAY: u8 = 1;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        let best = self.elves.first().ok_or_else(|| anyhow!("no elves"))?;
+        debug!(index = best.index.0, "best elf");
+        Ok(best.count.into())
+    }
+
+    fn part_2(&self) -> Result<Answer, Error> {
+        if self.elves.len() < 3 {
+            bail!("only {} elves", self.elves.len());
+        }
+        Ok(self.elves[0..3].iter().map(|e| e.count).sum::<u64>().into())
+    }
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day01::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let elves = top_elves(SAMPLE, 5).unwrap();
+        assert_eq!(elves.len(), 5);
+        let counts: Vec<_> = elves.iter().map(|e| e.count).collect();
+        assert_eq!(counts, [24000, 11000, 10000, 6000, 4000]);
+
+        let padded = format!("\n{}\n\n\n", SAMPLE.replace("\n\n", "\n  \n\n"));
+        assert_eq!(top_elves(&padded, 5).unwrap(), elves);
+
+        let error = top_elves("1000\n\n20x0", 3).unwrap_err();
+        assert!(format!("{error:#}").starts_with("line 3: \"20x0\""));
+    }
+
+    #[test]
+    fn test_sum() {
+        let elves = top_elves(SAMPLE, 3).unwrap();
+        assert_eq!(elves[0].index, Reverse(4));
+        let day = Day01 { elves };
+        assert_eq!(day.part_1().unwrap(), Answer::Number(24000));
+        assert_eq!(day.part_2().unwrap(), Answer::Number(45000));
+    }
+
+    #[test]
+    fn test_top_k() {
+        assert_eq!(top_elves(SAMPLE, 1).unwrap()[0].count, 24000);
+        assert!(top_elves(SAMPLE, 0).unwrap().is_empty());
+        assert_eq!(top_elves(SAMPLE, 10).unwrap().len(), 5);
+
+        // Ties go to the elf that came first.
+        let tied = top_elves("5\n\n7\n\n5\n\n3", 2).unwrap();
+        assert_eq!(tied[1].count, 5);
+        assert_eq!(tied[1].index, Reverse(1));
+    }
+}
@@ -0,0 +1,357 @@
+//! Day 2: Rock Paper Scissors.
+
+use crate::{
+    parse::parse_lines_with,
+    solution::{self, Answer, Answers, Solution},
+};
+use anyhow::{bail, Context, Error};
+use itertools::Itertools;
+use std::str::FromStr;
+
+pub const PART1_DATA: &str = include_str!("../data/day02.txt");
+pub const SAMPLE: &str = include_str!("../data/day02_sample.txt");
+
+/// One of a game's shapes, and the shapes it beats by index.
+#[derive(Debug)]
+struct Shape {
+    name: &'static str,
+    beats: &'static [usize],
+}
+
+/// A game in the rock paper scissors family. Shapes score one more than
+/// their index, and a shape beats the ones listed for it.
+#[derive(Debug)]
+pub struct Rules {
+    shapes: &'static [Shape],
+}
+
+pub const ROCK_PAPER_SCISSORS: Rules = Rules {
+    shapes: &[
+        Shape {
+            name: "Rock",
+            beats: &[2],
+        },
+        Shape {
+            name: "Paper",
+            beats: &[0],
+        },
+        Shape {
+            name: "Scissors",
+            beats: &[1],
+        },
+    ],
+};
+
+const ROCK_PAPER_SCISSORS_LIZARD_SPOCK: Rules = Rules {
+    shapes: &[
+        Shape {
+            name: "Rock",
+            beats: &[2, 3],
+        },
+        Shape {
+            name: "Paper",
+            beats: &[0, 4],
+        },
+        Shape {
+            name: "Scissors",
+            beats: &[1, 3],
+        },
+        Shape {
+            name: "Lizard",
+            beats: &[4, 1],
+        },
+        Shape {
+            name: "Spock",
+            beats: &[2, 0],
+        },
+    ],
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Lose,
+    Draw,
+    Win,
+}
+
+impl Outcome {
+    const ALL: [Outcome; 3] = [Self::Lose, Self::Draw, Self::Win];
+
+    fn score(&self) -> usize {
+        match self {
+            Self::Lose => 0,
+            Self::Draw => 3,
+            Self::Win => 6,
+        }
+    }
+}
+
+impl Rules {
+    fn len(&self) -> usize {
+        self.shapes.len()
+    }
+
+    fn outcome(&self, me: usize, them: usize) -> Outcome {
+        if me == them {
+            Outcome::Draw
+        } else if self.shapes[me].beats.contains(&them) {
+            Outcome::Win
+        } else {
+            Outcome::Lose
+        }
+    }
+
+    fn score(&self, me: usize, them: usize) -> usize {
+        me + 1 + self.outcome(me, them).score()
+    }
+
+    /// The lowest scoring shape to play against `them` for `outcome`.
+    fn shape_for(&self, them: usize, outcome: Outcome) -> usize {
+        (0..self.len())
+            .find(|me| self.outcome(*me, them) == outcome)
+            .expect("every shape beats and loses to something")
+    }
+
+    /// Letters in the strategy guide's second column, ending at `Z` so
+    /// that rock paper scissors gets the puzzle's `X`, `Y` and `Z`.
+    fn codes(&self) -> impl Iterator<Item = char> {
+        (b'Z' + 1 - self.len() as u8..=b'Z').map(char::from)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Game {
+    RockPaperScissors,
+    RockPaperScissorsLizardSpock,
+}
+
+impl Game {
+    pub fn rules(&self) -> &'static Rules {
+        match self {
+            Self::RockPaperScissors => &ROCK_PAPER_SCISSORS,
+            Self::RockPaperScissorsLizardSpock => &ROCK_PAPER_SCISSORS_LIZARD_SPOCK,
+        }
+    }
+}
+
+impl FromStr for Game {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "rps" => Self::RockPaperScissors,
+            "rpsls" => Self::RockPaperScissorsLizardSpock,
+            _ => bail!("unknown game {s:?}, expected rps or rpsls"),
+        })
+    }
+}
+
+/// A line of the strategy guide: the opponent's shape, and the letter for
+/// us as an index into [`Rules::codes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Turn {
+    them: usize,
+    code: usize,
+}
+
+pub fn parse_guide(s: &str, rules: &Rules) -> Result<Vec<Turn>, Error> {
+    let letter = |c: Option<char>, first: char, what: &str| {
+        c.and_then(|c| (c as usize).checked_sub(first as usize))
+            .filter(|index| *index < rules.len())
+            .with_context(|| format!("expected {what}"))
+    };
+    let first_code = rules.codes().next().expect("shapes");
+    parse_lines_with(s, |line| {
+        let mut parts = line.split_whitespace();
+        let them = parts.next().and_then(|p| p.chars().next());
+        let code = parts.next().and_then(|p| p.chars().next());
+        Ok(Turn {
+            them: letter(them, 'A', "the opponent's shape")?,
+            code: letter(code, first_code, "our letter")?,
+        })
+    })
+}
+
+/// What the second column of the strategy guide might mean.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Interpretation {
+    /// Each letter is the shape to play.
+    Plays(Vec<usize>),
+    /// Each letter is how the round should end.
+    Outcomes(Vec<Outcome>),
+}
+
+impl Interpretation {
+    /// The puzzle's first reading: `X` is rock, `Y` paper, `Z` scissors.
+    fn part_1(rules: &Rules) -> Self {
+        Self::Plays((0..rules.len()).collect())
+    }
+
+    /// The puzzle's second reading: `X` lose, `Y` draw, `Z` win.
+    fn part_2() -> Self {
+        Self::Outcomes(Outcome::ALL.to_vec())
+    }
+
+    /// Every way of assigning shapes to letters, and for games with three
+    /// letters every way of assigning outcomes.
+    fn all(rules: &Rules) -> Vec<Self> {
+        let mut all: Vec<_> = (0..rules.len())
+            .permutations(rules.len())
+            .map(Self::Plays)
+            .collect();
+        if rules.len() == Outcome::ALL.len() {
+            all.extend(
+                Outcome::ALL
+                    .into_iter()
+                    .permutations(Outcome::ALL.len())
+                    .map(Self::Outcomes),
+            );
+        }
+        all
+    }
+
+    fn shape(&self, rules: &Rules, turn: &Turn) -> usize {
+        match self {
+            Self::Plays(shapes) => shapes[turn.code],
+            Self::Outcomes(outcomes) => rules.shape_for(turn.them, outcomes[turn.code]),
+        }
+    }
+
+    fn score(&self, rules: &Rules, guide: &[Turn]) -> usize {
+        guide
+            .iter()
+            .map(|turn| rules.score(self.shape(rules, turn), turn.them))
+            .sum()
+    }
+
+    pub fn describe(&self, rules: &Rules) -> String {
+        let meanings: Vec<String> = match self {
+            Self::Plays(shapes) => shapes
+                .iter()
+                .map(|shape| rules.shapes[*shape].name.to_string())
+                .collect(),
+            Self::Outcomes(outcomes) => outcomes.iter().map(|o| format!("{o:?}")).collect(),
+        };
+        rules
+            .codes()
+            .zip(meanings)
+            .map(|(code, meaning)| format!("{code}={meaning}"))
+            .join(" ")
+    }
+}
+
+/// Interpretations of `guide` that give a total of `score`.
+pub fn infer(rules: &Rules, guide: &[Turn], score: usize) -> Vec<Interpretation> {
+    Interpretation::all(rules)
+        .into_iter()
+        .filter(|interpretation| interpretation.score(rules, guide) == score)
+        .collect()
+}
+
+/// Day 2 has no options.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options;
+
+pub struct Day02 {
+    guide: Vec<Turn>,
+}
+
+impl Day02 {
+    pub fn new(input: &str, _options: &Options) -> Result<Self, Error> {
+        Ok(Self {
+            guide: parse_guide(input, &ROCK_PAPER_SCISSORS)?,
+        })
+    }
+}
+
+impl Solution for Day02 {
+    const DAY: u8 = 2;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        let rules = &ROCK_PAPER_SCISSORS;
+        Ok(Interpretation::part_1(rules)
+            .score(rules, &self.guide)
+            .into())
+    }
+
+    fn part_2(&self) -> Result<Answer, Error> {
+        let rules = &ROCK_PAPER_SCISSORS;
+        Ok(Interpretation::part_2().score(rules, &self.guide).into())
+    }
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day02::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const RPS: &Rules = &ROCK_PAPER_SCISSORS;
+    const RPSLS: &Rules = &ROCK_PAPER_SCISSORS_LIZARD_SPOCK;
+
+    #[test]
+    fn test_parse() {
+        let turns = parse_guide(SAMPLE, RPS).unwrap();
+        assert_eq!(turns.len(), 3);
+        assert_eq!(turns[0], Turn { them: 0, code: 1 });
+        assert!(parse_guide("A Y\nD X", RPS).is_err());
+        assert!(parse_guide("A", RPS).is_err());
+        assert_eq!(
+            parse_guide("E V", RPSLS).unwrap(),
+            [Turn { them: 4, code: 0 }]
+        );
+    }
+
+    #[test]
+    fn test_score() {
+        let turns = parse_guide(SAMPLE, RPS).unwrap();
+        let score = Interpretation::part_1(RPS).score(RPS, &turns);
+        assert_eq!(score, 15);
+    }
+
+    #[test]
+    fn test_score_part2() {
+        let turns = parse_guide(SAMPLE, RPS).unwrap();
+        let score = Interpretation::part_2().score(RPS, &turns);
+        assert_eq!(score, 12);
+    }
+
+    #[test]
+    fn test_rules_are_consistent() {
+        for rules in [RPS, RPSLS] {
+            for (a, b) in (0..rules.len()).tuple_combinations() {
+                let a_wins = rules.outcome(a, b) == Outcome::Win;
+                let b_wins = rules.outcome(b, a) == Outcome::Win;
+                assert!(a_wins != b_wins, "{a} and {b}");
+            }
+            for them in 0..rules.len() {
+                for outcome in Outcome::ALL {
+                    let me = rules.shape_for(them, outcome);
+                    assert_eq!(rules.outcome(me, them), outcome);
+                }
+            }
+        }
+        assert_eq!(RPSLS.outcome(4, 0), Outcome::Win);
+        assert_eq!(RPSLS.outcome(3, 4), Outcome::Win);
+        assert_eq!(RPSLS.score(1, 4), 2 + 6);
+        assert_eq!(RPSLS.codes().collect::<String>(), "VWXYZ");
+    }
+
+    #[test]
+    fn test_infer() {
+        let turns = parse_guide(SAMPLE, RPS).unwrap();
+        assert_eq!(Interpretation::all(RPS).len(), 12);
+        assert!(infer(RPS, &turns, 15).contains(&Interpretation::part_1(RPS)));
+        assert!(infer(RPS, &turns, 12).contains(&Interpretation::part_2()));
+        assert!(infer(RPS, &turns, 1000).is_empty());
+        assert_eq!(
+            Interpretation::part_2().describe(RPS),
+            "X=Lose Y=Draw Z=Win"
+        );
+        assert_eq!(Interpretation::all(RPSLS).len(), 120);
+        assert_eq!("rpsls".parse::<Game>().unwrap().rules().len(), 5);
+    }
+}
@@ -0,0 +1,307 @@
+//! Day 3: Rucksack Reorganization.
+
+use crate::{
+    parse::parse_lines,
+    solution::{self, Answer, Answers, Solution},
+};
+use anyhow::{bail, ensure, Context, Error};
+use std::{collections::HashSet, fmt::Write, slice::Chunks, str::FromStr};
+
+pub const DATA: &str = include_str!("../data/day03.txt");
+pub const SAMPLE: &str = include_str!("../data/day03_sample.txt");
+
+fn as_priority(c: char, base_char: char, base_value: usize) -> usize {
+    c as usize - base_char as usize + base_value
+}
+
+#[derive(Debug)]
+pub struct Item(pub char);
+
+impl Item {
+    pub fn priority(&self) -> usize {
+        match self.0 {
+            'A'..='Z' => as_priority(self.0, 'A', 27),
+            'a'..='z' => as_priority(self.0, 'a', 1),
+            _ => panic!("unsupported item priority"),
+        }
+    }
+}
+
+impl From<char> for Item {
+    fn from(c: char) -> Self {
+        Self(c)
+    }
+}
+
+/// The one item type in `types`, or an error describing why there isn't
+/// exactly one.
+fn only_type(types: HashSet<char>, what: &str) -> Result<char, Error> {
+    let mut types: Vec<_> = types.into_iter().collect();
+    match types.len() {
+        1 => Ok(types[0]),
+        0 => bail!("no item type is in {what}"),
+        _ => {
+            types.sort_unstable();
+            bail!(
+                "more than one item type is in {what}: {}",
+                types.iter().collect::<String>()
+            )
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Rucksack {
+    compartments: [String; 2],
+}
+
+impl Rucksack {
+    pub fn misplaced_type(&self) -> Result<Item, Error> {
+        let contents_0: HashSet<_> = self.compartments[0].chars().collect();
+        let contents_1: HashSet<_> = self.compartments[1].chars().collect();
+        let misplaced = contents_0.intersection(&contents_1).copied().collect();
+        let what = format!("both compartments of {}", self.compartments.concat());
+        Ok(Item::from(only_type(misplaced, &what)?))
+    }
+
+    pub fn all_types(&self) -> HashSet<char> {
+        let contents_0: HashSet<_> = self.compartments[0].chars().collect();
+        let contents_1: HashSet<_> = self.compartments[1].chars().collect();
+        contents_0.union(&contents_1).copied().collect()
+    }
+}
+
+impl FromStr for Rucksack {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        if let Some(c) = s.chars().find(|c| !c.is_ascii_alphabetic()) {
+            bail!("{c:?} isn't an item type");
+        }
+        let len = s.len();
+        ensure!(
+            len.is_multiple_of(2),
+            "{len} items won't split between two compartments"
+        );
+        let slice = len / 2;
+        Ok(Self {
+            compartments: [s[0..slice].to_string(), s[slice..].to_string()],
+        })
+    }
+}
+
+pub fn parse_rucksacks(s: &str) -> Result<Vec<Rucksack>, Error> {
+    parse_lines(s)
+}
+
+fn sum_rucksacks(rucksacks: &[Rucksack]) -> Result<usize, Error> {
+    rucksacks
+        .iter()
+        .map(|sack| Ok(sack.misplaced_type()?.priority()))
+        .sum()
+}
+
+/// The one item type every rucksack in the group carries.
+fn find_badge(rucksacks: &[Rucksack]) -> Result<char, Error> {
+    let common = rucksacks
+        .iter()
+        .map(Rucksack::all_types)
+        .reduce(|common, types| common.intersection(&types).copied().collect())
+        .unwrap_or_default();
+    let what = format!("every rucksack of the group of {}", rucksacks.len());
+    only_type(common, &what)
+}
+
+/// Split the rucksacks into groups of `group_size`, which has to divide
+/// them evenly.
+fn groups(rucksacks: &[Rucksack], group_size: usize) -> Result<Chunks<'_, Rucksack>, Error> {
+    if group_size == 0 || !rucksacks.len().is_multiple_of(group_size) {
+        bail!(
+            "{} rucksacks can't be split into groups of {group_size}",
+            rucksacks.len()
+        );
+    }
+    Ok(rucksacks.chunks(group_size))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// Number of elves in each badge group.
+    pub group_size: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self { group_size: 3 }
+    }
+}
+
+pub struct Day03 {
+    rucksacks: Vec<Rucksack>,
+    group_size: usize,
+}
+
+impl Day03 {
+    pub fn new(input: &str, options: &Options) -> Result<Self, Error> {
+        Ok(Self {
+            rucksacks: parse_rucksacks(input)?,
+            group_size: options.group_size,
+        })
+    }
+}
+
+impl Solution for Day03 {
+    const DAY: u8 = 3;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        Ok(sum_rucksacks(&self.rucksacks)?.into())
+    }
+
+    fn part_2(&self) -> Result<Answer, Error> {
+        let mut priority = 0;
+        for (index, set) in groups(&self.rucksacks, self.group_size)?.enumerate() {
+            let badge = find_badge(set).with_context(|| format!("group {}", index + 1))?;
+            let badge_item = Item(badge);
+            priority += badge_item.priority();
+        }
+        Ok(priority.into())
+    }
+}
+
+pub fn report(rucksacks: &[Rucksack], group_size: usize) -> Result<String, Error> {
+    let mut report = String::new();
+    for (index, sack) in rucksacks.iter().enumerate() {
+        let item = sack.misplaced_type()?;
+        writeln!(
+            report,
+            "rucksack {}: misplaced {} priority {}",
+            index + 1,
+            item.0,
+            item.priority()
+        )?;
+    }
+    for (index, set) in groups(rucksacks, group_size)?.enumerate() {
+        let badge = Item(find_badge(set)?);
+        writeln!(
+            report,
+            "group {}: badge {} priority {}",
+            index + 1,
+            badge.0,
+            badge.priority()
+        )?;
+    }
+    Ok(report)
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day03::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_priority() {
+        assert_eq!(Item::from('A').priority(), 27);
+        assert_eq!(Item::from('D').priority(), 30);
+        assert_eq!(Item::from('a').priority(), 1);
+        assert_eq!(Item::from('b').priority(), 2);
+    }
+
+    #[test]
+    fn test_parse() {
+        let rucksacks = parse_rucksacks(SAMPLE).unwrap();
+        assert_eq!(rucksacks.len(), 6);
+        let sack_1 = &rucksacks[0];
+        assert_eq!(sack_1.compartments[0], "vJrwpWtwJgWr");
+        assert_eq!(sack_1.compartments[1], "hcsFMMfFFhFp");
+
+        let error = parse_rucksacks("abcd\nabc\n").unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "line 2: \"abc\": 3 items won't split between two compartments"
+        );
+        let error = parse_rucksacks("ab1d").unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "line 1: \"ab1d\": '1' isn't an item type"
+        );
+    }
+
+    #[test]
+    fn test_misplaced() {
+        let sack = "vJrwpWtwJgWrhcsFMMfFFhFp".parse::<Rucksack>().unwrap();
+        let misplaced = sack.misplaced_type().unwrap();
+        assert_eq!(misplaced.0, 'p');
+
+        let sack = "jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL"
+            .parse::<Rucksack>()
+            .unwrap();
+        let misplaced = sack.misplaced_type().unwrap();
+        assert_eq!(misplaced.0, 'L');
+
+        let error = "abcd"
+            .parse::<Rucksack>()
+            .unwrap()
+            .misplaced_type()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "no item type is in both compartments of abcd"
+        );
+    }
+
+    #[test]
+    fn test_sum_of_misplaced() {
+        let rucksacks = parse_rucksacks(SAMPLE).unwrap();
+        let sum = sum_rucksacks(&rucksacks).unwrap();
+        assert_eq!(sum, 157);
+    }
+
+    #[test]
+    fn test_find_group() {
+        const BADGES: &[char] = &['r', 'Z'];
+        let rucksacks = parse_rucksacks(SAMPLE).unwrap();
+        for (index, set) in rucksacks.chunks(3).enumerate() {
+            let badge = find_badge(set).unwrap();
+            assert_eq!(badge, BADGES[index]);
+        }
+    }
+
+    #[test]
+    fn test_group_size() {
+        let rucksacks = parse_rucksacks(SAMPLE).unwrap();
+        assert!(groups(&rucksacks, 4).is_err());
+        assert!(groups(&rucksacks, 0).is_err());
+        assert_eq!(
+            find_badge(&rucksacks[..1])
+                .unwrap_err()
+                .to_string()
+                .split(':')
+                .next(),
+            Some("more than one item type is in every rucksack of the group of 1")
+        );
+        assert!(find_badge(&rucksacks).is_err());
+
+        let day = Day03 {
+            rucksacks,
+            group_size: 2,
+        };
+        assert!(day.part_2().is_err());
+        let day = Day03 {
+            group_size: 3,
+            ..day
+        };
+        assert_eq!(day.part_2().unwrap(), Answer::Number(70));
+    }
+
+    #[test]
+    fn test_report() {
+        let report = report(&parse_rucksacks(SAMPLE).unwrap(), 3).unwrap();
+        assert!(report.starts_with("rucksack 1: misplaced p priority 16\n"));
+        assert!(report.ends_with("group 2: badge Z priority 52\n"));
+        assert_eq!(report.lines().count(), 8);
+    }
+}
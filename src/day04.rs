@@ -0,0 +1,212 @@
+//! Day 4: Camp Cleanup.
+
+use crate::{
+    intervals::IntervalSet,
+    solution::{self, Answer, Answers, Solution},
+};
+use anyhow::{anyhow, ensure, Context, Error};
+use itertools::Itertools;
+use std::{ops::RangeInclusive, str::FromStr};
+
+type Asssignment = RangeInclusive<usize>;
+
+#[derive(Debug)]
+struct Elf {
+    assignment: Asssignment,
+}
+
+impl Elf {
+    fn sections(&self) -> IntervalSet<usize> {
+        self.assignment.clone().into()
+    }
+
+    pub fn contains(&self, other: &Elf) -> bool {
+        let theirs = other.sections();
+        self.sections().intersect(&theirs) == theirs
+    }
+
+    /// Sections both elves were assigned.
+    pub fn overlap(&self, other: &Elf) -> IntervalSet<usize> {
+        self.sections().intersect(&other.sections())
+    }
+
+    pub fn overlaps(&self, other: &Elf) -> bool {
+        !self.overlap(other).is_empty()
+    }
+}
+
+impl FromStr for Elf {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s
+            .trim()
+            .split_once('-')
+            .ok_or_else(|| anyhow!("expected start-end, got {s:?}"))?;
+        let start = start.parse().with_context(|| format!("start of {s:?}"))?;
+        let end = end.parse().with_context(|| format!("end of {s:?}"))?;
+        ensure!(start <= end, "{s:?} ends before it starts");
+        Ok(Self {
+            assignment: start..=end,
+        })
+    }
+}
+
+/// Elves that were assigned sections together, two of them in the puzzle.
+#[derive(Debug)]
+pub struct ElfGroup {
+    elves: Vec<Elf>,
+}
+
+impl ElfGroup {
+    /// Every pair of different elves in the group.
+    fn pairs(&self) -> impl Iterator<Item = (&Elf, &Elf)> {
+        self.elves.iter().tuple_combinations()
+    }
+
+    /// Whether one elf's sections are all inside another's.
+    pub fn fully_contained(&self) -> bool {
+        self.pairs().any(|(a, b)| a.contains(b) || b.contains(a))
+    }
+
+    pub fn overlaps(&self) -> bool {
+        self.pairs().any(|(a, b)| a.overlaps(b))
+    }
+
+    /// Sections assigned to more than one elf in the group.
+    pub fn overlap(&self) -> IntervalSet<usize> {
+        self.pairs().fold(IntervalSet::new(), |overlap, (a, b)| {
+            overlap.merge(&a.overlap(b))
+        })
+    }
+}
+
+impl FromStr for ElfGroup {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let elves = s.split(',').map(str::parse).collect::<Result<_, _>>()?;
+        Ok(Self { elves })
+    }
+}
+
+pub fn parse_groups(s: &str) -> Result<Vec<ElfGroup>, Error> {
+    s.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            line.parse()
+                .with_context(|| format!("line {}: {line:?}", i + 1))
+        })
+        .collect()
+}
+
+fn count_fully_contained_groups(groups: &[ElfGroup]) -> usize {
+    groups
+        .iter()
+        .filter(|group| group.fully_contained())
+        .count()
+}
+
+fn count_overlapping_groups(groups: &[ElfGroup]) -> usize {
+    groups.iter().filter(|group| group.overlaps()).count()
+}
+
+pub const DATA: &str = include_str!("../data/day04.txt");
+pub const SAMPLE: &str = include_str!("../data/day04_sample.txt");
+
+/// Day 4 has no options.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options;
+
+pub struct Day04 {
+    groups: Vec<ElfGroup>,
+}
+
+impl Day04 {
+    pub fn new(input: &str, _options: &Options) -> Result<Self, Error> {
+        Ok(Self {
+            groups: parse_groups(input)?,
+        })
+    }
+}
+
+impl Solution for Day04 {
+    const DAY: u8 = 4;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        Ok(count_fully_contained_groups(&self.groups).into())
+    }
+
+    fn part_2(&self) -> Result<Answer, Error> {
+        Ok(count_overlapping_groups(&self.groups).into())
+    }
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day04::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let groups = parse_groups(SAMPLE).unwrap();
+        assert_eq!(groups.len(), 6);
+        let first_group = &groups[0];
+        assert_eq!(first_group.elves[0].assignment, 2..=4);
+        assert_eq!(first_group.elves[1].assignment, 6..=8);
+
+        let pen_group = &groups[4];
+        assert_eq!(pen_group.elves[0].assignment, 6..=6);
+        assert_eq!(pen_group.elves[1].assignment, 4..=6);
+
+        let last_group = &groups[5];
+        assert_eq!(last_group.elves[0].assignment, 2..=6);
+        assert_eq!(last_group.elves[1].assignment, 4..=8);
+
+        assert!(parse_groups("2-4,6-8\n")
+            .unwrap()
+            .iter()
+            .all(|g| g.elves.len() == 2));
+        let error = parse_groups("2-4,6-8\n2-x,4-5").unwrap_err();
+        assert!(format!("{error:#}").contains("line 2"));
+        assert!(parse_groups("2-4;6-8").is_err());
+
+        let error = parse_groups("2-4,6-8\n4-2,2-4").unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "line 2: \"4-2,2-4\": \"4-2\" ends before it starts"
+        );
+    }
+
+    #[test]
+    fn test_count_fully_contained_groups() {
+        let groups = parse_groups(SAMPLE).unwrap();
+        let fully = count_fully_contained_groups(&groups);
+        assert_eq!(fully, 2);
+    }
+
+    #[test]
+    fn test_overlapping_groups() {
+        let groups = parse_groups(SAMPLE).unwrap();
+        let fully = count_overlapping_groups(&groups);
+        assert_eq!(fully, 4);
+        let lengths: Vec<_> = groups.iter().map(|g| g.overlap().len()).collect();
+        assert_eq!(lengths, [0, 0, 1, 5, 1, 3]);
+    }
+
+    #[test]
+    fn test_larger_groups() {
+        let groups = parse_groups("1-3,5-7,9-9\n1-4,3-6,5-8\n1-9,2-2,20-30").unwrap();
+        assert!(!groups[0].overlaps());
+        assert!(groups[1].overlaps());
+        assert!(!groups[1].fully_contained());
+        assert_eq!(groups[1].overlap().intervals(), &[3..=6]);
+        assert!(groups[2].fully_contained());
+        assert_eq!(groups[2].overlap().len(), 1);
+    }
+}
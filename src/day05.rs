@@ -0,0 +1,359 @@
+//! Day 5: Supply Stacks.
+
+use crate::solution::{self, Answer, Answers, Solution};
+use anyhow::{anyhow, bail, Error};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, digit1, line_ending, multispace0, satisfy, space0, space1, u64},
+    combinator::{all_consuming, cut, map, not, value},
+    error::{context, convert_error, VerboseError},
+    multi::{count, many0, separated_list0, separated_list1},
+    sequence::{delimited, preceded, terminated, tuple},
+    Finish, IResult,
+};
+use std::str::FromStr;
+
+pub const DATA: &str = include_str!("../data/day05.txt");
+pub const SAMPLE: &str = include_str!("../data/day05_sample.txt");
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Move {
+    pub count: usize,
+    pub source: usize,
+    pub destination: usize,
+    /// The line of the input the move is on.
+    pub line: usize,
+}
+
+/// The crane doing the moving, which decides what order a move's crates
+/// end up in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CraneModel {
+    /// Moves one crate at a time, so a move's crates land reversed.
+    CrateMover9000,
+    /// Moves all of a move's crates at once, keeping their order.
+    CrateMover9001,
+    /// Alternates: the first move keeps its order, the second is reversed,
+    /// and so on.
+    CrateMover9002,
+}
+
+impl FromStr for CraneModel {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "9000" => Self::CrateMover9000,
+            "9001" => Self::CrateMover9001,
+            "9002" => Self::CrateMover9002,
+            _ => bail!("unknown crane model {s:?}, expected 9000, 9001 or 9002"),
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct StackMap {
+    stacks: Vec<Vec<char>>,
+    /// Moves executed so far.
+    moves_made: usize,
+}
+
+impl StackMap {
+    pub fn add_item(&mut self, index: usize, item: char) {
+        if self.stacks.len() <= index {
+            self.stacks.resize_with(index + 1, Default::default);
+        }
+        let stack = &mut self.stacks[index];
+        stack.push(item);
+    }
+
+    /// Make `move_order`, unless its source stack hasn't got enough crates.
+    pub fn execute(&mut self, move_order: &Move, model: CraneModel) -> Result<(), Error> {
+        let reversed = match model {
+            CraneModel::CrateMover9000 => true,
+            CraneModel::CrateMover9001 => false,
+            CraneModel::CrateMover9002 => self.moves_made % 2 == 1,
+        };
+        let Move {
+            count,
+            source,
+            destination,
+            line,
+        } = *move_order;
+        let height = self.stacks[source].len();
+        if count > height {
+            bail!(
+                "line {line}: move {count} from {} to {}, but stack {} only has {height} crates",
+                source + 1,
+                destination + 1,
+                source + 1
+            );
+        }
+        let mut crates: Vec<_> = self.stacks[source].drain(0..count).collect();
+        if reversed {
+            crates.reverse();
+        }
+        self.stacks[destination].splice(0..0, crates);
+        self.moves_made += 1;
+        Ok(())
+    }
+
+    pub fn execute_all(&mut self, moves: &[Move], model: CraneModel) -> Result<(), Error> {
+        for move_order in moves {
+            self.execute(move_order, model)?;
+        }
+        Ok(())
+    }
+
+    pub fn top_crates(&self) -> String {
+        self.stacks
+            .iter()
+            .filter_map(|stack| stack.first())
+            .collect()
+    }
+}
+
+type ParseResult<'a, T> = IResult<&'a str, T, VerboseError<&'a str>>;
+
+fn number(input: &str) -> ParseResult<'_, usize> {
+    map(u64, |v| v as usize)(input)
+}
+
+/// One column of the picture: `[A]` for a crate or three spaces for none.
+fn crate_cell(input: &str) -> ParseResult<'_, Option<char>> {
+    alt((
+        map(
+            delimited(char('['), satisfy(|c| c.is_ascii_alphabetic()), char(']')),
+            Some,
+        ),
+        value(None, tag("   ")),
+    ))(input)
+}
+
+/// A line of the picture. Anything before the stack numbers has to be
+/// one, so a malformed row is reported rather than taken for the footer.
+fn crate_row(input: &str) -> ParseResult<'_, Vec<Option<char>>> {
+    preceded(
+        not(tuple((space0, digit1))),
+        context(
+            "crate row",
+            cut(terminated(
+                separated_list1(char(' '), crate_cell),
+                tuple((space0, line_ending)),
+            )),
+        ),
+    )(input)
+}
+
+fn stack_numbers(input: &str) -> ParseResult<'_, Vec<usize>> {
+    context(
+        "stack numbers",
+        delimited(space0, separated_list1(space1, number), space0),
+    )(input)
+}
+
+fn move_line(input: &str) -> ParseResult<'_, Move> {
+    context(
+        "move",
+        preceded(
+            tag("move "),
+            cut(map(
+                tuple((
+                    number,
+                    preceded(tag(" from "), number),
+                    preceded(tag(" to "), number),
+                )),
+                |(count, source, destination)| Move {
+                    count,
+                    source,
+                    destination,
+                    line: 0,
+                },
+            )),
+        ),
+    )(input)
+}
+
+/// Parse the crate picture and the moves. Stack numbers in the moves are
+/// checked against the picture's footer and made zero-based.
+fn parse_data(data: &str) -> Result<(StackMap, Vec<Move>), Error> {
+    let (rows, footer, moves) = all_consuming(tuple((
+        many0(crate_row),
+        terminated(stack_numbers, count(line_ending, 2)),
+        terminated(separated_list0(line_ending, move_line), multispace0),
+    )))(data)
+    .finish()
+    .map(|(_, parsed)| parsed)
+    .map_err(|e| anyhow!("failed to parse crates:\n{}", convert_error(data, e)))?;
+
+    let stack_count = footer.len();
+    let footer_line = rows.len() + 1;
+    if !footer.iter().copied().eq(1..=stack_count) {
+        bail!("line {footer_line}: stacks should be numbered 1 to {stack_count}, got {footer:?}");
+    }
+
+    let mut stack_map = StackMap {
+        stacks: vec![vec![]; stack_count],
+        ..StackMap::default()
+    };
+    for (line, row) in rows.iter().enumerate() {
+        if row.len() > stack_count {
+            bail!(
+                "line {}: {} columns of crates but only {stack_count} stacks",
+                line + 1,
+                row.len()
+            );
+        }
+        for (index, item) in row.iter().enumerate() {
+            if let Some(item) = item {
+                stack_map.add_item(index, *item);
+            }
+        }
+    }
+
+    let first_move_line = footer_line + 2;
+    let moves = moves
+        .into_iter()
+        .enumerate()
+        .map(|(i, m)| {
+            let line = first_move_line + i;
+            if m.count == 0 {
+                bail!("line {line}: a move of no crates");
+            }
+            for stack in [m.source, m.destination] {
+                if !(1..=stack_count).contains(&stack) {
+                    bail!("line {line}: no stack {stack}, there are {stack_count}");
+                }
+            }
+            Ok(Move {
+                source: m.source - 1,
+                destination: m.destination - 1,
+                line,
+                ..m
+            })
+        })
+        .collect::<Result<_, Error>>()?;
+
+    Ok((stack_map, moves))
+}
+
+/// Day 5 has no options.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options;
+
+pub struct Day05 {
+    map: StackMap,
+    moves: Vec<Move>,
+}
+
+impl Day05 {
+    pub fn new(input: &str, _options: &Options) -> Result<Self, Error> {
+        let (map, moves) = parse_data(input)?;
+        Ok(Self { map, moves })
+    }
+
+    pub fn top_crates(&self, model: CraneModel) -> Result<String, Error> {
+        let mut map = self.map.clone();
+        map.execute_all(&self.moves, model)?;
+        Ok(map.top_crates())
+    }
+}
+
+impl Solution for Day05 {
+    const DAY: u8 = 5;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        Ok(self.top_crates(CraneModel::CrateMover9000)?.into())
+    }
+
+    fn part_2(&self) -> Result<Answer, Error> {
+        Ok(self.top_crates(CraneModel::CrateMover9001)?.into())
+    }
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day05::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let (map, moves) = parse_data(SAMPLE).expect("parse");
+        assert_eq!(map.stacks.len(), 3);
+        assert_eq!(map.stacks[0], ['N', 'Z']);
+        assert_eq!(map.stacks[1], ['D', 'C', 'M']);
+        assert_eq!(map.stacks[2], ['P']);
+        assert_eq!(moves.len(), 4);
+        assert_eq!(moves[1].count, 3);
+        assert_eq!(moves[1].source, 0);
+        assert_eq!(moves[1].destination, 2);
+
+        let padded = SAMPLE.replace("    [D]", "    [D]    ") + "\n";
+        assert_eq!(parse_data(&padded).expect("parse").0.stacks, map.stacks);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |s: &str| parse_data(s).unwrap_err().to_string();
+        assert!(error(&SAMPLE.replace("[C]", "[C")).contains("in crate row"));
+        assert!(
+            error(&SAMPLE.replace(" 3\n", " 4\n")).contains("line 4: stacks should be numbered")
+        );
+        assert!(error(&SAMPLE.replace("[P]", "[P] [Q]")).contains("line 3: 4 columns"));
+        assert!(error(&SAMPLE.replace("to 3", "to 4")).contains("line 7: no stack 4"));
+        assert!(error(&SAMPLE.replace("move 1 from 1", "move 0 from 1")).contains("line 9:"));
+        assert!(error(&SAMPLE.replace("from 2 to 1\n", "from 2 too 1\n")).contains("in move"));
+    }
+
+    fn top_crates(model: CraneModel) -> String {
+        let (mut map, moves) = parse_data(SAMPLE).expect("parse");
+        map.execute_all(&moves, model).unwrap();
+        map.top_crates()
+    }
+
+    #[test]
+    fn test_crate_mover_9000() {
+        assert_eq!(top_crates(CraneModel::CrateMover9000), "CMZ");
+    }
+
+    #[test]
+    fn test_crate_mover_9001() {
+        assert_eq!(top_crates(CraneModel::CrateMover9001), "MCD");
+    }
+
+    #[test]
+    fn test_crate_mover_9002() {
+        let (mut map, moves) = parse_data(SAMPLE).expect("parse");
+        map.execute(&moves[0], CraneModel::CrateMover9002).unwrap();
+        assert_eq!(map.stacks[0], ['D', 'N', 'Z']);
+        map.execute(&moves[1], CraneModel::CrateMover9002).unwrap();
+        assert_eq!(map.stacks[2], ['Z', 'N', 'D', 'P']);
+        assert_eq!(top_crates(CraneModel::CrateMover9002), "MCZ");
+    }
+
+    #[test]
+    fn test_too_few_crates() {
+        let day = Day05::new(&SAMPLE.replace("move 3 from 1", "move 5 from 1"), &Options).unwrap();
+        for model in [CraneModel::CrateMover9000, CraneModel::CrateMover9002] {
+            assert_eq!(
+                day.top_crates(model).unwrap_err().to_string(),
+                "line 7: move 5 from 1 to 3, but stack 1 only has 3 crates"
+            );
+        }
+        assert!(day.part_2().is_err());
+    }
+
+    #[test]
+    fn test_crane_model() {
+        assert_eq!(
+            "9001".parse::<CraneModel>().unwrap(),
+            CraneModel::CrateMover9001
+        );
+        assert!("9003".parse::<CraneModel>().is_err());
+    }
+}
@@ -0,0 +1,172 @@
+//! Day 6: Tuning Trouble.
+
+use crate::solution::{self, Answer, Answers, Solution};
+use anyhow::{anyhow, Error};
+use std::{
+    collections::VecDeque,
+    io::{self, BufReader, Read},
+};
+
+pub const DATA: &str = include_str!("../data/day06.txt");
+pub const SAMPLE: &str = include_str!("../data/day06_sample.txt");
+
+/// Watches a stream for a window of `window` bytes that are all different.
+/// Each byte's count in the window is kept, along with how many byte values
+/// appear more than once, so every byte is handled in constant time.
+#[derive(Debug)]
+pub struct Scanner {
+    window: usize,
+    buffer: VecDeque<u8>,
+    counts: [usize; 256],
+    repeated: usize,
+    received: usize,
+}
+
+impl Scanner {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window,
+            buffer: VecDeque::with_capacity(window + 1),
+            counts: [0; 256],
+            repeated: 0,
+            received: 0,
+        }
+    }
+
+    pub fn received(&mut self, b: u8) {
+        if self.buffer.len() >= self.window {
+            let old = self.buffer.pop_front().expect("full buffer") as usize;
+            self.counts[old] -= 1;
+            if self.counts[old] == 1 {
+                self.repeated -= 1;
+            }
+        }
+        self.buffer.push_back(b);
+        self.counts[b as usize] += 1;
+        if self.counts[b as usize] == 2 {
+            self.repeated += 1;
+        }
+        self.received += 1;
+    }
+
+    /// Whether the last `window` bytes were all different.
+    pub fn at_marker(&self) -> bool {
+        self.buffer.len() == self.window && self.repeated == 0
+    }
+
+    pub fn received_count(&self) -> usize {
+        self.received
+    }
+
+    /// How many bytes have to be read before the first marker.
+    pub fn scan(bytes: impl IntoIterator<Item = u8>, window: usize) -> Option<usize> {
+        let mut scanner = Scanner::new(window);
+        for b in bytes {
+            scanner.received(b);
+            if scanner.at_marker() {
+                return Some(scanner.received_count());
+            }
+        }
+        None
+    }
+
+    pub fn run_scanner(data: &str, window: usize) -> Option<usize> {
+        Self::scan(data.bytes(), window)
+    }
+
+    /// Like `run_scanner`, reading a stream only as far as the marker.
+    pub fn scan_reader(reader: impl Read, window: usize) -> io::Result<Option<usize>> {
+        let mut error = None;
+        let marker = Self::scan(
+            BufReader::new(reader)
+                .bytes()
+                .map_while(|b| b.map_err(|e| error = Some(e)).ok()),
+            window,
+        );
+        match error {
+            Some(e) => Err(e),
+            None => Ok(marker),
+        }
+    }
+}
+
+/// Day 6 has no options.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options;
+
+pub struct Day06 {
+    input: String,
+}
+
+impl Day06 {
+    pub fn new(input: &str, _options: &Options) -> Result<Self, Error> {
+        Ok(Self {
+            input: input.to_string(),
+        })
+    }
+}
+
+impl Solution for Day06 {
+    const DAY: u8 = 6;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        let received_count = Scanner::run_scanner(&self.input, 4);
+        Ok(received_count
+            .ok_or_else(|| anyhow!("no start-of-packet marker"))?
+            .into())
+    }
+
+    fn part_2(&self) -> Result<Answer, Error> {
+        let received_count = Scanner::run_scanner(&self.input, 14);
+        Ok(received_count
+            .ok_or_else(|| anyhow!("no start-of-message marker"))?
+            .into())
+    }
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day06::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_scanner_for_data<const N: usize>(expected: usize, data: &str) {
+        let received_count = Scanner::run_scanner(data, N);
+        assert_eq!(received_count, Some(expected));
+        let streamed = Scanner::scan_reader(data.as_bytes(), N).unwrap();
+        assert_eq!(streamed, Some(expected));
+    }
+
+    #[test]
+    fn test_scanner() {
+        test_scanner_for_data::<4>(7, SAMPLE);
+        test_scanner_for_data::<4>(5, "bvwbjplbgvbhsrlpgdmjqwftvncz");
+        test_scanner_for_data::<4>(10, "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg");
+        test_scanner_for_data::<4>(11, "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw");
+
+        test_scanner_for_data::<14>(19, SAMPLE);
+        test_scanner_for_data::<14>(23, "bvwbjplbgvbhsrlpgdmjqwftvncz");
+        test_scanner_for_data::<14>(23, "nppdvjthqldpwncqszvftbrmjlhg");
+        test_scanner_for_data::<14>(29, "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg");
+        test_scanner_for_data::<14>(26, "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw");
+    }
+
+    #[test]
+    fn test_scanner_windows() {
+        assert_eq!(Scanner::run_scanner("aaaa", 1), Some(1));
+        assert_eq!(Scanner::run_scanner("aab", 2), Some(3));
+        assert_eq!(Scanner::run_scanner("abab", 3), None);
+        assert_eq!(Scanner::run_scanner("", 4), None);
+
+        // A long run of repeats before the marker.
+        let long = "ab".repeat(100_000) + "cd";
+        assert_eq!(Scanner::run_scanner(&long, 4), Some(200_002));
+        assert_eq!(
+            Scanner::scan_reader(long.as_bytes(), 4).unwrap(),
+            Some(200_002)
+        );
+    }
+}
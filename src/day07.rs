@@ -0,0 +1,461 @@
+//! Day 7: No Space Left On Device.
+
+use crate::solution::{self, Answer, Answers, Solution};
+use anyhow::{anyhow, bail, ensure, Context, Error};
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use tracing::debug;
+
+#[derive(Debug, PartialEq, Clone)]
+enum Line {
+    Ls,
+    Cd(String),
+    File(String, usize),
+    Directory(String),
+}
+
+impl FromStr for Line {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let mut word_iter = s.split(' ');
+        let first_word = word_iter.next().unwrap_or_default();
+        let name = |name: Option<&str>, what: &str| match name {
+            Some(name) if !name.is_empty() => Ok(name.to_string()),
+            _ => Err(anyhow!("{what} with no name")),
+        };
+        Ok(match first_word {
+            "$" => match word_iter.next().unwrap_or_default() {
+                "cd" => Line::Cd(name(word_iter.next(), "a cd")?),
+                "ls" => Line::Ls,
+                command => bail!("unknown command {command:?}"),
+            },
+            "dir" => Line::Directory(name(word_iter.next(), "a directory")?),
+            _ => {
+                let size = first_word
+                    .parse::<usize>()
+                    .with_context(|| format!("{first_word:?} isn't a file size"))?;
+                Line::File(name(word_iter.next(), "a file")?, size)
+            }
+        })
+    }
+}
+
+pub const DATA: &str = include_str!("../data/day07.txt");
+pub const SAMPLE: &str = include_str!("../data/day07_sample.txt");
+
+/// The directories being listed, from the root down to the current one,
+/// with the size of what's been seen in each so far.
+struct SizeStream<I> {
+    lines: I,
+    stack: Vec<(PathBuf, u64)>,
+    /// Directories finished with but not yet returned.
+    finished: VecDeque<(PathBuf, u64)>,
+    /// Directories whose files have been counted. A listing is a snapshot
+    /// of the directory, so listing it again adds nothing new.
+    listed: HashSet<PathBuf>,
+    /// Whether the files being listed have been counted already.
+    relisting: bool,
+    /// How many lines have been read.
+    line_count: usize,
+}
+
+impl<I> SizeStream<I> {
+    /// Leave the current directory, which is now complete.
+    fn pop(&mut self) {
+        if let Some((path, size)) = self.stack.pop() {
+            if let Some((_, parent_size)) = self.stack.last_mut() {
+                *parent_size += size;
+            }
+            self.finished.push_back((path, size));
+        }
+    }
+
+    /// Follow `cd` to `target`, which may be absolute and may have several
+    /// components, leaving each directory it climbs out of.
+    fn cd(&mut self, target: &str) {
+        if target.starts_with('/') {
+            while self.stack.len() > 1 {
+                self.pop();
+            }
+        }
+        for name in target.split('/').filter(|name| !name.is_empty()) {
+            match name {
+                "." => {}
+                ".." => {
+                    if self.stack.len() > 1 {
+                        self.pop();
+                    }
+                }
+                _ => {
+                    let path = self
+                        .stack
+                        .last()
+                        .map_or_else(|| PathBuf::from("/"), |(parent, _)| parent.join(name));
+                    self.stack.push((path, 0));
+                }
+            }
+        }
+    }
+}
+
+impl<I, S> Iterator for SizeStream<I>
+where
+    I: Iterator<Item = S>,
+    S: AsRef<str>,
+{
+    type Item = Result<(PathBuf, u64), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.finished.is_empty() {
+            let Some(line) = self.lines.next() else {
+                // Everything still open is complete at the end.
+                while !self.stack.is_empty() {
+                    self.pop();
+                }
+                break;
+            };
+            self.line_count += 1;
+            let line = line.as_ref();
+            let line = match line.parse::<Line>() {
+                Ok(line) => line,
+                Err(error) => {
+                    let line_count = self.line_count;
+                    return Some(Err(error.context(format!("line {line_count}: {line:?}"))));
+                }
+            };
+            match line {
+                Line::Cd(target) => {
+                    self.relisting = false;
+                    self.cd(&target);
+                }
+                Line::Ls => {
+                    self.relisting = self
+                        .stack
+                        .last()
+                        .is_some_and(|(path, _)| !self.listed.insert(path.clone()));
+                }
+                Line::File(_, size) => {
+                    if self.relisting {
+                        continue;
+                    }
+                    if let Some((_, dir_size)) = self.stack.last_mut() {
+                        *dir_size += size as u64;
+                    }
+                }
+                Line::Directory(_) => {}
+            }
+        }
+        self.finished.pop_front().map(Ok)
+    }
+}
+
+/// The total size of each directory in a terminal transcript, produced as
+/// soon as the transcript leaves it, so that only the directories between
+/// the root and the current one, and the names of those already listed,
+/// are held at once however long the transcript is. The root comes last.
+///
+/// A directory the transcript goes back into is produced again for each
+/// visit, with the size of what was found on that visit, so that the sizes
+/// given for a path add up to its total; `total_sizes` does the adding.
+/// Only a directory's first listing counts. A line that isn't a command
+/// or a listing is an error.
+fn stream_sizes<S: AsRef<str>>(
+    lines: impl IntoIterator<Item = S>,
+) -> impl Iterator<Item = Result<(PathBuf, u64), Error>> {
+    SizeStream {
+        lines: lines.into_iter(),
+        stack: vec![(PathBuf::from("/"), 0)],
+        finished: VecDeque::new(),
+        listed: HashSet::new(),
+        relisting: false,
+        line_count: 0,
+    }
+}
+
+/// Each directory's total size, however often the transcript visits it.
+fn total_sizes<S: AsRef<str>>(
+    lines: impl IntoIterator<Item = S>,
+) -> Result<BTreeMap<PathBuf, u64>, Error> {
+    let mut totals = BTreeMap::new();
+    for entry in stream_sizes(lines) {
+        let (path, size) = entry?;
+        *totals.entry(path).or_default() += size;
+    }
+    Ok(totals)
+}
+
+const SIZE_LIMIT: u64 = 100_000;
+
+fn find_sum_of_smalls(sizes: &BTreeMap<PathBuf, u64>) -> u64 {
+    sizes
+        .values()
+        .copied()
+        .filter(|size| *size <= SIZE_LIMIT)
+        .sum()
+}
+
+fn find_candidates(sizes: &BTreeMap<PathBuf, u64>, needed: u64) -> Vec<(u64, PathBuf)> {
+    sizes
+        .iter()
+        .filter(|(_, size)| **size >= needed)
+        .map(|(path, size)| (*size, path.clone()))
+        .collect()
+}
+
+const CAPACITY: u64 = 70_000_000;
+const SPACE_NEEDED: u64 = 30_000_000;
+
+/// Day 7 has no options.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options;
+
+pub struct Day07 {
+    /// Each directory's total size.
+    sizes: BTreeMap<PathBuf, u64>,
+}
+
+impl Day07 {
+    pub fn new(input: &str, _options: &Options) -> Result<Self, Error> {
+        ensure!(!input.trim().is_empty(), "no terminal output");
+        Ok(Self {
+            sizes: total_sizes(input.lines())?,
+        })
+    }
+
+    fn used_size(&self) -> u64 {
+        self.sizes.get(Path::new("/")).copied().unwrap_or_default()
+    }
+}
+
+impl Solution for Day07 {
+    const DAY: u8 = 7;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        Ok(find_sum_of_smalls(&self.sizes).into())
+    }
+
+    fn part_2(&self) -> Result<Answer, Error> {
+        let used_size = self.used_size();
+        let free_size = CAPACITY.checked_sub(used_size).with_context(|| {
+            format!("the files take up {used_size}, more than the disk's {CAPACITY}")
+        })?;
+        let target_min_size = SPACE_NEEDED.saturating_sub(free_size);
+        debug!(used_size, free_size, target_min_size);
+
+        let mut candidates = find_candidates(&self.sizes, target_min_size);
+        candidates.sort();
+        let (size, _) = candidates
+            .first()
+            .context("no directory is big enough to free the space")?;
+        Ok((*size).into())
+    }
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day07::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::{BufRead, Cursor};
+
+    fn dir_size(sizes: &BTreeMap<PathBuf, u64>, path: &str) -> Option<u64> {
+        sizes.get(Path::new(path)).copied()
+    }
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!("$ ls".parse::<Line>().unwrap(), Line::Ls);
+        assert_eq!(
+            "$ cd ..".parse::<Line>().unwrap(),
+            Line::Cd("..".to_string())
+        );
+        assert_eq!("$ cd a".parse::<Line>().unwrap(), Line::Cd("a".to_string()));
+        assert_eq!(
+            "0 a".parse::<Line>().unwrap(),
+            Line::File("a".to_string(), 0)
+        );
+        assert_eq!(
+            "dir b".parse::<Line>().unwrap(),
+            Line::Directory("b".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = total_sizes(["$ cd /", "$ ls", "12k a"]).unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "line 3: \"12k a\": \"12k\" isn't a file size: invalid digit found in string"
+        );
+        let error = total_sizes(["$ cd /", "$ rm a"]).unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "line 2: \"$ rm a\": unknown command \"rm\""
+        );
+        assert!("$ cd".parse::<Line>().is_err());
+        assert!("100".parse::<Line>().is_err());
+        assert!(matches!(
+            solve("", &Options),
+            Err(crate::Error::Parse { day: 7, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_sample() {
+        let lines: Vec<_> = SAMPLE
+            .lines()
+            .map(|line| line.parse::<Line>().unwrap())
+            .collect();
+        assert_eq!(lines.len(), 23);
+        assert_eq!(lines[0], Line::Cd("/".to_string()));
+        assert_eq!(lines[22], Line::File("k".to_string(), 7214296));
+
+        // Directories come out as the transcript leaves them.
+        let order: Vec<_> = stream_sizes(SAMPLE.lines())
+            .map(|entry| entry.unwrap())
+            .map(|(path, _)| path.to_str().unwrap().to_string())
+            .collect();
+        assert_eq!(order, ["/a/e", "/a", "/d", "/"]);
+
+        let day = Day07 {
+            sizes: total_sizes(SAMPLE.lines()).unwrap(),
+        };
+        assert_eq!(day.used_size(), 48381165);
+        assert_eq!(dir_size(&day.sizes, "/a/e"), Some(584));
+        assert_eq!(dir_size(&day.sizes, "/a"), Some(94853));
+        assert_eq!(dir_size(&day.sizes, "/d"), Some(24933642));
+        assert!(dir_size(&day.sizes, "/b").is_none());
+
+        assert_eq!(find_sum_of_smalls(&day.sizes), 95437);
+
+        let free_size = CAPACITY - day.used_size();
+        let mut candidates = find_candidates(&day.sizes, SPACE_NEEDED - free_size);
+        candidates.sort();
+        assert_eq!(candidates[0], (24933642, PathBuf::from("/d")));
+        assert_eq!(day.part_2().unwrap(), Answer::from(24933642));
+    }
+
+    #[test]
+    fn test_prefix_directories() {
+        const PREFIXED: &str = r#"$ cd /
+$ ls
+dir a
+dir ab
+$ cd a
+$ ls
+100 x
+$ cd ..
+$ cd ab
+$ ls
+dir a
+20000 y
+$ cd a
+$ ls
+3 z"#;
+        let sizes = total_sizes(PREFIXED.lines()).unwrap();
+        assert_eq!(dir_size(&sizes, "/a"), Some(100));
+        assert_eq!(dir_size(&sizes, "/ab"), Some(20003));
+        assert_eq!(dir_size(&sizes, "/ab/a"), Some(3));
+        assert_eq!(dir_size(&sizes, "/"), Some(20103));
+        assert_eq!(find_sum_of_smalls(&sizes), 20103 + 100 + 20003 + 3);
+
+        let mut candidates = find_candidates(&sizes, 150);
+        candidates.sort();
+        assert_eq!(
+            candidates,
+            [(20003, PathBuf::from("/ab")), (20103, PathBuf::from("/"))]
+        );
+    }
+
+    #[test]
+    fn test_stream_sizes() {
+        // Lines read from a file work as well as borrowed ones.
+        let sizes: Vec<_> = stream_sizes(Cursor::new(SAMPLE).lines().map(Result::unwrap))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(sizes.len(), 4);
+
+        // A transcript that never ends still gives sizes as it goes.
+        let endless = ["$ cd /"]
+            .into_iter()
+            .chain(["$ cd a", "$ ls", "10 x", "$ cd .."].into_iter().cycle());
+        let first: Vec<_> = stream_sizes(endless)
+            .take(3)
+            .map(|entry| entry.unwrap().1)
+            .collect();
+        assert_eq!(first, [10, 0, 0]);
+    }
+
+    #[test]
+    fn test_absolute_cd() {
+        const ABSOLUTE: &str = r#"$ cd /
+$ ls
+dir a
+dir b
+$ cd a
+$ ls
+dir c
+1 x
+$ cd c
+$ ls
+10 y
+$ cd /
+$ cd b
+$ ls
+100 z
+$ cd /a/c/
+$ cd ../../b/./
+$ cd /b/d
+$ ls
+1000 w"#;
+        let sizes = total_sizes(ABSOLUTE.lines()).unwrap();
+        assert_eq!(dir_size(&sizes, "/a/c"), Some(10));
+        assert_eq!(dir_size(&sizes, "/a"), Some(11));
+        assert_eq!(dir_size(&sizes, "/b/d"), Some(1000));
+        assert_eq!(dir_size(&sizes, "/b"), Some(1100));
+        assert_eq!(dir_size(&sizes, "/"), Some(1111));
+        assert_eq!(sizes.len(), 5);
+    }
+
+    #[test]
+    fn test_repeated_ls() {
+        const REPEATED: &str = r#"$ cd /
+$ ls
+dir a
+5 r
+$ ls
+dir a
+5 r
+$ cd a
+$ ls
+7 x
+$ cd /
+$ ls
+dir a
+5 r
+$ cd a
+$ ls
+7 x
+$ cd ..
+$ cd a
+$ ls
+7 x"#;
+        let sizes = total_sizes(REPEATED.lines()).unwrap();
+        assert_eq!(dir_size(&sizes, "/a"), Some(7));
+        assert_eq!(dir_size(&sizes, "/"), Some(12));
+
+        // The sample listed twice over comes to the same sizes.
+        let twice = SAMPLE.lines().chain(["$ cd /"]).chain(SAMPLE.lines());
+        assert_eq!(
+            total_sizes(twice).unwrap(),
+            total_sizes(SAMPLE.lines()).unwrap()
+        );
+    }
+}
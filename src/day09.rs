@@ -0,0 +1,563 @@
+//! Day 9: Rope Bridge.
+
+use crate::{
+    direction::Dir8,
+    parse::parse_lines,
+    solution::{self, Answer, Answers, Solution},
+};
+use anyhow::{anyhow, bail, Error};
+use euclid::{point2, vec2};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashSet},
+    iter,
+    str::FromStr,
+};
+
+pub type Point = euclid::default::Point2D<isize>;
+type Vector = euclid::default::Vector2D<isize>;
+type Box = euclid::default::Box2D<isize>;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Move {
+    pub step: Vector,
+    pub count: isize,
+}
+
+/// A step given as `dx,dy`. The knots only know how to follow a head that
+/// moves to one of its eight neighbors, so each part is -1, 0 or 1.
+fn parse_step(s: &str) -> Result<Vector, Error> {
+    let step = parse_pair(s)?.to_vector();
+    if step == vec2(0, 0) || step.x.abs() > 1 || step.y.abs() > 1 {
+        bail!("step {s:?} doesn't move to a neighboring position");
+    }
+    Ok(step)
+}
+
+/// A point written `x,y`.
+pub fn parse_pair(s: &str) -> Result<Point, Error> {
+    let (x, y) = s
+        .split_once(',')
+        .ok_or_else(|| anyhow!("expected x,y, got {s:?}"))?;
+    Ok(point2(x.trim().parse()?, y.trim().parse()?))
+}
+
+impl FromStr for Move {
+    type Err = Error;
+
+    /// A direction, or a step as `dx,dy`, and how many times to take it.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (step, count) = s
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| anyhow!("expected a direction and a count"))?;
+        let step = if step.contains(',') {
+            parse_step(step)?
+        } else {
+            step_for(step.parse::<Dir8>()?)
+        };
+        let count = count.trim().parse::<isize>()?;
+        if count < 0 {
+            bail!("negative count {count}");
+        }
+        Ok(Self { step, count })
+    }
+}
+
+/// The step for a direction. The rope's y axis points up, the opposite of
+/// `Dir8`'s.
+fn step_for(d: Dir8) -> Vector {
+    let v = Vector::from(d);
+    vec2(v.x, -v.y)
+}
+
+pub type MoveList = Vec<Move>;
+
+pub const DATA: &str = include_str!("../data/day09.txt");
+pub const SAMPLE: &str = include_str!("../data/day09_sample.txt");
+
+pub fn parse(s: &str) -> Result<MoveList, Error> {
+    parse_lines(s)
+}
+
+fn tail_from_head(head: Point, tail: Point) -> Point {
+    let v = head - tail;
+
+    let delta = if v.x == 0 || v.y == 0 {
+        let deltax = if v.x > 1 {
+            1
+        } else if v.x < -1 {
+            -1
+        } else {
+            0
+        };
+
+        let deltay = if v.y > 1 {
+            1
+        } else if v.y < -1 {
+            -1
+        } else {
+            0
+        };
+
+        vec2(deltax, deltay)
+    } else {
+        let deltax = match v.x.cmp(&0) {
+            Ordering::Greater => 1,
+            Ordering::Less => -1,
+            Ordering::Equal => 0,
+        };
+
+        let deltay = match v.y.cmp(&0) {
+            Ordering::Greater => 1,
+            Ordering::Less => -1,
+            Ordering::Equal => 0,
+        };
+
+        vec2(deltax, deltay)
+    };
+    let mut new_tail = tail + delta;
+
+    if new_tail == head {
+        new_tail = tail;
+    }
+
+    new_tail
+}
+
+/// Where every knot starts unless `--start` says otherwise.
+pub const START: Point = point2(1, 1);
+
+pub struct Rope {
+    start: Point,
+    knots: Vec<Point>,
+    pub visited: HashSet<Point>,
+}
+
+impl Rope {
+    pub fn new(knot_count: usize, start: Point) -> Self {
+        assert!(knot_count >= 2, "a rope needs at least two knots");
+        let knots = vec![start; knot_count];
+        let visited = HashSet::from([start]);
+        Self {
+            start,
+            knots,
+            visited,
+        }
+    }
+
+    fn tail(&self) -> Point {
+        *self.knots.last().expect("tail")
+    }
+
+    /// Where each knot is, head first.
+    pub fn snapshot(&self) -> Vec<Point> {
+        self.knots.clone()
+    }
+
+    /// Move the head one step, and each knot after it in turn.
+    pub fn step(&mut self, step: Vector) {
+        self.knots[0] += step;
+        for index in 0..self.knots.len() - 1 {
+            let trailing = index + 1;
+            self.knots[trailing] = tail_from_head(self.knots[index], self.knots[trailing]);
+        }
+        self.visited.insert(self.tail());
+    }
+
+    fn knot_char(&self, index: usize) -> char {
+        match index {
+            0 => 'H',
+            _ if self.knots.len() == 2 => 'T',
+            _ => char::from_digit(index as u32, 36).unwrap_or('*'),
+        }
+    }
+
+    fn char_at(&self, p: &Point) -> char {
+        if let Some(index) = self.knots.iter().position(|k| k == p) {
+            self.knot_char(index)
+        } else if *p == self.start {
+            's'
+        } else if self.visited.contains(p) {
+            '#'
+        } else {
+            '.'
+        }
+    }
+
+    /// Draw the knots and visited positions with y increasing upwards, like
+    /// the puzzle's illustrations. The grid grows to fit everything seen so far.
+    pub fn render(&self) -> String {
+        let frame = Box::from_points(
+            self.visited
+                .iter()
+                .chain(self.knots.iter())
+                .chain(iter::once(&self.start)),
+        );
+        let mut s = String::new();
+        for y in (frame.min.y..=frame.max.y).rev() {
+            for x in frame.min.x..=frame.max.x {
+                s.push(self.char_at(&point2(x, y)));
+            }
+            s.push('\n');
+        }
+        s
+    }
+}
+
+/// Every move broken down into single steps of the head.
+pub fn steps(moves: &MoveList) -> impl Iterator<Item = Vector> + '_ {
+    moves
+        .iter()
+        .flat_map(|one_move| (0..one_move.count).map(move |_| one_move.step))
+}
+
+/// The rope's shape after each step of the head, as [`Rope::snapshot`]
+/// gives it.
+pub fn snapshots(
+    moves: &MoveList,
+    knot_count: usize,
+    start: Point,
+) -> impl Iterator<Item = Vec<Point>> + '_ {
+    let mut rope = Rope::new(knot_count, start);
+    steps(moves).map(move |step| {
+        rope.step(step);
+        rope.snapshot()
+    })
+}
+
+/// The positions the tail of a rope of `knot_count` knots visits.
+pub fn execute_moves(moves: &MoveList, knot_count: usize, start: Point) -> HashSet<Point> {
+    let mut rope = Rope::new(knot_count, start);
+    for step in steps(moves) {
+        rope.step(step);
+    }
+    rope.visited
+}
+
+/// The positions every knot of a rope of `knot_count` knots visits, head
+/// first.
+pub fn knot_visits(moves: &MoveList, knot_count: usize, start: Point) -> Vec<HashSet<Point>> {
+    let mut visits = vec![HashSet::from([start]); knot_count];
+    for snapshot in snapshots(moves, knot_count, start) {
+        for (visited, knot) in visits.iter_mut().zip(snapshot) {
+            visited.insert(knot);
+        }
+    }
+    visits
+}
+
+/// Visited positions by knot number, for `--dump-visits`.
+pub type Visits = BTreeMap<usize, HashSet<Point>>;
+
+/// How `--dump-visits` writes the visited positions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VisitFormat {
+    /// A `knot,x,y` line for every position each knot visited.
+    #[default]
+    Csv,
+    /// A plain PGM bitmap with y increasing upwards, brighter where more
+    /// knots went.
+    Pgm,
+}
+
+impl FromStr for VisitFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "csv" => Self::Csv,
+            "pgm" => Self::Pgm,
+            _ => bail!("unknown visit format {s:?}, expected csv or pgm"),
+        })
+    }
+}
+
+impl VisitFormat {
+    pub fn write(&self, visits: &Visits) -> String {
+        match self {
+            Self::Csv => visits_csv(visits),
+            Self::Pgm => visits_pgm(visits),
+        }
+    }
+}
+
+fn visits_csv(visits: &Visits) -> String {
+    let mut csv = String::from("knot,x,y\n");
+    for (knot, visited) in visits {
+        let mut positions: Vec<_> = visited.iter().collect();
+        positions.sort_by_key(|p| (p.y, p.x));
+        for p in positions {
+            csv += &format!("{knot},{},{}\n", p.x, p.y);
+        }
+    }
+    csv
+}
+
+fn visits_pgm(visits: &Visits) -> String {
+    let frame = Box::from_points(visits.values().flatten());
+    let (width, height) = (frame.width() + 1, frame.height() + 1);
+    let mut pgm = format!("P2\n{width} {height}\n{}\n", visits.len().max(1));
+    for y in (frame.min.y..=frame.max.y).rev() {
+        let row: Vec<_> = (frame.min.x..=frame.max.x)
+            .map(|x| {
+                let p = point2(x, y);
+                visits
+                    .values()
+                    .filter(|v| v.contains(&p))
+                    .count()
+                    .to_string()
+            })
+            .collect();
+        pgm += &row.join(" ");
+        pgm.push('\n');
+    }
+    pgm
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// Where the rope starts.
+    pub start: Point,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self { start: START }
+    }
+}
+
+pub struct Day09 {
+    moves: MoveList,
+    start: Point,
+}
+
+impl Day09 {
+    pub fn new(input: &str, options: &Options) -> Result<Self, Error> {
+        Ok(Self {
+            moves: parse(input)?,
+            start: options.start,
+        })
+    }
+}
+
+impl Solution for Day09 {
+    const DAY: u8 = 9;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        Ok(execute_moves(&self.moves, 2, self.start).len().into())
+    }
+
+    fn part_2(&self) -> Result<Answer, Error> {
+        Ok(execute_moves(&self.moves, 10, self.start).len().into())
+    }
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day09::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    const SAMPLE2: &str = r#"R 5
+U 8
+L 8
+D 3
+R 17
+D 10
+L 25
+U 20"#;
+
+    #[test]
+    fn test_parse() {
+        let moves = parse(SAMPLE).unwrap();
+        assert_eq!(moves.len(), 8);
+        assert_eq!(
+            moves[0],
+            Move {
+                step: vec2(1, 0),
+                count: 4
+            }
+        );
+        assert_eq!(
+            moves[3],
+            Move {
+                step: vec2(0, -1),
+                count: 1
+            }
+        );
+    }
+    #[test]
+    fn test_parse_diagonal() {
+        let moves = parse("UL 2\nDR 1\n1,-1 3\n0,1 2\n").unwrap();
+        let steps: Vec<_> = moves.iter().map(|m| m.step).collect();
+        assert_eq!(steps, [vec2(-1, 1), vec2(1, -1), vec2(1, -1), vec2(0, 1)]);
+        assert_eq!(moves[2].count, 3);
+
+        for bad in ["X 1", "U", "U -1", "2,0 1", "0,0 4", "1;1 2"] {
+            assert!(parse(bad).is_err(), "{bad}");
+        }
+        let error = parse("R 1\nQ 2").unwrap_err();
+        assert!(format!("{error:#}").starts_with("line 2"));
+    }
+
+    #[test]
+    fn test_diagonal_moves() {
+        // The tail trails one behind a head moving diagonally.
+        let moves = parse("UR 4").unwrap();
+        assert_eq!(execute_moves(&moves, 2, START).len(), 4);
+        let mut rope = Rope::new(3, point2(-5, 7));
+        for step in steps(&moves) {
+            rope.step(step);
+        }
+        assert_eq!(rope.knots, [point2(-1, 11), point2(-2, 10), point2(-3, 9)]);
+        assert!(rope.render().ends_with("s....\n"));
+        assert_eq!(parse_pair(" -5, 7").unwrap(), point2(-5, 7));
+    }
+
+    #[test]
+    fn test_tail_from_head() {
+        let new_tail = tail_from_head(point2(5, 3), point2(4, 1));
+        assert_eq!(new_tail, point2(5, 2));
+        let new_tail = tail_from_head(point2(4, 5), point2(5, 4));
+        assert_eq!(new_tail, point2(5, 4));
+    }
+
+    #[test]
+    fn test_part_1() {
+        let moves = parse(SAMPLE).unwrap();
+        let positions = execute_moves(&moves, 2, START);
+        assert_eq!(positions.len(), 13);
+        assert!(positions.contains(&START));
+        assert!(positions.contains(&point2(3, 5)));
+        assert!(!positions.contains(&point2(1, 5)));
+    }
+
+    #[test]
+    fn test_part_2() {
+        let moves = parse(SAMPLE).unwrap();
+        let positions = execute_moves(&moves, 10, START);
+        assert_eq!(positions, HashSet::from([START]));
+
+        let moves = parse(SAMPLE2).unwrap();
+        let positions = execute_moves(&moves, 10, START);
+        assert_eq!(positions.len(), 36);
+    }
+
+    #[test]
+    fn test_render() {
+        let moves = parse(SAMPLE).unwrap();
+        let mut rope = Rope::new(2, START);
+        for step in steps(&moves) {
+            rope.step(step);
+        }
+        assert_eq!(
+            rope.render(),
+            "\
+..##.
+...##
+.TH##
+....#
+s###.
+"
+        );
+    }
+
+    #[test]
+    fn test_knot_count() {
+        let moves = parse(SAMPLE2).unwrap();
+        assert_eq!(execute_moves(&moves, 2, START).len(), 88);
+        assert!(execute_moves(&moves, 20, START).len() <= execute_moves(&moves, 10, START).len());
+    }
+
+    #[test]
+    fn test_visits() {
+        let moves = parse(SAMPLE2).unwrap();
+        let visits = knot_visits(&moves, 10, START);
+        assert_eq!(visits.len(), 10);
+        assert_eq!(visits[9], execute_moves(&moves, 10, START));
+        assert_eq!(visits[1], execute_moves(&moves, 2, START));
+
+        let moves = parse("R 3\nU 1").unwrap();
+        let visits: Visits = knot_visits(&moves, 2, START)
+            .into_iter()
+            .enumerate()
+            .collect();
+        assert_eq!(
+            VisitFormat::Csv.write(&visits),
+            "knot,x,y\n0,1,1\n0,2,1\n0,3,1\n0,4,1\n0,4,2\n1,1,1\n1,2,1\n1,3,1\n"
+        );
+        assert_eq!(
+            VisitFormat::Pgm.write(&visits),
+            "P2\n4 2\n2\n0 0 0 1\n2 2 2 1\n"
+        );
+        assert_eq!("pgm".parse::<VisitFormat>().unwrap(), VisitFormat::Pgm);
+        assert!("png".parse::<VisitFormat>().is_err());
+    }
+
+    #[test]
+    fn test_snapshots() {
+        let moves = parse("R 2\nU 2").unwrap();
+        let shapes: Vec<_> = snapshots(&moves, 3, START).collect();
+        assert_eq!(
+            shapes,
+            [
+                vec![point2(2, 1), START, START],
+                vec![point2(3, 1), point2(2, 1), START],
+                vec![point2(3, 2), point2(2, 1), START],
+                vec![point2(3, 3), point2(3, 2), point2(2, 2)],
+            ]
+        );
+    }
+
+    fn moves() -> impl Strategy<Value = MoveList> {
+        let step = prop::sample::select(vec![
+            vec2(1, 0),
+            vec2(-1, 0),
+            vec2(0, 1),
+            vec2(0, -1),
+            vec2(1, 1),
+            vec2(1, -1),
+            vec2(-1, 1),
+            vec2(-1, -1),
+        ]);
+        prop::collection::vec((step, 1..6isize), 0..20).prop_map(|moves| {
+            moves
+                .into_iter()
+                .map(|(step, count)| Move { step, count })
+                .collect()
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_two_knots_follow(moves in moves()) {
+            let mut tail = START;
+            for (head, shape) in steps(&moves)
+                .scan(START, |head, step| {
+                    *head += step;
+                    Some(*head)
+                })
+                .zip(snapshots(&moves, 2, START))
+            {
+                tail = tail_from_head(head, tail);
+                prop_assert_eq!(shape, vec![head, tail]);
+            }
+        }
+
+        #[test]
+        fn test_knots_stay_touching(moves in moves()) {
+            for (long, short) in snapshots(&moves, 10, START).zip(snapshots(&moves, 2, START)) {
+                prop_assert_eq!(&long[..2], &short[..]);
+                for pair in long.windows(2) {
+                    let gap = pair[0] - pair[1];
+                    prop_assert!(gap.x.abs() <= 1 && gap.y.abs() <= 1, "{:?}", long);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! Day 10: Cathode-Ray Tube.
+
+use crate::{
+    ocr,
+    solution::{self, Answer, Answers, Solution},
+    vm::{self, Cpu, Instruction, Program},
+};
+use anyhow::Error;
+use std::{fmt, iter};
+use tracing::debug;
+
+const TARGET_CYCLES: &[usize] = &[20, 60, 100, 140, 180, 220];
+pub const DATA: &str = include_str!("../data/day10.txt");
+pub const SAMPLE: &str = include_str!("../data/day10_sample.txt");
+
+const SCREEN_WIDTH: usize = 40;
+
+/// What happens during one cycle: the value of X, the pixel the CRT draws
+/// and the instruction being executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceRow {
+    cycle: usize,
+    x: isize,
+    pixel: char,
+    instruction: Instruction,
+}
+
+impl TraceRow {
+    const HEADER: &'static str = "cycle,x,pixel,instruction";
+}
+
+impl fmt::Display for TraceRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{},{},{},{}",
+            self.cycle, self.x, self.pixel, self.instruction
+        )
+    }
+}
+
+/// Every cycle of `program`, from the first until it halts.
+pub fn trace(p: &Program) -> Vec<TraceRow> {
+    let mut rows = vec![];
+    let mut cpu = Cpu::new(p.clone());
+    while let Some(instruction) = cpu.current().copied() {
+        let column = (cpu.cycle - 1) % SCREEN_WIDTH;
+        let sprite_range = cpu.x - 1..=cpu.x + 1;
+        let pixel = if sprite_range.contains(&(column as isize)) {
+            '#'
+        } else {
+            '.'
+        };
+        rows.push(TraceRow {
+            cycle: cpu.cycle,
+            x: cpu.x,
+            pixel,
+            instruction,
+        });
+        cpu.clock();
+    }
+    rows
+}
+
+/// The trace as CSV, with a header line.
+pub fn trace_csv(rows: &[TraceRow]) -> String {
+    iter::once(TraceRow::HEADER.to_string())
+        .chain(rows.iter().map(TraceRow::to_string))
+        .map(|line| line + "\n")
+        .collect()
+}
+
+pub fn draw_screen(p: &Program) -> Vec<String> {
+    trace(p)
+        .chunks(SCREEN_WIDTH)
+        .map(|row| row.iter().map(|cycle| cycle.pixel).collect())
+        .collect()
+}
+
+/// Day 10 has no options.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options;
+
+pub struct Day10 {
+    program: Program,
+}
+
+impl Day10 {
+    pub fn new(input: &str, _options: &Options) -> Result<Self, Error> {
+        Ok(Self {
+            program: vm::parse(input)?,
+        })
+    }
+}
+
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        let mut cpu = Cpu::new(self.program.clone());
+        let mut signal_strength_sum = 0;
+        for cycle in TARGET_CYCLES {
+            if !cpu.run_until(*cycle) {
+                break;
+            }
+            signal_strength_sum += cpu.x * *cycle as isize;
+        }
+        Ok(signal_strength_sum.into())
+    }
+
+    fn part_2(&self) -> Result<Answer, Error> {
+        let screen = draw_screen(&self.program);
+        debug!("screen = {screen:#?}");
+        Ok(ocr::recognize(&screen).into())
+    }
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day10::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn parse(s: &str) -> Program {
+        vm::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let program = parse(SAMPLE);
+        assert_eq!(program.len(), 146);
+    }
+
+    #[test]
+    fn test_part1() {
+        const TARGET_X: &[usize] = &[21, 19, 18, 21, 16, 18];
+        const TARGET_SIGNAL_STRENGTHS: &[usize] = &[420, 1140, 1800, 2940, 2880, 3960];
+        let program = parse(SAMPLE);
+        let mut cpu = Cpu::new(program);
+
+        let targets: HashMap<usize, (usize, usize)> = TARGET_CYCLES
+            .iter()
+            .copied()
+            .zip(
+                TARGET_X
+                    .iter()
+                    .copied()
+                    .zip(TARGET_SIGNAL_STRENGTHS.iter().copied()),
+            )
+            .collect();
+
+        let mut signal_strength_sum = 0;
+        while cpu.running() {
+            if let Some(target) = targets.get(&cpu.cycle) {
+                assert_eq!(target.0 as isize, cpu.x);
+                let signal_strength = cpu.x * cpu.cycle as isize;
+                assert_eq!(target.1 as isize, signal_strength);
+                signal_strength_sum += signal_strength;
+            }
+            cpu.clock();
+        }
+        assert_eq!(signal_strength_sum, 13140);
+    }
+
+    #[test]
+    fn test_part1_run_until() {
+        let day = Day10 {
+            program: parse(SAMPLE),
+        };
+        assert_eq!(day.part_1().unwrap(), Answer::Number(13140));
+    }
+
+    #[test]
+    fn test_trace_matches_fixture() {
+        const FIXTURE: &str = include_str!("../data/day10_sample_trace.csv");
+        let rows = trace(&parse(SAMPLE));
+        assert_eq!(rows.len(), 240);
+
+        let mut lines = FIXTURE.lines();
+        assert_eq!(lines.next(), Some(TraceRow::HEADER));
+        for (row, line) in rows.iter().zip(lines) {
+            let fields: Vec<_> = line.split(',').collect();
+            assert_eq!(fields[0], row.cycle.to_string());
+            assert_eq!(fields[1], row.x.to_string(), "X during cycle {}", row.cycle);
+        }
+        assert_eq!(trace_csv(&rows), FIXTURE);
+    }
+
+    #[test]
+    fn test_part2() {
+        let program = parse(SAMPLE);
+        let expected = [
+            "##..##..##..##..##..##..##..##..##..##..",
+            "###...###...###...###...###...###...###.",
+            "####....####....####....####....####....",
+            "#####.....#####.....#####.....#####.....",
+            "######......######......######......####",
+            "#######.......#######.......#######.....",
+        ];
+        let screen = draw_screen(&program);
+        for (expected, line) in screen.iter().zip(expected.iter()) {
+            assert_eq!(expected, line);
+        }
+    }
+
+    #[test]
+    fn test_recognize_puzzle_input() {
+        let screen = draw_screen(&parse(DATA));
+        assert_eq!(ocr::recognize(&screen), "PGPHBEAB");
+    }
+}
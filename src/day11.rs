@@ -0,0 +1,908 @@
+//! Day 11: Monkey in the Middle.
+
+use crate::solution::{self, Answer, Answers, Solution};
+use anyhow::{anyhow, bail, Error};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, line_ending, multispace0, multispace1, space0, space1, u64},
+    combinator::{all_consuming, cut, map, value},
+    error::{context, convert_error, VerboseError},
+    multi::separated_list1,
+    sequence::{delimited, preceded, tuple},
+    Finish, IResult,
+};
+use rayon::prelude::*;
+use std::{cmp::Reverse, collections::HashMap, fmt};
+use tracing::debug;
+
+pub const DATA: &str = include_str!("../data/day11.txt");
+pub const SAMPLE: &str = include_str!("../data/day11_sample.txt");
+
+type WorryValue = u64;
+
+type ParseResult<'a, T> = IResult<&'a str, T, VerboseError<&'a str>>;
+
+fn index(input: &str) -> ParseResult<'_, usize> {
+    map(u64, |v| v as usize)(input)
+}
+
+/// A labelled line inside a monkey block, like `  Test: divisible by 23`.
+fn field<'a, T>(
+    label: &'static str,
+    parser: impl FnMut(&'a str) -> ParseResult<'a, T>,
+) -> impl FnMut(&'a str) -> ParseResult<'a, T> {
+    context(
+        label,
+        delimited(
+            tuple((line_ending, space0)),
+            preceded(tag(label), parser),
+            space0,
+        ),
+    )
+}
+
+fn monkey_label(input: &str) -> ParseResult<'_, usize> {
+    context("monkey label", delimited(tag("Monkey "), index, char(':')))(input)
+}
+
+fn starting_items(input: &str) -> ParseResult<'_, Vec<WorryValue>> {
+    field(
+        "Starting items:",
+        preceded(
+            space0,
+            separated_list1(tuple((char(','), space0)), map(u64, |v| v as WorryValue)),
+        ),
+    )(input)
+}
+
+fn operand(input: &str) -> ParseResult<'_, Value> {
+    alt((value(Value::Old, tag("old")), map(u64, Value::Constant)))(input)
+}
+
+fn operation(input: &str) -> ParseResult<'_, Operation> {
+    alt((
+        value(Operation::Addition, char('+')),
+        value(Operation::Multiplication, char('*')),
+    ))(input)
+}
+
+fn expression(input: &str) -> ParseResult<'_, Expression> {
+    field(
+        "Operation: new =",
+        map(
+            tuple((
+                preceded(space0, operand),
+                delimited(space1, operation, space1),
+                operand,
+            )),
+            |(lhs, operation, rhs)| Expression {
+                lhs,
+                operation,
+                rhs,
+            },
+        ),
+    )(input)
+}
+
+fn test_divisor(input: &str) -> ParseResult<'_, usize> {
+    field("Test: divisible by", preceded(space0, index))(input)
+}
+
+fn true_target(input: &str) -> ParseResult<'_, usize> {
+    field("If true: throw to monkey", preceded(space0, index))(input)
+}
+
+fn false_target(input: &str) -> ParseResult<'_, usize> {
+    field("If false: throw to monkey", preceded(space0, index))(input)
+}
+
+fn monkey(input: &str) -> ParseResult<'_, Monkey> {
+    // Once a monkey label has matched, errors in the rest of the block are
+    // reported rather than ending the list of monkeys.
+    map(
+        tuple((
+            monkey_label,
+            cut(tuple((
+                starting_items,
+                expression,
+                test_divisor,
+                true_target,
+                false_target,
+            ))),
+        )),
+        |(index, (items, expression, test_divisor, true_target, false_target))| Monkey {
+            index,
+            items,
+            expression,
+            test_divisor,
+            true_target,
+            false_target,
+            inspection_count: 0,
+        },
+    )(input)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Value {
+    Constant(WorryValue),
+    Old,
+}
+
+impl Value {
+    fn evaluate(&self, value: WorryValue) -> WorryValue {
+        match self {
+            Self::Constant(v) => *v,
+            Self::Old => value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    Addition,
+    Multiplication,
+}
+
+impl Operation {
+    fn evaluate(&self, a: WorryValue, b: WorryValue) -> WorryValue {
+        match self {
+            Self::Addition => a + b,
+            Self::Multiplication => a * b,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Expression {
+    lhs: Value,
+    operation: Operation,
+    rhs: Value,
+}
+
+impl Expression {
+    fn apply(self, value: WorryValue) -> WorryValue {
+        let left_value = self.lhs.evaluate(value);
+        let right_value = self.rhs.evaluate(value);
+        self.operation.evaluate(left_value, right_value)
+    }
+}
+
+#[derive(Debug)]
+struct Throw {
+    target: usize,
+    item: WorryValue,
+}
+
+#[derive(Debug, Clone)]
+pub struct Monkey {
+    index: usize,
+    items: Vec<WorryValue>,
+    expression: Expression,
+    test_divisor: usize,
+    true_target: usize,
+    false_target: usize,
+    inspection_count: u128,
+}
+
+impl Monkey {
+    fn apply_expression(&mut self) {
+        self.items
+            .iter_mut()
+            .for_each(|item| *item = self.expression.apply(*item));
+    }
+
+    fn reduce_worry(&mut self, policy: WorryPolicy) {
+        self.items
+            .iter_mut()
+            .for_each(|item| *item = policy.apply(*item));
+    }
+
+    /// The worry level of `item` once this monkey has inspected it, and
+    /// whether it passes the monkey's test.
+    fn inspect(&self, item: WorryValue, policy: WorryPolicy) -> (bool, WorryValue) {
+        let item = policy.apply(self.expression.apply(item));
+        (item.is_multiple_of(self.test_divisor as WorryValue), item)
+    }
+
+    fn inspect_items(&mut self) -> Vec<Throw> {
+        self.inspection_count += self.items.len() as u128;
+        let test_divisor = self.test_divisor;
+        let true_target = self.true_target;
+        let false_target = self.false_target;
+        let (for_true_target, for_false_target): (Vec<_>, Vec<_>) = self
+            .items
+            .iter()
+            .partition(|item| *item % (test_divisor as WorryValue) == 0);
+        let thrown_items: Vec<Throw> = for_true_target
+            .iter()
+            .map(|item| Throw {
+                target: true_target,
+                item: *item,
+            })
+            .chain(for_false_target.iter().map(|item| Throw {
+                target: false_target,
+                item: *item,
+            }))
+            .collect();
+
+        self.items.clear();
+
+        thrown_items
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Constant(v) => write!(f, "{v}"),
+            Self::Old => write!(f, "old"),
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Addition => write!(f, "+"),
+            Self::Multiplication => write!(f, "*"),
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.lhs, self.operation, self.rhs)
+    }
+}
+
+/// The monkey's block of notes as it appears in the input, without its
+/// inspection count.
+impl fmt::Display for Monkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let items: Vec<_> = self.items.iter().map(WorryValue::to_string).collect();
+        writeln!(f, "Monkey {}:", self.index)?;
+        writeln!(f, "  Starting items: {}", items.join(", "))?;
+        writeln!(f, "  Operation: new = {}", self.expression)?;
+        writeln!(f, "  Test: divisible by {}", self.test_divisor)?;
+        writeln!(f, "    If true: throw to monkey {}", self.true_target)?;
+        write!(f, "    If false: throw to monkey {}", self.false_target)
+    }
+}
+
+pub type MonkeyList = Vec<Monkey>;
+
+pub fn parse(s: &str) -> Result<MonkeyList, Error> {
+    all_consuming(delimited(
+        multispace0,
+        separated_list1(multispace1, monkey),
+        multispace0,
+    ))(s)
+    .finish()
+    .map(|(_, monkeys)| monkeys)
+    .map_err(|e| anyhow!("failed to parse monkeys:\n{}", convert_error(s, e)))
+    .and_then(check_monkeys)
+}
+
+/// Check that there are at least two monkeys, numbered in order, that
+/// only throw to each other and never test divisibility by zero.
+fn check_monkeys(monkeys: MonkeyList) -> Result<MonkeyList, Error> {
+    if monkeys.len() < 2 {
+        bail!("monkey business takes two monkeys, got {}", monkeys.len());
+    }
+    for (position, monkey) in monkeys.iter().enumerate() {
+        if monkey.index != position {
+            bail!(
+                "monkey {} is monkey number {position} in the list",
+                monkey.index
+            );
+        }
+        for target in [monkey.true_target, monkey.false_target] {
+            if target >= monkeys.len() {
+                bail!(
+                    "monkey {position} throws to monkey {target}, but there are only {}",
+                    monkeys.len()
+                );
+            }
+        }
+        if monkey.test_divisor == 0 {
+            bail!("monkey {position} tests divisibility by 0");
+        }
+    }
+    Ok(monkeys)
+}
+
+fn gcd(a: WorryValue, b: WorryValue) -> WorryValue {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// How worry levels are kept manageable after each inspection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorryPolicy {
+    /// Relief that the item wasn't damaged divides worry by this amount.
+    DivideBy(WorryValue),
+    /// Reduce worry modulo a common multiple of every monkey's divisor.
+    ///
+    /// Monkeys only ever ask whether worry is divisible by their own
+    /// divisor `d`. If `d` divides `m` then `(w mod m) mod d == w mod d`,
+    /// and both `+` and `*` give the same result mod `m` whether or not
+    /// their inputs were reduced first (the Chinese remainder theorem
+    /// view), so every test comes out the same while worry stays below `m`.
+    Modulo(WorryValue),
+    /// Leave worry levels alone.
+    #[allow(unused)]
+    None,
+}
+
+impl WorryPolicy {
+    /// The `Modulo` policy using the least common multiple of the monkeys'
+    /// test divisors.
+    pub fn common_multiple(monkeys: &MonkeyList) -> Self {
+        let lcm = monkeys
+            .iter()
+            .map(|monkey| monkey.test_divisor as WorryValue)
+            .fold(1, |lcm, d| lcm / gcd(lcm, d) * d);
+        Self::Modulo(lcm)
+    }
+
+    fn apply(&self, value: WorryValue) -> WorryValue {
+        match self {
+            Self::DivideBy(divisor) => value / divisor,
+            Self::Modulo(modulus) => value % modulus,
+            Self::None => value,
+        }
+    }
+}
+
+pub fn execute_round(monkeys: &mut MonkeyList, policy: WorryPolicy) {
+    for index in 0..monkeys.len() {
+        monkeys[index].apply_expression();
+        monkeys[index].reduce_worry(policy);
+        let throws = monkeys[index].inspect_items();
+        for throw in throws {
+            monkeys[throw.target].items.push(throw.item);
+        }
+    }
+}
+
+/// The same as `execute_round`, but each monkey inspects its items in
+/// parallel. The throws are made afterwards, in the same order.
+///
+/// `--bench` on my input puts this about 25 times slower than the serial
+/// version: a monkey only ever holds a few dozen items, which isn't enough
+/// work to pay for handing them to other threads.
+pub fn execute_round_parallel(monkeys: &mut MonkeyList, policy: WorryPolicy) {
+    for index in 0..monkeys.len() {
+        let monkey = &mut monkeys[index];
+        let mut inspected: Vec<_> = monkey
+            .items
+            .par_iter()
+            .map(|item| monkey.inspect(*item, policy))
+            .collect();
+        monkey.items.clear();
+        monkey.inspection_count += inspected.len() as u128;
+        // Items that pass the test are thrown first.
+        inspected.sort_by_key(|(passed, _)| !passed);
+        let (true_target, false_target) = (monkey.true_target, monkey.false_target);
+        for (passed, item) in inspected {
+            let target = if passed { true_target } else { false_target };
+            monkeys[target].items.push(item);
+        }
+    }
+}
+
+/// Where every item is, with each monkey's items sorted. Items move
+/// independently of each other, so the order a monkey holds them in makes
+/// no difference to where they go or how often they're inspected.
+type ItemState = Vec<Vec<WorryValue>>;
+
+fn item_state(monkeys: &MonkeyList) -> ItemState {
+    monkeys
+        .iter()
+        .map(|monkey| {
+            let mut items = monkey.items.clone();
+            items.sort_unstable();
+            items
+        })
+        .collect()
+}
+
+pub fn inspection_counts(monkeys: &MonkeyList) -> Vec<u128> {
+    monkeys.iter().map(|m| m.inspection_count).collect()
+}
+
+/// Each monkey's inspection count after every round, by plain simulation.
+/// Entry `r` is the counts after round `r`, so entry 0 is all zeros.
+pub fn history(monkeys: &MonkeyList, rounds: usize, policy: WorryPolicy) -> Vec<Vec<u128>> {
+    let mut monkeys = monkeys.clone();
+    let mut history = Vec::with_capacity(rounds + 1);
+    history.push(inspection_counts(&monkeys));
+    for _ in 0..rounds {
+        execute_round(&mut monkeys, policy);
+        history.push(inspection_counts(&monkeys));
+    }
+    history
+}
+
+/// The history as CSV for plotting, one line per monkey per round from
+/// round 1, with a header line.
+pub fn history_csv(history: &[Vec<u128>]) -> String {
+    let mut csv = String::from("round,monkey,inspections\n");
+    for (round, counts) in history.iter().enumerate().skip(1) {
+        for (monkey, count) in counts.iter().enumerate() {
+            csv += &format!("{round},{monkey},{count}\n");
+        }
+    }
+    csv
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// Rounds of throwing for part 2.
+    pub rounds: usize,
+    /// Inspect each monkey's items in parallel.
+    pub parallel: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            rounds: 10000,
+            parallel: false,
+        }
+    }
+}
+
+pub struct Day11 {
+    monkeys: MonkeyList,
+    rounds: usize,
+    parallel: bool,
+}
+
+impl Day11 {
+    pub fn new(input: &str, options: &Options) -> Result<Self, Error> {
+        Ok(Self {
+            monkeys: parse(input)?,
+            rounds: options.rounds,
+            parallel: options.parallel,
+        })
+    }
+
+    fn execute_round(&self, monkeys: &mut MonkeyList, policy: WorryPolicy) {
+        if self.parallel {
+            execute_round_parallel(monkeys, policy);
+        } else {
+            execute_round(monkeys, policy);
+        }
+    }
+
+    /// How many times each monkey inspects an item in `rounds` rounds of
+    /// throwing.
+    ///
+    /// Under a policy that keeps worry bounded there are only so many
+    /// places the items can be, so they eventually return to where they
+    /// were at some earlier round. From then on every cycle adds the same
+    /// inspections, and the remaining rounds can be skipped.
+    fn inspection_counts(&self, rounds: usize, policy: WorryPolicy) -> Vec<u128> {
+        let mut monkeys = self.monkeys.clone();
+        let mut seen: HashMap<ItemState, usize> = HashMap::new();
+        // The inspection counts at the start of each round.
+        let mut history: Vec<Vec<u128>> = vec![];
+        for round in 0..rounds {
+            if round % 100 == 0 {
+                debug!(round);
+            }
+            let counts = inspection_counts(&monkeys);
+            if let Some(start) = seen.insert(item_state(&monkeys), round) {
+                let period = round - start;
+                let remaining = rounds - round;
+                let (cycles, rest) = ((remaining / period) as u128, remaining % period);
+                debug!(start, period, "items are back where they were");
+                return (0..monkeys.len())
+                    .map(|i| {
+                        let per_cycle = counts[i] - history[start][i];
+                        let partial = history[start + rest][i] - history[start][i];
+                        counts[i] + cycles * per_cycle + partial
+                    })
+                    .collect();
+            }
+            history.push(counts);
+            self.execute_round(&mut monkeys, policy);
+        }
+        inspection_counts(&monkeys)
+    }
+
+    /// Monkey business after `rounds` rounds of throwing.
+    fn monkey_business(&self, rounds: usize, policy: WorryPolicy) -> u128 {
+        let mut counts = self.inspection_counts(rounds, policy);
+        counts.sort_by_key(|count| Reverse(*count));
+        counts[0] * counts[1]
+    }
+}
+
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        Ok(self.monkey_business(20, WorryPolicy::DivideBy(3)).into())
+    }
+
+    fn part_2(&self) -> Result<Answer, Error> {
+        let policy = WorryPolicy::common_multiple(&self.monkeys);
+        Ok(self.monkey_business(self.rounds, policy).into())
+    }
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day11::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    const RELIEF: WorryPolicy = WorryPolicy::DivideBy(3);
+
+    fn compare_worries(worries: &[WorryValue], expected: &[usize]) {
+        assert_eq!(worries.len(), expected.len());
+        for i in 0..worries.len() {
+            assert_eq!(worries[i] as usize, expected[i]);
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let monkeys = parse(SAMPLE).expect("parse");
+        assert_eq!(monkeys.len(), 4);
+        compare_worries(&monkeys[0].items, &[79, 98]);
+        assert_eq!(monkeys[0].test_divisor, 23);
+        assert_eq!(monkeys[0].true_target, 2);
+        assert_eq!(monkeys[0].false_target, 3);
+        assert_eq!(monkeys[2].expression.lhs, Value::Old);
+        assert_eq!(monkeys[2].expression.rhs, Value::Old);
+        assert_eq!(monkeys[3].expression.operation, Operation::Addition);
+    }
+
+    #[test]
+    fn test_parse_many_monkeys() {
+        let input: String = (0..12)
+            .map(|i| {
+                format!(
+                    "Monkey {i}:\n  Starting items: {i}\n  Operation: new = old + {i}\n  \
+                     Test: divisible by 2\n    If true: throw to monkey {}\n    \
+                     If false: throw to monkey 11\n",
+                    (i + 1) % 12
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let monkeys = parse(&input).expect("parse");
+        assert_eq!(monkeys.len(), 12);
+        assert_eq!(monkeys[11].index, 11);
+        assert_eq!(monkeys[10].true_target, 11);
+        assert_eq!(monkeys[11].false_target, 11);
+    }
+
+    fn render(monkeys: &[Monkey]) -> String {
+        let blocks: Vec<_> = monkeys.iter().map(Monkey::to_string).collect();
+        blocks.join("\n\n")
+    }
+
+    fn value_strategy() -> impl Strategy<Value = Value> {
+        prop_oneof![
+            Just(Value::Old),
+            (1..20 as WorryValue).prop_map(Value::Constant)
+        ]
+    }
+
+    fn expression_strategy() -> impl Strategy<Value = Expression> {
+        let operation = prop_oneof![Just(Operation::Addition), Just(Operation::Multiplication)];
+        (value_strategy(), operation, value_strategy()).prop_map(|(lhs, operation, rhs)| {
+            Expression {
+                lhs,
+                operation,
+                rhs,
+            }
+        })
+    }
+
+    /// Between two and eight monkeys that only throw to other monkeys.
+    fn monkeys_strategy() -> impl Strategy<Value = MonkeyList> {
+        (2..8usize).prop_flat_map(|count| {
+            let monkey = (
+                prop::collection::vec(1..100 as WorryValue, 1..5),
+                expression_strategy(),
+                prop::sample::select(vec![2, 3, 5, 7, 11, 13, 17, 19, 23]),
+                1..count,
+                1..count,
+            );
+            prop::collection::vec(monkey, count).prop_map(move |monkeys| {
+                monkeys
+                    .into_iter()
+                    .enumerate()
+                    .map(
+                        |(index, (items, expression, test_divisor, true_step, false_step))| {
+                            Monkey {
+                                index,
+                                items,
+                                expression,
+                                test_divisor,
+                                true_target: (index + true_step) % count,
+                                false_target: (index + false_step) % count,
+                                inspection_count: 0,
+                            }
+                        },
+                    )
+                    .collect()
+            })
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_round_trip(monkeys in monkeys_strategy()) {
+            let input = render(&monkeys);
+            let parsed = parse(&input).unwrap();
+            prop_assert_eq!(render(&parsed), input);
+        }
+
+        #[test]
+        fn test_parallel_matches_serial(monkeys in monkeys_strategy()) {
+            let policy = WorryPolicy::common_multiple(&monkeys);
+            let mut serial = monkeys.clone();
+            let mut parallel = monkeys;
+            for _ in 0..20 {
+                execute_round(&mut serial, policy);
+                execute_round_parallel(&mut parallel, policy);
+            }
+            for (serial, parallel) in serial.iter().zip(&parallel) {
+                prop_assert_eq!(&serial.items, &parallel.items);
+                prop_assert_eq!(serial.inspection_count, parallel.inspection_count);
+            }
+        }
+
+        #[test]
+        fn test_generated_monkeys_run(monkeys in monkeys_strategy()) {
+            // Worry grows without bound under the part 1 policy, so only the
+            // part 2 policy is safe for arbitrary monkeys.
+            let policy = WorryPolicy::common_multiple(&monkeys);
+            let day = Day11 { monkeys, rounds: 200, parallel: false };
+            let business = day.monkey_business(200, policy);
+            let items: usize = day.monkeys.iter().map(|m| m.items.len()).sum();
+            prop_assert!(business <= (200 * items as u128).pow(2));
+        }
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let bad = SAMPLE.replace("new = old + 6", "new = old - 6");
+        let err = parse(&bad).unwrap_err().to_string();
+        assert!(err.contains("Operation: new ="), "{err}");
+        assert!(err.contains("line 10"), "{err}");
+
+        let bad = SAMPLE.replace("Monkey 2:", "Monkey 5:");
+        let err = parse(&bad).unwrap_err().to_string();
+        assert_eq!(err, "monkey 5 is monkey number 2 in the list");
+
+        let bad = SAMPLE.replace("If true: throw to monkey 1", "If true: throw to monkey 4");
+        let err = parse(&bad).unwrap_err().to_string();
+        assert_eq!(err, "monkey 2 throws to monkey 4, but there are only 4");
+
+        let bad = SAMPLE.replace("divisible by 19", "divisible by 0");
+        let err = parse(&bad).unwrap_err().to_string();
+        assert_eq!(err, "monkey 1 tests divisibility by 0");
+
+        let one = SAMPLE
+            .split("\n\n")
+            .next()
+            .unwrap()
+            .replace("monkey 2", "monkey 0");
+        let one = one.replace("monkey 3", "monkey 0");
+        let err = parse(&one).unwrap_err().to_string();
+        assert_eq!(err, "monkey business takes two monkeys, got 1");
+    }
+
+    #[test]
+    fn test_part1() {
+        let mut monkeys = parse(SAMPLE).expect("parse");
+        execute_round(&mut monkeys, RELIEF);
+        compare_worries(&monkeys[0].items, &[20, 23, 27, 26]);
+        compare_worries(&monkeys[1].items, &[2080, 25, 167, 207, 401, 1046]);
+        compare_worries(&monkeys[2].items, &[]);
+        compare_worries(&monkeys[3].items, &[]);
+
+        execute_round(&mut monkeys, RELIEF);
+        compare_worries(&monkeys[0].items, &[695, 10, 71, 135, 350]);
+        compare_worries(&monkeys[1].items, &[43, 49, 58, 55, 362]);
+        compare_worries(&monkeys[2].items, &[]);
+        compare_worries(&monkeys[3].items, &[]);
+
+        execute_round(&mut monkeys, RELIEF);
+        compare_worries(&monkeys[0].items, &[16, 18, 21, 20, 122]);
+        compare_worries(&monkeys[1].items, &[1468, 22, 150, 286, 739]);
+        compare_worries(&monkeys[2].items, &[]);
+        compare_worries(&monkeys[3].items, &[]);
+
+        execute_round(&mut monkeys, RELIEF);
+        compare_worries(&monkeys[0].items, &[491, 9, 52, 97, 248, 34]);
+        compare_worries(&monkeys[1].items, &[39, 45, 43, 258]);
+        compare_worries(&monkeys[2].items, &[]);
+        compare_worries(&monkeys[3].items, &[]);
+
+        execute_round(&mut monkeys, RELIEF);
+        compare_worries(&monkeys[0].items, &[15, 17, 16, 88, 1037]);
+        compare_worries(&monkeys[1].items, &[20, 110, 205, 524, 72]);
+        compare_worries(&monkeys[2].items, &[]);
+        compare_worries(&monkeys[3].items, &[]);
+
+        execute_round(&mut monkeys, RELIEF);
+        compare_worries(&monkeys[0].items, &[8, 70, 176, 26, 34]);
+        compare_worries(&monkeys[1].items, &[481, 32, 36, 186, 2190]);
+        compare_worries(&monkeys[2].items, &[]);
+        compare_worries(&monkeys[3].items, &[]);
+
+        execute_round(&mut monkeys, RELIEF);
+        compare_worries(&monkeys[0].items, &[162, 12, 14, 64, 732, 17]);
+        compare_worries(&monkeys[1].items, &[148, 372, 55, 72]);
+        compare_worries(&monkeys[2].items, &[]);
+        compare_worries(&monkeys[3].items, &[]);
+
+        execute_round(&mut monkeys, RELIEF);
+        compare_worries(&monkeys[0].items, &[51, 126, 20, 26, 136]);
+        compare_worries(&monkeys[1].items, &[343, 26, 30, 1546, 36]);
+        compare_worries(&monkeys[2].items, &[]);
+        compare_worries(&monkeys[3].items, &[]);
+
+        execute_round(&mut monkeys, RELIEF);
+        compare_worries(&monkeys[0].items, &[116, 10, 12, 517, 14]);
+        compare_worries(&monkeys[1].items, &[108, 267, 43, 55, 288]);
+        compare_worries(&monkeys[2].items, &[]);
+        compare_worries(&monkeys[3].items, &[]);
+
+        execute_round(&mut monkeys, RELIEF);
+        compare_worries(&monkeys[0].items, &[91, 16, 20, 98]);
+        compare_worries(&monkeys[1].items, &[481, 245, 22, 26, 1092, 30]);
+        compare_worries(&monkeys[2].items, &[]);
+        compare_worries(&monkeys[3].items, &[]);
+
+        execute_round(&mut monkeys, RELIEF);
+        execute_round(&mut monkeys, RELIEF);
+        execute_round(&mut monkeys, RELIEF);
+        execute_round(&mut monkeys, RELIEF);
+        execute_round(&mut monkeys, RELIEF);
+        compare_worries(&monkeys[0].items, &[83, 44, 8, 184, 9, 20, 26, 102]);
+        compare_worries(&monkeys[1].items, &[110, 36]);
+        compare_worries(&monkeys[2].items, &[]);
+        compare_worries(&monkeys[3].items, &[]);
+
+        execute_round(&mut monkeys, RELIEF);
+        execute_round(&mut monkeys, RELIEF);
+        execute_round(&mut monkeys, RELIEF);
+        execute_round(&mut monkeys, RELIEF);
+        execute_round(&mut monkeys, RELIEF);
+        compare_worries(&monkeys[0].items, &[10, 12, 14, 26, 34]);
+        compare_worries(&monkeys[1].items, &[245, 93, 53, 199, 115]);
+        compare_worries(&monkeys[2].items, &[]);
+        compare_worries(&monkeys[3].items, &[]);
+
+        assert_eq!(inspection_counts(&monkeys), [101, 95, 7, 105]);
+        let history = history(&parse(SAMPLE).expect("parse"), 20, RELIEF);
+        assert_eq!(history[20], inspection_counts(&monkeys));
+
+        monkeys.sort_by_key(|m| Reverse(m.inspection_count));
+
+        let monkey_business = monkeys[0].inspection_count * monkeys[1].inspection_count;
+        assert_eq!(monkey_business, 10605);
+    }
+
+    #[test]
+    fn test_worry_policy() {
+        assert_eq!(RELIEF.apply(1501), 500);
+        assert_eq!(WorryPolicy::Modulo(10).apply(1501), 1);
+        assert_eq!(WorryPolicy::None.apply(1501), 1501);
+
+        let monkeys = parse(SAMPLE).expect("parse");
+        assert_eq!(
+            WorryPolicy::common_multiple(&monkeys),
+            WorryPolicy::Modulo(23 * 19 * 13 * 17)
+        );
+
+        let mut monkeys = parse(SAMPLE.replace("by 19", "by 46").as_str()).expect("parse");
+        assert_eq!(
+            WorryPolicy::common_multiple(&monkeys),
+            WorryPolicy::Modulo(46 * 13 * 17)
+        );
+
+        // Reduced worry makes the same decisions as unreduced worry.
+        let mut unreduced = monkeys.clone();
+        let policy = WorryPolicy::common_multiple(&monkeys);
+        for _ in 0..3 {
+            execute_round(&mut monkeys, policy);
+            execute_round(&mut unreduced, WorryPolicy::None);
+        }
+        for (reduced, unreduced) in monkeys.iter().zip(unreduced.iter()) {
+            assert_eq!(reduced.inspection_count, unreduced.inspection_count);
+            let expected: Vec<_> = unreduced.items.iter().map(|w| policy.apply(*w)).collect();
+            assert_eq!(reduced.items, expected);
+        }
+    }
+
+    #[test]
+    fn test_part2() {
+        let monkeys = parse(SAMPLE).expect("parse");
+        let policy = WorryPolicy::common_multiple(&monkeys);
+        let history = history(&monkeys, 10_000, policy);
+
+        // The counts the puzzle shows after some of the rounds.
+        let sampled: [(usize, [u128; 4]); 12] = [
+            (1, [2, 4, 3, 6]),
+            (20, [99, 97, 8, 103]),
+            (1000, [5204, 4792, 199, 5192]),
+            (2000, [10419, 9577, 392, 10391]),
+            (3000, [15638, 14358, 587, 15593]),
+            (4000, [20858, 19138, 780, 20797]),
+            (5000, [26075, 23921, 974, 26000]),
+            (6000, [31294, 28702, 1165, 31204]),
+            (7000, [36508, 33488, 1360, 36400]),
+            (8000, [41728, 38268, 1553, 41606]),
+            (9000, [46945, 43051, 1746, 46807]),
+            (10000, [52166, 47830, 1938, 52013]),
+        ];
+        for (round, counts) in sampled {
+            assert_eq!(history[round], counts, "after round {round}");
+        }
+
+        let mut counts = history[10_000].clone();
+        counts.sort_by_key(|count| Reverse(*count));
+        assert_eq!(counts[0] * counts[1], 2713310158);
+    }
+
+    #[test]
+    fn test_history_csv() {
+        let monkeys = parse(SAMPLE).expect("parse");
+        let csv = history_csv(&history(&monkeys, 20, RELIEF));
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("round,monkey,inspections"));
+        assert_eq!(lines.clone().count(), 20 * 4);
+        assert_eq!(
+            lines.take(4).collect::<Vec<_>>(),
+            ["1,0,2", "1,1,4", "1,2,3", "1,3,5"]
+        );
+        assert!(csv.ends_with("20,3,105\n"));
+    }
+
+    #[test]
+    fn test_fast_forward() {
+        let monkeys = parse(SAMPLE).expect("parse");
+        let policy = WorryPolicy::common_multiple(&monkeys);
+        let day = Day11 {
+            monkeys: monkeys.clone(),
+            rounds: 10_000,
+            parallel: false,
+        };
+        assert_eq!(day.monkey_business(10_000, policy), 2713310158);
+
+        // The sample's items first return to an earlier position after
+        // 175 + 76608 rounds, so this skips ahead about once.
+        let (start, period) = (175, 76608);
+        let rounds = 160_000;
+        let history = history(&monkeys, rounds, policy);
+        assert_eq!(day.inspection_counts(rounds, policy), history[rounds]);
+
+        let rounds = 1_000_000_000;
+        let (cycles, rest) = ((rounds - start) / period, (rounds - start) % period);
+        let expected: Vec<_> = (0..4)
+            .map(|i| {
+                let per_cycle = history[start + period][i] - history[start][i];
+                history[start + rest][i] + cycles as u128 * per_cycle
+            })
+            .collect();
+        assert_eq!(day.inspection_counts(rounds, policy), expected);
+    }
+}
@@ -0,0 +1,626 @@
+//! Day 12: Hill Climbing Algorithm.
+
+use crate::{
+    grid::{Grid2D, Point},
+    search::{self, GridTrace, Heuristic, Observer, TieBreak},
+    solution::{self, Answer, Answers, Solution},
+};
+use anyhow::{bail, ensure, Context, Error};
+use euclid::point2;
+use std::{cmp::Ordering, collections::HashMap};
+use tracing::debug;
+
+pub const DATA: &str = include_str!("../data/day12.txt");
+pub const SAMPLE: &str = include_str!("../data/day12_sample.txt");
+
+fn height_value(c: char) -> usize {
+    c as usize - 'a' as usize
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Element {
+    Start,
+    End,
+    Height(usize),
+}
+
+impl Element {
+    fn elevation(&self) -> usize {
+        match self {
+            Self::Start => height_value('a'),
+            Self::End => height_value('z'),
+            Self::Height(v) => *v,
+        }
+    }
+
+    fn is_legal_from(&self, other: &Element) -> bool {
+        let my_height = self.elevation();
+        let other_height = other.elevation();
+        let delta = other_height as isize - my_height as isize;
+
+        delta <= 1
+    }
+
+    /// Legality of walking backwards, from `other` into `self`.
+    fn is_legal_reverse_from(&self, other: &Element) -> bool {
+        let my_height = self.elevation();
+        let other_height = other.elevation();
+        let delta = other_height as isize - my_height as isize;
+
+        delta >= -1
+    }
+}
+
+impl TryFrom<char> for Element {
+    type Error = Error;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        Ok(match c {
+            'S' => Element::Start,
+            'E' => Element::End,
+            'a'..='z' => Element::Height(height_value(c)),
+            _ => bail!("illegal height {c:?}"),
+        })
+    }
+}
+
+fn direction_char(from: Point, to: Point) -> char {
+    let x_cmp = to.x.cmp(&from.x);
+    let y_cmp = to.y.cmp(&from.y);
+
+    match x_cmp {
+        Ordering::Less => '<',
+        Ordering::Greater => '>',
+        Ordering::Equal => match y_cmp {
+            Ordering::Less => '^',
+            Ordering::Greater => 'v',
+            Ordering::Equal => panic!("no direction"),
+        },
+    }
+}
+
+/// A node in the searches: a cell, or `None` for the moment before the
+/// search has picked which of the starting cells to set off from.
+type Node = Option<Point>;
+
+/// A heightmap, which may have several starts and ends and, below it
+/// after a blank line, a grid of digits giving what it costs to step onto
+/// each cell. Without one every step costs one.
+#[derive(Debug, Clone)]
+pub struct Map {
+    data: Grid2D<Element>,
+    starts: Vec<Point>,
+    ends: Vec<Point>,
+    costs: Option<Grid2D<usize>>,
+}
+
+impl Map {
+    fn get_element(&self, p: &Point) -> Element {
+        self.data[*p]
+    }
+
+    fn cost(&self, p: Point) -> usize {
+        self.costs.as_ref().map_or(1, |costs| costs[p])
+    }
+
+    fn all_elevation_a(&self) -> Vec<Point> {
+        self.data
+            .iter()
+            .filter(|(_, e)| e.elevation() == 0)
+            .map(|(p, _)| p)
+            .collect()
+    }
+
+    /// In-bounds points orthogonally adjacent to `p`.
+    fn neighbors(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        self.data.neighbors4(p)
+    }
+
+    fn successors(&self, p: Point) -> Vec<Point> {
+        self.successors_with(p, Element::is_legal_from)
+    }
+
+    fn successors_reverse(&self, p: Point) -> Vec<Point> {
+        self.successors_with(p, Element::is_legal_reverse_from)
+    }
+
+    fn successors_with(&self, p: Point, is_legal: fn(&Element, &Element) -> bool) -> Vec<Point> {
+        let element = self.get_element(&p);
+        self.neighbors(p)
+            .filter(|n| is_legal(&element, &self.get_element(n)))
+            .collect()
+    }
+
+    /// An observer that draws the search spreading over the map every
+    /// `every` expansions.
+    fn search_trace(&self, every: usize) -> GridTrace<Node> {
+        GridTrace::new(
+            self.data.width(),
+            self.data.height(),
+            |node| node.map_or((usize::MAX, usize::MAX), |p| (p.x as usize, p.y as usize)),
+            every,
+        )
+    }
+
+    pub fn render_result(&self, result: &[Point]) -> String {
+        let mut trail = self.data.map(|e| match e {
+            Element::End => 'E',
+            _ => '.',
+        });
+        for step in result.windows(2) {
+            trail[step[0]] = direction_char(step[0], step[1]);
+        }
+        trail.to_string()
+    }
+
+    /// Each row of the map with `path` drawn over it in arrows, for drawing
+    /// in colour: what each cell shows, its elevation from 0 at a to 25 at
+    /// z, and where it is on the path.
+    pub fn path_cells(&self, path: &[Point]) -> Vec<Vec<PathCell>> {
+        let arrows: HashMap<Point, char> = path
+            .windows(2)
+            .map(|step| (step[0], direction_char(step[0], step[1])))
+            .collect();
+        let (first, last) = (path.first(), path.last());
+        self.data
+            .rows()
+            .enumerate()
+            .map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(|(x, element)| {
+                        let p = point2(x as isize, y as isize);
+                        let c = match (arrows.get(&p), element) {
+                            (Some(arrow), _) => *arrow,
+                            (None, Element::Start) => 'S',
+                            (None, Element::End) => 'E',
+                            (None, Element::Height(h)) => (b'a' + *h as u8) as char,
+                        };
+                        let mark = if Some(&p) == first {
+                            PathMark::First
+                        } else if Some(&p) == last {
+                            PathMark::Last
+                        } else if arrows.contains_key(&p) {
+                            PathMark::Step
+                        } else {
+                            PathMark::Off
+                        };
+                        PathCell {
+                            c,
+                            elevation: element.elevation(),
+                            mark,
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Where a cell is on a path drawn over the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathMark {
+    First,
+    Last,
+    Step,
+    Off,
+}
+
+/// One cell of the map with a path drawn over it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathCell {
+    pub c: char,
+    pub elevation: usize,
+    pub mark: PathMark,
+}
+
+pub fn parse(s: &str) -> Result<Map, Error> {
+    let (heights, costs) = match s.split_once("\n\n") {
+        Some((heights, costs)) if !costs.trim().is_empty() => (heights, Some(costs)),
+        _ => (s, None),
+    };
+    let data = Grid2D::parse(heights, Element::try_from)?;
+    let find = |wanted: Element| {
+        let found: Vec<_> = data
+            .iter()
+            .filter(|(_, e)| **e == wanted)
+            .map(|(p, _)| p)
+            .collect();
+        if found.is_empty() {
+            bail!("no {wanted:?} on the map");
+        }
+        Ok(found)
+    };
+    let costs = costs
+        .map(|costs| {
+            let costs = Grid2D::parse(costs.trim_end(), |c| {
+                c.to_digit(10)
+                    .map(|cost| cost as usize)
+                    .with_context(|| format!("bad cost {c:?}"))
+            })
+            .context("costs")?;
+            ensure!(
+                (costs.width(), costs.height()) == (data.width(), data.height()),
+                "the costs are {}x{} but the map is {}x{}",
+                costs.width(),
+                costs.height(),
+                data.width(),
+                data.height()
+            );
+            Ok(costs)
+        })
+        .transpose()?;
+    Ok(Map {
+        starts: find(Element::Start)?,
+        ends: find(Element::End)?,
+        costs,
+        data,
+    })
+}
+
+/// The cheapest path from any of `starts` to a cell where `success` is
+/// true, moving to `successors` at `step_cost`, with its cost. With an
+/// `estimate` of the cost left from a cell the search is A*; otherwise
+/// maps without costs are searched breadth first, and with them by
+/// Dijkstra.
+fn cheapest_path(
+    map: &Map,
+    starts: &[Point],
+    successors: impl Fn(Point) -> Vec<Point>,
+    step_cost: impl Fn(Point, Point) -> usize,
+    success: impl Fn(Point) -> bool,
+    estimate: Option<&dyn Fn(Point) -> usize>,
+    observer: &mut impl Observer<Node, usize>,
+) -> Option<(Vec<Point>, usize)> {
+    let next = |node: &Node| -> Vec<(Node, usize)> {
+        match node {
+            None => starts.iter().map(|p| (Some(*p), 0)).collect(),
+            Some(p) => successors(*p)
+                .into_iter()
+                .map(|n| (Some(n), step_cost(*p, n)))
+                .collect(),
+        }
+    };
+    let done = |node: &Node| node.is_some_and(&success);
+    let (path, cost) = if let Some(estimate) = estimate {
+        search::astar_with(
+            &None,
+            next,
+            |node: &Node| node.map_or(0, estimate),
+            done,
+            TieBreak::default(),
+            observer,
+        )?
+    } else if map.costs.is_some() {
+        search::dijkstra(&None, next, done, observer)?
+    } else {
+        let path = search::bfs(
+            &None,
+            |node| next(node).into_iter().map(|(n, _)| n),
+            done,
+            observer,
+        )?;
+        // Leaving the virtual start isn't a step.
+        let steps = path.len() - 2;
+        (path, steps)
+    };
+    Some((path.into_iter().flatten().collect(), cost))
+}
+
+/// The cheapest path from any of `starts` to an end, guided by
+/// `heuristic` unless it's [`Heuristic::Zero`].
+fn find_path_from(
+    map: &Map,
+    starts: &[Point],
+    heuristic: Heuristic,
+    observer: &mut impl Observer<Node, usize>,
+) -> Option<(Vec<Point>, usize)> {
+    // Every step costs at least the cheapest cell, so this many times the
+    // distance to the nearest end can't be too much.
+    let cheapest = map
+        .costs
+        .as_ref()
+        .map_or(1, |costs| costs.iter().map(|(_, c)| *c).min().unwrap_or(0));
+    let estimate = |p: Point| {
+        map.ends
+            .iter()
+            .map(|end| heuristic.estimate(p, *end) as usize * cheapest)
+            .min()
+            .unwrap_or(0)
+    };
+    cheapest_path(
+        map,
+        starts,
+        |p| map.successors(p),
+        |_, to| map.cost(to),
+        |p| map.get_element(&p) == Element::End,
+        (heuristic != Heuristic::Zero).then_some(&estimate as &dyn Fn(Point) -> usize),
+        observer,
+    )
+}
+
+pub fn find_path(
+    map: &Map,
+    observer: &mut impl Observer<Node, usize>,
+) -> Option<(Vec<Point>, usize)> {
+    find_path_with(map, Heuristic::Zero, observer)
+}
+
+/// [`find_path`] by A* with `heuristic`.
+pub fn find_path_with(
+    map: &Map,
+    heuristic: Heuristic,
+    observer: &mut impl Observer<Node, usize>,
+) -> Option<(Vec<Point>, usize)> {
+    find_path_from(map, &map.starts, heuristic, observer)
+}
+
+/// Find the cheapest path from any elevation-a cell to an end with a
+/// single search that walks backwards from the ends.
+pub fn find_path_reverse(
+    map: &Map,
+    observer: &mut impl Observer<Node, usize>,
+) -> Option<(Vec<Point>, usize)> {
+    let (mut path, cost) = cheapest_path(
+        map,
+        &map.ends,
+        |p| map.successors_reverse(p),
+        // Walking forwards, this step would have been onto `from`.
+        |from, _| map.cost(from),
+        |p| map.get_element(&p).elevation() == 0,
+        None,
+        observer,
+    )?;
+    path.reverse();
+    Some((path, cost))
+}
+
+fn find_path_each_start(
+    map: &Map,
+    heuristic: Heuristic,
+    observer: &mut impl Observer<Node, usize>,
+) -> Option<(Vec<Point>, usize)> {
+    map.all_elevation_a()
+        .iter()
+        .filter_map(|p| find_path_from(map, &[*p], heuristic, &mut *observer))
+        .min_by_key(|(_, cost)| *cost)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// Find the part 2 trailhead with a search from every elevation-a cell
+    /// instead of a single reverse search from the end.
+    pub each_start: bool,
+    /// Log the cells the search has expanded every this many steps.
+    pub show_search: Option<usize>,
+    /// Search towards the ends by A* with this, or breadth first or by
+    /// Dijkstra with [`Heuristic::Zero`]. The reverse search in part 2 has
+    /// no one place to head for, so it never uses one.
+    pub heuristic: Heuristic,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            each_start: false,
+            show_search: None,
+            heuristic: Heuristic::Zero,
+        }
+    }
+}
+
+pub struct Day12 {
+    map: Map,
+    each_start: bool,
+    show_search: Option<usize>,
+    heuristic: Heuristic,
+}
+
+impl Day12 {
+    pub fn new(input: &str, options: &Options) -> Result<Self, Error> {
+        Ok(Self {
+            map: parse(input)?,
+            each_start: options.each_start,
+            show_search: options.show_search,
+            heuristic: options.heuristic,
+        })
+    }
+
+    fn search_trace(&self) -> Option<GridTrace<Node>> {
+        self.show_search.map(|every| self.map.search_trace(every))
+    }
+}
+
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        let (path, cost) = find_path_with(&self.map, self.heuristic, &mut self.search_trace())
+            .context("no path to the end")?;
+        debug!("\n{}", self.map.render_result(&path));
+        Ok(cost.into())
+    }
+
+    fn part_2(&self) -> Result<Answer, Error> {
+        let mut trace = self.search_trace();
+        let (trail, cost) = if self.each_start {
+            find_path_each_start(&self.map, self.heuristic, &mut trace)
+        } else {
+            find_path_reverse(&self.map, &mut trace)
+        }
+        .context("no path to the end from elevation a")?;
+        debug!("\n{}", self.map.render_result(&trail));
+        Ok(cost.into())
+    }
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day12::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let map = parse(SAMPLE).unwrap();
+        assert_eq!((map.data.width(), map.data.height()), (8, 5));
+        assert_eq!(map.starts, [point2(0, 0)]);
+        assert_eq!(map.ends, [point2(5, 2)]);
+        assert!(map.costs.is_none());
+        assert!(parse("abc").is_err());
+        assert!(parse("SbE\n\n12").is_err());
+        assert!(parse("SbE\n\n1x1").is_err());
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let map = parse(SAMPLE).unwrap();
+        let corner: Vec<_> = map.neighbors(point2(0, 0)).collect();
+        assert_eq!(corner, [point2(1, 0), point2(0, 1)]);
+        assert_eq!(map.neighbors(point2(3, 2)).count(), 4);
+        assert_eq!(map.successors(point2(0, 0)), [point2(1, 0), point2(0, 1)]);
+    }
+
+    #[test]
+    fn test_part1() {
+        let map = parse(SAMPLE).unwrap();
+        let (path, steps) = find_path(&map, &mut ()).unwrap();
+        assert_eq!(steps, 31);
+        assert_eq!(path.len(), 32);
+        assert_eq!(path[0], point2(0, 0));
+    }
+
+    #[test]
+    fn test_part2() {
+        let map = parse(SAMPLE).unwrap();
+        let (_, steps) = find_path_each_start(&map, Heuristic::Zero, &mut ()).unwrap();
+        assert_eq!(steps, 29);
+    }
+
+    #[test]
+    fn test_part2_reverse() {
+        let map = parse(SAMPLE).unwrap();
+
+        let (trail, steps) = find_path_reverse(&map, &mut ()).unwrap();
+        assert_eq!(steps, 29);
+        assert_eq!(trail.last(), Some(&point2(5, 2)));
+        assert_eq!(
+            trail.len(),
+            find_path_each_start(&map, Heuristic::Zero, &mut ())
+                .unwrap()
+                .0
+                .len()
+        );
+    }
+
+    /// The letters from b to y, to climb from a start to an end.
+    const CLIMB: &str = "bcdefghijklmnopqrstuvwxy";
+
+    #[test]
+    fn test_several_starts_and_ends() {
+        // Only the second start can climb out, but either end will do.
+        let walled = "z".repeat(24);
+        let input = format!("S{walled}E\n{}\nS{CLIMB}E", "z".repeat(26));
+        let map = parse(&input).unwrap();
+        assert_eq!(map.starts, [point2(0, 0), point2(0, 2)]);
+        assert_eq!(map.ends, [point2(25, 0), point2(25, 2)]);
+        let (path, steps) = find_path(&map, &mut ()).unwrap();
+        assert_eq!(steps, 25);
+        assert_eq!(path[0], point2(0, 2));
+        assert_eq!(find_path_reverse(&map, &mut ()).unwrap().1, 25);
+    }
+
+    #[test]
+    fn test_costs() {
+        // The top row is the shortest way but dear, so the cheapest goes
+        // along the bottom and steps up to the end at the last moment.
+        let input = format!(
+            "S{CLIMB}E\na{CLIMB}z\n\n1{}1\n{}\n",
+            "9".repeat(24),
+            "1".repeat(26)
+        );
+        let map = parse(&input).unwrap();
+        let (path, cost) = find_path(&map, &mut ()).unwrap();
+        assert_eq!(cost, 27);
+        assert_eq!(path.len(), 28);
+        assert_eq!(
+            find_path_each_start(&map, Heuristic::Zero, &mut ())
+                .unwrap()
+                .1,
+            26
+        );
+        assert_eq!(find_path_reverse(&map, &mut ()).unwrap().1, 26);
+
+        // The sample's the same with every cost one.
+        let ones = "1".repeat(8) + "\n";
+        let map = parse(&format!("{SAMPLE}\n\n{}", ones.repeat(5))).unwrap();
+        assert!(map.costs.is_some());
+        assert_eq!(find_path(&map, &mut ()).unwrap().1, 31);
+        assert_eq!(find_path_reverse(&map, &mut ()).unwrap().1, 29);
+    }
+
+    #[test]
+    fn test_heuristics() {
+        let sample = parse(SAMPLE).unwrap();
+        let ones = "1".repeat(8) + "\n";
+        let costed = parse(&format!("{SAMPLE}\n\n{}", ones.repeat(5))).unwrap();
+        for heuristic in [Heuristic::Taxicab, Heuristic::Chebyshev] {
+            let (path, steps) = find_path_with(&sample, heuristic, &mut ()).unwrap();
+            assert_eq!((path.len(), steps), (32, 31), "{heuristic:?}");
+            assert_eq!(find_path_with(&costed, heuristic, &mut ()).unwrap().1, 31);
+            assert_eq!(
+                find_path_each_start(&sample, heuristic, &mut ()).unwrap().1,
+                29
+            );
+        }
+
+        // A* shouldn't look at more of the map than breadth first does.
+        let expansions = |heuristic| {
+            let mut expanded = 0;
+            find_path_with(&sample, heuristic, &mut |_: &search::Progress<
+                Node,
+                usize,
+            >| { expanded += 1 });
+            expanded
+        };
+        assert!(expansions(Heuristic::Taxicab) <= expansions(Heuristic::Zero));
+    }
+
+    #[test]
+    fn test_path_cells() {
+        let map = parse(SAMPLE).unwrap();
+        let (trail, _) = find_path_reverse(&map, &mut ()).unwrap();
+        let rows = map.path_cells(&trail);
+        assert_eq!(rows.len(), 5);
+        assert!(rows.iter().all(|row| row.len() == 8));
+        let start = trail[0];
+        assert_eq!(
+            rows[start.y as usize][start.x as usize],
+            PathCell {
+                c: direction_char(trail[0], trail[1]),
+                elevation: 0,
+                mark: PathMark::First
+            }
+        );
+        assert_eq!(rows[2][5].c, 'E');
+        assert_eq!(rows[2][5].mark, PathMark::Last);
+        assert_eq!(rows[0][0].c, 'S');
+        let steps = rows
+            .iter()
+            .flatten()
+            .filter(|cell| cell.mark == PathMark::Step);
+        assert_eq!(steps.count(), trail.len() - 2);
+    }
+
+    #[test]
+    fn test_search_trace() {
+        let map = parse(SAMPLE).unwrap();
+        let mut trace = map.search_trace(usize::MAX);
+        find_path(&map, &mut trace);
+        assert_eq!(trace.visits(0, 0), 1);
+        assert_eq!(trace.render().lines().count(), 5);
+    }
+}
@@ -0,0 +1,468 @@
+//! Day 13: Distress Signal.
+
+use crate::{
+    parse::parse_lines_with,
+    solution::{self, Answer, Answers, Solution},
+};
+use anyhow::{anyhow, bail, Error};
+use nom::{
+    branch::alt,
+    character::complete::{char, u32},
+    combinator::all_consuming,
+    multi::separated_list0,
+    sequence::delimited,
+    Finish, IResult,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::{Ordering, PartialOrd},
+    fmt,
+    str::FromStr,
+};
+use tracing::debug;
+
+pub const DATA: &str = include_str!("../data/day13.txt");
+pub const SAMPLE: &str = include_str!("../data/day13_sample.txt");
+
+fn packet_value(input: &str) -> IResult<&str, Packet> {
+    let (input, value) = u32(input)?;
+    Ok((input, Packet::Value(value)))
+}
+
+fn bracketed(input: &str) -> IResult<&str, Packet> {
+    let (input, values) = delimited(
+        char('['),
+        separated_list0(char(','), alt((packet_value, bracketed))),
+        char(']'),
+    )(input)?;
+    Ok((input, Packet::List(values)))
+}
+
+/// A packet is a JSON array of numbers and arrays, so serde can read and
+/// write it as well as the nom parser.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Packet {
+    List(Vec<Packet>),
+    Value(u32),
+}
+
+impl PartialOrd for Packet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Compare two lists element by element, with the shorter list first if
+/// one is a prefix of the other.
+fn compare_lists(left: &[Packet], right: &[Packet]) -> Ordering {
+    left.iter().cmp(right.iter())
+}
+
+impl Ord for Packet {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Value(v), Self::Value(ov)) => v.cmp(ov),
+            (Self::List(v), Self::List(ov)) => compare_lists(v, ov),
+            (Self::Value(_), Self::List(ov)) => compare_lists(std::slice::from_ref(self), ov),
+            (Self::List(v), Self::Value(_)) => compare_lists(v, std::slice::from_ref(other)),
+        }
+    }
+}
+
+impl FromStr for Packet {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        all_consuming(bracketed)(s.trim())
+            .finish()
+            .map(|(_, packet)| packet)
+            .map_err(|e| anyhow!("bad packet: {e}"))
+    }
+}
+
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Value(v) => write!(f, "{v}"),
+            Self::List(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PacketPair {
+    left: Packet,
+    right: Packet,
+}
+
+impl PacketPair {
+    fn is_ordered(&self) -> bool {
+        self.left.cmp(&self.right) == Ordering::Less
+    }
+}
+
+/// Which parser reads the packets.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Backend {
+    #[default]
+    Nom,
+    Json,
+}
+
+impl Backend {
+    fn parse_packet(&self, s: &str) -> Result<Packet, Error> {
+        match self {
+            Self::Nom => s.parse(),
+            Self::Json => match serde_json::from_str(s)? {
+                packet @ Packet::List(_) => Ok(packet),
+                Packet::Value(_) => bail!("a packet has to be a list"),
+            },
+        }
+    }
+}
+
+impl FromStr for Backend {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "nom" => Self::Nom,
+            "json" => Self::Json,
+            _ => bail!("unknown parser {s:?}, expected nom or json"),
+        })
+    }
+}
+
+/// Parse every packet in the input one line at a time, ignoring the pair
+/// structure.
+pub fn parse_packets(s: &str, backend: Backend) -> Result<Vec<Packet>, Error> {
+    parse_lines_with(s, |line| backend.parse_packet(line))
+}
+
+/// Pair up the packets in order. The blank lines between pairs don't
+/// matter.
+fn pairs(packets: &[Packet]) -> Result<Vec<PacketPair>, Error> {
+    if packets.len() % 2 == 1 {
+        bail!("{} packets can't be paired up", packets.len());
+    }
+    Ok(packets
+        .chunks(2)
+        .map(|pair| PacketPair {
+            left: pair[0].clone(),
+            right: pair[1].clone(),
+        })
+        .collect())
+}
+
+fn dividers() -> [Packet; 2] {
+    [2, 6].map(|v| Packet::List(vec![Packet::List(vec![Packet::Value(v)])]))
+}
+
+/// All the packets and the two dividers, in the right order.
+pub fn sorted_with_dividers(packets: &[Packet]) -> Vec<Packet> {
+    let mut packets: Vec<_> = packets.iter().cloned().chain(dividers()).collect();
+    packets.sort();
+    packets
+}
+
+/// The product of the dividers' places, found by sorting them in with the
+/// packets.
+fn sorted_marker_value(packets: &[Packet]) -> usize {
+    let packets = sorted_with_dividers(packets);
+    dividers()
+        .iter()
+        .map(|divider| packets.iter().position(|p| p == divider).expect("divider") + 1)
+        .product()
+}
+
+/// The product of the dividers' places, without sorting: a divider's place
+/// is one more than the number of packets, and earlier dividers, that would
+/// be sorted before it. Packets that compare equal to a divider count too,
+/// since the sort is stable and the dividers go in after the packets.
+fn calculate_marker_value(packets: &[Packet]) -> usize {
+    let dividers = dividers();
+    dividers
+        .iter()
+        .enumerate()
+        .map(|(earlier, divider)| earlier + packets.iter().filter(|p| *p <= divider).count() + 1)
+        .product()
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    /// Which parser to read packets with.
+    pub parser: Backend,
+    /// Find the dividers' places in part 2 by sorting all the packets, to
+    /// check the counting.
+    pub sort: bool,
+}
+
+pub struct Day13 {
+    packets: Vec<Packet>,
+    sort: bool,
+}
+
+impl Day13 {
+    pub fn new(input: &str, options: &Options) -> Result<Self, Error> {
+        Ok(Self {
+            packets: parse_packets(input, options.parser)?,
+            sort: options.sort,
+        })
+    }
+}
+
+impl Solution for Day13 {
+    const DAY: u8 = 13;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        let correct_indices: Vec<_> = pairs(&self.packets)?
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| p.is_ordered().then_some(i + 1))
+            .collect();
+        debug!(?correct_indices);
+        Ok(correct_indices.iter().sum::<usize>().into())
+    }
+
+    fn part_2(&self) -> Result<Answer, Error> {
+        Ok(match self.sort {
+            true => sorted_marker_value(&self.packets),
+            false => calculate_marker_value(&self.packets),
+        }
+        .into())
+    }
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day13::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn parse(s: &str) -> Result<Vec<PacketPair>, Error> {
+        pairs(&parse_packets(s, Backend::Nom)?)
+    }
+
+    /// Direct transcription of the puzzle's comparison rules.
+    fn reference_cmp(left: &Packet, right: &Packet) -> Ordering {
+        match (left, right) {
+            (Packet::Value(l), Packet::Value(r)) => l.cmp(r),
+            (Packet::List(l), Packet::List(r)) => {
+                let mut i = 0;
+                loop {
+                    match (l.get(i), r.get(i)) {
+                        (None, None) => return Ordering::Equal,
+                        (None, Some(_)) => return Ordering::Less,
+                        (Some(_), None) => return Ordering::Greater,
+                        (Some(a), Some(b)) => {
+                            let ordering = reference_cmp(a, b);
+                            if ordering != Ordering::Equal {
+                                return ordering;
+                            }
+                        }
+                    }
+                    i += 1;
+                }
+            }
+            (Packet::Value(l), Packet::List(_)) => {
+                reference_cmp(&Packet::List(vec![Packet::Value(*l)]), right)
+            }
+            (Packet::List(_), Packet::Value(r)) => {
+                reference_cmp(left, &Packet::List(vec![Packet::Value(*r)]))
+            }
+        }
+    }
+
+    fn packet_strategy() -> impl Strategy<Value = Packet> {
+        let leaf = (0u32..4).prop_map(Packet::Value);
+        leaf.prop_recursive(4, 32, 4, |inner| {
+            prop::collection::vec(inner, 0..4).prop_map(Packet::List)
+        })
+    }
+
+    /// Packets as they appear in the input, which are always lists.
+    fn list_strategy() -> impl Strategy<Value = Packet> {
+        prop::collection::vec(packet_strategy(), 0..5).prop_map(Packet::List)
+    }
+
+    fn render_pairs(pairs: &[(Packet, Packet)]) -> String {
+        pairs
+            .iter()
+            .map(|(left, right)| format!("{left}\n{right}\n"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    proptest! {
+        #[test]
+        fn test_packet_round_trip(packet in list_strategy()) {
+            prop_assert_eq!(packet.to_string().parse::<Packet>().unwrap(), packet);
+        }
+
+        #[test]
+        fn test_backends_agree(packet in list_strategy()) {
+            let text = packet.to_string();
+            prop_assert_eq!(serde_json::to_string(&packet).unwrap(), text.clone());
+            prop_assert_eq!(Backend::Json.parse_packet(&text).unwrap(), packet);
+        }
+
+        #[test]
+        fn test_parse_generated(
+            pairs in prop::collection::vec((list_strategy(), list_strategy()), 1..6),
+        ) {
+            let input = render_pairs(&pairs);
+            let parsed = parse(&input).unwrap();
+            prop_assert_eq!(parsed.len(), pairs.len());
+            for (pair, (left, right)) in parsed.iter().zip(&pairs) {
+                prop_assert_eq!(&pair.left, left);
+                prop_assert_eq!(&pair.right, right);
+            }
+            let day = Day13 {
+                packets: parse_packets(&input, Backend::Nom).unwrap(),
+                sort: false,
+            };
+            day.part_1().unwrap();
+            day.part_2().unwrap();
+        }
+
+        #[test]
+        fn test_marker_value_agrees(packets in prop::collection::vec(list_strategy(), 0..20)) {
+            // Sorting finds a copy of a divider in the input instead of the
+            // divider itself, which the puzzle's inputs never have.
+            prop_assume!(packets.iter().all(|p| !dividers().contains(p)));
+            prop_assert_eq!(calculate_marker_value(&packets), sorted_marker_value(&packets));
+        }
+
+        #[test]
+        fn test_cmp_matches_reference(a in packet_strategy(), b in packet_strategy()) {
+            prop_assert_eq!(a.cmp(&b), reference_cmp(&a, &b));
+        }
+
+        #[test]
+        fn test_cmp_total_order(
+            a in packet_strategy(),
+            b in packet_strategy(),
+            c in packet_strategy(),
+        ) {
+            prop_assert_eq!(a.cmp(&a), Ordering::Equal);
+            prop_assert_eq!(a.cmp(&b), b.cmp(&a).reverse());
+            if a <= b && b <= c {
+                prop_assert!(a <= c);
+            }
+        }
+    }
+
+    #[test]
+    fn test_nom() {
+        let r = bracketed("[3,2,1]");
+        let r = r.unwrap();
+        assert_eq!(
+            r.1,
+            Packet::List(vec![Packet::Value(3), Packet::Value(2), Packet::Value(1)])
+        );
+
+        let r = bracketed("[3,2,[1]]");
+        let r = r.unwrap();
+        assert_eq!(
+            r.1,
+            Packet::List(vec![
+                Packet::Value(3),
+                Packet::Value(2),
+                Packet::List(vec![Packet::Value(1)])
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let packet_pairs = parse(SAMPLE).unwrap();
+        assert_eq!(packet_pairs.len(), 8);
+        assert_eq!(
+            packet_pairs[0].left,
+            Packet::List(vec![
+                Packet::Value(1),
+                Packet::Value(1),
+                Packet::Value(3),
+                Packet::Value(1),
+                Packet::Value(1)
+            ])
+        );
+        assert_eq!(
+            packet_pairs[0].right,
+            Packet::List(vec![
+                Packet::Value(1),
+                Packet::Value(1),
+                Packet::Value(5),
+                Packet::Value(1),
+                Packet::Value(1)
+            ])
+        );
+        assert_eq!(packet_pairs[5].left, Packet::List(vec![]));
+        assert_eq!(packet_pairs[5].right, Packet::List(vec![Packet::Value(3),]));
+    }
+
+    #[test]
+    fn test_part_1() {
+        let packet_pairs = parse(SAMPLE).unwrap();
+        assert!(packet_pairs[0].is_ordered());
+        assert!(packet_pairs[1].is_ordered());
+        assert!(!packet_pairs[2].is_ordered());
+        assert!(packet_pairs[3].is_ordered());
+        assert!(!packet_pairs[4].is_ordered());
+        assert!(packet_pairs[5].is_ordered());
+        assert!(!packet_pairs[6].is_ordered());
+        assert!(!packet_pairs[7].is_ordered());
+    }
+
+    #[test]
+    fn test_parse_packets() {
+        let packets = parse_packets(SAMPLE, Backend::Nom).unwrap();
+        assert_eq!(packets.len(), 16);
+        assert_eq!(packets[11], Packet::List(vec![Packet::Value(3)]));
+
+        for bad in ["[1,2", "[1]]", "1", "[a]"] {
+            assert!(bad.parse::<Packet>().is_err(), "{bad}");
+        }
+        let error = parse("[1]\n[2]\n\n[3\n[4]").unwrap_err();
+        assert!(format!("{error:#}").starts_with("line 4: \"[3\""));
+        assert!(parse("[1]\n[2]\n\n[3]").is_err());
+    }
+
+    #[test]
+    fn test_json_backend() {
+        for input in [SAMPLE, DATA] {
+            assert_eq!(
+                parse_packets(input, Backend::Json).unwrap(),
+                parse_packets(input, Backend::Nom).unwrap()
+            );
+        }
+        for bad in ["[1,2", "1", "[1,\"a\"]", "[-1]"] {
+            assert!(Backend::Json.parse_packet(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_part_2() {
+        let packets = parse_packets(SAMPLE, Backend::Nom).unwrap();
+        assert_eq!(calculate_marker_value(&packets), 140);
+        assert_eq!(sorted_marker_value(&packets), 140);
+        // [[[2]]] compares equal to [[2]] and sorts before it.
+        let tied: Vec<Packet> = vec!["[[[2]]]".parse().unwrap()];
+        assert_eq!(calculate_marker_value(&tied), 6);
+        assert_eq!(sorted_marker_value(&tied), 6);
+    }
+}
@@ -0,0 +1,409 @@
+//! Day 14: Regolith Reservoir.
+
+use crate::{
+    grid::{Bounds, Grid2D, SparseGrid},
+    parse::parse_lines_with,
+    solution::{self, Answer, Answers, Solution},
+};
+use anyhow::{ensure, Context, Error};
+use euclid::{point2, vec2};
+
+pub const DATA: &str = include_str!("../data/day14.txt");
+pub const SAMPLE: &str = include_str!("../data/day14_sample.txt");
+
+pub type Point = euclid::default::Point2D<isize>;
+type Vector = euclid::default::Vector2D<isize>;
+type Rect = euclid::default::Rect<isize>;
+pub type RockList = Vec<Vec<Point>>;
+
+const SAND_ORIGIN: Point = point2(500, 0);
+
+/// Where sand tries to go, in order.
+const DELTAS: &[Vector] = &[vec2(0, 1), vec2(-1, 1), vec2(1, 1)];
+
+struct LineIter {
+    current: Point,
+    end: Point,
+    delta: Vector,
+}
+
+impl LineIter {
+    fn new(start: Point, end: Point) -> Self {
+        let b = Bounds::from_points([start, end]);
+        let start = b.min;
+        let end = b.max;
+        let mut delta = end - start;
+        if delta.x > 0 {
+            delta.x /= delta.x;
+        }
+        if delta.y > 0 {
+            delta.y /= delta.y;
+        }
+        Self {
+            current: start,
+            delta,
+            end,
+        }
+    }
+}
+
+impl Iterator for LineIter {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Point> {
+        if self.current.x > self.end.x || self.current.y > self.end.y {
+            return None;
+        }
+        let next = self.current;
+        self.current += self.delta;
+        Some(next)
+    }
+}
+
+#[derive(Debug)]
+enum Block {
+    Rock,
+    Sand,
+}
+
+/// What's under the rocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FloorMode {
+    /// Nothing: sand that falls past the lowest rock falls forever.
+    None,
+    /// An endless floor this far below the lowest rock.
+    Below(isize),
+}
+
+#[derive(Debug)]
+pub struct RockFall {
+    bounds: Rect,
+    blocks: SparseGrid<Block>,
+    falling_sand: Option<Point>,
+    floor: Option<isize>,
+    units: usize,
+}
+
+impl RockFall {
+    pub fn new(list: RockList) -> Self {
+        let bounds = Rect::from_points(list.iter().flatten());
+        let mut blocks = SparseGrid::new();
+        for rock in list {
+            for i in 0..rock.len() - 1 {
+                let iter = LineIter::new(rock[i], rock[i + 1]).map(|p| (p, Block::Rock));
+                blocks.extend(iter);
+            }
+        }
+        Self {
+            bounds,
+            blocks,
+            falling_sand: Some(SAND_ORIGIN),
+            floor: None,
+            units: 1,
+        }
+    }
+
+    pub fn with_floor(self, floor: FloorMode) -> Self {
+        let floor = match floor {
+            FloorMode::None => None,
+            FloorMode::Below(depth) => Some(self.bounds.max_y() + depth),
+        };
+        Self { floor, ..self }
+    }
+
+    /// Step the falling sand, returning how many units came to rest once
+    /// sand either falls past the rocks or piles up to the source.
+    pub fn step(&mut self) -> Option<usize> {
+        if let Some(falling_sand) = self.falling_sand.as_mut() {
+            for delta in DELTAS {
+                let new_pos = *falling_sand + *delta;
+                if Some(new_pos.y) != self.floor && !self.blocks.contains(new_pos) {
+                    *falling_sand = new_pos;
+                    if self.floor.is_none() && new_pos.y > self.bounds.max_y() {
+                        return Some(self.units - 1);
+                    }
+                    return None;
+                }
+            }
+            self.blocks.insert(*falling_sand, Block::Sand);
+            if *falling_sand == SAND_ORIGIN {
+                return Some(self.units);
+            }
+            *falling_sand = SAND_ORIGIN;
+            self.units += 1;
+            return None;
+        }
+        None
+    }
+
+    /// Let sand fall until it stops, like `step`, but without starting
+    /// each unit from the source. Every unit follows the last one's path
+    /// until it finds somewhere new to go, so a unit can carry on from
+    /// where the last one came to rest, and the whole run takes time in
+    /// proportion to the sand rather than the sand times its depth.
+    fn run_fast(&mut self) -> usize {
+        let mut path = vec![SAND_ORIGIN];
+        let mut units = 0;
+        while let Some(&position) = path.last() {
+            let next = DELTAS
+                .iter()
+                .map(|delta| position + *delta)
+                .find(|p| Some(p.y) != self.floor && !self.blocks.contains(*p));
+            match next {
+                Some(next) if self.floor.is_none() && next.y > self.bounds.max_y() => break,
+                Some(next) => path.push(next),
+                None => {
+                    self.blocks.insert(position, Block::Sand);
+                    units += 1;
+                    path.pop();
+                }
+            }
+        }
+        self.falling_sand = None;
+        units
+    }
+
+    fn char_at(&self, p: &Point) -> char {
+        match self.blocks.get(*p) {
+            Some(Block::Rock) => '#',
+            Some(Block::Sand) => 'o',
+            None if *p == SAND_ORIGIN => '+',
+            None if self.falling_sand == Some(*p) => '@',
+            None if Some(p.y) == self.floor => '=',
+            None => '.',
+        }
+    }
+
+    /// Render the cave, with each character covering a `scale` x `scale`
+    /// square of cells. Falling sand wins over resting sand, which wins
+    /// over rock.
+    pub fn render(&self, scale: usize) -> String {
+        let scale = scale.max(1) as isize;
+        let mut frame = Bounds::from_points(
+            self.blocks
+                .bounds()
+                .into_iter()
+                .flat_map(|b| [b.min, b.max])
+                .chain(std::iter::once(SAND_ORIGIN))
+                .chain(self.falling_sand),
+        );
+        if let Some(floor) = self.floor {
+            frame.max.y = frame.max.y.max(floor);
+        }
+        let cells = |min: isize, max: isize| ((max - min) / scale + 1) as usize;
+        Grid2D::from_fn(
+            cells(frame.min.x, frame.max.x),
+            cells(frame.min.y, frame.max.y),
+            |cell| {
+                let (x, y) = (frame.min.x + cell.x * scale, frame.min.y + cell.y * scale);
+                (0..scale)
+                    .flat_map(|dy| (0..scale).map(move |dx| point2(x + dx, y + dy)))
+                    .map(|p| self.char_at(&p))
+                    .max_by_key(|c| match c {
+                        '@' => 5,
+                        '+' => 4,
+                        'o' => 3,
+                        '#' => 2,
+                        '=' => 1,
+                        _ => 0,
+                    })
+                    .unwrap_or('.')
+            },
+        )
+        .to_string()
+    }
+}
+
+fn parse_point(s: &str) -> Result<Point, Error> {
+    let (x, y) = s
+        .split_once(',')
+        .with_context(|| format!("{s:?} isn't a point"))?;
+    let coordinate = |c: &str| {
+        c.trim()
+            .parse::<isize>()
+            .with_context(|| format!("{c:?} isn't a coordinate"))
+    };
+    Ok(point2(coordinate(x)?, coordinate(y)?))
+}
+
+pub fn parse(s: &str) -> Result<RockList, Error> {
+    let rocks = parse_lines_with(s, |line| line.split(" -> ").map(parse_point).collect())?;
+    ensure!(!rocks.is_empty(), "no rocks");
+    Ok(rocks)
+}
+
+/// Units of sand that come to rest before it either falls past the rocks
+/// or piles up to the source.
+fn units_of_sand(rocks: &RockList, floor: FloorMode, fast: bool) -> usize {
+    let mut rockfall = RockFall::new(rocks.clone()).with_floor(floor);
+    if fast {
+        return rockfall.run_fast();
+    }
+    loop {
+        if let Some(units) = rockfall.step() {
+            return units;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    /// Follow each unit's path on from the last, rather than dropping every
+    /// unit from the source.
+    pub fast: bool,
+}
+
+pub struct Day14 {
+    rocks: RockList,
+    fast: bool,
+}
+
+impl Day14 {
+    pub fn new(input: &str, options: &Options) -> Result<Self, Error> {
+        Ok(Self {
+            rocks: parse(input)?,
+            fast: options.fast,
+        })
+    }
+}
+
+impl Solution for Day14 {
+    const DAY: u8 = 14;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        Ok(units_of_sand(&self.rocks, FloorMode::None, self.fast).into())
+    }
+
+    fn part_2(&self) -> Result<Answer, Error> {
+        Ok(units_of_sand(&self.rocks, FloorMode::Below(2), self.fast).into())
+    }
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day14::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use euclid::rect;
+
+    #[test]
+    fn test_parse() {
+        let l = parse(SAMPLE).unwrap();
+        assert_eq!(
+            l,
+            vec![
+                vec![point2(498, 4), point2(498, 6), point2(496, 6)],
+                vec![
+                    point2(503, 4),
+                    point2(502, 4),
+                    point2(502, 9),
+                    point2(494, 9)
+                ]
+            ]
+        );
+
+        let rockfall = RockFall::new(l);
+        assert_eq!(rockfall.bounds, rect(494, 4, 9, 5));
+
+        let error = parse("hello world").unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "line 1: \"hello world\": \"hello world\" isn't a point"
+        );
+        let error = parse("498,4 -> 498,6\n503,x -> 502,4").unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "line 2: \"503,x -> 502,4\": \"x\" isn't a coordinate: invalid digit found in string"
+        );
+        assert_eq!(parse("\n").unwrap_err().to_string(), "no rocks");
+    }
+
+    #[test]
+    fn test_line_iter() {
+        let points: Vec<_> = LineIter::new(point2(498, 4), point2(498, 6)).collect();
+        dbg!(&points);
+        assert_eq!(points, [point2(498, 4,), point2(498, 5,), point2(498, 6,)]);
+        let points: Vec<_> = LineIter::new(point2(498, 6), point2(496, 6)).collect();
+        assert_eq!(points, [point2(496, 6,), point2(497, 6,), point2(498, 6,)]);
+    }
+
+    #[test]
+    fn test_render() {
+        let l = parse(SAMPLE).unwrap();
+        let mut rockfall = RockFall::new(l);
+        assert_eq!(
+            rockfall.render(1),
+            r#"......+...
+..........
+..........
+..........
+....#...##
+....#...#.
+..###...#.
+........#.
+........#.
+#########.
+"#
+        );
+
+        rockfall.step();
+        assert_eq!(rockfall.render(1).lines().nth(1), Some("......@..."));
+        assert_eq!(rockfall.render(2).lines().next(), Some("...@."));
+    }
+
+    #[test]
+    fn test_part_1() {
+        let l = parse(SAMPLE).unwrap();
+        let mut rockfall = RockFall::new(l);
+        loop {
+            if let Some(amount) = rockfall.step() {
+                assert_eq!(amount, 24);
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn test_part_2() {
+        let l = parse(SAMPLE).unwrap();
+        let mut rockfall = RockFall::new(l).with_floor(FloorMode::Below(2));
+        loop {
+            if let Some(amount) = rockfall.step() {
+                assert_eq!(amount, 93);
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn test_both_parts() {
+        for fast in [false, true] {
+            let day = Day14 {
+                rocks: parse(SAMPLE).unwrap(),
+                fast,
+            };
+            assert_eq!(day.part_1().unwrap(), 24.into());
+            assert_eq!(day.part_2().unwrap(), 93.into());
+            let day = Day14 {
+                rocks: parse(DATA).unwrap(),
+                fast,
+            };
+            assert_eq!(day.part_1().unwrap(), 737.into());
+        }
+    }
+
+    #[test]
+    fn test_fast_matches_naive() {
+        for input in [SAMPLE, DATA] {
+            let rocks = parse(input).unwrap();
+            for floor in [FloorMode::None, FloorMode::Below(2)] {
+                let naive = units_of_sand(&rocks, floor, false);
+                let mut rockfall = RockFall::new(rocks.clone()).with_floor(floor);
+                assert_eq!(rockfall.run_fast(), naive, "{floor:?}");
+            }
+        }
+    }
+}
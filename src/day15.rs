@@ -0,0 +1,531 @@
+//! Day 15: Beacon Exclusion Zone.
+
+use crate::{
+    grid::taxicab_distance,
+    intervals::IntervalSet,
+    parse::parse_lines,
+    solution::{self, Answer, Answers, Solution},
+};
+use anyhow::{anyhow, bail, Context, Error};
+use euclid::point2;
+use rayon::prelude::*;
+use std::{collections::HashSet, fmt, ops::RangeInclusive, str::FromStr};
+use tracing::debug;
+
+pub type Coord = i64;
+pub type Point = euclid::default::Point2D<Coord>;
+
+type ImpossibleRange = RangeInclusive<Coord>;
+
+pub const DATA: &str = include_str!("../data/day15.txt");
+pub const SAMPLE: &str = include_str!("../data/day15_sample.txt");
+
+#[derive(Debug)]
+pub struct Sensor {
+    location: Point,
+    closest: Point,
+    distance: Coord,
+}
+
+impl Sensor {
+    fn new(location: Point, closest: Point) -> Self {
+        Self {
+            location,
+            closest,
+            distance: taxicab_distance(location, closest),
+        }
+    }
+
+    fn covers(&self, p: Point) -> bool {
+        taxicab_distance(self.location, p) <= self.distance
+    }
+
+    fn impossible_range(&self, y: Coord) -> Option<ImpossibleRange> {
+        let distance_to_row = (self.location.y - y).abs();
+        (distance_to_row <= self.distance).then(|| {
+            let remaining = self.distance - distance_to_row;
+            let x = self.location.x;
+            let l_x = x - remaining;
+            let h_x = x + remaining;
+            l_x..=h_x
+        })
+    }
+}
+
+crate::regex_struct! {
+    /// One line of the report, as written.
+    struct Report = r"Sensor at x=(-?\d+),\s+y=(-?\d+):\s+closest beacon is at x=(-?\d+),\s+y=(-?\d+)" {
+        x: Coord,
+        y: Coord,
+        beacon_x: Coord,
+        beacon_y: Coord,
+    }
+}
+
+impl From<Report> for Sensor {
+    fn from(report: Report) -> Self {
+        Self::new(
+            point2(report.x, report.y),
+            point2(report.beacon_x, report.beacon_y),
+        )
+    }
+}
+
+/// One sensor per line.
+pub fn parse(s: &str) -> Result<Vec<Sensor>, Error> {
+    Ok(parse_lines::<Report>(s)?
+        .into_iter()
+        .map(Sensor::from)
+        .collect())
+}
+
+fn impossible_ranges_with_limit(
+    row: Coord,
+    limit: Option<Coord>,
+    sensors: &[Sensor],
+) -> Vec<ImpossibleRange> {
+    let mut ranges: IntervalSet<Coord> = sensors
+        .iter()
+        .filter_map(|sensor| sensor.impossible_range(row))
+        .collect();
+
+    if let Some(limit) = limit {
+        ranges = ranges.intersect(&(0..=limit - 1).into());
+    }
+
+    ranges.intervals().to_vec()
+}
+
+fn impossible_ranges(row: Coord, sensors: &[Sensor]) -> Vec<ImpossibleRange> {
+    impossible_ranges_with_limit(row, None, sensors)
+}
+
+/// Positions in `row` that the sensors rule out for a beacon. Beacons the
+/// sensors have already found don't count.
+fn impossible_locations(row: Coord, sensors: &[Sensor]) -> Coord {
+    let covered: IntervalSet<Coord> = impossible_ranges(row, sensors).into_iter().collect();
+    let beacons: HashSet<Point> = sensors
+        .iter()
+        .map(|sensor| sensor.closest)
+        .filter(|beacon| beacon.y == row && covered.contains(beacon.x))
+        .collect();
+    covered.len() - beacons.len() as Coord
+}
+
+fn is_covered(p: Point, sensors: &[Sensor]) -> bool {
+    sensors.iter().any(|sensor| sensor.covers(p))
+}
+
+/// The positions in row `y` of the search area that no sensor covers.
+fn row_gaps(y: Coord, max: Coord, sensors: &[Sensor]) -> IntervalSet<Coord> {
+    let ranges: IntervalSet<Coord> = impossible_ranges_with_limit(y, Some(max + 1), sensors)
+        .into_iter()
+        .collect();
+    ranges.complement(0..=max)
+}
+
+/// The first position in row `y` that no sensor covers.
+fn row_gap(y: Coord, max: Coord, sensors: &[Sensor]) -> Option<Point> {
+    let gap = row_gaps(y, max, sensors).intervals().first().cloned();
+    gap.map(|gap| point2(*gap.start(), y))
+}
+
+/// Uncovered positions in one row of the search area.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gap {
+    pub y: Coord,
+    pub x: RangeInclusive<Coord>,
+}
+
+impl fmt::Display for Gap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.x.start() == self.x.end() {
+            true => write!(f, "x={}, y={}", self.x.start(), self.y),
+            false => write!(f, "x={}..={}, y={}", self.x.start(), self.x.end(), self.y),
+        }
+    }
+}
+
+/// Every position in the search area that no sensor covers, checking every
+/// row, top to bottom.
+pub fn candidates(max: Coord, sensors: &[Sensor]) -> Vec<Gap> {
+    (0..=max)
+        .into_par_iter()
+        .flat_map_iter(|y| {
+            row_gaps(y, max, sensors)
+                .intervals()
+                .iter()
+                .map(|x| Gap { y, x: x.clone() })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// How many candidates an error lists before summing up the rest.
+const CANDIDATES_SHOWN: usize = 10;
+
+/// The distress beacon's position, if the sensors leave exactly one place
+/// for it. Otherwise the error lists the places they leave, which is more
+/// likely a mistake reading the sensors than a puzzle with two answers.
+pub fn distress_beacon(max: Coord, sensors: &[Sensor]) -> Result<Point, Error> {
+    let gaps = candidates(max, sensors);
+    match gaps.as_slice() {
+        [] => bail!("no gap for the distress beacon"),
+        [Gap { y, x }] if x.start() == x.end() => return Ok(point2(*x.start(), *y)),
+        _ => (),
+    }
+    let count: Coord = gaps.iter().map(|gap| gap.x.end() - gap.x.start() + 1).sum();
+    let mut shown: Vec<_> = gaps
+        .iter()
+        .take(CANDIDATES_SHOWN)
+        .map(Gap::to_string)
+        .collect();
+    if gaps.len() > CANDIDATES_SHOWN {
+        shown.push(format!("and {} more rows", gaps.len() - CANDIDATES_SHOWN));
+    }
+    bail!(
+        "{count} places the distress beacon could be: {}",
+        shown.join("; ")
+    )
+}
+
+/// Check every row for a gap in the sensors' coverage.
+fn find_gap_rows(max: Coord, sensors: &[Sensor]) -> Option<Point> {
+    (0..=max).find_map(|y| row_gap(y, max, sensors))
+}
+
+/// The same as `find_gap_rows`, but checking rows on every thread, and
+/// stopping them all once one finds a gap.
+fn find_gap_rows_parallel(max: Coord, sensors: &[Sensor]) -> Option<Point> {
+    (0..=max)
+        .into_par_iter()
+        .find_map_any(|y| row_gap(y, max, sensors))
+}
+
+/// The only uncovered point must sit just outside some sensor's range, and
+/// with every neighbour covered it's where two of those boundary lines cross,
+/// or where one meets the edge of the search area. Boundary lines are
+/// `x + y = a` or `x - y = b`, so check each crossing.
+fn find_gap_perimeter(max: Coord, sensors: &[Sensor]) -> Option<Point> {
+    let mut sums = HashSet::from([0, 2 * max]);
+    let mut differences = HashSet::from([-max, max]);
+    for sensor in sensors {
+        let Point { x, y, .. } = sensor.location;
+        let d = sensor.distance + 1;
+        sums.extend([x + y - d, x + y + d]);
+        differences.extend([x - y - d, x - y + d]);
+    }
+
+    let crossings = sums.iter().flat_map(|a| {
+        differences
+            .iter()
+            .filter(move |b| (a + *b) % 2 == 0)
+            .map(move |b| point2((a + b) / 2, (a - b) / 2))
+    });
+    let sum_edges = sums.iter().flat_map(|a| {
+        [
+            point2(0, *a),
+            point2(max, a - max),
+            point2(*a, 0),
+            point2(a - max, max),
+        ]
+    });
+    let difference_edges = differences.iter().flat_map(|b| {
+        [
+            point2(0, -b),
+            point2(max, max - b),
+            point2(*b, 0),
+            point2(b + max, max),
+        ]
+    });
+
+    crossings
+        .chain(sum_edges)
+        .chain(difference_edges)
+        .filter(|p| (0..=max).contains(&p.x) && (0..=max).contains(&p.y))
+        .find(|p| !is_covered(*p, sensors))
+}
+
+/// Split the search area into quadrants, dropping any that a single sensor
+/// covers completely, until what's left is one point.
+fn find_gap_quad(max: Coord, sensors: &[Sensor]) -> Option<Point> {
+    fn search(min: Point, max: Point, sensors: &[Sensor]) -> Option<Point> {
+        if min.x > max.x || min.y > max.y {
+            return None;
+        }
+        let corners = [min, point2(min.x, max.y), point2(max.x, min.y), max];
+        if sensors
+            .iter()
+            .any(|sensor| corners.iter().all(|corner| sensor.covers(*corner)))
+        {
+            return None;
+        }
+        if min == max {
+            return Some(min);
+        }
+        let mid = point2((min.x + max.x) / 2, (min.y + max.y) / 2);
+        [
+            (min, mid),
+            (point2(mid.x + 1, min.y), point2(max.x, mid.y)),
+            (point2(min.x, mid.y + 1), point2(mid.x, max.y)),
+            (point2(mid.x + 1, mid.y + 1), max),
+        ]
+        .into_iter()
+        .find_map(|(min, max)| search(min, max, sensors))
+    }
+
+    search(point2(0, 0), point2(max, max), sensors)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Solver {
+    #[default]
+    Rows,
+    ParallelRows,
+    Perimeter,
+    Quad,
+}
+
+impl Solver {
+    pub const ALL: [Solver; 4] = [
+        Solver::Rows,
+        Solver::ParallelRows,
+        Solver::Perimeter,
+        Solver::Quad,
+    ];
+
+    pub fn find_gap(&self, max: Coord, sensors: &[Sensor]) -> Option<Point> {
+        match self {
+            Self::Rows => find_gap_rows(max, sensors),
+            Self::ParallelRows => find_gap_rows_parallel(max, sensors),
+            Self::Perimeter => find_gap_perimeter(max, sensors),
+            Self::Quad => find_gap_quad(max, sensors),
+        }
+    }
+}
+
+impl FromStr for Solver {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "rows" => Self::Rows,
+            "parallel-rows" => Self::ParallelRows,
+            "perimeter" => Self::Perimeter,
+            "quad" => Self::Quad,
+            _ => bail!("unknown solver {s:?}, expected rows, parallel-rows, perimeter or quad"),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// The row to count impossible beacon positions in for part 1.
+    pub row: Coord,
+    /// The largest coordinate the distress beacon can have.
+    pub max_x: Coord,
+    /// How to search for the distress beacon.
+    pub solver: Solver,
+    /// Check every row, making sure there's only one place the distress
+    /// beacon could be, instead of using `solver`.
+    pub unique: bool,
+}
+
+/// The sample's row and bounds.
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            row: 10,
+            max_x: 20,
+            solver: Solver::default(),
+            unique: false,
+        }
+    }
+}
+
+pub struct Day15 {
+    sensors: Vec<Sensor>,
+    row: Coord,
+    max_x: Coord,
+    solver: Solver,
+    unique: bool,
+}
+
+impl Day15 {
+    pub fn new(input: &str, options: &Options) -> Result<Self, Error> {
+        Ok(Self {
+            sensors: parse(input)?,
+            row: options.row,
+            max_x: options.max_x,
+            solver: options.solver,
+            unique: options.unique,
+        })
+    }
+}
+
+impl Solution for Day15 {
+    const DAY: u8 = 15;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        Ok(impossible_locations(self.row, &self.sensors).into())
+    }
+
+    fn part_2(&self) -> Result<Answer, Error> {
+        let Point { x, y, .. } = if self.unique {
+            distress_beacon(self.max_x, &self.sensors)?
+        } else {
+            self.solver
+                .find_gap(self.max_x, &self.sensors)
+                .ok_or_else(|| anyhow!("no gap for the distress beacon"))?
+        };
+        debug!(x, y, "distress beacon");
+        Ok(tuning_frequency(point2(x, y))?.into())
+    }
+}
+
+const FM: Coord = 4_000_000;
+
+/// The distress beacon's tuning frequency, `x * 4_000_000 + y`, which
+/// only fits in 64 bits for the coordinates the puzzle allows.
+fn tuning_frequency(p: Point) -> Result<Coord, Error> {
+    p.x.checked_mul(FM)
+        .and_then(|x| x.checked_add(p.y))
+        .with_context(|| format!("tuning frequency for {p:?} overflows"))
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day15::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let sensors = parse(SAMPLE).unwrap();
+        assert_eq!(sensors.len(), 14);
+        let error = parse(&SAMPLE.replace("y=18:", "y=18;")).unwrap_err();
+        assert!(format!("{error:#}").starts_with("line 1: \"Sensor at x=2, y=18;"));
+    }
+
+    #[test]
+    fn test_taxicab_distance() {
+        let sensors = parse(SAMPLE).unwrap();
+
+        let sensor = &sensors[0];
+        assert_eq!(sensor.distance, 7);
+
+        let sensor = &sensors[4];
+        assert_eq!(sensor.distance, 4);
+    }
+
+    #[test]
+    fn test_tuning_frequency() {
+        assert_eq!(tuning_frequency(point2(14, 11)).unwrap(), 56000011);
+        assert_eq!(
+            tuning_frequency(point2(FM, FM)).unwrap(),
+            16_000_004_000_000
+        );
+        let max_x = Coord::MAX / FM;
+        assert!(tuning_frequency(point2(max_x, 0)).is_ok());
+        assert!(tuning_frequency(point2(max_x + 1, 0)).is_err());
+        assert!(tuning_frequency(point2(max_x, Coord::MAX)).is_err());
+    }
+
+    #[test]
+    fn test_impossible_range() {
+        let sensors = parse(SAMPLE).unwrap();
+        let sensor = &sensors[6];
+        assert_eq!(sensor.location, point2(8, 7));
+        let r = sensor.impossible_range(10);
+        assert_eq!(r, Some(2..=14));
+        let r = sensor.impossible_range(4);
+        assert_eq!(r, Some(2..=14));
+        let r = sensor.impossible_range(5);
+        assert_eq!(r, Some(1..=15));
+        let r = sensor.impossible_range(16);
+        assert_eq!(r, Some(8..=8));
+        let r = sensor.impossible_range(17);
+        assert_eq!(r, None);
+
+        let ranges = impossible_ranges(11, &sensors);
+        assert_eq!(ranges.len(), 2);
+
+        let ranges = impossible_ranges(10, &sensors);
+        assert_eq!(ranges.len(), 1);
+    }
+
+    #[test]
+    fn test_part_1() {
+        let sensors = parse(SAMPLE).unwrap();
+        let ranges = impossible_ranges(10, &sensors);
+        assert_eq!(ranges, vec![-2..=24]);
+        assert_eq!(impossible_locations(10, &sensors), 26);
+    }
+
+    #[test]
+    fn test_part_2() {
+        let sensors = parse(SAMPLE).unwrap();
+        let ranges = impossible_ranges_with_limit(11, Some(21), &sensors);
+        assert_eq!(ranges, vec![0..=13, 15..=20]);
+
+        for solver in Solver::ALL {
+            assert_eq!(
+                solver.find_gap(20, &sensors),
+                Some(point2(14, 11)),
+                "{solver:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_gap_on_edge() {
+        // One sensor covering everything but the corner at (0, 0).
+        let sensors = vec![Sensor::new(point2(4, 4), point2(4, 11))];
+        for solver in Solver::ALL {
+            assert_eq!(
+                solver.find_gap(4, &sensors),
+                Some(point2(0, 0)),
+                "{solver:?}"
+            );
+        }
+        // And one that covers the whole area.
+        let sensors = vec![Sensor::new(point2(2, 2), point2(2, 6))];
+        for solver in Solver::ALL {
+            assert_eq!(solver.find_gap(4, &sensors), None, "{solver:?}");
+        }
+    }
+
+    #[test]
+    fn test_candidates() {
+        let sensors = parse(SAMPLE).unwrap();
+        assert_eq!(candidates(20, &sensors), [Gap { y: 11, x: 14..=14 }]);
+        assert_eq!(distress_beacon(20, &sensors).unwrap(), point2(14, 11));
+
+        // Without the sensor at (20, 1), its corner of the area opens up.
+        let mut sensors = sensors;
+        let missing = sensors.iter().position(|s| s.location == point2(20, 1));
+        sensors.remove(missing.unwrap());
+        let gaps = candidates(20, &sensors);
+        assert!(gaps.len() > 1);
+        assert!(gaps.contains(&Gap { y: 11, x: 14..=14 }));
+        let error = distress_beacon(20, &sensors).unwrap_err().to_string();
+        assert!(
+            error.contains(" places the distress beacon could be: x="),
+            "{error}"
+        );
+        assert!(error.contains("; x=14, y=11"), "{error}");
+
+        let everywhere = distress_beacon(40, &[]).unwrap_err().to_string();
+        assert_eq!(
+            everywhere,
+            "1681 places the distress beacon could be: x=0..=40, y=0; x=0..=40, y=1; \
+             x=0..=40, y=2; x=0..=40, y=3; x=0..=40, y=4; x=0..=40, y=5; x=0..=40, y=6; \
+             x=0..=40, y=7; x=0..=40, y=8; x=0..=40, y=9; and 31 more rows"
+        );
+        let covered = vec![Sensor::new(point2(2, 2), point2(2, 6))];
+        assert!(distress_beacon(4, &covered).is_err());
+    }
+}
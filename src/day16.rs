@@ -0,0 +1,1211 @@
+//! Day 16: Proboscidea Volcanium.
+
+use crate::{
+    cancel::{Cancel, Cancelled},
+    parse::parse_lines,
+    search,
+    smallset::{IdSet, Ids},
+    solution::{self, Answer, Answers, Solution},
+};
+use anyhow::{anyhow, bail, Context, Error};
+use internment::Intern;
+use petgraph::graphmap::UnGraphMap;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::{self, Debug, Display, Write as _},
+    io::Write,
+    process::{Command, Stdio},
+    str::FromStr,
+};
+use tracing::{debug, debug_span, trace};
+
+/// Open valves, by their IDs in [`Volcano::valve_ids`].
+type OpenValves = IdSet;
+
+#[derive(Clone, PartialEq, Hash, Copy, PartialOrd, Ord, Eq)]
+pub struct RoomId(Intern<String>);
+
+impl RoomId {
+    pub fn new(s: &str) -> Self {
+        Self(Intern::new(s.to_string()))
+    }
+}
+
+impl Display for RoomId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.as_ref())
+    }
+}
+
+impl Debug for RoomId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.as_ref())
+    }
+}
+
+pub const DATA: &str = include_str!("../data/day16.txt");
+pub const SAMPLE: &str = include_str!("../data/day16_sample.txt");
+
+#[derive(Debug, Clone)]
+struct Room {
+    room_id: RoomId,
+    flow: usize,
+    tunnels: Vec<RoomId>,
+}
+
+crate::regex_struct! {
+    /// One line of the scan, as written.
+    struct Scan = r"^Valve ([A-Z][A-Z]) has flow rate=(\d+); tunnels? leads? to valves? ([A-Z, ]+)$" {
+        valve: String,
+        flow: usize,
+        tunnels: String,
+    }
+}
+
+impl From<Scan> for Room {
+    fn from(scan: Scan) -> Self {
+        Self {
+            room_id: RoomId::new(&scan.valve),
+            flow: scan.flow,
+            tunnels: scan.tunnels.split(", ").map(RoomId::new).collect(),
+        }
+    }
+}
+
+type RoomMap = HashMap<RoomId, Room>;
+type FlowGraph = UnGraphMap<RoomId, String>;
+/// The valve rooms and the start, with each edge weighted by the minutes it
+/// takes to walk between them.
+type ValveGraph = UnGraphMap<RoomId, usize>;
+
+#[derive(Debug, PartialEq)]
+#[allow(unused)]
+enum Action {
+    Move(RoomId),
+    Open,
+    Idle,
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct Volcano {
+    rooms: RoomMap,
+    graph: FlowGraph,
+    /// `graph` with the corridors of rooms without flow contracted.
+    valves: ValveGraph,
+    /// The rooms with flow, numbered in name order.
+    valve_ids: Ids<RoomId>,
+}
+
+fn successors(point: &RoomId, graph: &FlowGraph) -> Vec<RoomId> {
+    graph.neighbors(*point).collect()
+}
+
+impl Volcano {
+    fn new(rooms: RoomMap) -> Result<Self, Error> {
+        let mut valve_rooms: Vec<_> = rooms
+            .values()
+            .filter_map(|r| (r.flow > 0).then_some(r.room_id))
+            .collect();
+        valve_rooms.sort();
+        let count = valve_rooms.len();
+        let valve_ids =
+            Ids::from_keys(valve_rooms).with_context(|| format!("{count} rooms have valves"))?;
+        let graph = Self::make_graph(&rooms);
+        let valves = Self::contract(&rooms, &graph, &RoomId::new("AA"));
+        Ok(Self {
+            rooms,
+            graph,
+            valves,
+            valve_ids,
+        })
+    }
+
+    fn make_graph(rooms: &RoomMap) -> FlowGraph {
+        let edges: Vec<_> = rooms
+            .values()
+            .flat_map(|r| {
+                r.tunnels
+                    .iter()
+                    .map(|t| (r.room_id, *t))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        FlowGraph::from_edges(&edges)
+    }
+
+    /// Fold each chain of rooms without flow into a single edge between the
+    /// rooms at its ends, keeping only the valves and `start`. An edge is
+    /// only added for a walk that passes through no other kept room, since
+    /// that walk is already the sum of shorter edges.
+    fn contract(rooms: &RoomMap, graph: &FlowGraph, start: &RoomId) -> ValveGraph {
+        let kept = |room_id: &RoomId| room_id == start || rooms[room_id].flow > 0;
+        let mut valves = ValveGraph::new();
+        for from in graph.nodes().filter(kept) {
+            valves.add_node(from);
+            let mut seen = HashSet::from([from]);
+            let mut queue = VecDeque::from([(from, 0)]);
+            while let Some((room_id, distance)) = queue.pop_front() {
+                for next in graph.neighbors(room_id) {
+                    if !seen.insert(next) {
+                        continue;
+                    }
+                    if kept(&next) {
+                        let weight = valves.edge_weight(from, next).copied();
+                        if weight.is_none_or(|weight| distance + 1 < weight) {
+                            valves.add_edge(from, next, distance + 1);
+                        }
+                    } else {
+                        queue.push_back((next, distance + 1));
+                    }
+                }
+            }
+        }
+        valves
+    }
+
+    /// Minutes to walk from `start` to `end` over the contracted graph.
+    fn distance(&self, start: &RoomId, end: &RoomId) -> usize {
+        let (_, distance) = search::dijkstra(
+            start,
+            |room_id| {
+                self.valves
+                    .edges(*room_id)
+                    .map(|(_, next, weight)| (next, *weight))
+                    .collect::<Vec<_>>()
+            },
+            |room_id| room_id == end,
+            &mut (),
+        )
+        .expect("valves are connected");
+        distance
+    }
+
+    fn path_between(&self, start: &RoomId, end: &RoomId) -> Vec<RoomId> {
+        let graph = self.graph.clone();
+        let path = search::bfs(start, |p| successors(p, &graph), |p| p == end, &mut ()).unwrap();
+        path[1..].to_vec()
+    }
+
+    fn valued_path_between(
+        &self,
+        start: &RoomId,
+        end: &RoomId,
+        limit: usize,
+    ) -> (usize, Vec<RoomId>) {
+        let path = self.path_between(start, end);
+        let len = path.len();
+        let flow = self.rooms.get(end).expect("room").flow;
+        let value = limit.saturating_sub(len + 1) * flow;
+        (value, path)
+    }
+
+    #[cfg(test)]
+    fn path_between_str(&self, start: &str, end: &str) -> Vec<RoomId> {
+        let start = RoomId::new(start);
+        let end = RoomId::new(end);
+        self.path_between(&start, &end)
+    }
+
+    /// The rooms with flow, in name order.
+    pub fn rooms_with_valves(&self) -> Vec<RoomId> {
+        self.valve_ids.keys().to_vec()
+    }
+
+    fn remaining_closed_valves(&self, open_valves: &OpenValves) -> Vec<RoomId> {
+        let valves = self.valve_ids.keys().iter().enumerate();
+        valves
+            .filter_map(|(id, room_id)| (!open_valves.contains(id)).then_some(*room_id))
+            .collect()
+    }
+
+    fn current_flow(&self, open_valves: &OpenValves) -> usize {
+        open_valves
+            .iter()
+            .map(|id| self.rooms[self.valve_ids.key(id)].flow)
+            .sum()
+    }
+
+    /// Open the valve in `room_id`, if it has one worth opening.
+    fn open(&self, open_valves: &mut OpenValves, room_id: &RoomId) {
+        if let Some(id) = self.valve_ids.get(room_id) {
+            open_valves.insert(id);
+        }
+    }
+
+    fn actions(&self, path: &[RoomId]) -> Vec<Action> {
+        let mut actions = vec![];
+        for room_id in path.iter() {
+            actions.push(Action::Move(*room_id));
+        }
+        actions.push(Action::Open);
+        actions
+    }
+}
+
+pub fn path_pressure(volcano: &Volcano, start: &RoomId, path: &[&RoomId], limit: usize) -> usize {
+    let mut total_pressure = 0;
+    let mut open_valves = OpenValves::default();
+    let mut player_location = *start;
+    let mut time = 1;
+    for next_destination in path {
+        let path_to_next_destination = volcano.path_between(&player_location, next_destination);
+        let actions = volcano.actions(path_to_next_destination.as_slice());
+        for action in actions {
+            let current_flow = volcano.current_flow(&open_valves);
+            total_pressure += current_flow;
+            match &action {
+                Action::Move(t) => {
+                    player_location = *t;
+                }
+
+                Action::Open => {
+                    volcano.open(&mut open_valves, &player_location);
+                }
+
+                Action::Idle => (),
+            }
+            time += 1;
+
+            if time > limit {
+                trace!(?path, "ran out of time");
+                return total_pressure;
+            }
+        }
+    }
+    while time <= limit {
+        let current_flow = volcano.current_flow(&open_valves);
+        total_pressure += current_flow;
+        time += 1;
+    }
+
+    total_pressure
+}
+
+/// One room per line.
+pub fn parse(s: &str) -> Result<Volcano, Error> {
+    let rooms = parse_lines::<Scan>(s)?
+        .into_iter()
+        .map(Room::from)
+        .map(|r| (r.room_id, r))
+        .collect();
+
+    Volcano::new(rooms)
+}
+
+/// What `--graph` writes: graphviz's own DOT text, or a drawing of it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GraphFormat {
+    #[default]
+    Dot,
+    Svg,
+    Png,
+}
+
+impl FromStr for GraphFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dot" => Ok(Self::Dot),
+            "svg" => Ok(Self::Svg),
+            "png" => Ok(Self::Png),
+            _ => bail!("unknown graph format {s:?}, expected dot, svg or png"),
+        }
+    }
+}
+
+/// The tunnels as an undirected DOT graph. Rooms are shaded and sized by
+/// their flow rate so the valves worth opening stand out, and the start
+/// room is drawn with a double border. With `contracted`, only the valves
+/// and the start are drawn, joined by edges labelled with how many minutes
+/// apart they are.
+pub fn to_dot(volcano: &Volcano, start: &RoomId, contracted: bool) -> String {
+    let max_flow = volcano
+        .rooms
+        .values()
+        .map(|r| r.flow)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    let mut dot =
+        String::from("graph volcano {\n    node [shape=circle, style=filled, fixedsize=true];\n");
+    let mut rooms: Vec<_> = volcano
+        .rooms
+        .values()
+        .filter(|r| !contracted || volcano.valves.contains_node(r.room_id))
+        .collect();
+    rooms.sort_by_key(|r| r.room_id);
+    for room in rooms {
+        let heat = room.flow as f64 / max_flow as f64;
+        let shape = if room.room_id == *start {
+            ", shape=doublecircle"
+        } else {
+            ""
+        };
+        // Stuck valves are white, the best one deep orange.
+        writeln!(
+            dot,
+            "    {id} [label=\"{id}\\n{flow}\", fillcolor=\"0.08 {heat:.2} 1.0\", width={width:.2}{shape}];",
+            id = room.room_id,
+            flow = room.flow,
+            width = 0.6 + 0.6 * heat,
+        )
+        .expect("write to string");
+    }
+    if contracted {
+        let mut edges: Vec<_> = volcano
+            .valves
+            .all_edges()
+            .map(|(a, b, weight)| (a.min(b), a.max(b), *weight))
+            .collect();
+        edges.sort();
+        for (a, b, weight) in edges {
+            writeln!(dot, "    {a} -- {b} [label=\"{weight}\"];").expect("write to string");
+        }
+    } else {
+        let mut tunnels: Vec<_> = volcano
+            .graph
+            .all_edges()
+            .map(|(a, b, _)| (a.min(b), a.max(b)))
+            .collect();
+        tunnels.sort();
+        for (a, b) in tunnels {
+            writeln!(dot, "    {a} -- {b};").expect("write to string");
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Lay out `dot` with graphviz's `dot` command.
+pub fn render_dot(dot: &str, format: GraphFormat) -> Result<Vec<u8>, Error> {
+    let format = match format {
+        GraphFormat::Dot => return Ok(dot.as_bytes().to_vec()),
+        GraphFormat::Svg => "svg",
+        GraphFormat::Png => "png",
+    };
+    let mut child = Command::new("dot")
+        .arg(format!("-T{format}"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("running graphviz's dot, is graphviz installed?")?;
+    child
+        .stdin
+        .take()
+        .expect("piped stdin")
+        .write_all(dot.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!(
+            "dot exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+#[derive(Default, Debug)]
+enum Mode {
+    Moving(usize, RoomId),
+    Opening(RoomId),
+    #[default]
+    Idle,
+}
+
+#[derive(Default, Debug)]
+struct Solver {
+    path: Vec<RoomId>,
+    open_valves: OpenValves,
+    current_flow: usize,
+    total_pressure: usize,
+    mode: Mode,
+}
+
+pub const TIME_LIMIT: usize = 30;
+
+impl Solver {
+    fn step(&mut self, index: usize, time: usize, volcano: &Volcano) -> Option<Vec<Solver>> {
+        trace!(index, time, solver = ?self, "step");
+        self.total_pressure += self.current_flow;
+        match self.mode {
+            Mode::Idle => None,
+            Mode::Moving(mut distance_remaining, target) => {
+                distance_remaining -= 1;
+                if distance_remaining == 0 {
+                    trace!(index, %target, "reached");
+                    self.mode = Mode::Opening(target);
+                } else {
+                    self.mode = Mode::Moving(distance_remaining, target);
+                }
+                None
+            }
+            Mode::Opening(target) => {
+                self.path.push(target);
+                trace!(index, %target, "opening");
+                volcano.open(&mut self.open_valves, &target);
+                self.current_flow = volcano.current_flow(&self.open_valves);
+                let remaining_closed_valves = volcano.remaining_closed_valves(&self.open_valves);
+                trace!(index, ?remaining_closed_valves);
+                if remaining_closed_valves.is_empty() {
+                    self.mode = Mode::Idle;
+                    None
+                } else {
+                    let mut paths: Vec<_> = remaining_closed_valves
+                        .iter()
+                        .map(|r| volcano.valued_path_between(&target, r, TIME_LIMIT - time))
+                        .collect();
+
+                    paths.sort_by_key(|p| p.0);
+                    paths.reverse();
+
+                    let mut solvers: Vec<_> = paths
+                        .iter()
+                        .map(|(_value, path)| {
+                            let target = *path.iter().last().expect("target");
+                            trace!(index, %target, "new solver");
+                            Solver {
+                                path: self.path.clone(),
+                                mode: Mode::Moving(path.len(), target),
+                                open_valves: self.open_valves,
+                                current_flow: self.current_flow,
+                                total_pressure: self.total_pressure,
+                            }
+                        })
+                        .collect();
+                    let mut new_self = solvers.remove(0);
+                    std::mem::swap(self, &mut new_self);
+                    Some(solvers)
+                }
+            }
+        }
+    }
+}
+
+pub fn solver_solve(v: &Volcano) -> usize {
+    let start_room = RoomId::new("AA");
+
+    let mut paths: Vec<_> = v
+        .rooms_with_valves()
+        .iter()
+        .map(|r| v.valued_path_between(&start_room, r, TIME_LIMIT))
+        .collect();
+
+    paths.sort_by_key(|p| p.0);
+    paths.reverse();
+
+    let mut solvers: Vec<_> = paths
+        .iter()
+        .map(|(_value, path)| Solver {
+            mode: Mode::Moving(path.len(), *path.iter().last().expect("target")),
+            ..Solver::default()
+        })
+        .collect();
+
+    for time in 1..=TIME_LIMIT {
+        let _span = debug_span!("minute", time).entered();
+        debug!(solvers = solvers.len());
+        let new_solvers: Vec<_> = solvers
+            .iter_mut()
+            .enumerate()
+            .flat_map(|(index, solver)| solver.step(index, time, v).unwrap_or_default())
+            .collect();
+
+        solvers.extend(new_solvers);
+    }
+
+    solvers.sort_by_key(|s| s.total_pressure);
+    solvers.reverse();
+
+    solvers[0].total_pressure
+}
+
+/// The most valves the table of every subset of them is built for; beyond
+/// this its size gets out of hand, so [`MemoSearch`] is used instead.
+const MAX_SUBSET_VALVES: usize = 20;
+
+/// Just the valves worth opening, with the travel time between each pair.
+/// Rooms with no flow only matter as corridors, so they're folded into the
+/// distances. Valves are numbered as in [`Volcano::valve_ids`], so sets of
+/// them are [`OpenValves`].
+#[derive(Debug)]
+struct ValveTable {
+    flows: Vec<usize>,
+    /// `distances[a][b]` is the minutes to walk from valve `a` to valve `b`.
+    /// The start room comes after the valves.
+    distances: Vec<Vec<usize>>,
+}
+
+impl ValveTable {
+    fn new(volcano: &Volcano, start: &RoomId) -> Self {
+        let mut rooms = volcano.rooms_with_valves();
+        let flows = rooms.iter().map(|r| volcano.rooms[r].flow).collect();
+
+        rooms.push(*start);
+        let distances = rooms
+            .iter()
+            .map(|from| rooms.iter().map(|to| volcano.distance(from, to)).collect())
+            .collect();
+
+        Self { flows, distances }
+    }
+
+    fn valve_count(&self) -> usize {
+        self.flows.len()
+    }
+
+    fn start(&self) -> usize {
+        self.valve_count()
+    }
+
+    /// The most pressure that can be released in `limit` minutes by opening
+    /// exactly each subset of the valves, indexed by [`IdSet::index`].
+    ///
+    /// States are (valve, minutes remaining, opened valves), where the valve is
+    /// the one just opened. Working from the most time remaining down, each
+    /// state only ever leads to ones with less time, so one pass over the
+    /// layers is enough.
+    fn best_by_subset(&self, limit: usize, cancel: &Cancel) -> Result<Vec<usize>, Cancelled> {
+        let count = self.valve_count();
+        let mut best = vec![0; 1 << count];
+        let mut layers: Vec<HashMap<(usize, OpenValves), usize>> = vec![HashMap::new(); limit + 1];
+        layers[limit].insert((self.start(), OpenValves::new()), 0);
+
+        for remaining in (1..=limit).rev() {
+            cancel.check()?;
+            for ((valve, opened), pressure) in std::mem::take(&mut layers[remaining]) {
+                best[opened.index()] = best[opened.index()].max(pressure);
+                for next in (0..count).filter(|next| !opened.contains(*next)) {
+                    let cost = self.distances[valve][next] + 1;
+                    if cost >= remaining {
+                        continue;
+                    }
+                    let left = remaining - cost;
+                    let released = pressure + left * self.flows[next];
+                    let entry = layers[left].entry((next, opened.with(next))).or_default();
+                    *entry = (*entry).max(released);
+                }
+            }
+        }
+
+        Ok(best)
+    }
+}
+
+/// Part 1: the best any single set of valves can do.
+fn dp_solve(volcano: &Volcano, limit: usize, cancel: &Cancel) -> Result<usize, Error> {
+    let table = ValveTable::new(volcano, &RoomId::new("AA"));
+    Ok(table
+        .best_by_subset(limit, cancel)?
+        .into_iter()
+        .max()
+        .unwrap_or_default())
+}
+
+/// Part 2: you and the elephant open disjoint sets of valves, so the answer
+/// is the best split of the valves between you.
+fn dp_solve_with_elephant(
+    volcano: &Volcano,
+    limit: usize,
+    cancel: &Cancel,
+) -> Result<usize, Error> {
+    let table = ValveTable::new(volcano, &RoomId::new("AA"));
+    let count = table.valve_count();
+    let mut best = table.best_by_subset(limit, cancel)?;
+
+    // Make each entry the best over all of its subsets, since whoever is
+    // assigned a set of valves needn't open all of them.
+    for bit in 0..count {
+        cancel.check()?;
+        for mask in 0..best.len() {
+            if mask & (1 << bit) != 0 {
+                best[mask] = best[mask].max(best[mask ^ (1 << bit)]);
+            }
+        }
+    }
+
+    let all = best.len() - 1;
+    Ok((0..best.len())
+        .map(|mine| best[mine] + best[all ^ mine])
+        .max()
+        .unwrap_or_default())
+}
+
+/// How well the memo in [`MemoSearch`] did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoStats {
+    entries: usize,
+    hits: usize,
+    misses: usize,
+}
+
+impl Display for MemoStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lookups = (self.hits + self.misses).max(1);
+        write!(
+            f,
+            "{} entries, {} hits, {} misses ({:.1}% hit rate)",
+            self.entries,
+            self.hits,
+            self.misses,
+            100.0 * self.hits as f64 / lookups as f64
+        )
+    }
+}
+
+/// A depth first search over which valve to open next, remembering the
+/// best from each (valve, minutes remaining, opened valves) so that orders
+/// arriving at the same state share the work. With an elephant, once you
+/// stop opening valves the elephant starts from the beginning with the
+/// valves you left it, which is part of the key too.
+struct MemoSearch<'a> {
+    table: &'a ValveTable,
+    limit: usize,
+    memo: HashMap<(usize, usize, OpenValves, bool), usize>,
+    stats: MemoStats,
+    cancel: &'a Cancel,
+}
+
+/// How many new memo entries the search makes between checking whether
+/// it's been cancelled.
+const MEMO_CHECK_EVERY: usize = 4096;
+
+impl<'a> MemoSearch<'a> {
+    fn new(table: &'a ValveTable, limit: usize, cancel: &'a Cancel) -> Self {
+        Self {
+            table,
+            limit,
+            memo: HashMap::new(),
+            stats: MemoStats::default(),
+            cancel,
+        }
+    }
+
+    /// The most pressure still to be released by opening valves from
+    /// `valve` on, with `remaining` minutes left.
+    fn most_pressure(
+        &mut self,
+        valve: usize,
+        remaining: usize,
+        opened: OpenValves,
+        elephant: bool,
+    ) -> Result<usize, Cancelled> {
+        let key = (valve, remaining, opened, elephant);
+        if let Some(pressure) = self.memo.get(&key) {
+            self.stats.hits += 1;
+            return Ok(*pressure);
+        }
+        self.stats.misses += 1;
+        if self.stats.misses.is_multiple_of(MEMO_CHECK_EVERY) {
+            self.cancel.check()?;
+        }
+
+        let table = self.table;
+        let mut best = if elephant {
+            self.most_pressure(table.start(), self.limit, opened, false)?
+        } else {
+            0
+        };
+        for next in (0..table.valve_count()).filter(|next| !opened.contains(*next)) {
+            let cost = table.distances[valve][next] + 1;
+            if cost >= remaining {
+                continue;
+            }
+            let left = remaining - cost;
+            let pressure = left * table.flows[next]
+                + self.most_pressure(next, left, opened.with(next), elephant)?;
+            best = best.max(pressure);
+        }
+
+        self.memo.insert(key, best);
+        Ok(best)
+    }
+}
+
+/// [`dp_solve`] or, with `elephant`, [`dp_solve_with_elephant`], by
+/// [`MemoSearch`] instead.
+fn memo_solve(
+    volcano: &Volcano,
+    limit: usize,
+    elephant: bool,
+    cancel: &Cancel,
+) -> Result<(usize, MemoStats), Error> {
+    cancel.check()?;
+    let table = ValveTable::new(volcano, &RoomId::new("AA"));
+    let mut search = MemoSearch::new(&table, limit, cancel);
+    let pressure = search.most_pressure(table.start(), limit, OpenValves::new(), elephant)?;
+    let stats = MemoStats {
+        entries: search.memo.len(),
+        ..search.stats
+    };
+    Ok((pressure, stats))
+}
+
+const ELEPHANT_TIME_LIMIT: usize = 26;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// Minutes until the volcano erupts.
+    pub time_limit: usize,
+    /// Use a memoised search over which valve to open next instead of the
+    /// table of valve subsets.
+    pub memo: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            time_limit: TIME_LIMIT,
+            memo: false,
+        }
+    }
+}
+
+pub struct Day16 {
+    volcano: Volcano,
+    time_limit: usize,
+    memo: bool,
+    /// What the memo did for each part solved with it.
+    memo_stats: RefCell<Vec<(u8, MemoStats)>>,
+}
+
+impl Day16 {
+    pub fn new(input: &str, options: &Options) -> Result<Self, Error> {
+        Ok(Self {
+            volcano: parse(input)?,
+            time_limit: options.time_limit,
+            memo: options.memo,
+            memo_stats: RefCell::new(vec![]),
+        })
+    }
+
+    /// What the memo did for each part solved so far with `memo` set.
+    pub fn memo_stats(&self) -> Vec<(u8, MemoStats)> {
+        self.memo_stats.borrow().clone()
+    }
+
+    fn solve(&self, part: u8, cancel: &Cancel) -> Result<usize, Error> {
+        let elephant = part == 2;
+        // Teaching the elephant takes the same four minutes however long
+        // there is.
+        let teaching = TIME_LIMIT - ELEPHANT_TIME_LIMIT;
+        let limit = match elephant {
+            true => self.time_limit.checked_sub(teaching).ok_or_else(|| {
+                anyhow!(
+                    "{} minutes isn't long enough to teach the elephant, which takes {teaching}",
+                    self.time_limit
+                )
+            })?,
+            false => self.time_limit,
+        };
+        let valves = self.volcano.valve_ids.len();
+        let memo = self.memo || valves > MAX_SUBSET_VALVES;
+        if !memo {
+            return if elephant {
+                dp_solve_with_elephant(&self.volcano, limit, cancel)
+            } else {
+                dp_solve(&self.volcano, limit, cancel)
+            };
+        }
+        if !self.memo {
+            debug!(
+                valves,
+                "too many valves for the table of subsets, searching instead"
+            );
+        }
+        let (pressure, stats) = memo_solve(&self.volcano, limit, elephant, cancel)?;
+        debug!(part, %stats, "memo");
+        self.memo_stats.borrow_mut().push((part, stats));
+        Ok(pressure)
+    }
+}
+
+impl Solution for Day16 {
+    const DAY: u8 = 16;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        self.solve_part(1, &Cancel::default())
+    }
+
+    fn part_2(&self) -> Result<Answer, Error> {
+        self.solve_part(2, &Cancel::default())
+    }
+
+    fn solve_part(&self, part: u8, cancel: &Cancel) -> Result<Answer, Error> {
+        Ok(self.solve(part, cancel)?.into())
+    }
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day16::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse::blocks;
+    use itertools::Itertools;
+    use regex::Regex;
+
+    fn to_path(path: &[&str]) -> Vec<RoomId> {
+        path.iter().map(|r| RoomId::new(r)).collect::<Vec<RoomId>>()
+    }
+
+    fn to_ref_path<'a>(path: &'a [RoomId]) -> Vec<&'a RoomId> {
+        path.iter().collect::<Vec<&'a RoomId>>()
+    }
+
+    #[derive(Debug)]
+    struct ExampleStep {
+        #[allow(unused)]
+        time: usize,
+        open_valves: Vec<String>,
+        pressure: usize,
+        action: Action,
+    }
+
+    impl From<&str> for ExampleStep {
+        fn from(s: &str) -> Self {
+            let parts = s.lines().map(str::trim).collect::<Vec<_>>();
+            let re = Regex::new(r"== Minute (\d+) ==").expect("regex");
+            let time = re.captures(parts[0]).expect("captures")[1]
+                .parse::<usize>()
+                .expect("usize");
+            let valve_info = parts[1];
+
+            #[allow(unused)]
+            let mut open_valves: Vec<String> = vec![];
+            let pressure;
+            let mut action = Action::Idle;
+
+            match valve_info {
+                "No valves are open." => {
+                    pressure = 0;
+                    open_valves = vec![];
+                }
+                _ => {
+                    let re = Regex::new(r"[A-Z][A-Z]").expect("regex");
+                    open_valves = re
+                        .captures_iter(valve_info)
+                        .map(|cap| cap[0].to_string())
+                        .collect();
+                    let re = Regex::new(r"releasing (\d+) pressure").expect("regex");
+                    pressure = re.captures(valve_info).expect("captures")[1]
+                        .parse::<usize>()
+                        .expect("usize");
+                }
+            }
+
+            if parts.len() > 2 {
+                let re = Regex::new(r"You ([a-z]+).*valve ([A-Z][A-Z]).").expect("re");
+                let captures = re.captures(parts[2]).expect("captures");
+                action = match &captures[1] {
+                    "move" => Action::Move(RoomId::new(&captures[2])),
+                    "open" => Action::Open,
+                    _ => Action::Idle,
+                };
+            }
+
+            Self {
+                time,
+                open_valves,
+                pressure,
+                action,
+            }
+        }
+    }
+
+    const EXAMPLE_SOLUTION: &str = include_str!("../data/day16_example.txt");
+
+    #[test]
+    fn test_parse() {
+        let v = parse(SAMPLE).unwrap();
+        dbg!(&v);
+        assert_eq!(v.rooms.len(), 10);
+        assert_eq!(v.rooms[&RoomId::new("HH")].tunnels, [RoomId::new("GG")]);
+        let error = parse(&SAMPLE.replace("rate=13", "rate=x")).unwrap_err();
+        assert!(format!("{error:#}").starts_with("line 2: \"Valve BB"));
+    }
+
+    #[test]
+    fn test_example_solution() {
+        let example_steps: Vec<_> = blocks(EXAMPLE_SOLUTION)
+            .into_iter()
+            .map(ExampleStep::from)
+            .collect();
+
+        assert_eq!(example_steps.len(), TIME_LIMIT);
+        assert_eq!(example_steps[0].action, Action::Move(RoomId::new("DD")));
+        assert_eq!(example_steps[0].pressure, 0);
+        assert_eq!(example_steps[0].open_valves.len(), 0);
+
+        let middle_step = &example_steps[17];
+        assert_eq!(middle_step.action, Action::Move(RoomId::new("GG")));
+        assert_eq!(middle_step.pressure, 76);
+        assert_eq!(middle_step.open_valves.len(), 4);
+
+        let last_step = &example_steps[29];
+        assert_eq!(last_step.action, Action::Idle);
+        assert_eq!(last_step.pressure, 81);
+        assert_eq!(last_step.open_valves.len(), 6);
+
+        let v = parse(SAMPLE).unwrap();
+
+        let mut total_pressure = 0;
+
+        let mut open_valves = OpenValves::default();
+        let mut player_location = RoomId::new("AA");
+
+        for step in example_steps.iter() {
+            println!("doing step {:?}", step);
+            let current_flow = v.current_flow(&open_valves);
+            total_pressure += current_flow;
+            assert_eq!(step.pressure, current_flow);
+            match &step.action {
+                Action::Move(t) => {
+                    player_location = *t;
+                }
+
+                Action::Open => {
+                    v.open(&mut open_valves, &player_location);
+                }
+
+                Action::Idle => (),
+            }
+            dbg!(&player_location);
+            dbg!(&open_valves);
+        }
+
+        assert_eq!(total_pressure, 1651);
+    }
+
+    #[test]
+    fn test_volcano() {
+        let v = parse(SAMPLE).unwrap();
+
+        let path = v.path_between_str("AA", "HH");
+        assert_eq!(path.len(), 5);
+
+        dbg!(&path);
+    }
+
+    #[test]
+    fn test_permute_solve() {
+        let v = parse(SAMPLE).unwrap();
+        let start_room = RoomId::new("AA");
+
+        let rooms = v.rooms_with_valves();
+
+        let one_path = to_path(&["DD", "BB", "JJ", "HH", "EE", "CC"]);
+        let one_solution = path_pressure(
+            &v,
+            &start_room,
+            &to_ref_path(one_path.as_slice()),
+            TIME_LIMIT,
+        );
+        assert_eq!(one_solution, 1651);
+
+        let mut solutions: Vec<_> = rooms
+            .iter()
+            .permutations(rooms.len())
+            .map(|path| {
+                (
+                    path_pressure(&v, &start_room, path.as_slice(), TIME_LIMIT),
+                    path.clone(),
+                )
+            })
+            .collect();
+
+        solutions.sort_by_key(|s| s.0);
+
+        solutions.reverse();
+
+        dbg!(&solutions[0..10]);
+
+        assert_eq!(solutions[0].1, to_ref_path(one_path.as_slice()));
+    }
+
+    #[test]
+    fn test_value_solve() {
+        let v = parse(SAMPLE).unwrap();
+        let total_pressure = solver_solve(&v);
+
+        assert_eq!(total_pressure, 1651);
+    }
+
+    #[test]
+    fn test_valve_table() {
+        let v = parse(SAMPLE).unwrap();
+        let table = ValveTable::new(&v, &RoomId::new("AA"));
+        assert_eq!(table.valve_count(), 6);
+        // BB, CC, DD, EE, HH, JJ then AA.
+        assert_eq!(table.distances[table.start()][4], 5);
+        assert_eq!(table.distances[4][table.start()], 5);
+        assert_eq!(table.distances[0][5], 3);
+    }
+
+    #[test]
+    fn test_dp_solve() {
+        let v = parse(SAMPLE).unwrap();
+        let cancel = Cancel::default();
+        assert_eq!(dp_solve(&v, TIME_LIMIT, &cancel).unwrap(), 1651);
+
+        let table = ValveTable::new(&v, &RoomId::new("AA"));
+        let best = table.best_by_subset(TIME_LIMIT, &cancel).unwrap();
+        assert_eq!(best[0], 0);
+        // Just DD: walk one minute, open it for the next, then 28 minutes of 20.
+        assert_eq!(best[OpenValves::new().with(2).index()], 28 * 20);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let dot = to_dot(&parse(SAMPLE).unwrap(), &RoomId::new("AA"), false);
+        assert!(dot.starts_with("graph volcano {"));
+        assert!(dot.contains(
+            "AA [label=\"AA\\n0\", fillcolor=\"0.08 0.00 1.0\", width=0.60, shape=doublecircle];"
+        ));
+        assert!(dot.contains("HH [label=\"HH\\n22\", fillcolor=\"0.08 1.00 1.0\", width=1.20];"));
+        assert!(dot.contains("    AA -- DD;\n"));
+        assert!(!dot.contains("DD -- AA"));
+        assert_eq!(dot.matches(" -- ").count(), 10);
+        assert!("jpeg".parse::<GraphFormat>().is_err());
+
+        let dot = to_dot(&parse(SAMPLE).unwrap(), &RoomId::new("AA"), true);
+        assert!(!dot.contains("FF [label"));
+        assert!(dot.contains("    AA -- JJ [label=\"2\"];\n"));
+        assert!(dot.contains("    EE -- HH [label=\"3\"];\n"));
+        assert_eq!(dot.matches(" -- ").count(), 7);
+    }
+
+    #[test]
+    fn test_contract() {
+        let v = parse(SAMPLE).unwrap();
+        assert_eq!(v.valves.node_count(), 7);
+        let mut edges: Vec<_> = v
+            .valves
+            .all_edges()
+            .map(|(a, b, weight)| (a.min(b).to_string(), a.max(b).to_string(), *weight))
+            .collect();
+        edges.sort();
+        let expected = [
+            ("AA", "BB", 1),
+            ("AA", "DD", 1),
+            ("AA", "JJ", 2),
+            ("BB", "CC", 1),
+            ("CC", "DD", 1),
+            ("DD", "EE", 1),
+            ("EE", "HH", 3),
+        ]
+        .map(|(a, b, weight)| (a.to_string(), b.to_string(), weight));
+        assert_eq!(edges, expected);
+        assert_eq!(v.distance(&RoomId::new("JJ"), &RoomId::new("HH")), 7);
+    }
+
+    #[test]
+    fn test_dp_solve_with_elephant() {
+        let v = parse(SAMPLE).unwrap();
+        let cancel = Cancel::default();
+        assert_eq!(
+            dp_solve_with_elephant(&v, ELEPHANT_TIME_LIMIT, &cancel).unwrap(),
+            1707
+        );
+    }
+
+    #[test]
+    fn test_memo_solve() {
+        let v = parse(SAMPLE).unwrap();
+        let cancel = Cancel::default();
+        let (pressure, stats) = memo_solve(&v, TIME_LIMIT, false, &cancel).unwrap();
+        assert_eq!(pressure, 1651);
+        assert!(stats.hits > 0);
+        assert_eq!(stats.entries, stats.misses);
+        let (pressure, _) = memo_solve(&v, ELEPHANT_TIME_LIMIT, true, &cancel).unwrap();
+        assert_eq!(pressure, 1707);
+    }
+
+    /// A corridor from AA through `count` valves of flow 1, a minute apart.
+    fn corridor(count: usize) -> String {
+        let names: Vec<_> = (b'B'..=b'Z')
+            .flat_map(|a| (b'A'..=b'Z').map(move |b| format!("{}{}", a as char, b as char)))
+            .take(count)
+            .collect();
+        let rooms: Vec<_> = std::iter::once("AA")
+            .chain(names.iter().map(String::as_str))
+            .collect();
+        let mut lines = vec![];
+        for (index, room) in rooms.iter().enumerate() {
+            let flow = if index == 0 { 0 } else { 1 };
+            let tunnels: Vec<_> = [index.checked_sub(1), Some(index + 1)]
+                .into_iter()
+                .flatten()
+                .filter_map(|neighbour| rooms.get(neighbour))
+                .copied()
+                .collect();
+            lines.push(format!(
+                "Valve {room} has flow rate={flow}; tunnels lead to valves {}",
+                tunnels.join(", ")
+            ));
+        }
+        lines.join("\n")
+    }
+
+    #[test]
+    fn test_many_valves() {
+        // Too many for the table, so this is searched instead. Opening
+        // each valve on the way, the nth is open for 30 - 2n minutes.
+        let day = Day16::new(&corridor(MAX_SUBSET_VALVES + 1), &Options::default()).unwrap();
+        assert_eq!(day.part_1().unwrap(), Answer::from(210));
+
+        let error = Day16::new(&corridor(65), &Options::default())
+            .err()
+            .unwrap();
+        assert_eq!(
+            format!("{error:#}"),
+            "65 rooms have valves: a set can only hold 64 IDs"
+        );
+    }
+
+    #[test]
+    fn test_short_time_limit() {
+        let options = Options {
+            time_limit: 3,
+            ..Options::default()
+        };
+        let day = Day16::new(SAMPLE, &options).unwrap();
+        assert_eq!(day.part_1().unwrap(), Answer::from(20));
+        let error = day.part_2().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "3 minutes isn't long enough to teach the elephant, which takes 4"
+        );
+        let options = Options {
+            time_limit: 4,
+            ..Options::default()
+        };
+        let day = Day16::new(SAMPLE, &options).unwrap();
+        assert_eq!(day.part_2().unwrap(), Answer::from(0));
+    }
+
+    #[test]
+    fn test_cancel() {
+        let cancel = Cancel::default();
+        cancel.cancel();
+        for memo in [false, true] {
+            let options = Options {
+                memo,
+                ..Options::default()
+            };
+            let day = Day16::new(SAMPLE, &options).unwrap();
+            for part in [1, 2] {
+                let error = day.solve_part(part, &cancel).unwrap_err();
+                assert!(error.downcast_ref::<Cancelled>().is_some(), "{error}");
+            }
+        }
+    }
+}
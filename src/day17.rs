@@ -0,0 +1,499 @@
+//! Day 17: Pyroclastic Flow.
+
+use crate::{
+    grid::{Bounds, SparseGrid},
+    search,
+    solution::{self, Answer, Answers, Solution},
+};
+use anyhow::{bail, ensure, Context, Error};
+use euclid::{point2, vec2};
+use std::collections::{HashMap, HashSet};
+use tracing::debug;
+
+pub const DATA: &str = include_str!("../data/day17.txt");
+pub const SAMPLE: &str = include_str!("../data/day17_sample.txt");
+
+#[derive(Debug, Clone, Copy)]
+pub enum Jet {
+    Left,
+    Right,
+}
+
+impl TryFrom<char> for Jet {
+    type Error = Error;
+
+    fn try_from(c: char) -> Result<Self, Error> {
+        match c {
+            '<' => Ok(Jet::Left),
+            '>' => Ok(Jet::Right),
+            _ => bail!("{c:?} isn't a jet"),
+        }
+    }
+}
+
+impl From<&Jet> for Vector {
+    fn from(j: &Jet) -> Self {
+        match j {
+            Jet::Left => vec2(-1, 0),
+            Jet::Right => vec2(1, 0),
+        }
+    }
+}
+
+pub type Jets = Vec<Jet>;
+
+pub type Point = euclid::default::Point2D<isize>;
+type Vector = euclid::default::Vector2D<isize>;
+
+pub type BlockSet = HashSet<Point>;
+
+const MAX_X: isize = 7;
+
+fn block_collides_with_wall(p: &Point) -> bool {
+    p.x < 0 || p.x >= MAX_X
+}
+
+fn block_collides_with_floor(p: &Point) -> bool {
+    p.y < 0
+}
+
+#[derive(Debug)]
+struct Shape {
+    blocks: Vec<Point>,
+    name: char,
+}
+
+// impl Debug for Shape {
+//     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+//         write!(f, "{}", self.name)
+//     }
+// }
+
+impl Shape {
+    fn horiz() -> Shape {
+        let blocks = (0..4).map(|x| point2(x, 0)).collect();
+        Self { blocks, name: '-' }
+    }
+
+    fn plus() -> Shape {
+        let blocks = [
+            point2(1, 0),
+            point2(0, 1),
+            point2(1, 1),
+            point2(2, 1),
+            point2(1, 2),
+        ]
+        .to_vec();
+        Self { blocks, name: '+' }
+    }
+
+    fn inverted_l() -> Shape {
+        let blocks = [
+            point2(2, 2),
+            point2(2, 1),
+            point2(0, 0),
+            point2(1, 0),
+            point2(2, 0),
+        ]
+        .to_vec();
+        Self {
+            blocks, name: '⅃'
+        }
+    }
+
+    fn vertical() -> Shape {
+        let blocks = (0..4).map(|y| point2(0, y)).collect();
+        Self { blocks, name: '|' }
+    }
+
+    fn block() -> Shape {
+        let blocks = [point2(0, 0), point2(1, 0), point2(0, 1), point2(1, 1)].to_vec();
+        Self {
+            blocks, name: '▀'
+        }
+    }
+
+    fn shape_for(index: usize) -> Self {
+        match index % 5 {
+            0 => Self::horiz(),
+            1 => Self::plus(),
+            2 => Self::inverted_l(),
+            3 => Self::vertical(),
+            4 => Self::block(),
+            _ => unreachable!(),
+        }
+    }
+
+    fn translate(&self, v: Vector) -> Shape {
+        let blocks = self.blocks.iter().map(|p| *p + v).collect();
+        Self {
+            blocks,
+            name: self.name,
+        }
+    }
+
+    fn collides_with_wall(&self) -> bool {
+        self.blocks.iter().any(block_collides_with_wall)
+    }
+
+    fn collides_with_floor(&self) -> bool {
+        self.blocks.iter().any(block_collides_with_floor)
+    }
+
+    fn collides_with(&self, rocks: &SparseGrid<()>) -> bool {
+        self.blocks.iter().any(|p| rocks.contains(*p))
+    }
+
+    fn shape_set(&self) -> BlockSet {
+        self.blocks.iter().copied().collect()
+    }
+}
+
+/// The jet pattern, which is one line of `<` and `>`.
+pub fn parse(s: &str) -> Result<Jets, Error> {
+    let jets = s
+        .trim()
+        .chars()
+        .enumerate()
+        .map(|(i, c)| Jet::try_from(c).with_context(|| format!("column {}", i + 1)))
+        .collect::<Result<Jets, Error>>()?;
+    ensure!(!jets.is_empty(), "no jets");
+    Ok(jets)
+}
+
+pub fn render(chamber: &Chamber, shape_set: &BlockSet) {
+    let total_box = Bounds::from_points(
+        chamber
+            .rocks
+            .bounds()
+            .into_iter()
+            .flat_map(|b| [b.min, b.max])
+            .chain(shape_set.iter().copied()),
+    );
+    println!("total_box = {total_box:?}");
+
+    for y in (0..(total_box.max.y + 1)).rev() {
+        let s = (0..MAX_X)
+            .map(|x| {
+                let p = point2(x, y);
+                if chamber.rocks.contains(p) {
+                    '#'
+                } else if shape_set.contains(&p) {
+                    '@'
+                } else {
+                    '.'
+                }
+            })
+            .collect::<String>();
+        println!("|{s}|");
+    }
+}
+
+/// What a [`Chamber`] looks like to the next rock. When two chambers have
+/// the same fingerprint, what happens next is the same in both, so once
+/// one repeats the tower grows in a cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Fingerprint {
+    shape: usize,
+    jet: usize,
+    profile: [isize; MAX_X as usize],
+}
+
+/// Where rocks have come to rest, and where the rocks and jets are up to.
+/// Only the rows a falling rock could still reach are kept, so the
+/// chamber stays small however tall the tower grows.
+#[derive(Debug, Default)]
+pub struct Chamber {
+    /// The rocks in the rows kept, counting from the lowest as zero.
+    rocks: SparseGrid<()>,
+    /// How many rows have been pruned from under the ones kept.
+    pruned: isize,
+    /// How many rocks have fallen so far, which picks the next one's shape.
+    dropped: usize,
+    /// The next jet to push a rock, wrapped to the length of the pattern.
+    jet_index: usize,
+}
+
+impl Chamber {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn height(&self) -> isize {
+        self.pruned + self.top()
+    }
+
+    /// The height of the rows kept.
+    fn top(&self) -> isize {
+        self.rocks.bounds().map_or(0, |b| b.max.y + 1)
+    }
+
+    /// Forget the rows below the lowest one a rock could still reach. Rocks
+    /// only move sideways and down, so the air they can get to is what a
+    /// flood fill reaches from above the tower going the same ways, and
+    /// the row under the lowest of that is the last that can hold one up.
+    fn prune(&mut self) {
+        let top = self.top();
+        let air = search::reachable(
+            &point2(0, top),
+            |p| {
+                [vec2(-1, 0), vec2(1, 0), vec2(0, -1)]
+                    .into_iter()
+                    .map(|v| *p + v)
+                    .filter(|p| {
+                        !block_collides_with_wall(p)
+                            && !block_collides_with_floor(p)
+                            && !self.rocks.contains(*p)
+                    })
+                    .collect::<Vec<_>>()
+            },
+            &mut (),
+        );
+        let cut = air.iter().map(|p| p.y).min().unwrap_or(0) - 1;
+        if cut > 0 {
+            self.rocks = self
+                .rocks
+                .points()
+                .filter(|p| p.y >= cut)
+                .map(|p| (p - vec2(0, cut), ()))
+                .collect();
+            self.pruned += cut;
+        }
+    }
+
+    /// How far below the top of the tower each column's highest rock is.
+    /// Two towers with the same profile look the same to the next rock,
+    /// unless it slips down a gap deeper than the rocks are tall.
+    fn surface_profile(&self) -> [isize; MAX_X as usize] {
+        let height = self.top();
+        let mut profile = [0; MAX_X as usize];
+        for (x, depth) in (0..MAX_X).zip(profile.iter_mut()) {
+            *depth = height - self.rocks.column_range(x).map_or(0, |(_, top)| top + 1);
+        }
+        profile
+    }
+
+    fn fingerprint(&self) -> Fingerprint {
+        Fingerprint {
+            shape: self.dropped % 5,
+            jet: self.jet_index,
+            profile: self.surface_profile(),
+        }
+    }
+
+    /// Drop the next rock, pushed around by `jets`, until it comes to rest.
+    /// With a `frame`, show it the chamber and the falling rock after every
+    /// move.
+    fn drop_rock(&mut self, jets: &Jets, mut frame: Option<&mut Frame>) -> Result<(), Error> {
+        let mut shape = Shape::shape_for(self.dropped);
+        let v = vec2(2, self.top() + 3);
+        shape = shape.translate(v);
+        if let Some(frame) = &mut frame {
+            frame(self, &shape.shape_set())?;
+        }
+        loop {
+            let jet = jets[self.jet_index];
+            self.jet_index = (self.jet_index + 1) % jets.len();
+            let v = Vector::from(&jet);
+            let new_shape = shape.translate(v);
+            if !new_shape.collides_with_wall() && !new_shape.collides_with(&self.rocks) {
+                shape = new_shape;
+            }
+            if let Some(frame) = &mut frame {
+                frame(self, &shape.shape_set())?;
+            }
+
+            let new_shape = shape.translate(vec2(0, -1));
+            if new_shape.collides_with_floor() || new_shape.collides_with(&self.rocks) {
+                self.rocks.extend(shape.blocks.iter().map(|p| (*p, ())));
+                break;
+            } else {
+                shape = new_shape;
+            }
+            if let Some(frame) = &mut frame {
+                frame(self, &shape.shape_set())?;
+            }
+        }
+        self.dropped += 1;
+        self.prune();
+        Ok(())
+    }
+}
+
+/// Something shown the chamber and the falling rock after every move.
+pub type Frame<'a> = dyn FnMut(&Chamber, &BlockSet) -> Result<(), Error> + 'a;
+
+/// Drop `count` rocks into the chamber, pushed around by `jets`.
+pub fn drop_rocks(jets: &Jets, count: usize) -> Chamber {
+    let mut chamber = Chamber::new();
+    for _ in 0..count {
+        chamber.drop_rock(jets, None).expect("nothing to fail");
+    }
+    chamber
+}
+
+/// Drop rocks like [`drop_rocks`], showing `frame` the chamber and the
+/// falling rock after every move.
+pub fn watch_rocks(jets: &Jets, count: usize, frame: &mut Frame) -> Result<Chamber, Error> {
+    let mut chamber = Chamber::new();
+    for _ in 0..count {
+        chamber.drop_rock(jets, Some(&mut *frame))?;
+    }
+    Ok(chamber)
+}
+
+/// The tower repeating itself: after `start` rocks, every `length` more
+/// add `height_gain` to its height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub start_height: isize,
+    pub length: usize,
+    pub height_gain: isize,
+}
+
+/// Drop up to `limit` rocks until the chamber's fingerprint repeats.
+pub fn find_cycle(jets: &Jets, limit: usize) -> Result<Option<Cycle>, Error> {
+    let mut chamber = Chamber::new();
+    let mut seen = HashMap::new();
+    while chamber.dropped <= limit {
+        let (dropped, height) = (chamber.dropped, chamber.height());
+        if let Some((start, start_height)) = seen.insert(chamber.fingerprint(), (dropped, height)) {
+            return Ok(Some(Cycle {
+                start,
+                start_height,
+                length: dropped - start,
+                height_gain: height - start_height,
+            }));
+        }
+        chamber.drop_rock(jets, None)?;
+    }
+    Ok(None)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// Rocks to drop.
+    pub limit: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self { limit: 2022 }
+    }
+}
+
+pub struct Day17 {
+    jets: Jets,
+    limit: usize,
+}
+
+impl Day17 {
+    pub fn new(input: &str, options: &Options) -> Result<Self, Error> {
+        Ok(Self {
+            jets: parse(input)?,
+            limit: options.limit,
+        })
+    }
+}
+
+impl Solution for Day17 {
+    const DAY: u8 = 17;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        let chamber = drop_rocks(&self.jets, self.limit);
+        debug!(profile = ?chamber.surface_profile());
+        Ok(chamber.height().into())
+    }
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day17::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_surface_profile() {
+        // A flat rock in columns 2 to 5 with a vertical one on its right.
+        let chamber = Chamber {
+            rocks: Shape::horiz()
+                .translate(vec2(2, 0))
+                .blocks
+                .into_iter()
+                .chain(Shape::vertical().translate(vec2(5, 1)).blocks)
+                .map(|p| (p, ()))
+                .collect(),
+            ..Chamber::new()
+        };
+        assert_eq!(chamber.height(), 5);
+        assert_eq!(chamber.surface_profile(), [5, 5, 4, 4, 4, 0, 5]);
+        assert_eq!(Chamber::new().surface_profile(), [0; 7]);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("<>>\n").unwrap().len(), 3);
+        let error = parse("<>x>").unwrap_err();
+        assert_eq!(format!("{error:#}"), "column 3: 'x' isn't a jet");
+        assert_eq!(parse("\n").unwrap_err().to_string(), "no jets");
+        assert!(matches!(
+            solve("", &Options::default()),
+            Err(crate::Error::Parse { day: 17, .. })
+        ));
+    }
+
+    #[test]
+    fn test_part_1() {
+        let jets = parse(SAMPLE).unwrap();
+        assert_eq!(drop_rocks(&jets, 2022).height(), 3068);
+        assert_eq!(drop_rocks(&jets, 1).height(), 1);
+    }
+
+    #[test]
+    fn test_prune() {
+        let jets = parse(SAMPLE).unwrap();
+        let chamber = drop_rocks(&jets, 2022);
+        assert_eq!(chamber.height(), 3068);
+        assert!(chamber.pruned > 3000);
+        assert!(chamber.rocks.len() < 200);
+
+        // A full row seals off everything under it.
+        let mut chamber = Chamber {
+            rocks: (0..MAX_X)
+                .map(|x| point2(x, 3))
+                .chain([point2(0, 0), point2(6, 5)])
+                .map(|p| (p, ()))
+                .collect(),
+            ..Chamber::new()
+        };
+        chamber.prune();
+        assert_eq!(chamber.pruned, 3);
+        assert_eq!(chamber.height(), 6);
+        assert_eq!(chamber.rocks.len(), 8);
+        assert!(chamber.rocks.contains(point2(6, 2)));
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let jets = parse(SAMPLE).unwrap();
+        let chamber = drop_rocks(&jets, 7);
+        let fingerprint = chamber.fingerprint();
+        assert_eq!(fingerprint.shape, 2);
+        assert!(fingerprint.jet < jets.len());
+        assert_eq!(fingerprint.profile, chamber.surface_profile());
+
+        let cycle = find_cycle(&jets, 2022).unwrap().unwrap();
+        assert_eq!(cycle.length % 5, 0);
+        // The cycle predicts the height of a longer tower.
+        let rocks = cycle.start + 3 * cycle.length;
+        assert_eq!(
+            drop_rocks(&jets, rocks).height(),
+            cycle.start_height + 3 * cycle.height_gain
+        );
+        assert_eq!(find_cycle(&jets, 10).unwrap(), None);
+    }
+}
@@ -0,0 +1,173 @@
+//! Day 18: Boiling Boulders.
+
+use crate::{
+    grid3::{Point, VoxelSet},
+    solution::{self, Answer, Answers, Solution},
+};
+use anyhow::Error;
+use euclid::vec3;
+use std::{collections::HashMap, io::Write};
+
+pub const DATA: &str = include_str!("../data/day18.txt");
+pub const SAMPLE: &str = include_str!("../data/day18_sample.txt");
+
+/// Day 18 has no options.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options;
+
+pub struct Day18 {
+    droplet: VoxelSet,
+}
+
+impl Day18 {
+    pub fn new(input: &str, _options: &Options) -> Result<Self, Error> {
+        Ok(Self {
+            droplet: VoxelSet::parse(input)?,
+        })
+    }
+}
+
+impl Solution for Day18 {
+    const DAY: u8 = 18;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        Ok(self.droplet.surface_area().into())
+    }
+
+    fn part_2(&self) -> Result<Answer, Error> {
+        Ok(self.droplet.exterior_surface_area().into())
+    }
+}
+
+/// What the droplet is made of: its separate pieces of lava and the
+/// pockets of air sealed inside, each with its size and surface area.
+pub fn analyze(droplet: &VoxelSet) -> String {
+    let mut report = String::new();
+    let mut describe = |kind: &str, sets: &[VoxelSet]| {
+        report += &format!("{} {kind}\n", sets.len());
+        for (i, set) in sets.iter().enumerate() {
+            report += &format!(
+                "  {}: {} cubes, surface area {}, exterior {}\n",
+                i + 1,
+                set.len(),
+                set.surface_area(),
+                set.exterior_surface_area()
+            );
+        }
+    };
+    describe("lava components", &droplet.components());
+    describe("air pockets", &droplet.air_pockets());
+    report
+}
+
+/// The droplet's outside as a mesh in Wavefront OBJ format, two triangles
+/// for each face counted in part 2. Triangles wind counterclockwise seen
+/// from outside, as viewers expect.
+pub fn write_obj(droplet: &VoxelSet, mut out: impl Write) -> Result<(), Error> {
+    let mut vertices: HashMap<Point, usize> = HashMap::new();
+    let mut triangles = vec![];
+    for (voxel, normal) in droplet.exterior_faces() {
+        // Two edges of the face, in the order that makes their cross
+        // product the normal.
+        let (u, v) = match (normal.x, normal.y, normal.z) {
+            (1, _, _) => (vec3(0, 1, 0), vec3(0, 0, 1)),
+            (-1, _, _) => (vec3(0, 0, 1), vec3(0, 1, 0)),
+            (_, 1, _) => (vec3(0, 0, 1), vec3(1, 0, 0)),
+            (_, -1, _) => (vec3(1, 0, 0), vec3(0, 0, 1)),
+            (_, _, 1) => (vec3(1, 0, 0), vec3(0, 1, 0)),
+            _ => (vec3(0, 1, 0), vec3(1, 0, 0)),
+        };
+        // A voxel fills the unit cube above its point, so faces pointing
+        // the positive way are one further along.
+        let corner = voxel + normal.max(vec3(0, 0, 0));
+        let mut index = |p: Point| {
+            let next = vertices.len() + 1;
+            *vertices.entry(p).or_insert(next)
+        };
+        let corners = [corner, corner + u, corner + u + v, corner + v].map(&mut index);
+        triangles.push([corners[0], corners[1], corners[2]]);
+        triangles.push([corners[0], corners[2], corners[3]]);
+    }
+
+    let mut by_index: Vec<_> = vertices.into_iter().collect();
+    by_index.sort_by_key(|(_, i)| *i);
+    for (p, _) in by_index {
+        writeln!(out, "v {} {} {}", p.x, p.y, p.z)?;
+    }
+    for [a, b, c] in triangles {
+        writeln!(out, "f {a} {b} {c}")?;
+    }
+    Ok(())
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day18::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::grid3;
+    use euclid::point3;
+
+    #[test]
+    fn test_parse() {
+        let droplet = VoxelSet::parse(SAMPLE).unwrap();
+        assert_eq!(droplet.len(), 13);
+        assert!(droplet.contains(point3(2, 2, 2)));
+    }
+
+    #[test]
+    fn test_part_1() {
+        let droplet = VoxelSet::parse("1,1,1\n2,1,1").unwrap();
+        assert_eq!(droplet.surface_area(), 10);
+        assert_eq!(VoxelSet::parse(SAMPLE).unwrap().surface_area(), 64);
+    }
+
+    #[test]
+    fn test_analyze() {
+        let droplet = VoxelSet::parse(SAMPLE).unwrap();
+        let report = analyze(&droplet);
+        // Most of the sample's cubes only touch along edges, so they're
+        // pieces of their own.
+        assert!(report.starts_with("6 lava components\n  1: 8 cubes, surface area 34,"));
+        assert!(report.ends_with("1 air pockets\n  1: 1 cubes, surface area 6, exterior 6\n"));
+    }
+
+    #[test]
+    fn test_write_obj() {
+        let droplet = VoxelSet::parse(SAMPLE).unwrap();
+        let mut obj = vec![];
+        write_obj(&droplet, &mut obj).unwrap();
+        let obj = String::from_utf8(obj).unwrap();
+
+        let vertices: Vec<Point> = obj
+            .lines()
+            .filter_map(|line| line.strip_prefix("v "))
+            .map(|v| grid3::parse_point(&v.replace(' ', ",")).unwrap())
+            .collect();
+        let triangles: Vec<Vec<usize>> = obj
+            .lines()
+            .filter_map(|line| line.strip_prefix("f "))
+            .map(|f| f.split(' ').map(|i| i.parse().unwrap()).collect())
+            .collect();
+        assert_eq!(triangles.len(), 2 * 58);
+
+        // Each triangle faces away from the voxel it covers: its normal
+        // leads from its middle out of the droplet.
+        for triangle in triangles {
+            let [a, b, c] = [0, 1, 2].map(|i| vertices[triangle[i] - 1]);
+            let normal = (b - a).cross(c - a);
+            let outside = (a.to_vector() + b.to_vector() + c.to_vector()) * 2 + normal * 3;
+            let outside = (outside / 6).to_point();
+            assert!(!droplet.contains(outside), "{triangle:?}");
+        }
+    }
+
+    #[test]
+    fn test_part_2() {
+        let droplet = VoxelSet::parse(SAMPLE).unwrap();
+        assert_eq!(droplet.exterior_surface_area(), 58);
+    }
+}
@@ -0,0 +1,1389 @@
+//! Day 19: Not Enough Minerals.
+
+use crate::{
+    cancel::{Cancel, Cancelled},
+    solution::{self, Answer, Answers, Solution},
+};
+use anyhow::{anyhow, bail, Context, Error};
+use enum_iterator::{all, Sequence};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{multispace1, u64},
+    combinator::{all_consuming, map, value},
+    multi::separated_list1,
+    sequence::{delimited, pair, separated_pair, tuple},
+    Finish, IResult,
+};
+use once_cell::sync::Lazy;
+use rayon::prelude::*;
+use regex::Regex;
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt, iter,
+    ops::{Add, AddAssign, Mul, Range, Sub},
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use tracing::{debug, debug_span};
+
+#[repr(usize)]
+#[derive(Debug, Clone, Copy, PartialEq, Sequence)]
+enum ResourceType {
+    Ore,
+    Clay,
+    Obsidian,
+    Geode,
+}
+
+impl fmt::Display for ResourceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Ore => "ore",
+            Self::Clay => "clay",
+            Self::Obsidian => "obsidian",
+            Self::Geode => "geode",
+        })
+    }
+}
+
+type ResourceCount = usize;
+
+pub const DATA: &str = include_str!("../data/day19.txt");
+pub const SAMPLE: &str = include_str!("../data/day19_sample.txt");
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd, Ord, Hash, Eq)]
+struct Resources {
+    geode: ResourceCount,
+    obsidian: ResourceCount,
+    clay: ResourceCount,
+    ore: ResourceCount,
+}
+
+impl Resources {
+    /// `count` of one resource and none of the others.
+    fn of(resource_type: ResourceType, count: ResourceCount) -> Self {
+        let mut resources = Self::default();
+        *match resource_type {
+            ResourceType::Ore => &mut resources.ore,
+            ResourceType::Clay => &mut resources.clay,
+            ResourceType::Obsidian => &mut resources.obsidian,
+            ResourceType::Geode => &mut resources.geode,
+        } = count;
+        resources
+    }
+
+    fn contains(&self, other: &Resources) -> bool {
+        self.ore >= other.ore
+            && self.clay >= other.clay
+            && self.obsidian >= other.obsidian
+            && self.geode >= other.geode
+    }
+
+    /// These resources with anything more than could be spent in
+    /// `time_left` minutes thrown away. A surplus is as good as exactly the
+    /// spendable amount, so states differing only in it are really the same.
+    fn clamped(&self, max_spend: &Resources, time_left: usize) -> Self {
+        let spendable = |max: ResourceCount, have: ResourceCount| have.min(max * time_left);
+        Self {
+            ore: spendable(max_spend.ore, self.ore),
+            clay: spendable(max_spend.clay, self.clay),
+            obsidian: spendable(max_spend.obsidian, self.obsidian),
+            geode: self.geode,
+        }
+    }
+}
+
+impl Mul<ResourceCount> for Resources {
+    type Output = Self;
+
+    fn mul(self, rhs: ResourceCount) -> Self {
+        Self {
+            ore: self.ore * rhs,
+            clay: self.clay * rhs,
+            obsidian: self.obsidian * rhs,
+            geode: self.geode * rhs,
+        }
+    }
+}
+
+impl Sub for Resources {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self::Output {
+        Self {
+            ore: self.ore.checked_sub(other.ore).unwrap(),
+            clay: self.clay.checked_sub(other.clay).unwrap(),
+            obsidian: self.obsidian.checked_sub(other.obsidian).unwrap(),
+            geode: self.geode.checked_sub(other.geode).unwrap(),
+        }
+    }
+}
+
+impl Add for Resources {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self::Output {
+        Self {
+            ore: self.ore.checked_add(other.ore).unwrap(),
+            clay: self.clay.checked_add(other.clay).unwrap(),
+            obsidian: self.obsidian.checked_add(other.obsidian).unwrap(),
+            geode: self.geode.checked_add(other.geode).unwrap(),
+        }
+    }
+}
+
+impl AddAssign for Resources {
+    fn add_assign(&mut self, other: Self) {
+        *self = Self {
+            ore: self.ore.checked_add(other.ore).unwrap(),
+            clay: self.clay.checked_add(other.clay).unwrap(),
+            obsidian: self.obsidian.checked_add(other.obsidian).unwrap(),
+            geode: self.geode.checked_add(other.geode).unwrap(),
+        };
+    }
+}
+
+type StateSet = BTreeSet<State>;
+
+#[derive(Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+struct State {
+    robots: Robots,
+    resources: Resources,
+}
+
+impl State {
+    fn starting() -> Self {
+        Self {
+            robots: Robots {
+                ore: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn with_order(&self, bp: &Blueprint, _time: usize, robot_order: Robots) -> Self {
+        let mut resources = self.resources - bp.build_cost(&robot_order);
+        resources += resources_made(&self.robots);
+        let robots = self.robots + robot_order;
+
+        Self { robots, resources }
+    }
+
+    fn step(&self, bp: &Blueprint, time: usize, _limit: usize) -> StateSet {
+        let orders = order_permutation_s(&self.resources, &self.robots, bp);
+
+        orders
+            .into_iter()
+            .map(|o| self.with_order(bp, time, o))
+            .collect()
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Blueprint {
+    id: usize,
+    ore_robot: Resources,
+    clay_robot: Resources,
+    obsidian_robot: Resources,
+    geode_robot: Resources,
+}
+
+type ParseResult<'a, T> = IResult<&'a str, T>;
+
+fn resource_type(input: &str) -> ParseResult<'_, ResourceType> {
+    alt((
+        value(ResourceType::Ore, tag("ore")),
+        value(ResourceType::Clay, tag("clay")),
+        value(ResourceType::Obsidian, tag("obsidian")),
+        value(ResourceType::Geode, tag("geode")),
+    ))(input)
+}
+
+/// An amount of one resource, like `14 clay`.
+fn cost(input: &str) -> ParseResult<'_, Resources> {
+    map(
+        separated_pair(u64, multispace1, resource_type),
+        |(count, resource_type)| Resources::of(resource_type, count as ResourceCount),
+    )(input)
+}
+
+/// One sentence of a blueprint, like `Each obsidian robot costs 3 ore and
+/// 14 clay`, without its full stop. Words may be split across lines.
+fn robot_cost(input: &str) -> ParseResult<'_, (ResourceType, Resources)> {
+    let and = tuple((multispace1, tag("and"), multispace1));
+    tuple((
+        delimited(
+            pair(tag("Each"), multispace1),
+            resource_type,
+            tuple((
+                multispace1,
+                tag("robot"),
+                multispace1,
+                tag("costs"),
+                multispace1,
+            )),
+        ),
+        map(separated_list1(and, cost), |costs| {
+            costs.into_iter().fold(Resources::default(), Add::add)
+        }),
+    ))(input)
+}
+
+/// Where each blueprint starts, and its ID.
+static HEADER_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"Blueprint\s+(\d+)\s*:").expect("regex"));
+
+impl FromStr for Blueprint {
+    type Err = Error;
+
+    /// One blueprint, with its robots in any order and its sentences wrapped
+    /// however they like.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim_start();
+        let header = HEADER_RE
+            .captures(s)
+            .filter(|header| header.get(0).expect("match").start() == 0)
+            .ok_or_else(|| anyhow!("expected \"Blueprint <id>:\""))?;
+        let id = header[1].parse()?;
+        let body = &s[header.get(0).expect("match").end()..];
+        let mut costs = [None; 4];
+        let sentences = body.split('.').map(str::trim).filter(|s| !s.is_empty());
+        for sentence in sentences {
+            let (resource_type, cost) = all_consuming(robot_cost)(sentence)
+                .finish()
+                .map_err(|_| anyhow!("can't read {sentence:?}"))?
+                .1;
+            if costs[resource_type as usize].replace(cost).is_some() {
+                bail!("two costs for the {resource_type} robot");
+            }
+        }
+        let cost = |resource_type: ResourceType| {
+            costs[resource_type as usize]
+                .ok_or_else(|| anyhow!("no cost for the {resource_type} robot"))
+        };
+        Ok(Self {
+            id,
+            ore_robot: cost(ResourceType::Ore)?,
+            clay_robot: cost(ResourceType::Clay)?,
+            obsidian_robot: cost(ResourceType::Obsidian)?,
+            geode_robot: cost(ResourceType::Geode)?,
+        })
+    }
+}
+
+impl fmt::Display for Blueprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Blueprint {}: Each ore robot costs {} ore. Each clay robot costs {} ore. \
+             Each obsidian robot costs {} ore and {} clay. \
+             Each geode robot costs {} ore and {} obsidian.",
+            self.id,
+            self.ore_robot.ore,
+            self.clay_robot.ore,
+            self.obsidian_robot.ore,
+            self.obsidian_robot.clay,
+            self.geode_robot.ore,
+            self.geode_robot.obsidian,
+        )
+    }
+}
+
+impl Blueprint {
+    fn robot_cost(&self, resource_type: ResourceType) -> Resources {
+        match resource_type {
+            ResourceType::Ore => self.ore_robot,
+            ResourceType::Clay => self.clay_robot,
+            ResourceType::Obsidian => self.obsidian_robot,
+            ResourceType::Geode => self.geode_robot,
+        }
+    }
+
+    /// The most of each resource any one robot costs. There's no point
+    /// having more robots of a kind than this, since only one robot can be
+    /// built a minute.
+    fn max_spend(&self) -> Resources {
+        all::<ResourceType>().map(|rt| self.robot_cost(rt)).fold(
+            Resources::default(),
+            |max, cost| Resources {
+                ore: max.ore.max(cost.ore),
+                clay: max.clay.max(cost.clay),
+                obsidian: max.obsidian.max(cost.obsidian),
+                geode: max.geode.max(cost.geode),
+            },
+        )
+    }
+
+    fn build_cost(&self, robots: &Robots) -> Resources {
+        let mut cost = Default::default();
+        for rt in all::<ResourceType>() {
+            if robots.contains(rt) {
+                cost += self.robot_cost(rt);
+            }
+        }
+        cost
+    }
+}
+
+/// Every blueprint in `s`, however their lines are wrapped. Errors say which
+/// blueprint couldn't be read, and the line it starts on.
+fn parse(s: &str) -> Result<Vec<Blueprint>, Error> {
+    let starts: Vec<_> = HEADER_RE.find_iter(s).map(|m| m.start()).collect();
+    let first = starts.first().copied().unwrap_or(s.len());
+    if !s[..first].trim().is_empty() {
+        bail!("expected a blueprint, got {:?}", s[..first].trim());
+    }
+    let ends = starts.iter().skip(1).copied().chain(iter::once(s.len()));
+    starts
+        .iter()
+        .zip(ends)
+        .map(|(&start, end)| {
+            let text = &s[start..end];
+            text.parse().with_context(|| {
+                let id = &HEADER_RE.captures(text).expect("header")[1];
+                let line = s[..start].matches('\n').count() + 1;
+                format!("blueprint {id} on line {line}")
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Copy, Hash, Eq, PartialOrd, Ord)]
+struct Robots {
+    geode: ResourceCount,
+    obsidian: ResourceCount,
+    clay: ResourceCount,
+    ore: ResourceCount,
+}
+
+impl Robots {
+    /// Whether there are at least as many of every kind of robot as `other`.
+    fn contains_all(&self, other: &Robots) -> bool {
+        self.ore >= other.ore
+            && self.clay >= other.clay
+            && self.obsidian >= other.obsidian
+            && self.geode >= other.geode
+    }
+
+    fn contains(&self, resource_type: ResourceType) -> bool {
+        match resource_type {
+            ResourceType::Ore => self.ore > 0,
+            ResourceType::Clay => self.clay > 0,
+            ResourceType::Obsidian => self.obsidian > 0,
+            ResourceType::Geode => self.geode > 0,
+        }
+    }
+}
+
+impl Add for Robots {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self::Output {
+        Self {
+            ore: self.ore.checked_add(other.ore).unwrap(),
+            clay: self.clay.checked_add(other.clay).unwrap(),
+            obsidian: self.obsidian.checked_add(other.obsidian).unwrap(),
+            geode: self.geode.checked_add(other.geode).unwrap(),
+        }
+    }
+}
+
+impl AddAssign for Robots {
+    fn add_assign(&mut self, other: Self) {
+        *self = Self {
+            ore: self.ore.checked_add(other.ore).unwrap(),
+            clay: self.clay.checked_add(other.clay).unwrap(),
+            obsidian: self.obsidian.checked_add(other.obsidian).unwrap(),
+            geode: self.geode.checked_add(other.geode).unwrap(),
+        };
+    }
+}
+
+fn order_permutation_s(
+    resources: &Resources,
+    _robots: &Robots,
+    blueprint: &Blueprint,
+) -> Vec<Robots> {
+    let possible_builds = [
+        Robots::default(),
+        Robots {
+            geode: 1,
+            ..Robots::default()
+        },
+        Robots {
+            ore: 1,
+            ..Robots::default()
+        },
+        Robots {
+            clay: 1,
+            ..Robots::default()
+        },
+        Robots {
+            obsidian: 1,
+            ..Robots::default()
+        },
+    ];
+    let mut p = vec![];
+    for r in possible_builds.iter() {
+        let cost = blueprint.build_cost(r);
+        if resources.contains(&cost) {
+            p.push(*r);
+        }
+    }
+
+    p
+}
+
+#[allow(unused)]
+fn order_permutation(resources: &Resources, robots: &Robots, blueprint: &Blueprint) -> Vec<Robots> {
+    const ZERO_OR_ONE: Range<ResourceCount> = 0..2;
+    let mut p = vec![];
+    let max_clay = if robots.clay < blueprint.obsidian_robot.clay {
+        2
+    } else {
+        1
+    };
+    let max_ore = if robots.ore
+        < (blueprint.obsidian_robot.ore
+            + blueprint.geode_robot.ore
+            + blueprint.clay_robot.ore
+            + blueprint.ore_robot.ore)
+    {
+        2
+    } else {
+        1
+    };
+    let max_obsidian = if robots.obsidian < blueprint.geode_robot.obsidian {
+        2
+    } else {
+        1
+    };
+    for geode in ZERO_OR_ONE {
+        for obsidian in 0..max_obsidian {
+            for clay in 0..max_clay {
+                for ore in 0..max_ore {
+                    let robots = Robots {
+                        ore,
+                        clay,
+                        obsidian,
+                        geode,
+                    };
+                    let cost = blueprint.build_cost(&Robots {
+                        ore,
+                        clay,
+                        obsidian,
+                        geode,
+                    });
+                    if resources.contains(&cost) {
+                        p.push(robots);
+                    }
+                }
+            }
+        }
+    }
+    p
+}
+
+fn resources_made(robots: &Robots) -> Resources {
+    Resources {
+        ore: robots.ore,
+        clay: robots.clay,
+        obsidian: robots.obsidian,
+        geode: robots.geode,
+    }
+}
+
+/// The resource totals among states sharing a set of robots that aren't
+/// beaten in every resource by another. With the same robots, having no more
+/// of anything can never open more geodes, so those states are dropped.
+#[derive(Debug, Default, Clone)]
+struct ParetoFront {
+    resources: Vec<Resources>,
+}
+
+impl ParetoFront {
+    /// Add `resources` unless some total here has at least as much of
+    /// everything, removing any totals it beats. Returns whether it was added.
+    fn insert(&mut self, resources: Resources) -> bool {
+        if self.resources.iter().any(|r| r.contains(&resources)) {
+            return false;
+        }
+        self.resources.retain(|r| !resources.contains(r));
+        self.resources.push(resources);
+        true
+    }
+}
+
+/// Keep only the states no other state dominates, having at least as many
+/// of every robot and at least as much of every resource.
+fn prune_dominated(states: impl IntoIterator<Item = State>) -> StateSet {
+    let mut fronts: HashMap<Robots, ParetoFront> = HashMap::new();
+    for state in states {
+        fronts
+            .entry(state.robots)
+            .or_default()
+            .insert(state.resources);
+    }
+
+    // A state is also beaten by one with at least as many of every robot and
+    // at least as much of every resource, which the per-robots fronts can't
+    // see, so check each front against the fronts of richer robot sets.
+    let fronts: Vec<(Robots, ParetoFront)> = fronts.into_iter().collect();
+    fronts
+        .iter()
+        .flat_map(|(robots, front)| {
+            let richer: Vec<&ParetoFront> = fronts
+                .iter()
+                .filter(|(other, _)| other != robots && other.contains_all(robots))
+                .map(|(_, front)| front)
+                .collect();
+            front
+                .resources
+                .iter()
+                .filter(move |resources| {
+                    !richer
+                        .iter()
+                        .any(|front| front.resources.iter().any(|r| r.contains(resources)))
+                })
+                .map(|resources| State {
+                    robots: *robots,
+                    resources: *resources,
+                })
+        })
+        .collect()
+}
+
+pub const TIME_LIMIT: usize = 24;
+/// With the elephants' help there's time to open more geodes, but only the
+/// first few blueprints survived.
+const PART_2_TIME_LIMIT: usize = 32;
+const PART_2_BLUEPRINTS: usize = 3;
+
+/// The most geodes `bp` can open in `time_limit` minutes.
+fn max_geodes(
+    bp: &Blueprint,
+    time_limit: usize,
+    normalize: bool,
+    cancel: &Cancel,
+) -> Result<usize, Cancelled> {
+    Ok(frontier(bp, time_limit, normalize, cancel)?.0)
+}
+
+/// [`max_geodes`], and how many states were kept over all the minutes.
+/// With `normalize`, each state's stockpile is clamped to what could still
+/// be spent, so more states collapse together.
+fn frontier(
+    bp: &Blueprint,
+    time_limit: usize,
+    normalize: bool,
+    cancel: &Cancel,
+) -> Result<(usize, usize), Cancelled> {
+    let _span = debug_span!("blueprint", id = bp.id).entered();
+    let max_spend = bp.max_spend();
+    let mut states: StateSet = StateSet::new();
+    states.insert(State::starting());
+    let mut state_count = 0;
+
+    for time in 1..=time_limit {
+        cancel.check()?;
+        debug!(time, state_count = states.len());
+        let remaining = time_limit - time;
+        let new_states = states
+            .par_iter()
+            .flat_map(|state| state.step(bp, time, time_limit))
+            .map(|state| match normalize {
+                true => State {
+                    resources: state.resources.clamped(&max_spend, remaining),
+                    ..state
+                },
+                false => state,
+            })
+            .collect::<Vec<_>>();
+        states = prune_dominated(new_states);
+
+        // Even building a geode robot every remaining minute can't catch up
+        // with the geodes another state is already sure of.
+        let sure_geodes = |s: &State| s.resources.geode + s.robots.geode * remaining;
+        let most_sure = states.iter().map(sure_geodes).max().unwrap_or(0);
+        states
+            .retain(|s| sure_geodes(s) + remaining * remaining.saturating_sub(1) / 2 >= most_sure);
+        state_count += states.len();
+    }
+
+    let best = states
+        .into_iter()
+        .max_by_key(|s| s.resources.geode)
+        .expect("at least one state");
+    debug!(state = ?best, state_count, "best");
+    Ok((best.resources.geode, state_count))
+}
+
+/// The skip-ahead solver's memo, keyed on minutes left, robots and the
+/// stockpile clamped to what could still be spent.
+type GeodeMemo = HashMap<(usize, Robots, Resources), ResourceCount>;
+
+/// Minutes of collecting before `resources` covers `cost`, or `None` if
+/// there isn't a robot collecting something it needs.
+fn minutes_to_afford(cost: &Resources, resources: &Resources, robots: &Robots) -> Option<usize> {
+    let wait = |cost: ResourceCount, have: ResourceCount, rate: ResourceCount| {
+        let short = cost.saturating_sub(have);
+        match short {
+            0 => Some(0),
+            _ => (rate > 0).then(|| short.div_ceil(rate)),
+        }
+    };
+    Some(
+        wait(cost.ore, resources.ore, robots.ore)?
+            .max(wait(cost.clay, resources.clay, robots.clay)?)
+            .max(wait(cost.obsidian, resources.obsidian, robots.obsidian)?),
+    )
+}
+
+fn robot_of(resource_type: ResourceType) -> Robots {
+    let mut robots = Robots::default();
+    match resource_type {
+        ResourceType::Ore => robots.ore = 1,
+        ResourceType::Clay => robots.clay = 1,
+        ResourceType::Obsidian => robots.obsidian = 1,
+        ResourceType::Geode => robots.geode = 1,
+    }
+    robots
+}
+
+/// Geodes opened in the last `time_left` minutes, trying each kind of robot
+/// as the next one to build and jumping straight to the minute it's
+/// affordable instead of deciding minute by minute.
+fn skip_ahead(
+    bp: &Blueprint,
+    max_spend: &Resources,
+    time_left: usize,
+    robots: Robots,
+    resources: Resources,
+    memo: &mut GeodeMemo,
+) -> ResourceCount {
+    // Clamping lets far more states share a memo entry.
+    let resources = resources.clamped(max_spend, time_left);
+    let key = (time_left, robots, resources);
+    if let Some(geodes) = memo.get(&key) {
+        return *geodes;
+    }
+
+    let mut best = resources.geode + robots.geode * time_left;
+    for rt in all::<ResourceType>() {
+        let enough = match rt {
+            ResourceType::Ore => robots.ore >= max_spend.ore,
+            ResourceType::Clay => robots.clay >= max_spend.clay,
+            ResourceType::Obsidian => robots.obsidian >= max_spend.obsidian,
+            ResourceType::Geode => false,
+        };
+        if enough {
+            continue;
+        }
+        let cost = bp.robot_cost(rt);
+        let Some(wait) = minutes_to_afford(&cost, &resources, &robots) else {
+            continue;
+        };
+        // A robot finished in the last minute never collects anything.
+        let elapsed = wait + 1;
+        if elapsed >= time_left {
+            continue;
+        }
+        let resources = resources + resources_made(&robots) * elapsed - cost;
+        let geodes = skip_ahead(
+            bp,
+            max_spend,
+            time_left - elapsed,
+            robots + robot_of(rt),
+            resources,
+            memo,
+        );
+        best = best.max(geodes);
+    }
+
+    memo.insert(key, best);
+    best
+}
+
+/// The most geodes `bp` can open in `time_limit` minutes, found by
+/// [`skip_ahead`].
+fn max_geodes_skip_ahead(bp: &Blueprint, time_limit: usize) -> usize {
+    let mut memo = GeodeMemo::new();
+    let geodes = skip_ahead(
+        bp,
+        &bp.max_spend(),
+        time_limit,
+        State::starting().robots,
+        Resources::default(),
+        &mut memo,
+    );
+    debug!(id = bp.id, geodes, memo_size = memo.len());
+    geodes
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Solver {
+    #[default]
+    Frontier,
+    SkipAhead,
+}
+
+impl Solver {
+    /// `normalize` is for the frontier; the skip-ahead solver always clamps
+    /// its memo keys, and is only cancelled between blueprints.
+    fn max_geodes(
+        &self,
+        bp: &Blueprint,
+        time_limit: usize,
+        normalize: bool,
+        cancel: &Cancel,
+    ) -> Result<usize, Cancelled> {
+        match self {
+            Self::Frontier => max_geodes(bp, time_limit, normalize, cancel),
+            Self::SkipAhead => Ok(max_geodes_skip_ahead(bp, time_limit)),
+        }
+    }
+
+    /// The most geodes each blueprint can open, solving the blueprints in
+    /// parallel. The results are in the blueprints' order, however the
+    /// solving was spread across threads.
+    fn max_geodes_each(
+        &self,
+        blueprints: &[Blueprint],
+        time_limit: usize,
+        normalize: bool,
+        cancel: &Cancel,
+    ) -> Result<Vec<usize>, Cancelled> {
+        let finished = AtomicUsize::new(0);
+        blueprints
+            .par_iter()
+            .map(|bp| {
+                cancel.check()?;
+                let geodes = self.max_geodes(bp, time_limit, normalize, cancel)?;
+                let finished = finished.fetch_add(1, Ordering::Relaxed) + 1;
+                debug!(
+                    id = bp.id,
+                    geodes,
+                    finished,
+                    of = blueprints.len(),
+                    "blueprint done"
+                );
+                Ok(geodes)
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Solver {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "frontier" => Self::Frontier,
+            "skip-ahead" => Self::SkipAhead,
+            _ => bail!("unknown solver {s:?}, expected frontier or skip-ahead"),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// Minutes to collect geodes in for part 1; part 2 has eight more.
+    pub time_limit: usize,
+    /// Only score this many blueprints in part 1.
+    pub blueprint_limit: usize,
+    /// How to search.
+    pub solver: Solver,
+    /// Clamp the frontier's stockpiles to what could still be spent.
+    pub normalize: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            time_limit: TIME_LIMIT,
+            blueprint_limit: 2000,
+            solver: Solver::default(),
+            normalize: true,
+        }
+    }
+}
+
+pub struct Day19 {
+    blueprints: Vec<Blueprint>,
+    time_limit: usize,
+    blueprint_limit: usize,
+    solver: Solver,
+    normalize: bool,
+}
+
+impl Day19 {
+    pub fn new(input: &str, options: &Options) -> Result<Self, Error> {
+        Ok(Self {
+            blueprints: parse(input)?,
+            time_limit: options.time_limit,
+            blueprint_limit: options.blueprint_limit,
+            solver: options.solver,
+            normalize: options.normalize,
+        })
+    }
+}
+
+impl Day19 {
+    fn part_1_until(&self, cancel: &Cancel) -> Result<Answer, Error> {
+        let blueprints = &self.blueprints[0..self.blueprint_limit.min(self.blueprints.len())];
+        let geodes =
+            self.solver
+                .max_geodes_each(blueprints, self.time_limit, self.normalize, cancel)?;
+        let quality_level: usize = blueprints
+            .iter()
+            .zip(geodes)
+            .map(|(bp, geodes)| bp.id * geodes)
+            .sum();
+        Ok(quality_level.into())
+    }
+
+    fn part_2_until(&self, cancel: &Cancel) -> Result<Answer, Error> {
+        let time_limit = self.time_limit + (PART_2_TIME_LIMIT - TIME_LIMIT);
+        let blueprints = &self.blueprints[0..PART_2_BLUEPRINTS.min(self.blueprints.len())];
+        let total: usize = self
+            .solver
+            .max_geodes_each(blueprints, time_limit, self.normalize, cancel)?
+            .into_iter()
+            .product();
+        Ok(total.into())
+    }
+}
+
+impl Solution for Day19 {
+    const DAY: u8 = 19;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        self.part_1_until(&Cancel::default())
+    }
+
+    fn part_2(&self) -> Result<Answer, Error> {
+        self.part_2_until(&Cancel::default())
+    }
+
+    fn solve_part(&self, part: u8, cancel: &Cancel) -> Result<Answer, Error> {
+        match part {
+            1 => self.part_1_until(cancel),
+            _ => self.part_2_until(cancel),
+        }
+    }
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day19::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn blueprint_strategy() -> impl Strategy<Value = Blueprint> {
+        (1..40usize, prop::array::uniform6(1..6usize)).prop_map(|(id, costs)| Blueprint {
+            id,
+            ore_robot: Resources {
+                ore: costs[0],
+                ..Resources::default()
+            },
+            clay_robot: Resources {
+                ore: costs[1],
+                ..Resources::default()
+            },
+            obsidian_robot: Resources {
+                ore: costs[2],
+                clay: costs[3] * 3,
+                ..Resources::default()
+            },
+            geode_robot: Resources {
+                ore: costs[4],
+                obsidian: costs[5] * 3,
+                ..Resources::default()
+            },
+        })
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn test_round_trip(bps in prop::collection::vec(blueprint_strategy(), 1..4)) {
+            let input: String = bps.iter().map(|bp| format!("{bp}\n")).collect();
+            prop_assert_eq!(parse(&input).unwrap(), bps);
+        }
+
+        #[test]
+        fn test_solvers_agree(bp in blueprint_strategy()) {
+            let cancel = Cancel::default();
+            let frontier = Solver::Frontier.max_geodes(&bp, 14, true, &cancel).unwrap();
+            prop_assert_eq!(Solver::Frontier.max_geodes(&bp, 14, false, &cancel), Ok(frontier));
+            prop_assert_eq!(Solver::SkipAhead.max_geodes(&bp, 14, true, &cancel), Ok(frontier));
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let bps = parse(SAMPLE).unwrap();
+        dbg!(&bps);
+        assert_eq!(bps.len(), 2);
+        assert_eq!(bps[1].geode_robot.obsidian, 12);
+
+        let error = parse(&SAMPLE.replace("costs 3 ore and 8", "costs 3 ore or 8")).unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "blueprint 2 on line 2: can't read \"Each obsidian robot costs 3 ore or 8 clay\""
+        );
+    }
+
+    #[test]
+    fn test_parse_wrapped() {
+        // As the puzzle shows the sample, with the robots in another order
+        // and no newline at the end.
+        let wrapped = "\
+Blueprint 1:
+  Each ore robot costs 4 ore.
+  Each clay robot costs 2 ore.
+  Each obsidian robot costs 3 ore and 14 clay.
+  Each geode robot costs 2 ore and 7 obsidian.
+
+Blueprint 2:
+  Each geode robot costs 3 ore
+    and 12 obsidian.
+  Each clay robot costs 3 ore. Each ore robot costs 2 ore.
+  Each obsidian robot costs 3 ore and 8 clay.";
+        assert_eq!(parse(wrapped).unwrap(), parse(SAMPLE).unwrap());
+        assert_eq!(parse("").unwrap(), []);
+
+        let error = |s: &str| format!("{:#}", parse(s).unwrap_err());
+        assert_eq!(
+            error(&wrapped.replace("  Each clay robot costs 2 ore.\n", "")),
+            "blueprint 1 on line 1: no cost for the clay robot"
+        );
+        assert_eq!(
+            error(&wrapped.replace("Each ore robot costs 2", "Each clay robot costs 2")),
+            "blueprint 2 on line 7: two costs for the clay robot"
+        );
+        assert_eq!(
+            error(&format!("Blueprints:\n{wrapped}")),
+            "expected a blueprint, got \"Blueprints:\""
+        );
+    }
+
+    #[test]
+    fn test_order_permutation() {
+        let bps = parse(SAMPLE).unwrap();
+        let bp0 = &bps[0];
+
+        let r = Resources::default();
+        let robots = Robots::default();
+        let orders = order_permutation_s(&r, &robots, bp0);
+
+        assert_eq!(orders.len(), 1);
+
+        let r = Resources {
+            ore: 4,
+            ..Resources::default()
+        };
+
+        let orders = order_permutation_s(&r, &robots, bp0);
+
+        dbg!(&orders);
+        assert_eq!(orders.len(), 3);
+
+        let r = Resources {
+            ore: 8,
+            clay: 14,
+            ..Resources::default()
+        };
+
+        let orders = order_permutation_s(&r, &robots, bp0);
+
+        assert_eq!(orders.len(), 4);
+        let r = Resources {
+            ore: 4,
+            clay: 15,
+            ..Resources::default()
+        };
+
+        let orders = order_permutation_s(&r, &robots, bp0);
+
+        dbg!(&orders);
+
+        assert_eq!(orders.len(), 4);
+    }
+
+    #[test]
+    fn test_pareto_front() {
+        let resources = |ore, clay| Resources {
+            ore,
+            clay,
+            ..Resources::default()
+        };
+        let mut front = ParetoFront::default();
+        assert!(front.insert(resources(2, 1)));
+        assert!(front.insert(resources(1, 2)));
+        assert!(!front.insert(resources(1, 1)));
+        assert!(!front.insert(resources(2, 1)));
+        assert!(front.insert(resources(3, 3)));
+        assert_eq!(front.resources, vec![resources(3, 3)]);
+    }
+
+    #[test]
+    fn test_prune_dominated() {
+        let state = |ore_robots, ore| State {
+            robots: Robots {
+                ore: ore_robots,
+                ..Robots::default()
+            },
+            resources: Resources {
+                ore,
+                ..Resources::default()
+            },
+        };
+        let pruned = prune_dominated([state(1, 3), state(1, 2), state(2, 3), state(2, 1)]);
+        assert_eq!(pruned, StateSet::from([state(2, 3)]));
+    }
+
+    #[test]
+    fn test_max_geodes() {
+        let bps = parse(SAMPLE).unwrap();
+        let cancel = Cancel::default();
+        assert_eq!(max_geodes(&bps[0], TIME_LIMIT, true, &cancel), Ok(9));
+        assert_eq!(max_geodes(&bps[1], TIME_LIMIT, true, &cancel), Ok(12));
+        assert_eq!(max_geodes(&bps[1], TIME_LIMIT, false, &cancel), Ok(12));
+    }
+
+    #[test]
+    fn test_normalize() {
+        let bps = parse(SAMPLE).unwrap();
+        let max_spend = bps[0].max_spend();
+        let rich = Resources {
+            ore: 100,
+            clay: 3,
+            obsidian: 50,
+            geode: 7,
+        };
+        let clamped = rich.clamped(&max_spend, 2);
+        assert_eq!(
+            clamped,
+            Resources {
+                ore: 8,
+                clay: 3,
+                obsidian: 14,
+                geode: 7,
+            }
+        );
+
+        let cancel = Cancel::default();
+        for bp in &bps {
+            let (geodes, normalized) = frontier(bp, TIME_LIMIT, true, &cancel).unwrap();
+            let (raw_geodes, raw) = frontier(bp, TIME_LIMIT, false, &cancel).unwrap();
+            assert_eq!(geodes, raw_geodes);
+            assert!(normalized < raw, "{normalized} states, {raw} without");
+        }
+    }
+
+    #[test]
+    fn test_max_geodes_each() {
+        let bps = parse(SAMPLE).unwrap();
+        let bps = [bps[1].clone(), bps[0].clone(), bps[1].clone()];
+        let cancel = Cancel::default();
+        assert_eq!(
+            Solver::SkipAhead.max_geodes_each(&bps, TIME_LIMIT, true, &cancel),
+            Ok(vec![12, 9, 12])
+        );
+        assert_eq!(
+            Solver::Frontier.max_geodes_each(&[], TIME_LIMIT, true, &cancel),
+            Ok(vec![])
+        );
+    }
+
+    #[test]
+    fn test_cancel() {
+        let bps = parse(SAMPLE).unwrap();
+        let cancel = Cancel::default();
+        cancel.cancel();
+        for solver in [Solver::Frontier, Solver::SkipAhead] {
+            assert!(solver
+                .max_geodes_each(&bps, TIME_LIMIT, true, &cancel)
+                .is_err());
+        }
+        let day = Day19::new(SAMPLE, &Options::default()).unwrap();
+        let error = day.solve_part(2, &cancel).unwrap_err();
+        assert!(error.downcast_ref::<Cancelled>().is_some());
+    }
+
+    #[test]
+    fn test_minutes_to_afford() {
+        let bps = parse(SAMPLE).unwrap();
+        let robots = State::starting().robots;
+        let cost = bps[0].robot_cost(ResourceType::Clay);
+        assert_eq!(
+            minutes_to_afford(&cost, &Resources::default(), &robots),
+            Some(2)
+        );
+        let cost = bps[0].robot_cost(ResourceType::Obsidian);
+        assert_eq!(
+            minutes_to_afford(&cost, &Resources::default(), &robots),
+            None
+        );
+    }
+
+    #[test]
+    fn test_skip_ahead() {
+        let bps = parse(SAMPLE).unwrap();
+        assert_eq!(max_geodes_skip_ahead(&bps[0], TIME_LIMIT), 9);
+        assert_eq!(max_geodes_skip_ahead(&bps[1], TIME_LIMIT), 12);
+    }
+
+    #[test]
+    fn test_time_10() {
+        let bps = parse(SAMPLE).unwrap();
+        let bp0 = &bps[0];
+
+        println!("bp = {:#?}", bp0);
+
+        let state = State {
+            robots: Robots {
+                ore: 1,
+                clay: 3,
+                ..Robots::default()
+            },
+            resources: Resources {
+                ore: 4,
+                clay: 15,
+                ..Resources::default()
+            },
+        };
+
+        let expected_state = State {
+            robots: Robots {
+                ore: 1,
+                clay: 3,
+                obsidian: 1,
+                ..Robots::default()
+            },
+            resources: Resources {
+                ore: 2,
+                clay: 4,
+                ..Resources::default()
+            },
+        };
+        let new_state = state.with_order(
+            bp0,
+            10,
+            Robots {
+                obsidian: 1,
+                ..Robots::default()
+            },
+        );
+
+        assert_eq!(new_state, expected_state);
+    }
+
+    #[test]
+    fn test_solve() {
+        let bps = parse(SAMPLE).unwrap();
+        let bp0 = &bps[0];
+
+        println!("bp = {:#?}", bp0);
+
+        let expected_states: &[(usize, State)] = &[
+            (0, State::starting()),
+            (
+                1,
+                State {
+                    robots: Robots {
+                        ore: 1,
+                        ..Robots::default()
+                    },
+                    resources: Resources {
+                        ore: 1,
+                        ..Resources::default()
+                    },
+                },
+            ),
+            (
+                2,
+                State {
+                    robots: Robots {
+                        ore: 1,
+                        ..Robots::default()
+                    },
+                    resources: Resources {
+                        ore: 2,
+                        ..Resources::default()
+                    },
+                },
+            ),
+            (
+                3,
+                State {
+                    robots: Robots {
+                        ore: 1,
+                        clay: 1,
+                        ..Robots::default()
+                    },
+                    resources: Resources {
+                        ore: 1,
+                        clay: 0,
+                        ..Resources::default()
+                    },
+                },
+            ),
+            (
+                4,
+                State {
+                    robots: Robots {
+                        ore: 1,
+                        clay: 1,
+                        ..Robots::default()
+                    },
+                    resources: Resources {
+                        ore: 2,
+                        clay: 1,
+                        ..Resources::default()
+                    },
+                },
+            ),
+            (
+                5,
+                State {
+                    robots: Robots {
+                        ore: 1,
+                        clay: 2,
+                        ..Robots::default()
+                    },
+                    resources: Resources {
+                        ore: 1,
+                        clay: 2,
+                        ..Resources::default()
+                    },
+                },
+            ),
+            (
+                6,
+                State {
+                    robots: Robots {
+                        ore: 1,
+                        clay: 2,
+                        ..Robots::default()
+                    },
+                    resources: Resources {
+                        ore: 2,
+                        clay: 4,
+                        ..Resources::default()
+                    },
+                },
+            ),
+            (
+                7,
+                State {
+                    robots: Robots {
+                        ore: 1,
+                        clay: 3,
+                        ..Robots::default()
+                    },
+                    resources: Resources {
+                        ore: 1,
+                        clay: 6,
+                        ..Resources::default()
+                    },
+                },
+            ),
+            (
+                8,
+                State {
+                    robots: Robots {
+                        ore: 1,
+                        clay: 3,
+                        ..Robots::default()
+                    },
+                    resources: Resources {
+                        ore: 2,
+                        clay: 9,
+                        ..Resources::default()
+                    },
+                },
+            ),
+            (
+                9,
+                State {
+                    robots: Robots {
+                        ore: 1,
+                        clay: 3,
+                        ..Robots::default()
+                    },
+                    resources: Resources {
+                        ore: 3,
+                        clay: 12,
+                        ..Resources::default()
+                    },
+                },
+            ),
+            (
+                10,
+                State {
+                    robots: Robots {
+                        ore: 1,
+                        clay: 3,
+                        ..Robots::default()
+                    },
+                    resources: Resources {
+                        ore: 4,
+                        clay: 15,
+                        ..Resources::default()
+                    },
+                },
+            ),
+            (
+                11,
+                State {
+                    robots: Robots {
+                        ore: 1,
+                        clay: 3,
+                        obsidian: 1,
+                        ..Robots::default()
+                    },
+                    resources: Resources {
+                        ore: 2,
+                        clay: 4,
+                        ..Resources::default()
+                    },
+                },
+            ),
+        ];
+
+        let mut states: StateSet = StateSet::new();
+        states.insert(State::starting());
+
+        for (i, expected_state) in expected_states.iter().enumerate() {
+            let time = i + 1;
+            if !states.contains(&expected_state.1) {
+                println!("### time = {time}");
+                let mut state_list: Vec<_> = states.into_iter().collect();
+                state_list.sort_by_key(|s| s.resources);
+                println!("### states = {:#?}", state_list);
+                println!("### expected_state = {:#?}", expected_state);
+                panic!();
+            }
+            let new_states: StateSet = states
+                .iter()
+                .flat_map(|state| state.step(bp0, time, 24))
+                .collect();
+            states = new_states;
+        }
+
+        let mut state_list: Vec<_> = states.into_iter().collect();
+
+        state_list.sort_by_key(|s| s.resources);
+        state_list.reverse();
+
+        println!("states = {:#?}", &state_list[..4.min(state_list.len())]);
+    }
+}
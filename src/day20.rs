@@ -0,0 +1,237 @@
+//! Day 20: Grove Positioning System.
+
+use crate::{
+    circular::CircularList,
+    parse::parse_lines,
+    solution::{self, Answer, Answers, Solution},
+};
+use anyhow::{anyhow, Error};
+use tracing::debug;
+
+pub const DATA: &str = include_str!("../data/day20.txt");
+pub const SAMPLE: &str = include_str!("../data/day20_sample.txt");
+
+/// The numbers in the file, each multiplied by `key`.
+fn parse(s: &str, key: isize) -> Result<Vec<isize>, Error> {
+    Ok(parse_lines::<isize>(s)?
+        .into_iter()
+        .map(|value| value * key)
+        .collect())
+}
+
+/// Move each number, in the order they were given, as many places as its
+/// value.
+fn mix(list: &mut CircularList<isize>, values: &[isize]) {
+    for (id, value) in values.iter().enumerate() {
+        list.rotate_element(id, *value);
+    }
+}
+
+/// `values` after mixing them `rounds` times.
+fn mixed(values: &[isize], rounds: usize) -> CircularList<isize> {
+    let mut list = CircularList::new(values.iter().copied());
+    for _ in 0..rounds {
+        mix(&mut list, values);
+    }
+    list
+}
+
+/// The places after the zero that the puzzle adds up.
+pub const OFFSETS: [isize; 3] = [1000, 2000, 3000];
+
+/// The numbers some places after the zero in a mixed list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coordinates {
+    /// The number at each offset, in the order the offsets were given.
+    pub values: Vec<isize>,
+}
+
+impl Coordinates {
+    pub fn sum(&self) -> isize {
+        self.values.iter().sum()
+    }
+}
+
+/// The numbers `offsets` places after the zero in `list`, which was made
+/// from `values`. Offsets wrap around, and can be negative.
+fn coordinates(
+    list: &CircularList<isize>,
+    values: &[isize],
+    offsets: &[isize],
+) -> Result<Coordinates, Error> {
+    let zero = values
+        .iter()
+        .position(|value| *value == 0)
+        .ok_or_else(|| anyhow!("there's no zero"))?;
+    let zero_position = list.position_of(zero) as isize;
+    Ok(Coordinates {
+        values: offsets
+            .iter()
+            .map(|offset| *list.get_wrapping(zero_position + offset))
+            .collect(),
+    })
+}
+
+/// The numbers in `input` multiplied by `key`, mixed `rounds` times, at
+/// `offsets` places after the zero.
+pub fn decrypt(
+    input: &str,
+    key: isize,
+    rounds: usize,
+    offsets: &[isize],
+) -> Result<Coordinates, Error> {
+    let values = parse(input, key)?;
+    coordinates(&mixed(&values, rounds), &values, offsets)
+}
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Decryption key for part 2.
+    pub key: isize,
+    /// Rounds of mixing for part 2.
+    pub rounds: usize,
+    /// Places after the zero whose numbers both parts add up.
+    pub offsets: Vec<isize>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            key: 811589153,
+            rounds: 10,
+            offsets: OFFSETS.to_vec(),
+        }
+    }
+}
+
+pub struct Day20 {
+    input: String,
+    options: Options,
+}
+
+impl Day20 {
+    pub fn new(input: &str, options: &Options) -> Result<Self, Error> {
+        Ok(Self {
+            input: input.to_string(),
+            options: options.clone(),
+        })
+    }
+
+    fn grove_coordinates(&self, key: isize, rounds: usize) -> Result<Answer, Error> {
+        let coordinates = decrypt(&self.input, key, rounds, &self.options.offsets)?;
+        debug!(key, offsets = ?self.options.offsets, values = ?coordinates.values);
+        Ok(coordinates.sum().into())
+    }
+}
+
+impl Solution for Day20 {
+    const DAY: u8 = 20;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        self.grove_coordinates(1, 1)
+    }
+
+    fn part_2(&self) -> Result<Answer, Error> {
+        self.grove_coordinates(self.options.key, self.options.rounds)
+    }
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day20::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXPECTED: &[[isize; 7]] = &[
+        // Initial arrangement:
+        [1, 2, -3, 3, -2, 0, 4],
+        // 1 moves between 2 and -3:
+        [2, 1, -3, 3, -2, 0, 4],
+        // 2 moves between -3 and 3:
+        [1, -3, 2, 3, -2, 0, 4],
+        // -3 moves between -2 and 0:
+        [1, 2, 3, -2, -3, 0, 4],
+        // 3 moves between 0 and 4:
+        [1, 2, -2, -3, 0, 3, 4],
+        // -2 moves between 4 and 1:
+        [1, 2, -3, 0, 3, 4, -2],
+        // 0 does not move:
+        [1, 2, -3, 0, 3, 4, -2],
+        // 4 moves between -3 and 0:
+        [1, 2, -3, 4, 0, 3, -2],
+    ];
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse(SAMPLE, 1).unwrap(), [1, 2, -3, 3, -2, 0, 4]);
+        assert_eq!(parse(SAMPLE, 10).unwrap()[2], -30);
+        let error = parse("1\n2\nthree\n", 1).unwrap_err();
+        assert!(format!("{error:#}").starts_with("line 3"));
+    }
+
+    fn rotated_to_start(mut values: Vec<isize>, first: isize) -> Vec<isize> {
+        let start = values.iter().position(|v| *v == first).unwrap();
+        values.rotate_left(start);
+        values
+    }
+
+    #[test]
+    fn test_mix_steps() {
+        let values = parse(SAMPLE, 1).unwrap();
+        let mut list = CircularList::new(values.iter().copied());
+        for (id, expected) in EXPECTED[1..].iter().enumerate() {
+            list.rotate_element(id, values[id]);
+            let mixed = list.iter().copied().collect();
+            let expected = rotated_to_start(expected.to_vec(), 1);
+            assert_eq!(rotated_to_start(mixed, 1), expected, "after moving {id}");
+        }
+    }
+
+    #[test]
+    fn test_mix() {
+        let values = parse(SAMPLE, 1).unwrap();
+        let mut list = CircularList::new(values.iter().copied());
+        mix(&mut list, &values);
+        let expected = EXPECTED[EXPECTED.len() - 1].to_vec();
+        assert_eq!(
+            rotated_to_start(list.iter().copied().collect(), 1),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_part_1() {
+        let coordinates = decrypt(SAMPLE, 1, 1, &OFFSETS).unwrap();
+        assert_eq!(coordinates.values, [4, -3, 2]);
+        assert_eq!(coordinates.sum(), 3);
+        assert!(decrypt("1\n2\n", 1, 1, &OFFSETS).is_err());
+    }
+
+    #[test]
+    fn test_part_2() {
+        let coordinates = decrypt(SAMPLE, 811589153, 10, &OFFSETS).unwrap();
+        assert_eq!(coordinates.values, [811589153, 2434767459, -1623178306]);
+        assert_eq!(coordinates.sum(), 1623178306);
+    }
+
+    #[test]
+    fn test_offsets() {
+        // After one mix the sample is 1, 2, -3, 4, 0, 3, -2 from the 1.
+        let coordinates = decrypt(SAMPLE, 1, 1, &[0, 1, 2, 3, -1, 7, -8]).unwrap();
+        assert_eq!(coordinates.values, [0, 3, -2, 1, 4, 0, 4]);
+        assert_eq!(coordinates.sum(), 10);
+        assert_eq!(decrypt(SAMPLE, 1, 1, &[]).unwrap().sum(), 0);
+
+        let options = Options {
+            offsets: vec![1, 2],
+            ..Options::default()
+        };
+        let day = Day20::new(SAMPLE, &options).unwrap();
+        assert_eq!(day.part_1().unwrap(), Answer::Number(1));
+        // Without any mixing, 4 and 1 follow the zero.
+        assert_eq!(day.grove_coordinates(1, 0).unwrap(), Answer::Number(5));
+    }
+}
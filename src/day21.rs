@@ -0,0 +1,735 @@
+//! Day 21: Monkey Math.
+
+use crate::{
+    parse::parse_lines,
+    solution::{self, Answer, Answers, Solution},
+};
+use anyhow::{anyhow, bail, Context, Error};
+use petgraph::{
+    algo::{tarjan_scc, toposort},
+    graphmap::DiGraphMap,
+};
+use std::{collections::HashMap, fmt, str::FromStr};
+use tracing::debug;
+
+pub const DATA: &str = include_str!("../data/day21.txt");
+pub const SAMPLE: &str = include_str!("../data/day21_sample.txt");
+
+const ROOT: &str = "root";
+const HUMAN: &str = "humn";
+
+type Number = i64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    /// The remainder of dividing, with the sign of the left hand side.
+    Mod,
+    Pow,
+}
+
+impl Op {
+    fn apply(&self, lhs: Number, rhs: Number) -> Result<Number, Error> {
+        let result = match self {
+            Self::Add => lhs.checked_add(rhs),
+            Self::Sub => lhs.checked_sub(rhs),
+            Self::Mul => lhs.checked_mul(rhs),
+            Self::Div => {
+                if rhs == 0 || lhs.checked_rem(rhs).is_some_and(|rem| rem != 0) {
+                    bail!("{lhs} / {rhs} isn't a whole number");
+                }
+                lhs.checked_div(rhs)
+            }
+            Self::Mod => {
+                if rhs == 0 {
+                    bail!("{lhs} % 0 divides by zero");
+                }
+                lhs.checked_rem(rhs)
+            }
+            Self::Pow => {
+                let Ok(exponent) = u32::try_from(rhs) else {
+                    bail!("{lhs} ^ {rhs} isn't a whole number");
+                };
+                lhs.checked_pow(exponent)
+            }
+        };
+        result.ok_or_else(|| anyhow!("{lhs} {self} {rhs} overflows"))
+    }
+}
+
+impl FromStr for Op {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "+" => Self::Add,
+            "-" => Self::Sub,
+            "*" => Self::Mul,
+            "/" => Self::Div,
+            "%" => Self::Mod,
+            "^" => Self::Pow,
+            _ => bail!("unknown operator {s:?}"),
+        })
+    }
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Mod => "%",
+            Self::Pow => "^",
+        };
+        write!(f, "{symbol}")
+    }
+}
+
+/// What a monkey yells: a number, or the result of an operation on what
+/// two other monkeys yell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Job {
+    Number(Number),
+    Operation(String, Op, String),
+}
+
+/// One line of input, like `root: pppw + sjmn` or `dbpl: 5`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Monkey {
+    name: String,
+    job: Job,
+}
+
+impl FromStr for Monkey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, job) = s
+            .split_once(':')
+            .ok_or_else(|| anyhow!("expected a name and a job"))?;
+        let parts: Vec<_> = job.split_whitespace().collect();
+        let job = match parts[..] {
+            [number] => Job::Number(number.parse().context("bad number")?),
+            [lhs, op, rhs] => Job::Operation(lhs.to_string(), op.parse()?, rhs.to_string()),
+            _ => bail!("expected a number or an operation"),
+        };
+        Ok(Self {
+            name: name.trim().to_string(),
+            job,
+        })
+    }
+}
+
+/// An expression tree for a monkey's number, with `Var` standing in for
+/// the human's number when it's unknown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Const(Number),
+    Var,
+    BinOp(Box<Expr>, Op, Box<Expr>),
+}
+
+impl Expr {
+    /// The value of the expression with `Var` set to `var`.
+    fn evaluate(&self, var: Number) -> Result<Number, Error> {
+        match self {
+            Self::Const(n) => Ok(*n),
+            Self::Var => Ok(var),
+            Self::BinOp(lhs, op, rhs) => op.apply(lhs.evaluate(var)?, rhs.evaluate(var)?),
+        }
+    }
+
+    /// The value with `Var` set to `var`, using floating point division so
+    /// that it changes smoothly as `var` does.
+    fn evaluate_f64(&self, var: f64) -> f64 {
+        match self {
+            Self::Const(n) => *n as f64,
+            Self::Var => var,
+            Self::BinOp(lhs, op, rhs) => {
+                let (lhs, rhs) = (lhs.evaluate_f64(var), rhs.evaluate_f64(var));
+                match op {
+                    Op::Add => lhs + rhs,
+                    Op::Sub => lhs - rhs,
+                    Op::Mul => lhs * rhs,
+                    Op::Div => lhs / rhs,
+                    Op::Mod => lhs % rhs,
+                    Op::Pow => lhs.powf(rhs),
+                }
+            }
+        }
+    }
+
+    fn var_count(&self) -> usize {
+        match self {
+            Self::Const(_) => 0,
+            Self::Var => 1,
+            Self::BinOp(lhs, _, rhs) => lhs.var_count() + rhs.var_count(),
+        }
+    }
+
+    fn has_var(&self) -> bool {
+        self.var_count() > 0
+    }
+
+    /// Check that `invert` can solve the expression: `Var` appears once
+    /// and is never divided into something, which would make the
+    /// expression nonlinear, or taken a remainder of or raised to a power,
+    /// which can't be undone one way.
+    fn check_invertible(&self) -> Result<(), Error> {
+        match self.var_count() {
+            1 => {}
+            count => bail!("{HUMAN} appears {count} times"),
+        }
+        let mut expr = self;
+        while let Self::BinOp(lhs, op, rhs) = expr {
+            match op {
+                Op::Div if rhs.has_var() => bail!("{HUMAN} is in a divisor"),
+                Op::Mod => bail!("{HUMAN} is in a remainder"),
+                Op::Pow => bail!("{HUMAN} is in a power"),
+                _ => {}
+            }
+            expr = if lhs.has_var() { lhs } else { rhs };
+        }
+        Ok(())
+    }
+
+    /// `target / n` for undoing `self * n = target` or `n * self =
+    /// target`, which has to be a whole number for the monkeys' whole
+    /// numbers to get there.
+    fn divide_out(&self, target: Number, n: Number) -> Result<Number, Error> {
+        if n == 0 {
+            bail!("{self} * 0 = {target} can't be undone");
+        }
+        if target % n != 0 {
+            bail!(
+                "{HUMAN} can't be a whole number: {self} * {n} = {target} needs \
+                 {self} = {target} / {n}, which leaves {}",
+                target % n
+            );
+        }
+        Ok(target / n)
+    }
+
+    /// Find the value of `Var` that makes the expression equal `target` by
+    /// undoing each operation on the way down to it. Check the expression
+    /// with `check_invertible` first.
+    fn invert(&self, target: Number) -> Result<Number, Error> {
+        match self {
+            Self::Const(n) => bail!("no unknown to solve for in {n} = {target}"),
+            Self::Var => Ok(target),
+            Self::BinOp(lhs, op, rhs) => match (&**lhs, &**rhs) {
+                (unknown, Self::Const(n)) if unknown.has_var() => {
+                    // unknown op n = target
+                    let inverse = match op {
+                        Op::Add => Op::Sub.apply(target, *n),
+                        Op::Sub => Op::Add.apply(target, *n),
+                        Op::Mul => unknown.divide_out(target, *n),
+                        Op::Div if *n == 0 => bail!("{unknown} / 0 divides by zero"),
+                        Op::Div => Op::Mul.apply(target, *n),
+                        Op::Mod => bail!("{HUMAN} is in a remainder"),
+                        Op::Pow => bail!("{HUMAN} is in a power"),
+                    };
+                    unknown.invert(inverse?)
+                }
+                (Self::Const(n), unknown) if unknown.has_var() => {
+                    // n op unknown = target
+                    let inverse = match op {
+                        Op::Add => Op::Sub.apply(target, *n),
+                        Op::Sub => Op::Sub.apply(*n, target),
+                        Op::Mul => unknown.divide_out(target, *n),
+                        Op::Div => bail!("{HUMAN} is in a divisor"),
+                        Op::Mod => bail!("{HUMAN} is in a remainder"),
+                        Op::Pow => bail!("{HUMAN} is in a power"),
+                    };
+                    unknown.invert(inverse?)
+                }
+                _ => bail!("the unknown has to appear exactly once"),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Const(n) => write!(f, "{n}"),
+            Self::Var => write!(f, "x"),
+            Self::BinOp(lhs, op, rhs) => write!(f, "({lhs} {op} {rhs})"),
+        }
+    }
+}
+
+/// Every monkey's job, by name.
+#[derive(Debug, Clone)]
+struct Monkeys {
+    jobs: HashMap<String, Job>,
+}
+
+impl Monkeys {
+    fn parse(s: &str) -> Result<Self, Error> {
+        let mut jobs = HashMap::new();
+        for Monkey { name, job } in parse_lines(s)? {
+            if jobs.insert(name.clone(), job).is_some() {
+                bail!("{name} appears more than once");
+            }
+        }
+        if !jobs.contains_key(ROOT) {
+            bail!("there's no {ROOT} monkey");
+        }
+        let monkeys = Self { jobs };
+        monkeys.check_acyclic()?;
+        Ok(monkeys)
+    }
+
+    /// Make sure no monkey is waiting, however indirectly, on its own
+    /// number, naming the ones that are if some are.
+    fn check_acyclic(&self) -> Result<(), Error> {
+        let mut graph = DiGraphMap::<&str, ()>::new();
+        for (name, job) in &self.jobs {
+            graph.add_node(name);
+            if let Job::Operation(lhs, _, rhs) = job {
+                graph.add_edge(name, lhs, ());
+                graph.add_edge(name, rhs, ());
+            }
+        }
+        let Err(cycle) = toposort(&graph, None) else {
+            return Ok(());
+        };
+        let mut members = tarjan_scc(&graph)
+            .into_iter()
+            .find(|scc| scc.contains(&cycle.node_id()))
+            .expect("a cycle is strongly connected");
+        members.sort_unstable();
+        match members[..] {
+            [name] => bail!("{name} depends on its own number"),
+            _ => bail!("{} depend on each other's numbers", members.join(", ")),
+        }
+    }
+
+    fn job(&self, name: &str) -> Result<&Job, Error> {
+        self.jobs
+            .get(name)
+            .ok_or_else(|| anyhow!("no monkey called {name}"))
+    }
+
+    /// The expression for `name`'s number, with any part that doesn't
+    /// depend on the human folded down to a constant. With `unknown`, the
+    /// human's number becomes `Var` instead of what the input says.
+    fn expr(&self, name: &str, unknown: Option<&str>) -> Result<Expr, Error> {
+        if unknown == Some(name) {
+            return Ok(Expr::Var);
+        }
+        Ok(match self.job(name)? {
+            Job::Number(n) => Expr::Const(*n),
+            Job::Operation(lhs, op, rhs) => {
+                let lhs = self.expr(lhs, unknown)?;
+                let rhs = self.expr(rhs, unknown)?;
+                match (&lhs, &rhs) {
+                    (Expr::Const(lhs), Expr::Const(rhs)) => Expr::Const(
+                        op.apply(*lhs, *rhs)
+                            .with_context(|| format!("evaluating {name}"))?,
+                    ),
+                    _ => Expr::BinOp(Box::new(lhs), *op, Box::new(rhs)),
+                }
+            }
+        })
+    }
+
+    /// The two monkeys root compares.
+    fn root_operands(&self) -> Result<(&str, &str), Error> {
+        match self.job(ROOT)? {
+            Job::Operation(lhs, _, rhs) => Ok((lhs, rhs)),
+            Job::Number(_) => bail!("{ROOT} has to compare two monkeys"),
+        }
+    }
+}
+
+fn solve_part_1(monkeys: &Monkeys) -> Result<Number, Error> {
+    monkeys.expr(ROOT, None)?.evaluate(0)
+}
+
+/// The expressions for the two sides of root's comparison.
+fn root_sides(monkeys: &Monkeys) -> Result<(Expr, Expr), Error> {
+    let (lhs, rhs) = monkeys.root_operands()?;
+    Ok((
+        monkeys.expr(lhs, Some(HUMAN))?,
+        monkeys.expr(rhs, Some(HUMAN))?,
+    ))
+}
+
+/// Undo the operations on the human's side of root, one at a time.
+fn solve_by_inverting(monkeys: &Monkeys) -> Result<Number, Error> {
+    let (unknown, target) = match root_sides(monkeys)? {
+        (Expr::Const(target), unknown) | (unknown, Expr::Const(target)) => (unknown, target),
+        _ => bail!("{HUMAN} appears in both of {ROOT}'s branches, try --solver numeric"),
+    };
+    debug!(%unknown, target);
+    unknown
+        .check_invertible()
+        .context("can't invert, try --solver numeric")?;
+    unknown.invert(target)
+}
+
+/// How far either side of zero `solve_numerically` looks.
+const SEARCH_LIMIT: Number = 1 << 50;
+
+/// Binary search for where the difference between root's sides crosses
+/// zero, which works however often the human appears as long as the
+/// difference only ever rises or only ever falls. Monkeys divide exactly
+/// for the right number, so the neighbourhood of the crossing is checked
+/// with exact arithmetic.
+fn solve_numerically(monkeys: &Monkeys) -> Result<Number, Error> {
+    let (lhs, rhs) = root_sides(monkeys)?;
+    let difference = |x: Number| lhs.evaluate_f64(x as f64) - rhs.evaluate_f64(x as f64);
+    let (mut low, mut high) = (-SEARCH_LIMIT, SEARCH_LIMIT);
+    let rising = difference(low) < difference(high);
+    if (difference(low) > 0.0) == rising || (difference(high) < 0.0) == rising {
+        bail!("{ROOT}'s sides don't cross between {low} and {high}");
+    }
+    while high - low > 1 {
+        let middle = low + (high - low) / 2;
+        if (difference(middle) < 0.0) == rising {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    debug!(low, high);
+    (low - 8..=high + 8)
+        .find(|x| matches!((lhs.evaluate(*x), rhs.evaluate(*x)), (Ok(l), Ok(r)) if l == r))
+        .ok_or_else(|| anyhow!("no number near {low} makes {ROOT}'s sides match"))
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Solver {
+    #[default]
+    Invert,
+    Numeric,
+}
+
+impl Solver {
+    /// The number the human has to yell for root's two monkeys to match.
+    fn human_number(&self, monkeys: &Monkeys) -> Result<Number, Error> {
+        match self {
+            Self::Invert => solve_by_inverting(monkeys),
+            Self::Numeric => solve_numerically(monkeys),
+        }
+    }
+}
+
+impl FromStr for Solver {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "invert" => Self::Invert,
+            "numeric" => Self::Numeric,
+            _ => bail!("unknown solver {s:?}, expected invert or numeric"),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    /// How to find the human's number.
+    pub solver: Solver,
+}
+
+pub struct Day21 {
+    monkeys: Monkeys,
+    solver: Solver,
+}
+
+impl Day21 {
+    pub fn new(input: &str, options: &Options) -> Result<Self, Error> {
+        Ok(Self {
+            monkeys: Monkeys::parse(input)?,
+            solver: options.solver,
+        })
+    }
+}
+
+impl Solution for Day21 {
+    const DAY: u8 = 21;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        Ok(solve_part_1(&self.monkeys)?.into())
+    }
+
+    fn part_2(&self) -> Result<Answer, Error> {
+        Ok(self.solver.human_number(&self.monkeys)?.into())
+    }
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day21::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// A monkey's job, before the monkeys are given names.
+    #[derive(Debug, Clone)]
+    enum GeneratedJob {
+        Number(i64),
+        Operation(Box<GeneratedJob>, char, Box<GeneratedJob>),
+    }
+
+    impl GeneratedJob {
+        fn value(&self) -> i64 {
+            match self {
+                Self::Number(n) => *n,
+                Self::Operation(lhs, op, rhs) => {
+                    let (lhs, rhs) = (lhs.value(), rhs.value());
+                    match op {
+                        '+' => lhs + rhs,
+                        '-' => lhs - rhs,
+                        '*' => lhs * rhs,
+                        _ => unreachable!(),
+                    }
+                }
+            }
+        }
+
+        /// Write a line for each monkey this one listens to, then one for
+        /// itself, and return its name. Monkeys are named after their line,
+        /// except for `root`, which generated names can't clash with.
+        fn render(&self, lines: &mut Vec<String>, is_root: bool) -> String {
+            let job = match self {
+                Self::Number(n) => n.to_string(),
+                Self::Operation(lhs, op, rhs) => {
+                    let lhs = lhs.render(lines, false);
+                    let rhs = rhs.render(lines, false);
+                    format!("{lhs} {op} {rhs}")
+                }
+            };
+            let name = if is_root {
+                "root".to_string()
+            } else {
+                monkey_name(lines.len())
+            };
+            lines.push(format!("{name}: {job}"));
+            name
+        }
+    }
+
+    fn monkey_name(index: usize) -> String {
+        (0..4)
+            .map(|place| (b'a' + (index / 26usize.pow(3 - place) % 26) as u8) as char)
+            .collect()
+    }
+
+    /// Jobs small enough that no monkey's number overflows. Division is
+    /// left out since it has to come out exactly.
+    fn job_strategy() -> impl Strategy<Value = GeneratedJob> {
+        let leaf = (1..10i64).prop_map(GeneratedJob::Number);
+        leaf.prop_recursive(4, 16, 2, |inner| {
+            (
+                inner.clone(),
+                prop::sample::select(vec!['+', '-', '*']),
+                inner,
+            )
+                .prop_map(|(lhs, op, rhs)| {
+                    GeneratedJob::Operation(Box::new(lhs), op, Box::new(rhs))
+                })
+        })
+    }
+
+    /// The monkeys for `job` in a random order, with the answer at `root`.
+    fn monkeys_strategy() -> impl Strategy<Value = (String, i64)> {
+        job_strategy().prop_flat_map(|job| {
+            let mut lines = vec![];
+            job.render(&mut lines, true);
+            let value = job.value();
+            Just(lines)
+                .prop_shuffle()
+                .prop_map(move |lines| (lines.join("\n"), value))
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_generated_part_1((input, expected) in monkeys_strategy()) {
+            let monkeys = Monkeys::parse(&input).unwrap();
+            prop_assert_eq!(solve_part_1(&monkeys).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let monkeys = Monkeys::parse(SAMPLE).unwrap();
+        assert_eq!(monkeys.jobs.len(), 15);
+        assert_eq!(
+            monkeys.jobs["pppw"],
+            Job::Operation("cczh".to_string(), Op::Div, "lfqf".to_string())
+        );
+        assert_eq!(monkeys.jobs["dbpl"], Job::Number(5));
+
+        for bad in ["root: a & b", "root: a +", "root 5", "root: five"] {
+            assert!(Monkeys::parse(bad).is_err(), "{bad}");
+        }
+        let error = Monkeys::parse("root: a + b\na: 1\na: 2\n").unwrap_err();
+        assert_eq!(error.to_string(), "a appears more than once");
+        assert!(Monkeys::parse("a: 1").is_err());
+    }
+
+    #[test]
+    fn test_cycles() {
+        let error = Monkeys::parse("root: a + b\na: b * c\nb: a - c\nc: 1").unwrap_err();
+        assert_eq!(error.to_string(), "a, b depend on each other's numbers");
+        let error = Monkeys::parse("root: a + b\na: 4\nb: c / a\nc: d - b\nd: c * a").unwrap_err();
+        assert_eq!(error.to_string(), "b, c, d depend on each other's numbers");
+        let error = Monkeys::parse("root: a + b\na: a * b\nb: 2").unwrap_err();
+        assert_eq!(error.to_string(), "a depends on its own number");
+        // Two monkeys waiting on the same one isn't a cycle.
+        let diamond = Monkeys::parse("root: a + b\na: c * d\nb: c - d\nc: 5\nd: 2").unwrap();
+        assert_eq!(solve_part_1(&diamond).unwrap(), 13);
+    }
+
+    #[test]
+    fn test_expr() {
+        let monkeys = Monkeys::parse(SAMPLE).unwrap();
+        let (lhs, rhs) = monkeys.root_operands().unwrap();
+        let lhs = monkeys.expr(lhs, Some(HUMAN)).unwrap();
+        assert_eq!(lhs.to_string(), "((4 + (2 * (x - 3))) / 4)");
+        assert_eq!(lhs.evaluate(301).unwrap(), 150);
+        assert_eq!(monkeys.expr(rhs, Some(HUMAN)).unwrap(), Expr::Const(150));
+
+        let missing = Monkeys::parse("root: a + b\na: 1").unwrap();
+        assert!(solve_part_1(&missing).is_err());
+    }
+
+    #[test]
+    fn test_invert() {
+        // 10 - (x / 4) * 3 = 4
+        let expr = Expr::BinOp(
+            Box::new(Expr::Const(10)),
+            Op::Sub,
+            Box::new(Expr::BinOp(
+                Box::new(Expr::BinOp(
+                    Box::new(Expr::Var),
+                    Op::Div,
+                    Box::new(Expr::Const(4)),
+                )),
+                Op::Mul,
+                Box::new(Expr::Const(3)),
+            )),
+        );
+        assert!(expr.check_invertible().is_ok());
+        assert_eq!(expr.invert(4).unwrap(), 8);
+        assert_eq!(expr.evaluate(8).unwrap(), 4);
+        assert!(expr.invert(5).is_err());
+        assert!(Expr::Const(1).invert(1).is_err());
+
+        let squared = Expr::BinOp(Box::new(Expr::Var), Op::Mul, Box::new(Expr::Var));
+        let error = squared.check_invertible().unwrap_err();
+        assert_eq!(error.to_string(), "humn appears 2 times");
+        let reciprocal = Expr::BinOp(Box::new(Expr::Const(1)), Op::Div, Box::new(Expr::Var));
+        let error = reciprocal.check_invertible().unwrap_err();
+        assert_eq!(error.to_string(), "humn is in a divisor");
+    }
+
+    #[test]
+    fn test_operators() {
+        assert_eq!(Op::Div.apply(-6, 3).unwrap(), -2);
+        for (lhs, rhs) in [(7, 2), (6, -4), (-7, 2), (5, 0), (0, 0)] {
+            assert!(Op::Div.apply(lhs, rhs).is_err(), "{lhs} / {rhs}");
+        }
+        assert_eq!(Op::Div.apply(0, -5).unwrap(), 0);
+        assert_eq!(Op::Div.apply(Number::MIN, 1).unwrap(), Number::MIN);
+        assert!(Op::Div.apply(Number::MIN, -1).is_err());
+
+        assert_eq!(Op::Mod.apply(7, 3).unwrap(), 1);
+        assert_eq!(Op::Mod.apply(-7, 3).unwrap(), -1);
+        assert_eq!(Op::Mod.apply(7, -3).unwrap(), 1);
+        assert_eq!(Op::Mod.apply(6, 3).unwrap(), 0);
+        assert!(Op::Mod.apply(7, 0).is_err());
+
+        assert_eq!(Op::Pow.apply(2, 10).unwrap(), 1024);
+        assert_eq!(Op::Pow.apply(-3, 3).unwrap(), -27);
+        assert_eq!(Op::Pow.apply(5, 0).unwrap(), 1);
+        assert!(Op::Pow.apply(2, -1).is_err());
+        assert!(Op::Pow.apply(10, 30).is_err());
+
+        let monkeys = Monkeys::parse("root: a % b\na: c ^ d\nb: 5\nc: 3\nd: 4").unwrap();
+        assert_eq!(solve_part_1(&monkeys).unwrap(), 81 % 5);
+        assert_eq!(monkeys.expr("a", None).unwrap().to_string(), "81");
+    }
+
+    #[test]
+    fn test_inexact_division() {
+        // (humn - 3) * 4 = 10 has no whole number answer.
+        let input = "root: a + b\na: c * d\nc: humn - e\nd: 4\ne: 3\nb: 10\nhumn: 1";
+        let monkeys = Monkeys::parse(input).unwrap();
+        let error = Solver::Invert.human_number(&monkeys).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "humn can't be a whole number: (x - 3) * 4 = 10 needs (x - 3) = 10 / 4, which leaves 2"
+        );
+        let monkeys = Monkeys::parse(&input.replace("b: 10", "b: 12")).unwrap();
+        assert_eq!(Solver::Invert.human_number(&monkeys).unwrap(), 6);
+        let monkeys = Monkeys::parse(&input.replace("b: 10", "b: -12")).unwrap();
+        assert_eq!(Solver::Invert.human_number(&monkeys).unwrap(), 0);
+
+        // humn / 4 = 5 undoes exactly, and humn / 0 can't be undone.
+        let input = "root: a + b\na: humn / d\nd: 4\nb: 5\nhumn: 1";
+        let monkeys = Monkeys::parse(input).unwrap();
+        assert_eq!(Solver::Invert.human_number(&monkeys).unwrap(), 20);
+        let monkeys = Monkeys::parse(&input.replace("d: 4", "d: 0")).unwrap();
+        assert!(Solver::Invert.human_number(&monkeys).is_err());
+
+        // 3 * humn = 0 is fine, but 0 * humn = 3 isn't.
+        let input = "root: a + b\na: d * humn\nd: 3\nb: 0\nhumn: 1";
+        let monkeys = Monkeys::parse(input).unwrap();
+        assert_eq!(Solver::Invert.human_number(&monkeys).unwrap(), 0);
+        let monkeys = Monkeys::parse(&input.replace("d: 3", "d: 0").replace("b: 0", "b: 3"));
+        let error = Solver::Invert.human_number(&monkeys.unwrap()).unwrap_err();
+        assert_eq!(error.to_string(), "x * 0 = 3 can't be undone");
+
+        let input = "root: a + b\na: humn % d\nd: 4\nb: 1\nhumn: 1";
+        let error = Solver::Invert
+            .human_number(&Monkeys::parse(input).unwrap())
+            .unwrap_err();
+        assert!(format!("{error:#}").ends_with("humn is in a remainder"));
+    }
+
+    #[test]
+    fn test_solvers() {
+        let monkeys = Monkeys::parse(SAMPLE).unwrap();
+        assert_eq!(Solver::Numeric.human_number(&monkeys).unwrap(), 301);
+
+        // humn * 3 = humn + 10 has humn on both sides.
+        let both_sides = "root: a + b\na: humn * c\nb: humn + d\nc: 3\nd: 10\nhumn: 1";
+        let both_sides = Monkeys::parse(both_sides).unwrap();
+        assert!(Solver::Invert.human_number(&both_sides).is_err());
+        assert_eq!(Solver::Numeric.human_number(&both_sides).unwrap(), 5);
+
+        // humn * humn + humn = 30 has two answers, and the difference falls
+        // then rises, so neither solver can handle it.
+        let squared = "root: a + b\na: c + humn\nc: humn * humn\nb: 30\nhumn: 1";
+        let squared = Monkeys::parse(squared).unwrap();
+        let error = Solver::Invert.human_number(&squared).unwrap_err();
+        assert!(format!("{error:#}").ends_with("humn appears 3 times"));
+        assert!(Solver::Numeric.human_number(&squared).is_err());
+    }
+
+    #[test]
+    fn test_part_1() {
+        let monkeys = Monkeys::parse(SAMPLE).unwrap();
+        assert_eq!(solve_part_1(&monkeys).unwrap(), 152);
+    }
+
+    #[test]
+    fn test_part_2() {
+        let monkeys = Monkeys::parse(SAMPLE).unwrap();
+        assert_eq!(Solver::Invert.human_number(&monkeys).unwrap(), 301);
+    }
+}
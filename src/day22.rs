@@ -0,0 +1,793 @@
+//! Day 22: Monkey Map.
+
+use crate::{
+    direction::Dir4,
+    solution::{self, Answer, Answers, Solution},
+};
+use anyhow::{anyhow, bail, ensure, Context, Error};
+use euclid::{point2, vec2, vec3};
+use nom::{
+    branch::alt,
+    character::complete::{char, multispace0, u64},
+    combinator::{all_consuming, map, value},
+    error::{convert_error, VerboseError},
+    multi::many0,
+    sequence::delimited,
+    Finish, IResult,
+};
+use std::{collections::HashMap, str::FromStr};
+use tracing::trace;
+
+pub type Point = euclid::default::Point2D<isize>;
+type Vector = euclid::default::Vector2D<isize>;
+
+pub const DATA: &str = include_str!("../data/day22.txt");
+pub const SAMPLE: &str = include_str!("../data/day22_sample.txt");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MapCell {
+    Void,
+    Wall,
+    Open,
+}
+
+impl TryFrom<char> for MapCell {
+    type Error = Error;
+
+    fn try_from(c: char) -> Result<Self, Error> {
+        match c {
+            '.' => Ok(MapCell::Open),
+            '#' => Ok(MapCell::Wall),
+            ' ' => Ok(MapCell::Void),
+            _ => bail!("{c:?} isn't a map tile"),
+        }
+    }
+}
+
+type MapRow = Vec<MapCell>;
+
+/// The way the player last faced in each cell they've been in.
+pub type Trail = HashMap<Point, Dir4>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Player {
+    position: Point,
+    direction: Dir4,
+}
+
+impl Player {
+    fn password(&self) -> isize {
+        let one_pos = self.position + vec2(1, 1);
+        one_pos.x * 4
+            + one_pos.y * 1000
+            + match self.direction {
+                Dir4::North => 3,
+                Dir4::East => 0,
+                Dir4::South => 1,
+                Dir4::West => 2,
+            }
+    }
+}
+
+#[derive(Debug)]
+pub struct Map {
+    rows: Vec<MapRow>,
+    /// The leftmost open tile of the top row.
+    start: Point,
+}
+
+impl Map {
+    fn new(rows: Vec<MapRow>) -> Result<Self, Error> {
+        let top = rows.first().context("no map")?;
+        let x = top
+            .iter()
+            .position(|cell| *cell == MapCell::Open)
+            .context("no open tile in the top row to start on")?;
+        Ok(Self {
+            rows,
+            start: point2(x as isize, 0),
+        })
+    }
+
+    fn cell_at(&self, p: &Point) -> MapCell {
+        if p.x < 0 || p.y < 0 || p.y >= self.rows.len() as isize {
+            return MapCell::Void;
+        }
+
+        let p_u = p.to_usize();
+
+        let row = &self.rows[p_u.y];
+        if p_u.x >= row.len() {
+            return MapCell::Void;
+        }
+
+        row[p_u.x]
+    }
+
+    fn start_cell(&self) -> Point {
+        self.start
+    }
+
+    fn width(&self) -> isize {
+        self.rows.iter().map(Vec::len).max().unwrap_or_default() as isize
+    }
+
+    /// The first cell that isn't void walking from `from` in `direction`,
+    /// without wrapping.
+    fn first_non_void(&self, mut from: Point, direction: Dir4) -> Point {
+        let vec: Vector = direction.into();
+        while self.cell_at(&from) == MapCell::Void {
+            from += vec;
+        }
+        from
+    }
+
+    #[cfg(test)]
+    fn execute_step(&self, player: &Player, step: StepInstruction, rule: &dyn WrapRule) -> Player {
+        self.execute_step_with_trail(player, step, rule, &mut Trail::new())
+    }
+
+    /// Like `execute_step`, also marking every cell passed through in `trail`.
+    fn execute_step_with_trail(
+        &self,
+        player: &Player,
+        step: StepInstruction,
+        rule: &dyn WrapRule,
+        trail: &mut Trail,
+    ) -> Player {
+        let player = match step {
+            StepInstruction::TurnLeft => Player {
+                direction: player.direction.turn_left(),
+                ..*player
+            },
+            StepInstruction::TurnRight => Player {
+                direction: player.direction.turn_right(),
+                ..*player
+            },
+            StepInstruction::Go(distance) => {
+                let mut player = *player;
+                for _d in 0..distance {
+                    trail.insert(player.position, player.direction);
+                    let vec: Vector = player.direction.into();
+                    let ahead = Player {
+                        position: player.position + vec,
+                        ..player
+                    };
+                    let next = if self.cell_at(&ahead.position) == MapCell::Void {
+                        rule.wrap(self, &player)
+                    } else {
+                        Some(ahead)
+                    };
+                    match next {
+                        Some(next) if self.cell_at(&next.position) == MapCell::Open => {
+                            player = next;
+                        }
+                        _ => break,
+                    }
+                }
+                player
+            }
+        };
+        trail.insert(player.position, player.direction);
+        player
+    }
+
+    /// The board with the trail drawn over it as arrows, like the puzzle's
+    /// illustration.
+    pub fn render(&self, trail: &Trail) -> String {
+        let mut lines = vec![];
+        for (y, row) in self.rows.iter().enumerate() {
+            let line: String = row
+                .iter()
+                .enumerate()
+                .map(
+                    |(x, cell)| match (trail.get(&point2(x as isize, y as isize)), cell) {
+                        (Some(direction), _) => direction.as_char(),
+                        (None, MapCell::Void) => ' ',
+                        (None, MapCell::Wall) => '#',
+                        (None, MapCell::Open) => '.',
+                    },
+                )
+                .collect();
+            lines.push(line);
+        }
+        lines.join("\n")
+    }
+}
+
+/// Where a player ends up stepping off the edge of the map, so that the
+/// same walking code serves however the map joins up.
+pub trait WrapRule {
+    /// Where `player`, standing on the edge and facing the void, arrives, or
+    /// `None` if there's nowhere to go. The walk stops there if it's a wall.
+    fn wrap(&self, map: &Map, player: &Player) -> Option<Player>;
+}
+
+/// Part 1: come back in on the far side of the same row or column.
+struct FlatWrap;
+
+impl WrapRule for FlatWrap {
+    fn wrap(&self, map: &Map, player: &Player) -> Option<Player> {
+        let p = player.position;
+        let from = match player.direction {
+            Dir4::East => point2(0, p.y),
+            Dir4::West => point2(map.width() - 1, p.y),
+            Dir4::South => point2(p.x, 0),
+            Dir4::North => point2(p.x, map.rows.len() as isize - 1),
+        };
+        Some(Player {
+            position: map.first_non_void(from, player.direction),
+            ..*player
+        })
+    }
+}
+
+/// Treat the rectangle around the map as a torus, so leaving one side
+/// comes in at the other even where that's void, which stops the walk.
+/// On a map without holes this is the same as [`FlatWrap`].
+struct TorusWrap;
+
+impl WrapRule for TorusWrap {
+    fn wrap(&self, map: &Map, player: &Player) -> Option<Player> {
+        let vec: Vector = player.direction.into();
+        let p = player.position + vec;
+        Some(Player {
+            position: point2(
+                p.x.rem_euclid(map.width()),
+                p.y.rem_euclid(map.rows.len() as isize),
+            ),
+            ..*player
+        })
+    }
+}
+
+type Vector3 = euclid::default::Vector3D<isize>;
+
+/// One face of the cube and how it sits in space: which way it faces out
+/// and where the map's x and y axes point on it.
+#[derive(Debug, Clone, Copy)]
+struct Face {
+    /// The face's position in the map, counted in faces.
+    tile: Point,
+    normal: Vector3,
+    right: Vector3,
+    down: Vector3,
+}
+
+impl Face {
+    fn along(&self, direction: Dir4) -> Vector3 {
+        match direction {
+            Dir4::East => self.right,
+            Dir4::West => -self.right,
+            Dir4::South => self.down,
+            Dir4::North => -self.down,
+        }
+    }
+}
+
+/// Part 2: the map is the net of a cube, folded up. The faces can be laid
+/// out in any of the ways a cube unfolds.
+///
+/// Cells are placed in space using doubled coordinates, with the cube
+/// from `-size` to `size` on each axis, so that every cell's centre is at
+/// a whole number.
+struct CubeWrap {
+    size: isize,
+    faces: Vec<Face>,
+}
+
+impl CubeWrap {
+    fn new(map: &Map) -> Result<Self, Error> {
+        let cells: usize = map
+            .rows
+            .iter()
+            .map(|row| row.iter().filter(|c| **c != MapCell::Void).count())
+            .sum();
+        let size = ((cells / 6) as f64).sqrt() as isize;
+        ensure!(
+            size > 0 && (6 * size * size) as usize == cells,
+            "{cells} cells can't be folded into a cube"
+        );
+
+        let is_face = |tile: Point| map.cell_at(&(tile * size)) != MapCell::Void;
+        let first = (0..map.width())
+            .map(|x| point2(x, 0))
+            .find(|tile| is_face(*tile))
+            .context("no face in the first row")?;
+        let mut faces = vec![Face {
+            tile: first,
+            normal: vec3(0, 0, -1),
+            right: vec3(1, 0, 0),
+            down: vec3(0, 1, 0),
+        }];
+        // Folding over each edge of a face turns its axes a quarter turn
+        // towards the neighbor.
+        let mut next = 0;
+        while next < faces.len() {
+            let face = faces[next];
+            next += 1;
+            for direction in Dir4::ALL {
+                let vec: Vector = direction.into();
+                let tile = face.tile + vec;
+                if !is_face(tile) || faces.iter().any(|f| f.tile == tile) {
+                    continue;
+                }
+                let (normal, across) = (face.along(direction), -face.normal);
+                faces.push(match direction {
+                    Dir4::East => Face {
+                        tile,
+                        normal,
+                        right: across,
+                        ..face
+                    },
+                    Dir4::West => Face {
+                        tile,
+                        normal,
+                        right: -across,
+                        ..face
+                    },
+                    Dir4::South => Face {
+                        tile,
+                        normal,
+                        down: across,
+                        ..face
+                    },
+                    Dir4::North => Face {
+                        tile,
+                        normal,
+                        down: -across,
+                        ..face
+                    },
+                });
+            }
+        }
+        ensure!(faces.len() == 6, "the map has {} faces, not 6", faces.len());
+        Ok(Self { size, faces })
+    }
+
+    fn face_at(&self, p: Point) -> Option<&Face> {
+        let tile = point2(p.x.div_euclid(self.size), p.y.div_euclid(self.size));
+        self.faces.iter().find(|face| face.tile == tile)
+    }
+}
+
+impl WrapRule for CubeWrap {
+    fn wrap(&self, _map: &Map, player: &Player) -> Option<Player> {
+        let from = self.face_at(player.position)?;
+        let local = player.position - from.tile * self.size;
+        let centre = from.normal * self.size
+            + from.right * (2 * local.x + 1 - self.size)
+            + from.down * (2 * local.y + 1 - self.size);
+        // Over the edge, the travel direction becomes the new face's normal
+        // and the old face's normal points back the way we came.
+        let heading = from.along(player.direction);
+        let to = self.faces.iter().find(|face| face.normal == heading)?;
+        let arrived = centre + heading - from.normal - to.normal * self.size;
+        let x = (arrived.dot(to.right) + self.size - 1) / 2;
+        let y = (arrived.dot(to.down) + self.size - 1) / 2;
+        let direction = Dir4::ALL
+            .into_iter()
+            .find(|d| to.along(*d) == -from.normal)?;
+        Some(Player {
+            position: to.tile * self.size + vec2(x, y),
+            direction,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepInstruction {
+    Go(usize),
+    TurnLeft,
+    TurnRight,
+}
+
+pub type StepList = Vec<StepInstruction>;
+
+type ParseResult<'a, T> = IResult<&'a str, T, VerboseError<&'a str>>;
+
+fn step(input: &str) -> ParseResult<'_, StepInstruction> {
+    alt((
+        map(u64, |distance| StepInstruction::Go(distance as usize)),
+        value(StepInstruction::TurnLeft, char('L')),
+        value(StepInstruction::TurnRight, char('R')),
+    ))(input)
+}
+
+/// A path such as `10R5L5`, with any whitespace around or between the
+/// steps ignored. Turns needn't have a distance between them.
+fn parse_path(s: &str) -> Result<StepList, Error> {
+    all_consuming(many0(delimited(multispace0, step, multispace0)))(s)
+        .finish()
+        .map(|(_, steps)| steps)
+        .map_err(|e| anyhow!("failed to parse path:\n{}", convert_error(s, e)))
+}
+
+/// Merge runs of `Go` into one, which walks the same way in fewer steps.
+fn compress(path: &[StepInstruction]) -> StepList {
+    let mut compressed = StepList::with_capacity(path.len());
+    for step in path {
+        match (compressed.last_mut(), step) {
+            (Some(StepInstruction::Go(total)), StepInstruction::Go(distance)) => {
+                *total += distance;
+            }
+            _ => compressed.push(*step),
+        }
+    }
+    compressed
+}
+
+/// The [`WrapRule`]s, by name.
+#[derive(Debug, Clone, Copy)]
+pub enum Wrap {
+    Flat,
+    Cube,
+    Torus,
+}
+
+impl Wrap {
+    pub fn rule(self, map: &Map) -> Result<Box<dyn WrapRule>, Error> {
+        Ok(match self {
+            Self::Flat => Box::new(FlatWrap),
+            Self::Cube => Box::new(CubeWrap::new(map)?),
+            Self::Torus => Box::new(TorusWrap),
+        })
+    }
+}
+
+impl FromStr for Wrap {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "flat" => Self::Flat,
+            "cube" => Self::Cube,
+            "torus" => Self::Torus,
+            _ => bail!("unknown wrap {s:?}, expected flat, cube or torus"),
+        })
+    }
+}
+
+/// The map, then a blank line, then the path.
+pub fn parse(s: &str) -> Result<(Map, StepList), Error> {
+    let mut lines = s.lines();
+    let rows = lines
+        .by_ref()
+        .take_while(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            line.chars()
+                .map(MapCell::try_from)
+                .collect::<Result<MapRow, Error>>()
+                .with_context(|| format!("line {}: {line:?}", i + 1))
+        })
+        .collect::<Result<_, Error>>()?;
+    let path_text: String = lines.collect();
+    let path = parse_path(&path_text).context("path")?;
+
+    Ok((Map::new(rows)?, compress(&path)))
+}
+
+/// Follow `path` from the start, wrapping by `rule` and marking the cells
+/// walked through in `trail`.
+pub fn walk(map: &Map, path: &StepList, rule: &dyn WrapRule, trail: &mut Trail) -> Player {
+    let mut player = Player {
+        position: map.start_cell(),
+        direction: Dir4::East,
+    };
+    for step in path.iter() {
+        player = map.execute_step_with_trail(&player, *step, rule, trail);
+        trace!(?step, ?player, "after execute_step");
+    }
+    player
+}
+
+/// The password for where `path` ends up, whichever way the map wraps.
+fn final_password(map: &Map, path: &StepList, rule: &dyn WrapRule) -> isize {
+    walk(map, path, rule, &mut Trail::new()).password()
+}
+
+fn solve_part_1(map: &Map, path: &StepList) -> isize {
+    final_password(map, path, &FlatWrap)
+}
+
+fn solve_part_2(map: &Map, path: &StepList) -> Result<isize, Error> {
+    Ok(final_password(map, path, &CubeWrap::new(map)?))
+}
+
+/// Day 22 has no options.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options;
+
+pub struct Day22 {
+    map: Map,
+    path: StepList,
+}
+
+impl Day22 {
+    pub fn new(input: &str, _options: &Options) -> Result<Self, Error> {
+        let (map, path) = parse(input)?;
+        Ok(Self { map, path })
+    }
+}
+
+impl Solution for Day22 {
+    const DAY: u8 = 22;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        Ok(solve_part_1(&self.map, &self.path).into())
+    }
+
+    fn part_2(&self) -> Result<Answer, Error> {
+        Ok(solve_part_2(&self.map, &self.path)?.into())
+    }
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day22::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let (map, path) = parse(SAMPLE).unwrap();
+        assert_eq!(map.rows.len(), 12);
+        assert_eq!(path.len(), 13);
+
+        assert_eq!(map.cell_at(&point2(0, 0)), MapCell::Void);
+        assert_eq!(map.cell_at(&point2(9, 0)), MapCell::Open);
+        assert_eq!(map.cell_at(&point2(11, 0)), MapCell::Wall);
+
+        assert_eq!(map.start_cell(), point2(8, 0));
+
+        let error = parse("  ..\n  .x\n\n1").unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "line 2: \"  .x\": 'x' isn't a map tile"
+        );
+        let error = parse("  ##\n  ..\n\n1").unwrap_err();
+        assert_eq!(error.to_string(), "no open tile in the top row to start on");
+        assert!(matches!(
+            solve("", &Options),
+            Err(crate::Error::Parse { day: 22, .. })
+        ));
+        assert!(matches!(
+            solve("hello world", &Options),
+            Err(crate::Error::Parse { day: 22, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_path() {
+        use StepInstruction::*;
+        assert_eq!(
+            parse_path("10R5L5R10L4R5L5").unwrap(),
+            [
+                Go(10),
+                TurnRight,
+                Go(5),
+                TurnLeft,
+                Go(5),
+                TurnRight,
+                Go(10),
+                TurnLeft,
+                Go(4),
+                TurnRight,
+                Go(5),
+                TurnLeft,
+                Go(5)
+            ]
+        );
+        assert_eq!(
+            parse_path("3LL2\n").unwrap(),
+            [Go(3), TurnLeft, TurnLeft, Go(2)]
+        );
+        assert_eq!(parse_path(" R 7\r\n").unwrap(), [TurnRight, Go(7)]);
+        assert_eq!(parse_path("").unwrap(), []);
+        assert!(parse_path("10X5").is_err());
+
+        // A trailing newline after the path used to stop it parsing.
+        let (_, path) = parse(&format!("{SAMPLE}\n")).unwrap();
+        assert_eq!(path.len(), 13);
+    }
+
+    #[test]
+    fn test_compress() {
+        use StepInstruction::*;
+        assert_eq!(
+            compress(&[
+                Go(1),
+                Go(2),
+                TurnLeft,
+                Go(3),
+                TurnRight,
+                TurnRight,
+                Go(4),
+                Go(0)
+            ]),
+            [Go(3), TurnLeft, Go(3), TurnRight, TurnRight, Go(4)]
+        );
+        assert_eq!(compress(&parse_path("1 2 3").unwrap()), [Go(6)]);
+    }
+
+    #[test]
+    fn test_part_1() {
+        let (map, path) = parse(SAMPLE).unwrap();
+        let player = Player {
+            position: map.start_cell(),
+            direction: Dir4::East,
+        };
+        let new_player = map.execute_step(&player, path[0], &FlatWrap);
+        assert_eq!(point2(10, 0), new_player.position);
+        assert_eq!(Dir4::East, new_player.direction);
+
+        let new_player = map.execute_step(&new_player, path[1], &FlatWrap);
+        assert_eq!(point2(10, 0), new_player.position);
+        assert_eq!(Dir4::South, new_player.direction);
+
+        let new_player = map.execute_step(&new_player, path[2], &FlatWrap);
+        assert_eq!(point2(10, 5), new_player.position);
+        assert_eq!(Dir4::South, new_player.direction);
+
+        let new_player = map.execute_step(&new_player, path[3], &FlatWrap);
+        assert_eq!(point2(10, 5), new_player.position);
+        assert_eq!(Dir4::East, new_player.direction);
+
+        let new_player = map.execute_step(&new_player, path[4], &FlatWrap);
+        assert_eq!(point2(3, 5), new_player.position);
+        assert_eq!(Dir4::East, new_player.direction);
+
+        let new_player = map.execute_step(&new_player, path[5], &FlatWrap);
+        assert_eq!(point2(3, 5), new_player.position);
+        assert_eq!(Dir4::South, new_player.direction);
+
+        let new_player = map.execute_step(&new_player, path[6], &FlatWrap);
+        assert_eq!(point2(3, 7), new_player.position);
+        assert_eq!(Dir4::South, new_player.direction);
+
+        let new_player = map.execute_step(&new_player, path[7], &FlatWrap);
+        assert_eq!(point2(3, 7), new_player.position);
+        assert_eq!(Dir4::East, new_player.direction);
+
+        let new_player = map.execute_step(&new_player, path[8], &FlatWrap);
+        assert_eq!(point2(7, 7), new_player.position);
+        assert_eq!(Dir4::East, new_player.direction);
+
+        let new_player = map.execute_step(&new_player, path[9], &FlatWrap);
+        assert_eq!(point2(7, 7), new_player.position);
+        assert_eq!(Dir4::South, new_player.direction);
+
+        let new_player = map.execute_step(&new_player, path[10], &FlatWrap);
+        assert_eq!(point2(7, 5), new_player.position);
+        assert_eq!(Dir4::South, new_player.direction);
+
+        let new_player = map.execute_step(&new_player, path[11], &FlatWrap);
+        assert_eq!(point2(7, 5), new_player.position);
+        assert_eq!(Dir4::East, new_player.direction);
+
+        let new_player = map.execute_step(&new_player, path[12], &FlatWrap);
+        assert_eq!(point2(7, 5), new_player.position);
+        assert_eq!(Dir4::East, new_player.direction);
+
+        let password = new_player.password();
+
+        assert_eq!(password, 6032);
+    }
+
+    #[test]
+    fn test_render() {
+        let (map, path) = parse(SAMPLE).unwrap();
+        let mut trail = Trail::new();
+        let player = walk(&map, &path, &FlatWrap, &mut trail);
+        assert_eq!(player.password(), 6032);
+        let expected = r#"        >>v#
+        .#v.
+        #.v.
+        ..v.
+...#...v..v#
+>>>v...>#.>>
+..#v...#....
+...>>>>v..#.
+        ...#....
+        .....#..
+        .#......
+        ......#."#;
+        assert_eq!(map.render(&trail), expected);
+    }
+
+    #[test]
+    fn test_part_2() {
+        let (map, path) = parse(SAMPLE).unwrap();
+        assert_eq!(solve_part_2(&map, &path).unwrap(), 5031);
+    }
+
+    /// Every way off the edge of the map, for checking wrap rules.
+    fn edges(map: &Map) -> impl Iterator<Item = Player> + '_ {
+        (0..map.rows.len() as isize)
+            .flat_map(move |y| (0..map.width()).map(move |x| point2(x, y)))
+            .filter(|p| map.cell_at(p) != MapCell::Void)
+            .flat_map(|position| {
+                Dir4::ALL.into_iter().map(move |direction| Player {
+                    position,
+                    direction,
+                })
+            })
+            .filter(|player| {
+                let vec: Vector = player.direction.into();
+                map.cell_at(&(player.position + vec)) == MapCell::Void
+            })
+    }
+
+    #[test]
+    fn test_cube_wrap() {
+        for input in [SAMPLE, DATA] {
+            let (map, _) = parse(input).unwrap();
+            let cube = CubeWrap::new(&map).unwrap();
+            for player in edges(&map) {
+                // Going back over the same edge returns to where we were.
+                let arrived = cube.wrap(&map, &player).unwrap();
+                assert_ne!(map.cell_at(&arrived.position), MapCell::Void);
+                let back = Player {
+                    direction: arrived.direction.opposite(),
+                    ..arrived
+                };
+                let returned = cube.wrap(&map, &back).unwrap();
+                assert_eq!(returned.position, player.position, "{player:?}");
+                assert_eq!(returned.direction, player.direction.opposite());
+            }
+        }
+
+        // Leaving the sample's face 4 to the east comes in at the top of
+        // face 6 heading south, as in the puzzle's example.
+        let (map, _) = parse(SAMPLE).unwrap();
+        let player = Player {
+            position: point2(11, 5),
+            direction: Dir4::East,
+        };
+        let arrived = CubeWrap::new(&map).unwrap().wrap(&map, &player).unwrap();
+        assert_eq!(arrived.position, point2(14, 8));
+        assert_eq!(arrived.direction, Dir4::South);
+
+        let (map, _) = parse("..\n..\n\n1").unwrap();
+        assert!(CubeWrap::new(&map).is_err());
+
+        // Enough cells for faces of two, but none of them starts a face.
+        let (map, _) = parse(" .\n......\n......\n......\n.....\n\n1").unwrap();
+        let error = CubeWrap::new(&map).err().unwrap();
+        assert_eq!(error.to_string(), "no face in the first row");
+    }
+
+    #[test]
+    fn test_torus_wrap() {
+        let (map, path) = parse(SAMPLE).unwrap();
+        // Going west off row 5 comes in on the east side of the rectangle,
+        // which is void there, so the player stays put, where flat wrapping
+        // would go to the end of the row.
+        let player = Player {
+            position: point2(0, 5),
+            direction: Dir4::West,
+        };
+        let step = StepInstruction::Go(1);
+        assert_eq!(map.execute_step(&player, step, &TorusWrap), player);
+        assert_eq!(
+            map.execute_step(&player, step, &FlatWrap).position,
+            point2(11, 5)
+        );
+
+        // Without holes, a torus wraps the same way the flat map does.
+        let (open, _) = parse("...\n.#.\n...\n\n1").unwrap();
+        let every_way: StepList = parse_path("4R5R7R2L1").unwrap();
+        assert_eq!(
+            walk(&open, &every_way, &TorusWrap, &mut Trail::new()),
+            walk(&open, &every_way, &FlatWrap, &mut Trail::new())
+        );
+        assert_ne!(
+            final_password(&map, &path, &TorusWrap),
+            solve_part_1(&map, &path)
+        );
+    }
+}
@@ -0,0 +1,574 @@
+//! Day 23: Unstable Diffusion.
+
+use crate::{
+    cancel::{Cancel, Cancelled},
+    direction::{Dir4, Dir8},
+    grid::{Bounds, Grid2D, Point, SparseGrid},
+    solution::{self, Answer, Answers, Solution},
+};
+use anyhow::Error;
+use euclid::{point2, vec2};
+use rayon::prelude::*;
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap},
+};
+use tracing::{debug_span, trace};
+
+type Vector = euclid::default::Vector2D<isize>;
+
+pub const DATA: &str = include_str!("../data/day23.txt");
+pub const SAMPLE: &str = include_str!("../data/day23_sample.txt");
+
+/// The order elves consider moving in on the first round. Each round
+/// starts one further along.
+const PROPOSAL_ORDER: [Dir4; 4] = [Dir4::North, Dir4::South, Dir4::West, Dir4::East];
+
+/// The bits of a neighbor mask, in the order of [`Dir8::ALL`], for the
+/// three cells on the `direction` side.
+fn adjacent_mask(direction: Dir4) -> u8 {
+    match direction {
+        Dir4::North => 0b1000_0011,
+        Dir4::South => 0b0011_1000,
+        Dir4::West => 0b1110_0000,
+        Dir4::East => 0b0000_1110,
+    }
+}
+
+/// The directions elves consider in round `time`, in order.
+fn proposal_order(time: usize) -> impl Iterator<Item = Dir4> {
+    PROPOSAL_ORDER
+        .into_iter()
+        .cycle()
+        .skip(time % PROPOSAL_ORDER.len())
+        .take(PROPOSAL_ORDER.len())
+}
+
+pub type Proposal = Option<Dir4>;
+pub type ProposalList = Vec<Proposal>;
+type LocationMap = HashMap<Point, usize>;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Elf {
+    pub(crate) position: Point,
+}
+
+impl Elf {
+    fn propose(&self, world: &World) -> Proposal {
+        let neighbors = world.neighbor_mask(self.position);
+        if neighbors == 0 {
+            return None;
+        }
+        proposal_order(world.time).find(|direction| neighbors & adjacent_mask(*direction) == 0)
+    }
+
+    /// Move as proposed unless another elf wants the same spot, returning
+    /// whether this elf moved.
+    fn apply_proposal(&mut self, proposal: Proposal, locations_map: &LocationMap) -> bool {
+        if let Some(direction) = proposal {
+            let delta: Vector = direction.into();
+            let new_position = self.position + delta;
+            if locations_map
+                .get(&new_position)
+                .copied()
+                .unwrap_or_default()
+                <= 1
+            {
+                self.position = new_position;
+                return true;
+            }
+            trace!(?new_position, "collision");
+        }
+        false
+    }
+
+    fn calculate_proposal(&self, proposal: Proposal) -> Point {
+        proposal
+            .map(|direction| {
+                let delta: Vector = direction.into();
+                self.position + delta
+            })
+            .unwrap_or(self.position)
+    }
+}
+
+impl PartialOrd for Elf {
+    fn partial_cmp(&self, o: &Elf) -> Option<Ordering> {
+        Some(self.cmp(o))
+    }
+}
+
+impl Ord for Elf {
+    fn cmp(&self, o: &Elf) -> Ordering {
+        let x_ord = self.position.x.cmp(&o.position.x);
+        match x_ord {
+            Ordering::Equal => self.position.y.cmp(&o.position.y),
+            _ => x_ord,
+        }
+    }
+}
+
+pub fn direction_list(time: usize) -> String {
+    proposal_order(time).map(|d| d.as_char()).collect()
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct World {
+    pub elves: Vec<Elf>,
+    /// Where the elves are, for looking up neighbors without a scan.
+    occupied: SparseGrid<()>,
+    pub time: usize,
+    /// How many elves moved in each round so far.
+    moves: Vec<usize>,
+    /// Work out and apply proposals across threads
+    pub parallel: bool,
+    /// Look for neighbors by checking every elf instead of `occupied`
+    pub scan: bool,
+}
+
+/// A summary of where the elves are and how they got there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stats {
+    pub elves: usize,
+    /// Includes its max, like [`SparseGrid::bounds`].
+    pub bounding_box: Bounds,
+    pub empty_spaces: usize,
+    pub moves_per_round: Vec<usize>,
+}
+
+impl World {
+    fn new(elves: Vec<Elf>) -> Self {
+        let occupied = elves.iter().map(|e| (e.position, ())).collect();
+        Self {
+            elves,
+            occupied,
+            time: 0,
+            moves: Vec::new(),
+            parallel: false,
+            scan: false,
+        }
+    }
+
+    fn elf_at(&self, p: Point) -> bool {
+        if self.scan {
+            self.elves.iter().any(|elf| elf.position == p)
+        } else {
+            self.occupied.contains(p)
+        }
+    }
+
+    /// Which of the eight cells around `p` hold an elf, as bits in the order
+    /// of [`Dir8::ALL`].
+    fn neighbor_mask(&self, p: Point) -> u8 {
+        Dir8::ALL
+            .into_iter()
+            .enumerate()
+            .filter(|(_, d)| self.elf_at(p + Vector::from(*d)))
+            .fold(0, |mask, (index, _)| mask | 1 << index)
+    }
+
+    fn proposals(&self) -> ProposalList {
+        if self.parallel {
+            self.elves.par_iter().map(|e| e.propose(self)).collect()
+        } else {
+            self.elves.iter().map(|e| e.propose(self)).collect()
+        }
+    }
+
+    /// How many elves want to move to each location. Counting is
+    /// commutative, so merging per-thread counts gives the same map however
+    /// rayon splits the work.
+    fn proposed_locations(&self, proposals: &ProposalList) -> LocationMap {
+        if self.parallel {
+            self.elves
+                .par_iter()
+                .zip(proposals.par_iter().copied())
+                .fold(LocationMap::new, |mut locations_map, (e, p)| {
+                    *locations_map.entry(e.calculate_proposal(p)).or_default() += 1;
+                    locations_map
+                })
+                .reduce(LocationMap::new, |mut a, b| {
+                    for (p, count) in b {
+                        *a.entry(p).or_default() += count;
+                    }
+                    a
+                })
+        } else {
+            let mut locations_map: LocationMap = HashMap::new();
+            for (e, p) in self.elves.iter().zip(proposals.iter().copied()) {
+                *locations_map.entry(e.calculate_proposal(p)).or_default() += 1;
+            }
+            locations_map
+        }
+    }
+
+    fn apply_proposals(&mut self, proposals: ProposalList) {
+        let locations_map = self.proposed_locations(&proposals);
+        let moved = if self.parallel {
+            self.elves
+                .par_iter_mut()
+                .zip(proposals.par_iter().copied())
+                .map(|(e, p)| e.apply_proposal(p, &locations_map))
+                .filter(|moved| *moved)
+                .count()
+        } else {
+            self.elves
+                .iter_mut()
+                .zip(proposals.iter().copied())
+                .map(|(e, p)| e.apply_proposal(p, &locations_map))
+                .filter(|moved| *moved)
+                .count()
+        };
+        self.moves.push(moved);
+        self.occupied = self.elves.iter().map(|e| (e.position, ())).collect();
+    }
+
+    fn step(&mut self) {
+        self.time += 1;
+    }
+
+    fn empty_spaces(&self) -> usize {
+        let bbox_size = self.bounding_box().size().to_usize();
+        (bbox_size.width + 1) * (bbox_size.height + 1) - self.elves.len()
+    }
+
+    pub fn stats(&self) -> Stats {
+        Stats {
+            elves: self.elves.len(),
+            bounding_box: self.bounding_box(),
+            empty_spaces: self.empty_spaces(),
+            moves_per_round: self.moves.clone(),
+        }
+    }
+
+    fn render(&self) {
+        let empty_proposals = vec![None; self.elves.len()];
+        self.render_with_proposals(&empty_proposals);
+    }
+
+    fn render_with_proposals(&self, proposals: &ProposalList) {
+        println!(
+            "~~~ time = {:2} ~~~ {}",
+            self.time,
+            direction_list(self.time)
+        );
+        render_elves(&self.elves, proposals);
+    }
+
+    pub(crate) fn bounding_box(&self) -> Bounds {
+        self.occupied.bounds().unwrap_or_default()
+    }
+}
+
+/// The elves with a margin around them, each drawn as the direction it
+/// proposes to move or `#` if it's staying put.
+fn elf_field(elves: &[Elf], proposals: &ProposalList) -> (Point, Grid2D<char>) {
+    let bbox = Bounds::from_points(elves.iter().map(|e| e.position));
+    let origin = bbox.min - vec2(2, 2);
+    let field_size = (bbox.max - origin).to_usize() + vec2(2, 2);
+    let mut field = Grid2D::filled(field_size.x, field_size.y, '.');
+    for (elf, proposal) in elves.iter().zip(proposals.iter()) {
+        let p = (elf.position - origin).to_point();
+        field[p] = proposal.map_or('#', |d| d.as_char());
+    }
+    (origin, field)
+}
+
+/// [`elf_field`] as text, each row labelled with its y coordinate.
+pub fn field_text(elves: &[Elf], proposals: &ProposalList) -> String {
+    let (origin, field) = elf_field(elves, proposals);
+    (origin.y..)
+        .zip(field.rows())
+        .map(|(y, row)| format!("{y:04}{}\n", row.iter().collect::<String>()))
+        .collect()
+}
+
+fn render_elves(elves: &[Elf], proposals: &ProposalList) {
+    print!("{}", field_text(elves, proposals));
+}
+
+/// Run rounds until no elf wants to move, like [`solve_part_2`], showing
+/// `frame` the world and its proposals before each round and once more
+/// at the end.
+pub fn watch(
+    world: &mut World,
+    mut frame: impl FnMut(&World, &ProposalList) -> Result<(), Error>,
+) -> Result<usize, Error> {
+    loop {
+        let proposals = world.proposals();
+        frame(world, &proposals)?;
+        if proposals.iter().all(Option::is_none) {
+            return Ok(world.time + 1);
+        }
+        world.apply_proposals(proposals);
+        world.step();
+    }
+}
+
+fn maybe_elf(x: isize, y: isize, c: char) -> Option<Elf> {
+    (c == '#').then_some(Elf {
+        position: point2(x, y),
+    })
+}
+
+fn handle_line((y, line): (isize, &str), delta_x: isize) -> Vec<Elf> {
+    line.chars()
+        .enumerate()
+        .filter_map(|(x, c)| maybe_elf(x as isize - delta_x, y, c))
+        .collect()
+}
+
+pub fn parse(s: &str) -> World {
+    let elves: Vec<Elf> = s
+        .lines()
+        .enumerate()
+        .flat_map(|(y, s)| handle_line((y as isize, s), 0))
+        .collect();
+    World::new(elves)
+}
+
+fn solve_part_1(world: &mut World, expected: Option<&Vec<Vec<Elf>>>, print: bool) -> usize {
+    let empty_proposals = vec![None; world.elves.len()];
+    for i in 0..10 {
+        let time = i + 1;
+        if print {
+            println!("~~~ Before Round {time}");
+            world.render();
+        }
+        let proposals = world.proposals();
+        if print {
+            world.render_with_proposals(&proposals);
+        }
+        if proposals.iter().any(Option::is_some) {
+            world.apply_proposals(proposals);
+        } else {
+            break;
+        }
+        world.step();
+        if print {
+            println!("~~~ After Round {time}");
+            world.render();
+        }
+        if let Some(expected) = expected.as_ref() {
+            if expected.len() > i {
+                let e_set: BTreeSet<_> = expected[i].iter().collect();
+                let w_set: BTreeSet<_> = world.elves.iter().collect();
+                println!("~~~ expected");
+                render_elves(&expected[i], &empty_proposals);
+                itertools::assert_equal(e_set.iter(), w_set.iter());
+            }
+        }
+    }
+    world.empty_spaces()
+}
+
+pub fn solve_part_2(world: &mut World, cancel: &Cancel) -> Result<usize, Cancelled> {
+    loop {
+        cancel.check()?;
+        let _span = debug_span!("round", time = world.time).entered();
+        let proposals = world.proposals();
+        if proposals.iter().any(Option::is_some) {
+            world.apply_proposals(proposals);
+        } else {
+            return Ok(world.time + 1);
+        }
+        world.step();
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    /// Spread each round's work across threads.
+    pub parallel: bool,
+    /// Find each elf's neighbors by checking every other elf, as this day
+    /// first did, which is quadratic in the elves. Only worth it to see how
+    /// much the lookup saves.
+    pub scan: bool,
+}
+
+pub struct Day23 {
+    world: World,
+}
+
+impl Day23 {
+    pub fn new(input: &str, options: &Options) -> Result<Self, Error> {
+        let mut world = parse(input);
+        world.parallel = options.parallel;
+        world.scan = options.scan;
+        Ok(Self { world })
+    }
+}
+
+impl Solution for Day23 {
+    const DAY: u8 = 23;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        Ok(solve_part_1(&mut self.world.clone(), None, false).into())
+    }
+
+    fn part_2(&self) -> Result<Answer, Error> {
+        self.solve_part(2, &Cancel::default())
+    }
+
+    fn solve_part(&self, part: u8, cancel: &Cancel) -> Result<Answer, Error> {
+        match part {
+            1 => self.part_1(),
+            _ => Ok(solve_part_2(&mut self.world.clone(), cancel)?.into()),
+        }
+    }
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day23::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXPECTED_5: &str = include_str!("../data/day23_ex.txt");
+    const EXPECTED_10: &str = r#"xxx
+	.......#......
+	...........#..
+	..#.#..#......
+	......#.......
+	...#.....#..#.
+	.#......##....
+	.....##.......
+	..#........#..
+	....#.#..#....
+	..............
+	....#..#..#...
+	..............
+
+	"#;
+
+    fn parse_expected(s: &str) -> Vec<Vec<Elf>> {
+        let mut exp = vec![];
+        for chunk in s.split("\n\n") {
+            let mut elves = vec![];
+            for (y, line) in chunk.lines().enumerate().skip(1) {
+                elves.extend(handle_line((y as isize - 3, line), 3));
+            }
+            exp.push(elves);
+        }
+        exp
+    }
+
+    #[test]
+    fn test_parse() {
+        let world = parse(SAMPLE);
+        assert_eq!(world.time, 0);
+        assert_eq!(world.elves.len(), 22);
+        assert_eq!(world.elves[0].position, point2(4, 0));
+
+        let expected = parse_expected(EXPECTED_5);
+        dbg!(&expected);
+        assert_eq!(expected.len(), 5);
+    }
+
+    #[test]
+    fn test_neighbor_mask() {
+        let world = parse(SAMPLE);
+        // The elf at (4, 0) only has (4, 1) to the south and (3, 1) to the
+        // south west.
+        assert_eq!(world.neighbor_mask(point2(4, 0)), 0b0011_0000);
+        assert_eq!(world.elves[0].propose(&world), Some(Dir4::North));
+        assert!(world.elf_at(point2(2, 1)));
+        assert!(!world.elf_at(point2(1, 1)));
+    }
+
+    #[test]
+    fn test_elf_field() {
+        let world = parse(SAMPLE);
+        let (origin, field) = elf_field(&world.elves, &world.proposals());
+        assert_eq!(origin, point2(-2, -2));
+        assert_eq!((field.width(), field.height()), (10, 10));
+        assert_eq!(field[point2(6, 2)], '^');
+        assert_eq!(field.iter().filter(|(_, c)| **c != '.').count(), 22);
+    }
+
+    #[test]
+    fn test_watch() {
+        let mut world = parse(SAMPLE);
+        let mut frames = vec![];
+        let rounds = watch(&mut world, |world, proposals| {
+            frames.push(field_text(&world.elves, proposals));
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(rounds, 20);
+        assert_eq!(frames.len(), 20);
+        assert!(frames[0].starts_with("-002..........\n"));
+        assert!(!frames[19].contains(['^', 'v', '<', '>']));
+    }
+
+    #[test]
+    fn test_part_1() {
+        let mut world = parse(SAMPLE);
+        let expected = parse_expected(EXPECTED_5);
+        let empty_spaces = solve_part_1(&mut world, Some(&expected), true);
+        println!("### final expected\n{EXPECTED_10}");
+        assert_eq!(empty_spaces, 110);
+    }
+
+    #[test]
+    fn test_part_2() {
+        let mut world = parse(SAMPLE);
+        assert_eq!(solve_part_2(&mut world, &Cancel::default()), Ok(20));
+
+        let cancel = Cancel::default();
+        cancel.cancel();
+        assert!(solve_part_2(&mut parse(SAMPLE), &cancel).is_err());
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut world = parse(SAMPLE);
+        solve_part_1(&mut world, None, false);
+        let stats = world.stats();
+        assert_eq!(stats.elves, 22);
+        assert_eq!(stats.empty_spaces, 110);
+        assert_eq!(stats.moves_per_round.len(), 10);
+        assert_eq!(stats.moves_per_round[0], 11);
+
+        let rounds = solve_part_2(&mut world, &Cancel::default()).unwrap();
+        let stats = world.stats();
+        assert_eq!(stats.moves_per_round.len(), rounds - 1);
+        assert!(stats.moves_per_round.iter().all(|moved| *moved > 0));
+    }
+
+    #[test]
+    fn test_scan_matches_lookup() {
+        let options = Options {
+            scan: true,
+            ..Options::default()
+        };
+        let day = Day23::new(SAMPLE, &options).unwrap();
+        assert_eq!(day.part_1().unwrap(), Answer::from(110));
+        assert_eq!(day.part_2().unwrap(), Answer::from(20));
+    }
+
+    #[test]
+    fn test_parallel_matches_serial() {
+        let mut serial = parse(SAMPLE);
+        let mut parallel = parse(SAMPLE);
+        parallel.parallel = true;
+        for _ in 0..20 {
+            let proposals = serial.proposals();
+            assert_eq!(proposals, parallel.proposals());
+            serial.apply_proposals(proposals.clone());
+            parallel.apply_proposals(proposals);
+            serial.step();
+            parallel.step();
+            assert_eq!(serial.elves, parallel.elves);
+            assert_eq!(serial.moves, parallel.moves);
+        }
+        let cancel = Cancel::default();
+        assert_eq!(
+            solve_part_2(&mut parallel, &cancel),
+            solve_part_2(&mut serial, &cancel)
+        );
+    }
+}
@@ -0,0 +1,859 @@
+//! Day 24: Blizzard Basin.
+
+#![allow(dead_code)]
+
+use crate::{
+    direction::Dir4,
+    grid::taxicab_distance,
+    parse::parse_lines_with,
+    search::{self, GridTrace, Heuristic, Observer, Progress, TieBreak},
+    solution::{self, Answer, Answers, Solution},
+};
+use anyhow::{bail, ensure, Context, Error};
+use euclid::{point2, size2, vec2};
+use fixedbitset::FixedBitSet;
+use std::{
+    cell::Cell,
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
+use tracing::{debug, trace};
+
+pub type Coord = i64;
+pub type Point = euclid::default::Point2D<Coord>;
+type UPoint = euclid::default::Point2D<Coord>;
+type Box = euclid::default::Box2D<Coord>;
+type Vector = euclid::default::Vector2D<Coord>;
+type Rect = euclid::default::Rect<Coord>;
+
+pub const DATA: &str = include_str!("../data/day24.txt");
+pub const SAMPLE: &str = include_str!("../data/day24_sample.txt");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MapCell {
+    Blizzard(Dir4),
+    Wall,
+    Open,
+}
+
+impl TryFrom<char> for MapCell {
+    type Error = Error;
+
+    fn try_from(c: char) -> Result<Self, Error> {
+        Ok(match c {
+            '.' => MapCell::Open,
+            '#' => MapCell::Wall,
+            '^' => MapCell::Blizzard(Dir4::North),
+            '>' => MapCell::Blizzard(Dir4::East),
+            'v' => MapCell::Blizzard(Dir4::South),
+            '<' => MapCell::Blizzard(Dir4::West),
+            _ => bail!("{c:?} isn't a map cell"),
+        })
+    }
+}
+
+fn blizzards_from_row((y, cells): (usize, &MapRow)) -> Vec<Blizzard> {
+    cells
+        .iter()
+        .enumerate()
+        .map(|(x, cells)| (point2(x, y).to_i64(), cells))
+        .filter_map(Blizzard::from_cell)
+        .collect()
+}
+
+type MapRow = Vec<MapCell>;
+
+/// The open cells in row `y`, which for the top and bottom rows are the
+/// ways in and out of the valley.
+fn openings(rows: &[MapRow], y: usize) -> Vec<Point> {
+    rows[y]
+        .iter()
+        .enumerate()
+        .filter(|(_, cell)| **cell == MapCell::Open)
+        .map(|(x, _)| point2(x as Coord, y as Coord))
+        .collect()
+}
+
+#[derive(Debug)]
+pub struct Map {
+    bounds: Rect,
+    rows: Vec<MapRow>,
+    /// Gaps in the top wall, left to right.
+    entrances: Vec<Point>,
+    /// Gaps in the bottom wall, left to right.
+    exits: Vec<Point>,
+}
+
+impl Map {
+    fn new(rows: Vec<MapRow>) -> Result<Self, Error> {
+        let width = rows.first().map_or(0, Vec::len);
+        ensure!(
+            rows.len() >= 3 && width >= 3,
+            "a valley needs a wall all round it"
+        );
+        if let Some(y) = rows.iter().position(|row| row.len() != width) {
+            bail!("line {} isn't as wide as the first", y + 1);
+        }
+        let entrances = openings(&rows, 0);
+        let exits = openings(&rows, rows.len() - 1);
+        ensure!(!entrances.is_empty(), "no entrance");
+        ensure!(!exits.is_empty(), "no exit");
+        let bounds = Rect::new(point2(1, 1), size2(width - 2, rows.len() - 2).to_i64());
+        Ok(Self {
+            bounds,
+            rows,
+            entrances,
+            exits,
+        })
+    }
+
+    /// Where the puzzle's expedition starts: the first way in.
+    fn entrance(&self) -> Point {
+        self.entrances[0]
+    }
+
+    /// Where the puzzle's expedition is headed: the first way out.
+    fn exit(&self) -> Point {
+        self.exits[0]
+    }
+
+    fn cell_at(&self, p: &Point) -> MapCell {
+        if p.x < 0 || p.y < 0 {
+            return MapCell::Wall;
+        }
+
+        let p_u = p.to_usize();
+
+        if p_u.y >= self.rows.len() {
+            return MapCell::Wall;
+        }
+
+        let row = &self.rows[p_u.y];
+        if p_u.x >= row.len() {
+            return MapCell::Wall;
+        }
+
+        row[p_u.x]
+    }
+
+    /// An observer that draws where the search has been every `every`
+    /// expansions.
+    pub fn search_trace(&self, every: usize) -> GridTrace<MapState> {
+        GridTrace::new(
+            self.rows[0].len(),
+            self.rows.len(),
+            |state| (state.position.x as usize, state.position.y as usize),
+            every,
+        )
+    }
+
+    fn blizzard_starts(&self) -> Vec<Blizzard> {
+        self.rows
+            .iter()
+            .enumerate()
+            .flat_map(blizzards_from_row)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Blizzard {
+    position: Point,
+    direction: Dir4,
+}
+
+impl Blizzard {
+    fn from_cell((position, cell): (UPoint, &MapCell)) -> Option<Blizzard> {
+        match *cell {
+            MapCell::Blizzard(direction) => Some(Blizzard {
+                position,
+                direction,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Where the blizzard is a minute later. Blizzards leaving the valley
+    /// come back in on the other side, even past a gap in the wall.
+    fn new_pos(&self, map: &Map) -> Self {
+        let v: Vector = self.direction.into();
+        let position = self.position + v;
+        let position = if !map.bounds.contains(position) {
+            match self.direction {
+                Dir4::North => point2(position.x, map.bounds.max_y() - 1),
+                Dir4::South => point2(position.x, map.bounds.min_y()),
+                Dir4::East => point2(map.bounds.min_x(), position.y),
+                Dir4::West => point2(map.bounds.max_x() - 1, position.y),
+            }
+        } else {
+            position
+        };
+        Self {
+            direction: self.direction,
+            position,
+        }
+    }
+}
+
+pub fn parse(s: &str) -> Result<Map, Error> {
+    let rows = parse_lines_with(s, |line| line.chars().map(MapCell::try_from).collect())?;
+    Map::new(rows)
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn lcm(a: usize, b: usize) -> usize {
+    a / gcd(a, b) * b
+}
+
+/// The valley at one minute: walls, blizzards drawn as their direction or
+/// as a count where several share a cell, and the expedition as `E`.
+fn render_minute(map: &Map, blizzards: &[Blizzard], expedition: Point) -> String {
+    let mut cells: HashMap<Point, Vec<char>> = HashMap::new();
+    for b in blizzards {
+        cells
+            .entry(b.position)
+            .or_default()
+            .push(b.direction.as_char());
+    }
+    let mut s = String::new();
+    for y in 0..map.rows.len() as Coord {
+        for x in 0..map.rows[y as usize].len() as Coord {
+            let pt = point2(x, y);
+            let c = match cells.get(&pt).map(Vec::as_slice) {
+                // An expedition caught in a blizzard means a bad path.
+                Some(_) if pt == expedition => '?',
+                _ if pt == expedition => 'E',
+                Some([c]) => *c,
+                Some(many) => char::from_digit(many.len() as u32, 10).unwrap_or('*'),
+                None if map.cell_at(&pt) == MapCell::Wall => '#',
+                None => '.',
+            };
+            s.push(c);
+        }
+        s.push('\n');
+    }
+    s
+}
+
+/// Blizzard occupancy for every minute of the blizzard cycle, one bitset
+/// per minute indexed by cell.
+#[derive(Debug, Clone)]
+struct BlizzardCycle {
+    width: usize,
+    occupied: Vec<FixedBitSet>,
+}
+
+impl BlizzardCycle {
+    fn new(map: &Map) -> Self {
+        let width = map.rows[0].len();
+        let cell_count = width * map.rows.len();
+        let cycle = lcm(
+            map.bounds.size.width as usize,
+            map.bounds.size.height as usize,
+        );
+        let mut blizzards = map.blizzard_starts();
+        let mut occupied = Vec::with_capacity(cycle);
+        for _ in 0..cycle {
+            let mut bits = FixedBitSet::with_capacity(cell_count);
+            for b in blizzards.iter() {
+                bits.insert(b.position.y as usize * width + b.position.x as usize);
+            }
+            occupied.push(bits);
+            blizzards = blizzards.iter().map(|b| b.new_pos(map)).collect();
+        }
+        Self { width, occupied }
+    }
+
+    fn len(&self) -> usize {
+        self.occupied.len()
+    }
+
+    fn is_occupied(&self, p: &Point, time: usize) -> bool {
+        let index = p.y as usize * self.width + p.x as usize;
+        self.occupied[time % self.len()].contains(index)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MapState {
+    position: Point,
+    /// Minute within the blizzard cycle
+    time: usize,
+}
+
+impl MapState {
+    fn render(&self, map: &Map) -> String {
+        let mut blizzards = map.blizzard_starts();
+        for _ in 0..self.time {
+            blizzards = blizzards.iter().map(|b| b.new_pos(map)).collect();
+        }
+        render_minute(map, &blizzards, self.position)
+    }
+}
+
+fn successors(state: &MapState, map: &Map, blizzards: &BlizzardCycle) -> Vec<(MapState, usize)> {
+    let new_time = (state.time + 1) % blizzards.len();
+    Dir4::ALL
+        .into_iter()
+        .map(Vector::from)
+        .chain(std::iter::once(vec2(0, 0)))
+        .filter_map(|v| {
+            let new_p = state.position + v;
+            let map_cell = map.cell_at(&new_p);
+            (map_cell != MapCell::Wall && !blizzards.is_occupied(&new_p, new_time)).then_some((
+                MapState {
+                    position: new_p,
+                    time: new_time,
+                },
+                1,
+            ))
+        })
+        .collect::<Vec<_>>()
+}
+
+/// How to find the way across the valley.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algo {
+    /// A* over (position, minute of the blizzard cycle).
+    AStar {
+        heuristic: Heuristic,
+        tie_break: TieBreak,
+    },
+    /// Breadth first, a minute at a time, keeping every cell the expedition
+    /// could be in as one bitset per minute.
+    Layered,
+}
+
+impl Default for Algo {
+    fn default() -> Self {
+        Self::AStar {
+            heuristic: Heuristic::default(),
+            tie_break: TieBreak::default(),
+        }
+    }
+}
+
+impl FromStr for Algo {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "astar" => Self::default(),
+            "layered" => Self::Layered,
+            _ => bail!("unknown algorithm {s:?}, expected astar or layered"),
+        })
+    }
+}
+
+/// The states on a quickest path from `start` to `end`, leaving at
+/// `start_time` and including both ends.
+fn find_path(
+    start: Point,
+    end: Point,
+    map: &Map,
+    blizzards: &BlizzardCycle,
+    start_time: usize,
+    algo: Algo,
+    observer: &mut impl Observer<MapState, usize>,
+) -> Result<Vec<MapState>, Error> {
+    let (heuristic, tie_break) = match algo {
+        Algo::AStar {
+            heuristic,
+            tie_break,
+        } => (heuristic, tie_break),
+        Algo::Layered => {
+            return find_path_layered(start, end, map, blizzards, start_time, observer)
+        }
+    };
+    let initial_state = MapState {
+        position: start,
+        time: start_time % blizzards.len(),
+    };
+    let mut observe = |progress: &Progress<MapState, usize>| {
+        if progress.expanded.is_multiple_of(1000) {
+            let state = progress.node;
+            trace!(
+                expanded = progress.expanded,
+                time = state.time,
+                position = ?state.position,
+                distance = taxicab_distance(state.position, end)
+            );
+        }
+        observer.expand(progress);
+    };
+    let (path, _) = search::astar_with(
+        &initial_state,
+        |p| successors(p, map, blizzards),
+        |p| heuristic.estimate(p.position, end) as usize,
+        |state| state.position == end,
+        tie_break,
+        &mut observe,
+    )
+    .with_context(|| format!("no way from {start:?} to {end:?}"))?;
+
+    Ok(path)
+}
+
+/// [`find_path`] by spreading out from `start` a minute at a time. Each
+/// minute is a bitset of the cells the expedition could be in, which is
+/// cheap to step and to keep, and the path is found by walking back
+/// through them from `end`.
+fn find_path_layered(
+    start: Point,
+    end: Point,
+    map: &Map,
+    blizzards: &BlizzardCycle,
+    start_time: usize,
+    observer: &mut impl Observer<MapState, usize>,
+) -> Result<Vec<MapState>, Error> {
+    let width = blizzards.width;
+    let index = |p: Point| p.y as usize * width + p.x as usize;
+    let point = |i: usize| point2((i % width) as Coord, (i / width) as Coord);
+    let moves = || {
+        Dir4::ALL
+            .into_iter()
+            .map(Vector::from)
+            .chain(std::iter::once(vec2(0, 0)))
+    };
+    let state = |p: Point, minute: usize| MapState {
+        position: p,
+        time: (start_time + minute) % blizzards.len(),
+    };
+
+    let mut first = FixedBitSet::with_capacity(width * map.rows.len());
+    first.insert(index(start));
+    let mut layers = vec![first];
+    // The cells reachable next minute only depend on these and the minute
+    // of the cycle, so seeing both again means `end` is out of reach.
+    let mut seen = HashSet::new();
+    let mut expanded = 0;
+    while !layers.last().expect("layer").contains(index(end)) {
+        let minute = layers.len() - 1;
+        let layer = layers.last().expect("layer");
+        let cycle_minute = (start_time + minute) % blizzards.len();
+        if layer.is_clear() || !seen.insert((cycle_minute, layer.clone())) {
+            bail!("no way from {start:?} to {end:?}");
+        }
+        let time = start_time + minute + 1;
+        let frontier = layer.count_ones(..);
+        let mut next = FixedBitSet::with_capacity(layer.len());
+        for p in layer.ones().map(point) {
+            expanded += 1;
+            observer.expand(&Progress {
+                node: &state(p, minute),
+                cost: minute,
+                best: minute,
+                expanded,
+                frontier,
+            });
+            for new_p in moves().map(|v| p + v) {
+                if map.cell_at(&new_p) != MapCell::Wall && !blizzards.is_occupied(&new_p, time) {
+                    next.insert(index(new_p));
+                }
+            }
+        }
+        layers.push(next);
+    }
+
+    let mut path = vec![state(end, layers.len() - 1)];
+    for (minute, layer) in layers.iter().enumerate().rev().skip(1) {
+        let after = path.last().expect("path").position;
+        let p = moves()
+            .map(|v| after - v)
+            .find(|p| map.cell_at(p) != MapCell::Wall && layer.contains(index(*p)))
+            .expect("a cell the expedition came from");
+        path.push(state(p, minute));
+    }
+    path.reverse();
+    Ok(path)
+}
+
+/// Where the expedition is each minute of a trip visiting every waypoint
+/// of `route` in turn, leaving the first at `start_time`.
+pub fn route_positions(
+    map: &Map,
+    route: &[Point],
+    start_time: usize,
+    algo: Algo,
+    observer: &mut impl Observer<MapState, usize>,
+) -> Result<Vec<Point>, Error> {
+    let Some(first) = route.first() else {
+        bail!("a route needs at least one waypoint");
+    };
+    for waypoint in route {
+        if map.cell_at(waypoint) == MapCell::Wall {
+            bail!("waypoint {waypoint:?} is in a wall");
+        }
+    }
+    let blizzards = BlizzardCycle::new(map);
+    let mut positions = vec![*first];
+    for leg in route.windows(2) {
+        let minute = start_time + positions.len() - 1;
+        let path = find_path(leg[0], leg[1], map, &blizzards, minute, algo, observer)?;
+        debug!(from = ?leg[0], to = ?leg[1], minutes = path.len() - 1, "leg");
+        positions.extend(path[1..].iter().map(|state| state.position));
+    }
+    Ok(positions)
+}
+
+/// Minutes to visit every waypoint of `route` in turn, leaving the first
+/// at `start_time`.
+pub fn solve_route(
+    map: &Map,
+    route: &[Point],
+    start_time: usize,
+    algo: Algo,
+    observer: &mut impl Observer<MapState, usize>,
+) -> Result<usize, Error> {
+    Ok(route_positions(map, route, start_time, algo, observer)?.len() - 1)
+}
+
+fn solve_part_1(
+    map: &Map,
+    algo: Algo,
+    observer: &mut impl Observer<MapState, usize>,
+) -> Result<usize, Error> {
+    solve_route(map, &[map.entrance(), map.exit()], 0, algo, observer)
+}
+
+/// Back for the snacks and out again, after reaching the exit at
+/// `start_time`.
+fn solve_part_2(
+    map: &Map,
+    start_time: usize,
+    algo: Algo,
+    observer: &mut impl Observer<MapState, usize>,
+) -> Result<usize, Error> {
+    let route = [map.exit(), map.entrance(), map.exit()];
+    solve_route(map, &route, start_time, algo, observer)
+}
+
+/// The puzzle's route for `trips` trips back and forth across the valley.
+pub fn trips_route(map: &Map, trips: usize) -> Vec<Point> {
+    (0..=trips)
+        .map(|trip| {
+            if trip % 2 == 0 {
+                map.entrance()
+            } else {
+                map.exit()
+            }
+        })
+        .collect()
+}
+
+/// Where the expedition is each minute of `trips` trips back and forth
+/// across the valley, starting at the entrance at minute 0.
+fn journey(map: &Map, trips: usize, algo: Algo) -> Result<Vec<Point>, Error> {
+    route_positions(map, &trips_route(map, trips), 0, algo, &mut ())
+}
+
+/// One frame per minute of `positions`, with the blizzards moved along.
+pub fn replay(map: &Map, positions: &[Point]) -> Vec<String> {
+    let mut blizzards = map.blizzard_starts();
+    let mut frames = Vec::with_capacity(positions.len());
+    for (minute, position) in positions.iter().enumerate() {
+        frames.push(format!(
+            "Minute {minute}\n{}",
+            render_minute(map, &blizzards, *position)
+        ));
+        blizzards = blizzards.iter().map(|b| b.new_pos(map)).collect();
+    }
+    frames
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    /// Minutes the first trip takes, if already known.
+    pub presolved: Option<usize>,
+    /// Log the cells the search has expanded every this many steps.
+    pub show_search: Option<usize>,
+    /// How to search.
+    pub algo: Algo,
+}
+
+pub struct Day24 {
+    map: Map,
+    first_trip: Cell<Option<usize>>,
+    show_search: Option<usize>,
+    algo: Algo,
+}
+
+impl Day24 {
+    pub fn new(input: &str, options: &Options) -> Result<Self, Error> {
+        Ok(Self {
+            map: parse(input)?,
+            first_trip: Cell::new(options.presolved),
+            show_search: options.show_search,
+            algo: options.algo,
+        })
+    }
+
+    fn search_trace(&self) -> Option<GridTrace<MapState>> {
+        self.show_search.map(|every| self.map.search_trace(every))
+    }
+
+    /// Minutes to reach the goal the first time, which is where part 2
+    /// starts from.
+    fn first_trip(&self) -> Result<usize, Error> {
+        let minutes = match self.first_trip.get() {
+            Some(minutes) => minutes,
+            None => solve_part_1(&self.map, self.algo, &mut self.search_trace())?,
+        };
+        self.first_trip.set(Some(minutes));
+        Ok(minutes)
+    }
+}
+
+impl Solution for Day24 {
+    const DAY: u8 = 24;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        Ok(self.first_trip()?.into())
+    }
+
+    fn part_2(&self) -> Result<Answer, Error> {
+        let p1 = self.first_trip()?;
+        Ok((p1 + solve_part_2(&self.map, p1, self.algo, &mut self.search_trace())?).into())
+    }
+}
+
+/// A waypoint written `x,y`, counting from the top left corner of the map.
+pub fn parse_waypoint(s: &str) -> Result<Point, Error> {
+    let (x, y) = s
+        .split_once(',')
+        .with_context(|| format!("expected x,y, got {s:?}"))?;
+    let coord = |c: &str| {
+        c.trim()
+            .parse::<Coord>()
+            .with_context(|| format!("bad coordinate {c:?}"))
+    };
+    Ok(point2(coord(x)?, coord(y)?))
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day24::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let map = parse(SAMPLE).unwrap();
+        assert_eq!(map.bounds.size, size2(6, 4));
+        assert_eq!(map.bounds.origin, point2(1, 1));
+
+        let blizzards = map.blizzard_starts();
+        assert_eq!(blizzards.len(), 19);
+
+        assert_eq!(blizzards[0].position, point2(1, 1));
+        assert_eq!(blizzards[0].direction, Dir4::East);
+        assert_eq!(blizzards[1].position, point2(2, 1));
+        assert_eq!(blizzards[1].direction, Dir4::East);
+
+        let error = parse("#.###\n#.x.#\n###.#").unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "line 2: \"#.x.#\": 'x' isn't a map cell"
+        );
+        let error = parse("#.###\n#...#\n#####").unwrap_err();
+        assert_eq!(error.to_string(), "no exit");
+        let error = parse("#.###\n#..#\n###.#").unwrap_err();
+        assert_eq!(error.to_string(), "line 2 isn't as wide as the first");
+        assert!(matches!(
+            solve("", &Options::default()),
+            Err(crate::Error::Parse { day: 24, .. })
+        ));
+    }
+
+    #[test]
+    fn test_cycle() {
+        println!("sample");
+        let map = parse(SAMPLE).unwrap();
+        let blizzards = BlizzardCycle::new(&map);
+        assert_eq!(blizzards.len(), 12);
+        assert!(blizzards.is_occupied(&point2(1, 1), 0));
+        assert!(!blizzards.is_occupied(&point2(1, 1), 1));
+
+        println!("data");
+        let map = parse(DATA).unwrap();
+        let blizzards = BlizzardCycle::new(&map);
+        assert_eq!(blizzards.len(), 600);
+    }
+
+    #[test]
+    fn test_part_1() {
+        let map = parse(SAMPLE).unwrap();
+        let p1 = solve_part_1(&map, Algo::default(), &mut ()).unwrap();
+        assert_eq!(p1, 18);
+    }
+
+    #[test]
+    fn test_search_trace() {
+        let map = parse(SAMPLE).unwrap();
+        let mut trace = map.search_trace(usize::MAX);
+        let mut expanded = 0;
+        solve_part_1(&map, Algo::default(), &mut |_: &search::Progress<
+            MapState,
+            usize,
+        >| { expanded += 1 })
+        .unwrap();
+        solve_part_1(&map, Algo::default(), &mut trace).unwrap();
+        assert!(trace.visits(1, 0) > 0);
+        let visits: usize = (0..6)
+            .flat_map(|y| (0..8).map(move |x| (x, y)))
+            .map(|(x, y)| trace.visits(x, y))
+            .sum();
+        assert_eq!(visits, expanded);
+    }
+
+    #[test]
+    fn test_render() {
+        let map = parse(SAMPLE).unwrap();
+        let start = MapState {
+            position: map.entrance(),
+            time: 0,
+        };
+        assert_eq!(
+            start.render(&map),
+            "#E######\n#>>.<^<#\n#.<..<<#\n#>v.><>#\n#<^v^^>#\n######.#\n"
+        );
+        let down = MapState {
+            position: point2(1, 1),
+            time: 1,
+        };
+        assert_eq!(
+            down.render(&map),
+            "#.######\n#E>3.<.#\n#<..<<.#\n#>2.22.#\n#>v..^<#\n######.#\n"
+        );
+    }
+
+    #[test]
+    fn test_replay() {
+        let map = parse(SAMPLE).unwrap();
+        let there = journey(&map, 1, Algo::default()).unwrap();
+        assert_eq!(there.len() - 1, 18);
+        assert_eq!(there.last(), Some(&map.exit()));
+        let frames = replay(&map, &there);
+        assert_eq!(frames.len(), 19);
+        assert!(frames[0].starts_with("Minute 0\n#E######\n"));
+        assert!(frames[18].ends_with("######E#\n"));
+        assert!(frames.iter().all(|frame| !frame.contains('?')));
+
+        let there_and_back = journey(&map, 3, Algo::default()).unwrap();
+        assert_eq!(there_and_back.len() - 1, 54);
+        assert_eq!(there_and_back[..there.len()], there[..]);
+    }
+
+    #[test]
+    fn test_part_2() {
+        let map = parse(SAMPLE).unwrap();
+        let p1 = solve_part_1(&map, Algo::default(), &mut ()).unwrap();
+        assert_eq!(
+            p1 + solve_part_2(&map, p1, Algo::default(), &mut ()).unwrap(),
+            54
+        );
+    }
+
+    #[test]
+    fn test_solve_route() {
+        let map = parse(SAMPLE).unwrap();
+        let (entrance, exit) = (map.entrance(), map.exit());
+        assert_eq!(
+            solve_route(
+                &map,
+                &[entrance, exit, entrance, exit],
+                0,
+                Algo::default(),
+                &mut ()
+            )
+            .unwrap(),
+            54
+        );
+        assert_eq!(
+            solve_route(&map, &[entrance], 0, Algo::default(), &mut ()).unwrap(),
+            0
+        );
+        assert!(solve_route(&map, &[], 0, Algo::default(), &mut ()).is_err());
+        assert!(solve_route(&map, &[entrance, point2(0, 1)], 0, Algo::default(), &mut ()).is_err());
+
+        // Stopping off somewhere on the way can't be quicker.
+        let detour = [entrance, point2(6, 1), exit];
+        assert!(solve_route(&map, &detour, 0, Algo::default(), &mut ()).unwrap() >= 18);
+    }
+
+    #[test]
+    fn test_several_openings() {
+        let map = parse("#.#.##\n#....#\n#....#\n##.#.#").unwrap();
+        assert_eq!(map.entrances, [point2(1, 0), point2(3, 0)]);
+        assert_eq!(map.exits, [point2(2, 3), point2(4, 3)]);
+        let route = [map.entrances[1], map.exits[1]];
+        assert_eq!(
+            solve_route(&map, &route, 0, Algo::default(), &mut ()).unwrap(),
+            4
+        );
+
+        // A blizzard passing under a gap comes back in at the bottom.
+        let map = parse("#.####\n#v...#\n#....#\n#.####").unwrap();
+        let blizzard = map.blizzard_starts()[0];
+        assert_eq!(blizzard.new_pos(&map).new_pos(&map), blizzard);
+    }
+
+    #[test]
+    fn test_layered() {
+        let map = parse(SAMPLE).unwrap();
+        assert_eq!(solve_part_1(&map, Algo::Layered, &mut ()).unwrap(), 18);
+        assert_eq!(solve_part_2(&map, 18, Algo::Layered, &mut ()).unwrap(), 36);
+
+        // Its paths are as quick as A*'s, and dodge the blizzards too.
+        let there_and_back = journey(&map, 3, Algo::Layered).unwrap();
+        assert_eq!(there_and_back.len() - 1, 54);
+        assert!(replay(&map, &there_and_back)
+            .iter()
+            .all(|frame| !frame.contains('?')));
+
+        let mut expanded = 0;
+        solve_part_1(&map, Algo::Layered, &mut |_: &Progress<MapState, usize>| {
+            expanded += 1
+        })
+        .unwrap();
+        assert!(expanded > 18);
+
+        // A waypoint walled in on every side.
+        let map = parse("#.###\n#.#.#\n#####\n#.###").unwrap();
+        let route = [map.entrance(), point2(3, 1)];
+        assert!(solve_route(&map, &route, 0, Algo::Layered, &mut ()).is_err());
+        assert!("jps".parse::<Algo>().is_err());
+    }
+
+    #[test]
+    fn test_heuristics() {
+        let map = parse(SAMPLE).unwrap();
+        for heuristic in [Heuristic::Taxicab, Heuristic::Chebyshev, Heuristic::Zero] {
+            for tie_break in [TieBreak::Deepest, TieBreak::Shallowest, TieBreak::Oldest] {
+                let algo = Algo::AStar {
+                    heuristic,
+                    tie_break,
+                };
+                let minutes = solve_part_1(&map, algo, &mut ()).unwrap();
+                assert_eq!(minutes, 18, "{algo:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_waypoint() {
+        assert_eq!(parse_waypoint("6, 5").unwrap(), point2(6, 5));
+        assert!(parse_waypoint("6").is_err());
+        assert!(parse_waypoint("x,5").is_err());
+    }
+}
@@ -0,0 +1,294 @@
+//! Day 25: Full of Hot Air.
+
+use crate::solution::{self, Answer, Answers, Solution};
+use anyhow::{anyhow, bail, Error};
+use std::{
+    fmt::{self, Display},
+    iter::Sum,
+    ops::Add,
+    str::FromStr,
+};
+
+pub const DATA: &str = include_str!("../data/day25.txt");
+pub const SAMPLE: &str = include_str!("../data/day25_sample.txt");
+
+/// A SNAFU number of any size. Digits are kept least significant first
+/// with no leading zeros, so zero has none, and arithmetic is done on the
+/// digits directly.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+struct Snafu(Vec<i8>);
+
+impl Snafu {
+    fn zero() -> Self {
+        Self::default()
+    }
+
+    fn trim(mut self) -> Self {
+        while self.0.last() == Some(&0) {
+            self.0.pop();
+        }
+        self
+    }
+}
+
+impl From<isize> for Snafu {
+    fn from(v: isize) -> Self {
+        // Wide enough that stepping past isize::MIN can't overflow.
+        let mut v = v as i128;
+        let mut digits = vec![];
+        while v != 0 {
+            let digit = match v.rem_euclid(5) {
+                r @ 0..=2 => r,
+                r => r - 5,
+            };
+            digits.push(digit as i8);
+            v = (v - digit) / 5;
+        }
+        Self(digits)
+    }
+}
+
+impl TryFrom<&Snafu> for isize {
+    type Error = Error;
+
+    fn try_from(snafu: &Snafu) -> Result<Self, Self::Error> {
+        // The leading digits alone can be out of range when the whole
+        // number isn't, as with isize::MIN, so add up in something wider.
+        snafu
+            .0
+            .iter()
+            .rev()
+            .try_fold(0i128, |value, digit| {
+                value.checked_mul(5)?.checked_add(*digit as i128)
+            })
+            .and_then(|value| isize::try_from(value).ok())
+            .ok_or_else(|| anyhow!("{snafu} is too big for an isize"))
+    }
+}
+
+impl FromStr for Snafu {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            bail!("empty SNAFU number");
+        }
+        let digits = s
+            .chars()
+            .rev()
+            .map(|c| {
+                Ok(match c {
+                    '2' => 2,
+                    '1' => 1,
+                    '0' => 0,
+                    '-' => -1,
+                    '=' => -2,
+                    _ => bail!("illegal SNAFU digit {c:?} in {s:?}"),
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(Self(digits).trim())
+    }
+}
+
+impl Display for Snafu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "0");
+        }
+        for digit in self.0.iter().rev() {
+            let c = match digit {
+                2 => '2',
+                1 => '1',
+                0 => '0',
+                -1 => '-',
+                -2 => '=',
+                _ => unreachable!("SNAFU digit {digit}"),
+            };
+            write!(f, "{c}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Add for &Snafu {
+    type Output = Snafu;
+
+    /// Column addition: each column's total is between -5 and 5, and
+    /// anything outside -2..=2 carries one into the next column.
+    fn add(self, other: &Snafu) -> Snafu {
+        let len = self.0.len().max(other.0.len());
+        let mut digits = Vec::with_capacity(len + 1);
+        let mut carry = 0;
+        for i in 0..len {
+            let column = self.0.get(i).unwrap_or(&0) + other.0.get(i).unwrap_or(&0) + carry;
+            let digit;
+            (carry, digit) = match column {
+                3.. => (1, column - 5),
+                ..=-3 => (-1, column + 5),
+                _ => (0, column),
+            };
+            digits.push(digit);
+        }
+        digits.push(carry);
+        Snafu(digits).trim()
+    }
+}
+
+impl Add for Snafu {
+    type Output = Snafu;
+
+    fn add(self, other: Snafu) -> Snafu {
+        &self + &other
+    }
+}
+
+impl<'a> Sum<&'a Snafu> for Snafu {
+    fn sum<I: Iterator<Item = &'a Snafu>>(iter: I) -> Self {
+        iter.fold(Snafu::zero(), |sum, n| &sum + n)
+    }
+}
+
+fn parse(s: &str) -> Vec<String> {
+    s.lines().map(str::to_string).collect()
+}
+
+fn solve_part_1(s: &[String]) -> Result<String, Error> {
+    let values = s
+        .iter()
+        .map(|line| line.parse())
+        .collect::<Result<Vec<Snafu>, _>>()?;
+    Ok(values.iter().sum::<Snafu>().to_string())
+}
+
+/// Day 25 has no options.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options;
+
+pub struct Day25 {
+    value_list: Vec<String>,
+}
+
+impl Day25 {
+    pub fn new(input: &str, _options: &Options) -> Result<Self, Error> {
+        Ok(Self {
+            value_list: parse(input),
+        })
+    }
+}
+
+/// There's no part 2 on the last day.
+impl Solution for Day25 {
+    const DAY: u8 = 25;
+
+    fn part_1(&self) -> Result<Answer, Error> {
+        Ok(solve_part_1(&self.value_list)?.into())
+    }
+}
+
+/// Solve both parts for `input`.
+pub fn solve(input: &str, options: &Options) -> Result<Answers, crate::Error> {
+    solution::answers(|| Day25::new(input, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use itertools::assert_equal;
+    use proptest::prelude::*;
+
+    /// The value of a SNAFU number, or an error if it isn't one or is too big
+    /// for an `isize`.
+    fn parse_snafu(s: &str) -> Result<isize, Error> {
+        isize::try_from(&s.parse::<Snafu>()?)
+    }
+
+    /// Any `isize` as SNAFU. Balanced base five needs no sign, so negative
+    /// numbers just lead with `-` or `=`, and zero is `0`.
+    fn to_snafu_string(v: isize) -> String {
+        Snafu::from(v).to_string()
+    }
+
+    const EXPECTED: &[isize] = &[1747, 906, 198, 11, 201, 31, 1257, 32, 353, 107, 7, 3, 37];
+
+    #[test]
+    fn test_parse() {
+        let value_list = parse(SAMPLE);
+        let values: Vec<isize> = value_list
+            .iter()
+            .map(String::as_str)
+            .map(|s| parse_snafu(s).unwrap())
+            .collect();
+        assert_equal(values.iter(), EXPECTED.iter());
+
+        let sum: isize = values.iter().sum();
+        assert_eq!(sum, 4890);
+
+        assert_eq!(to_snafu_string(sum).as_str(), "2=-1=0");
+    }
+
+    #[test]
+    fn test_snafu() {
+        let value_list = parse(SAMPLE);
+        assert_eq!(solve_part_1(&value_list).unwrap(), "2=-1=0");
+        for (line, expected) in value_list.iter().zip(EXPECTED) {
+            let snafu: Snafu = line.parse().unwrap();
+            assert_eq!(&snafu.to_string(), line);
+            assert_eq!(parse_snafu(&snafu.to_string()).unwrap(), *expected);
+        }
+
+        assert_eq!(Snafu::zero().to_string(), "0");
+        assert_eq!("000".parse::<Snafu>().unwrap(), Snafu::zero());
+        assert_eq!("01=".parse::<Snafu>().unwrap().to_string(), "1=");
+        assert!("".parse::<Snafu>().is_err());
+        assert!("123".parse::<Snafu>().is_err());
+
+        let big: Snafu = "2".repeat(40).parse().unwrap();
+        assert_eq!(
+            (&big + &"1".parse::<Snafu>().unwrap()).to_string(),
+            format!("1{}", "=".repeat(40))
+        );
+        let minus: Snafu = "-".parse().unwrap();
+        assert_eq!(("1".parse::<Snafu>().unwrap() + minus).to_string(), "0");
+    }
+
+    #[test]
+    fn test_snafu_conversion() {
+        assert_eq!(to_snafu_string(0), "0");
+        assert_eq!(to_snafu_string(-1), "-");
+        assert_eq!(to_snafu_string(-3), "-2");
+        assert_eq!(to_snafu_string(-4890), "=21-20");
+        for v in -100_000..=100_000 {
+            assert_eq!(parse_snafu(&to_snafu_string(v)).unwrap(), v, "{v}");
+        }
+        for v in [isize::MIN, isize::MIN + 1, isize::MAX - 1, isize::MAX] {
+            assert_eq!(parse_snafu(&to_snafu_string(v)).unwrap(), v, "{v}");
+        }
+
+        let too_big = Snafu::from(isize::MAX) + Snafu::from(1);
+        assert!(isize::try_from(&too_big).is_err());
+        assert!(parse_snafu(&too_big.to_string()).is_err());
+        assert!(parse_snafu("").is_err());
+        assert!(parse_snafu("12a").is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn test_snafu_add_matches_integers(a in 1..isize::MAX / 4, b in 1..isize::MAX / 4) {
+            let sum = to_snafu_string(a).parse::<Snafu>().unwrap()
+                + to_snafu_string(b).parse::<Snafu>().unwrap();
+            prop_assert_eq!(sum.to_string(), to_snafu_string(a + b));
+        }
+
+        #[test]
+        fn test_snafu_integer_round_trip(v in any::<isize>()) {
+            prop_assert_eq!(parse_snafu(&to_snafu_string(v)).unwrap(), v);
+        }
+
+        #[test]
+        fn test_snafu_round_trip(s in "[12][-=012]{0,30}") {
+            let snafu: Snafu = s.parse().unwrap();
+            prop_assert_eq!(snafu.to_string(), s);
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! Compass directions on a grid where y increases downwards, as it does
+//! when reading puzzle input line by line.
+
+use anyhow::{bail, Error};
+use euclid::{default::Vector2D, vec2};
+use std::str::FromStr;
+
+/// The four orthogonal directions, clockwise from north.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dir4 {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Dir4 {
+    pub const ALL: [Dir4; 4] = [Self::North, Self::East, Self::South, Self::West];
+
+    pub fn turn_left(&self) -> Self {
+        match self {
+            Self::North => Self::West,
+            Self::East => Self::North,
+            Self::South => Self::East,
+            Self::West => Self::South,
+        }
+    }
+
+    pub fn turn_right(&self) -> Self {
+        match self {
+            Self::North => Self::East,
+            Self::East => Self::South,
+            Self::South => Self::West,
+            Self::West => Self::North,
+        }
+    }
+
+    pub fn opposite(&self) -> Self {
+        self.turn_left().turn_left()
+    }
+
+    /// The arrow the puzzles draw for this direction.
+    pub fn as_char(&self) -> char {
+        match self {
+            Self::North => '^',
+            Self::East => '>',
+            Self::South => 'v',
+            Self::West => '<',
+        }
+    }
+
+    fn delta(&self) -> (i8, i8) {
+        match self {
+            Self::North => (0, -1),
+            Self::East => (1, 0),
+            Self::South => (0, 1),
+            Self::West => (-1, 0),
+        }
+    }
+}
+
+/// Arrows, or the initial of up, down, left or right.
+impl TryFrom<char> for Dir4 {
+    type Error = Error;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        Ok(match c {
+            '^' | 'U' => Self::North,
+            '>' | 'R' => Self::East,
+            'v' | 'D' => Self::South,
+            '<' | 'L' => Self::West,
+            _ => bail!("{c:?} isn't a direction"),
+        })
+    }
+}
+
+impl<T: From<i8>> From<Dir4> for Vector2D<T> {
+    fn from(d: Dir4) -> Self {
+        let (x, y) = d.delta();
+        vec2(x.into(), y.into())
+    }
+}
+
+/// The orthogonal and diagonal directions, clockwise from north.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dir8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Dir8 {
+    pub const ALL: [Dir8; 8] = [
+        Self::North,
+        Self::NorthEast,
+        Self::East,
+        Self::SouthEast,
+        Self::South,
+        Self::SouthWest,
+        Self::West,
+        Self::NorthWest,
+    ];
+
+    fn index(&self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|d| d == self)
+            .expect("every direction")
+    }
+
+    /// An eighth of a turn anticlockwise.
+    pub fn turn_left(&self) -> Self {
+        Self::ALL[(self.index() + 7) % 8]
+    }
+
+    /// An eighth of a turn clockwise.
+    pub fn turn_right(&self) -> Self {
+        Self::ALL[(self.index() + 1) % 8]
+    }
+
+    pub fn opposite(&self) -> Self {
+        Self::ALL[(self.index() + 4) % 8]
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        self.index() % 2 == 1
+    }
+
+    fn delta(&self) -> (i8, i8) {
+        match self {
+            Self::North => (0, -1),
+            Self::NorthEast => (1, -1),
+            Self::East => (1, 0),
+            Self::SouthEast => (1, 1),
+            Self::South => (0, 1),
+            Self::SouthWest => (-1, 1),
+            Self::West => (-1, 0),
+            Self::NorthWest => (-1, -1),
+        }
+    }
+}
+
+impl From<Dir4> for Dir8 {
+    fn from(d: Dir4) -> Self {
+        match d {
+            Dir4::North => Self::North,
+            Dir4::East => Self::East,
+            Dir4::South => Self::South,
+            Dir4::West => Self::West,
+        }
+    }
+}
+
+/// Up, down, left and right by their initials, with diagonals written up or
+/// down first, like `UL` or `DR`.
+impl FromStr for Dir8 {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "U" => Self::North,
+            "UR" => Self::NorthEast,
+            "R" => Self::East,
+            "DR" => Self::SouthEast,
+            "D" => Self::South,
+            "DL" => Self::SouthWest,
+            "L" => Self::West,
+            "UL" => Self::NorthWest,
+            _ => bail!("unknown direction {s:?}"),
+        })
+    }
+}
+
+impl<T: From<i8>> From<Dir8> for Vector2D<T> {
+    fn from(d: Dir8) -> Self {
+        let (x, y) = d.delta();
+        vec2(x.into(), y.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    type Vector = Vector2D<isize>;
+
+    #[test]
+    fn test_dir4() {
+        for d in Dir4::ALL {
+            assert_eq!(d.turn_left().turn_right(), d);
+            assert_eq!(d.opposite().opposite(), d);
+            assert_ne!(d.opposite(), d);
+            let v: Vector = d.into();
+            let back: Vector = d.opposite().into();
+            assert_eq!(v + back, vec2(0, 0));
+            assert_eq!(Dir4::try_from(d.as_char()).unwrap(), d);
+        }
+        assert_eq!(Dir4::North.turn_right(), Dir4::East);
+        assert_eq!(Vector2D::<i64>::from(Dir4::West), vec2(-1, 0));
+        assert_eq!(Dir4::try_from('U').unwrap(), Dir4::North);
+        assert!(Dir4::try_from('x').is_err());
+    }
+
+    #[test]
+    fn test_dir8() {
+        for d in Dir8::ALL {
+            let v: Vector = d.into();
+            let left: Vector = d.turn_left().into();
+            let back: Vector = d.opposite().into();
+            assert_eq!(v + back, vec2(0, 0));
+            assert_ne!(v, left);
+            assert_eq!(d.is_diagonal(), v.x != 0 && v.y != 0);
+        }
+        for d in Dir4::ALL {
+            assert_eq!(Vector::from(Dir8::from(d)), Vector::from(d));
+        }
+        assert_eq!(Dir8::NorthWest.turn_right(), Dir8::North);
+        assert_eq!("DR".parse::<Dir8>().unwrap(), Dir8::SouthEast);
+        assert!("RD".parse::<Dir8>().is_err());
+    }
+}
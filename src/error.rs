@@ -0,0 +1,131 @@
+//! The error the days' `solve` functions and the runner return, which says
+//! which day and part went wrong, and tells a part a day doesn't solve apart
+//! from one that failed.
+//!
+//! Solvers themselves still use `anyhow`; a part can return one of these in
+//! its `anyhow::Error` to say more than that it failed.
+
+use crate::cancel::Cancelled;
+use std::{io, path::PathBuf, time::Duration};
+use thiserror::Error;
+
+/// Whatever a day's own code failed with.
+pub type Source = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("day {day}: bad input")]
+    Parse {
+        day: u8,
+        #[source]
+        source: Source,
+    },
+
+    #[error("reading {path:?}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("day {day} doesn't solve part {part}")]
+    UnsupportedPart { day: u8, part: u8 },
+
+    #[error("day {day} part {part} ran out of time after {limit:?}")]
+    SolverTimeout { day: u8, part: u8, limit: Duration },
+
+    #[error("day {day} part {part} failed")]
+    Solver {
+        day: u8,
+        part: u8,
+        #[source]
+        source: Source,
+    },
+
+    #[error("day {day}: submitting answers")]
+    Submit {
+        day: u8,
+        #[source]
+        source: Source,
+    },
+}
+
+impl Error {
+    /// `error` from parsing `day`'s input.
+    pub fn parsing(day: u8, error: anyhow::Error) -> Self {
+        match error.downcast() {
+            Ok(error) => error,
+            Err(error) => Self::Parse {
+                day,
+                source: error.into(),
+            },
+        }
+    }
+
+    /// `error` from solving `part` of `day`, unless it already says what
+    /// went wrong. A search that was cancelled ran out of time.
+    pub fn solving(day: u8, part: u8, error: anyhow::Error) -> Self {
+        let error = match error.downcast::<Cancelled>() {
+            Ok(Cancelled { after }) => {
+                return Self::SolverTimeout {
+                    day,
+                    part,
+                    limit: after,
+                }
+            }
+            Err(error) => error,
+        };
+        match error.downcast() {
+            Ok(error) => error,
+            Err(error) => Self::Solver {
+                day,
+                part,
+                source: error.into(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_context() {
+        let error = Error::parsing(13, anyhow!("line 4: \"[3\"").context("packets"));
+        assert!(matches!(error, Error::Parse { day: 13, .. }));
+        assert_eq!(
+            format!("{:#}", anyhow::Error::from(error)),
+            "day 13: bad input: packets: line 4: \"[3\""
+        );
+
+        let error = Error::solving(17, 1, anyhow!("no rocks"));
+        assert_eq!(error.to_string(), "day 17 part 1 failed");
+    }
+
+    #[test]
+    fn test_passed_through() {
+        let unsupported = Error::UnsupportedPart { day: 17, part: 2 };
+        let error = Error::solving(17, 2, unsupported.into());
+        assert!(matches!(error, Error::UnsupportedPart { day: 17, part: 2 }));
+        assert_eq!(error.to_string(), "day 17 doesn't solve part 2");
+
+        let timeout = Error::SolverTimeout {
+            day: 19,
+            part: 1,
+            limit: Duration::from_secs(5),
+        };
+        assert_eq!(
+            Error::solving(19, 1, timeout.into()).to_string(),
+            "day 19 part 1 ran out of time after 5s"
+        );
+        let cancelled = Cancelled {
+            after: Duration::from_secs(2),
+        };
+        assert!(matches!(
+            Error::solving(16, 2, cancelled.into()),
+            Error::SolverTimeout { day: 16, part: 2, limit } if limit == Duration::from_secs(2)
+        ));
+    }
+}
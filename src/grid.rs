@@ -0,0 +1,531 @@
+//! Grids of cells indexed by [`Point`]: [`Grid2D`] for the puzzles whose
+//! input is a picture, and [`SparseGrid`] for ones that grow without bound.
+
+use crate::{
+    direction::{Dir4, Dir8},
+    search,
+};
+use anyhow::{bail, Context, Error};
+use euclid::point2;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt,
+    ops::{Add, Index, IndexMut, Sub},
+};
+
+pub type Point = euclid::default::Point2D<isize>;
+pub type Vector = euclid::default::Vector2D<isize>;
+pub type Bounds = euclid::default::Box2D<isize>;
+
+/// Steps between `p` and `q` along the axes, for points with any integer
+/// coordinates. The difference is taken larger minus smaller, so it can't
+/// go negative on the way.
+pub fn taxicab_distance<T, U>(p: euclid::Point2D<T, U>, q: euclid::Point2D<T, U>) -> T
+where
+    T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T>,
+{
+    let apart = |a: T, b: T| if a > b { a - b } else { b - a };
+    apart(p.x, q.x) + apart(p.y, q.y)
+}
+
+/// The most steps between `p` and `q` along either axis, which is the
+/// distance when diagonal steps are allowed too.
+pub fn chebyshev_distance<T, U>(p: euclid::Point2D<T, U>, q: euclid::Point2D<T, U>) -> T
+where
+    T: Copy + PartialOrd + Sub<Output = T>,
+{
+    let apart = |a: T, b: T| if a > b { a - b } else { b - a };
+    let (x, y) = (apart(p.x, q.x), apart(p.y, q.y));
+    if x > y {
+        x
+    } else {
+        y
+    }
+}
+
+/// Cells stored row by row, with (0, 0) at the top left.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid2D<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid2D<T> {
+    /// A grid with every cell set by `f` from its position.
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(Point) -> T) -> Self {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| point2(x as isize, y as isize)))
+            .map(&mut f)
+            .collect();
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// A grid from its rows, which must all be the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self, Error> {
+        let width = rows.first().map_or(0, Vec::len);
+        if let Some(y) = rows.iter().position(|row| row.len() != width) {
+            bail!("row {y} has {} cells but row 0 has {width}", rows[y].len());
+        }
+        let height = rows.len();
+        Ok(Self {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// A grid with a cell for each character of each line, converted by
+    /// `cell`. Errors say which line and column was bad.
+    pub fn parse(s: &str, mut cell: impl FnMut(char) -> Result<T, Error>) -> Result<Self, Error> {
+        let rows: Vec<Vec<T>> = s
+            .lines()
+            .enumerate()
+            .map(|(y, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(|(x, c)| {
+                        cell(c).with_context(|| format!("line {}, column {}", y + 1, x + 1))
+                    })
+                    .collect::<Result<Vec<_>, Error>>()
+            })
+            .collect::<Result<_, _>>()?;
+        Self::from_rows(rows)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        (0..self.width as isize).contains(&p.x) && (0..self.height as isize).contains(&p.y)
+    }
+
+    fn offset(&self, p: Point) -> Option<usize> {
+        if self.contains(p) {
+            Some(p.y as usize * self.width + p.x as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.offset(p).map(|offset| &self.cells[offset])
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.offset(p).map(|offset| &mut self.cells[offset])
+    }
+
+    /// Every position in reading order.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width as isize;
+        (0..self.height as isize).flat_map(move |y| (0..width).map(move |x| point2(x, y)))
+    }
+
+    /// Every cell with its position, in reading order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> {
+        // chunks panics on zero, and a zero width grid has no cells anyway.
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn row(&self, y: usize) -> impl DoubleEndedIterator<Item = &T> {
+        self.cells[y * self.width..(y + 1) * self.width].iter()
+    }
+
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> {
+        assert!(x < self.width, "column {x} of {}", self.width);
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl DoubleEndedIterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid2D<U> {
+        Grid2D {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// The in-bounds cells north, east, south and west of `p`.
+    pub fn neighbors4(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        self.around(p, Dir4::ALL.map(Vector::from))
+    }
+
+    /// The in-bounds cells of the eight around `p`.
+    pub fn neighbors8(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        self.around(p, Dir8::ALL.map(Vector::from))
+    }
+
+    fn around<const N: usize>(
+        &self,
+        p: Point,
+        deltas: [Vector; N],
+    ) -> impl Iterator<Item = Point> + '_ {
+        deltas
+            .into_iter()
+            .map(move |delta| p + delta)
+            .filter(|n| self.contains(*n))
+    }
+
+    /// Every cell that can be reached from `start` by orthogonal steps,
+    /// where `can_step(from, to)` says whether a step between two cells'
+    /// values is allowed. Includes `start`.
+    pub fn flood_fill(
+        &self,
+        start: Point,
+        mut can_step: impl FnMut(&T, &T) -> bool,
+    ) -> HashSet<Point> {
+        if !self.contains(start) {
+            return HashSet::new();
+        }
+        search::reachable(
+            &start,
+            |p| {
+                let from = &self[*p];
+                self.neighbors4(*p)
+                    .filter(|n| can_step(from, &self[*n]))
+                    .collect::<Vec<_>>()
+            },
+            &mut (),
+        )
+    }
+}
+
+impl<T: Clone> Grid2D<T> {
+    pub fn filled(width: usize, height: usize, value: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    /// Rows become columns, so the cell at (x, y) moves to (y, x).
+    pub fn transpose(&self) -> Self {
+        Grid2D::from_fn(self.height, self.width, |p| self[point2(p.y, p.x)].clone())
+    }
+
+    /// The grid turned a quarter turn clockwise.
+    pub fn rotate_clockwise(&self) -> Self {
+        let height = self.height as isize;
+        Grid2D::from_fn(self.height, self.width, |p| {
+            self[point2(p.y, height - 1 - p.x)].clone()
+        })
+    }
+}
+
+impl<T> Index<Point> for Grid2D<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &T {
+        self.get(p)
+            .unwrap_or_else(|| panic!("{p:?} is outside {}x{}", self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid2D<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(p)
+            .unwrap_or_else(|| panic!("{p:?} is outside {width}x{height}"))
+    }
+}
+
+/// Each row on its own line, with a newline after the last.
+impl<T: fmt::Display> fmt::Display for Grid2D<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Cells scattered over an unbounded plane. Along with the cells it keeps
+/// which columns are occupied in each row and which rows in each column,
+/// so bounds and the ends of a row or column are cheap to ask for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point, T>,
+    rows: BTreeMap<isize, BTreeSet<isize>>,
+    columns: BTreeMap<isize, BTreeSet<isize>>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self {
+            cells: HashMap::new(),
+            rows: BTreeMap::new(),
+            columns: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        self.cells.contains_key(&p)
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.cells.get(&p)
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.cells.get_mut(&p)
+    }
+
+    /// Set the cell at `p`, returning what was there.
+    pub fn insert(&mut self, p: Point, value: T) -> Option<T> {
+        self.rows.entry(p.y).or_default().insert(p.x);
+        self.columns.entry(p.x).or_default().insert(p.y);
+        self.cells.insert(p, value)
+    }
+
+    pub fn remove(&mut self, p: Point) -> Option<T> {
+        let value = self.cells.remove(&p)?;
+        Self::forget(&mut self.rows, p.y, p.x);
+        Self::forget(&mut self.columns, p.x, p.y);
+        Some(value)
+    }
+
+    fn forget(index: &mut BTreeMap<isize, BTreeSet<isize>>, line: isize, at: isize) {
+        if let Some(set) = index.get_mut(&line) {
+            set.remove(&at);
+            if set.is_empty() {
+                index.remove(&line);
+            }
+        }
+    }
+
+    /// Occupied cells in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells.iter().map(|(p, value)| (*p, value))
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        self.cells.keys().copied()
+    }
+
+    /// The smallest box holding every cell. Unlike most boxes, `max` is
+    /// the last occupied cell rather than one past it.
+    pub fn bounds(&self) -> Option<Bounds> {
+        let (min_y, max_y) = Self::ends(self.rows.keys())?;
+        let (min_x, max_x) = Self::ends(self.columns.keys())?;
+        Some(Bounds::new(point2(min_x, min_y), point2(max_x, max_y)))
+    }
+
+    fn ends<'a>(mut keys: impl DoubleEndedIterator<Item = &'a isize>) -> Option<(isize, isize)> {
+        let first = *keys.next()?;
+        Some((first, keys.next_back().copied().unwrap_or(first)))
+    }
+
+    /// The first and last occupied x in row `y`.
+    pub fn row_range(&self, y: isize) -> Option<(isize, isize)> {
+        self.rows.get(&y).and_then(|xs| Self::ends(xs.iter()))
+    }
+
+    /// The first and last occupied y in column `x`.
+    pub fn column_range(&self, x: isize) -> Option<(isize, isize)> {
+        self.columns.get(&x).and_then(|ys| Self::ends(ys.iter()))
+    }
+
+    /// Every cell moved by `v`.
+    pub fn translate(&mut self, v: Vector) {
+        *self = std::mem::take(self)
+            .cells
+            .into_iter()
+            .map(|(p, value)| (p + v, value))
+            .collect();
+    }
+
+    /// Draw the cells in `frame`, which includes its `max`, one row per
+    /// line with y increasing downwards. `cell` picks the character for each
+    /// position from what's there, if anything.
+    pub fn render_in(
+        &self,
+        frame: Bounds,
+        mut cell: impl FnMut(Point, Option<&T>) -> char,
+    ) -> String {
+        let mut s = String::new();
+        for y in frame.min.y..=frame.max.y {
+            for x in frame.min.x..=frame.max.x {
+                let p = point2(x, y);
+                s.push(cell(p, self.get(p)));
+            }
+            s.push('\n');
+        }
+        s
+    }
+
+    /// Draw every cell within [`SparseGrid::bounds`].
+    pub fn render(&self, mut cell: impl FnMut(&T) -> char) -> String {
+        self.bounds().map_or_else(String::new, |frame| {
+            self.render_in(frame, |_, value| value.map_or('.', &mut cell))
+        })
+    }
+}
+
+impl<T> FromIterator<(Point, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Point, T)>>(iter: I) -> Self {
+        let mut grid = Self::new();
+        grid.extend(iter);
+        grid
+    }
+}
+
+impl<T> Extend<(Point, T)> for SparseGrid<T> {
+    fn extend<I: IntoIterator<Item = (Point, T)>>(&mut self, iter: I) {
+        for (p, value) in iter {
+            self.insert(p, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use euclid::vec2;
+
+    fn digits(s: &str) -> Grid2D<u32> {
+        Grid2D::parse(s, |c| c.to_digit(10).context("not a digit")).unwrap()
+    }
+
+    #[test]
+    fn test_taxicab_distance() {
+        let p: Point = point2(1, -2);
+        assert_eq!(taxicab_distance(p, p), 0);
+        assert_eq!(taxicab_distance(p, point2(-3, 2)), 8);
+        let p = point2::<i64, euclid::UnknownUnit>(-4_000_000_000, 3);
+        assert_eq!(taxicab_distance(p, point2(4_000_000_000, 0)), 8_000_000_003);
+        let p = point2::<u8, euclid::UnknownUnit>(250, 0);
+        assert_eq!(taxicab_distance(p, point2(0, 5)), 255);
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = digits("123\n456");
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[point2(2, 1)], 6);
+        assert_eq!(grid.get(point2(3, 0)), None);
+        assert_eq!(grid.get(point2(-1, 0)), None);
+        assert_eq!(grid.to_string(), "123\n456\n");
+
+        let error = Grid2D::parse("12\n3x", |c| c.to_digit(10).context("not a digit"));
+        assert!(format!("{:#}", error.unwrap_err()).starts_with("line 2, column 2"));
+        assert!(Grid2D::from_rows(vec![vec![1, 2], vec![3]]).is_err());
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = digits("123\n456");
+        let rows: Vec<_> = grid.rows().collect();
+        assert_eq!(rows, [[1, 2, 3], [4, 5, 6]]);
+        assert_eq!(grid.row(1).rev().copied().collect::<Vec<_>>(), [6, 5, 4]);
+        let columns: Vec<Vec<_>> = grid.columns().map(|c| c.copied().collect()).collect();
+        assert_eq!(columns, [[1, 4], [2, 5], [3, 6]]);
+        assert_eq!(grid.iter().nth(4), Some((point2(1, 1), &5)));
+    }
+
+    #[test]
+    fn test_transpose_and_rotate() {
+        let grid = digits("123\n456");
+        assert_eq!(grid.transpose().to_string(), "14\n25\n36\n");
+        assert_eq!(grid.transpose().transpose(), grid);
+        assert_eq!(grid.rotate_clockwise().to_string(), "41\n52\n63\n");
+        let mut turned = grid.clone();
+        for _ in 0..4 {
+            turned = turned.rotate_clockwise();
+        }
+        assert_eq!(turned, grid);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid2D::filled(3, 3, '.');
+        let corner: Vec<_> = grid.neighbors4(point2(0, 0)).collect();
+        assert_eq!(corner, [point2(1, 0), point2(0, 1)]);
+        assert_eq!(grid.neighbors4(point2(1, 1)).count(), 4);
+        assert_eq!(grid.neighbors8(point2(1, 1)).count(), 8);
+        assert_eq!(grid.neighbors8(point2(2, 2)).count(), 3);
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let grid = Grid2D::parse("..#.\n..#.\n###.\n....", |c| Ok(c == '#')).unwrap();
+        let open = |from: &bool, to: &bool| !from && !to;
+        assert_eq!(grid.flood_fill(point2(0, 0), open).len(), 4);
+        assert_eq!(grid.flood_fill(point2(3, 0), open).len(), 7);
+        assert!(grid.flood_fill(point2(9, 9), open).is_empty());
+    }
+
+    #[test]
+    fn test_sparse_grid() {
+        let mut grid: SparseGrid<char> = [
+            (point2(2, -1), 'a'),
+            (point2(-3, 4), 'b'),
+            (point2(5, 4), 'c'),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(grid.len(), 3);
+        assert_eq!(
+            grid.bounds(),
+            Some(Bounds::new(point2(-3, -1), point2(5, 4)))
+        );
+        assert_eq!(grid.row_range(4), Some((-3, 5)));
+        assert_eq!(grid.row_range(0), None);
+        assert_eq!(grid.column_range(2), Some((-1, -1)));
+
+        assert_eq!(grid.remove(point2(5, 4)), Some('c'));
+        assert_eq!(grid.remove(point2(5, 4)), None);
+        assert_eq!(
+            grid.bounds(),
+            Some(Bounds::new(point2(-3, -1), point2(2, 4)))
+        );
+        assert_eq!(grid.column_range(5), None);
+
+        grid.translate(vec2(3, 1));
+        assert_eq!(grid.get(point2(0, 5)), Some(&'b'));
+        assert_eq!(
+            grid.render(|c| *c),
+            ".....a\n......\n......\n......\n......\nb.....\n"
+        );
+
+        grid.remove(point2(5, 0));
+        grid.remove(point2(0, 5));
+        assert!(grid.is_empty());
+        assert_eq!(grid.bounds(), None);
+        assert_eq!(grid.render(|c| *c), "");
+    }
+}
@@ -0,0 +1,267 @@
+//! Points in three dimensions, and sets of unit cubes at integer points.
+
+use crate::{parse::parse_lines_with, search};
+use anyhow::{bail, Context, Error};
+use euclid::{point3, vec3};
+use std::collections::HashSet;
+
+pub type Coord = i64;
+pub type Point = euclid::default::Point3D<Coord>;
+pub type Vector = euclid::default::Vector3D<Coord>;
+pub type Bounds3 = euclid::default::Box3D<Coord>;
+
+/// Steps to the six cubes that share a face with a cube.
+pub const FACES: [Vector; 6] = [
+    vec3(-1, 0, 0),
+    vec3(1, 0, 0),
+    vec3(0, -1, 0),
+    vec3(0, 1, 0),
+    vec3(0, 0, -1),
+    vec3(0, 0, 1),
+];
+
+/// A point written `x,y,z`.
+pub fn parse_point(s: &str) -> Result<Point, Error> {
+    let parts = s
+        .split(',')
+        .map(|part| {
+            part.trim()
+                .parse()
+                .with_context(|| format!("bad coordinate {part:?}"))
+        })
+        .collect::<Result<Vec<Coord>, Error>>()?;
+    match parts[..] {
+        [x, y, z] => Ok(point3(x, y, z)),
+        _ => bail!("expected x,y,z, got {s:?}"),
+    }
+}
+
+pub fn taxicab_distance(p: Point, q: Point) -> Coord {
+    let d = (p - q).abs();
+    d.x + d.y + d.z
+}
+
+/// The six points that share a face with `p`.
+pub fn neighbors(p: Point) -> impl Iterator<Item = Point> {
+    FACES.into_iter().map(move |v| p + v)
+}
+
+/// A set of unit cubes, such as a lava droplet scanned a cube at a time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VoxelSet {
+    voxels: HashSet<Point>,
+}
+
+impl VoxelSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// One point per line; blank lines are skipped.
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        Ok(parse_lines_with(s, parse_point)?.into_iter().collect())
+    }
+
+    pub fn len(&self) -> usize {
+        self.voxels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.voxels.is_empty()
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        self.voxels.contains(&p)
+    }
+
+    pub fn insert(&mut self, p: Point) -> bool {
+        self.voxels.insert(p)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Point> + '_ {
+        self.voxels.iter().copied()
+    }
+
+    /// The smallest box holding every voxel. Unlike most boxes, `max` is
+    /// the last voxel rather than one past it.
+    pub fn bounding_box(&self) -> Option<Bounds3> {
+        (!self.is_empty()).then(|| Bounds3::from_points(self.voxels.iter()))
+    }
+
+    /// Faces not shared with another voxel.
+    pub fn surface_area(&self) -> usize {
+        self.iter()
+            .flat_map(neighbors)
+            .filter(|p| !self.contains(*p))
+            .count()
+    }
+
+    /// Faces reachable from outside, found by flood filling the air in a box
+    /// one larger than the voxels on every side. Pockets of air sealed inside
+    /// don't count.
+    pub fn exterior_surface_area(&self) -> usize {
+        self.exterior_faces().len()
+    }
+
+    /// The faces counted by [`exterior_surface_area`](Self::exterior_surface_area),
+    /// each as the voxel it's on and the direction it faces.
+    pub fn exterior_faces(&self) -> Vec<(Point, Vector)> {
+        self.outside_air()
+            .iter()
+            .flat_map(|air| FACES.iter().map(move |v| (*air + *v, -*v)))
+            .filter(|(p, _)| self.contains(*p))
+            .collect()
+    }
+
+    /// The air around the voxels, in a box one larger than them on every
+    /// side.
+    fn outside_air(&self) -> HashSet<Point> {
+        let Some(bbox) = self.bounding_box() else {
+            return HashSet::new();
+        };
+        // Box3D excludes its max, so grow it by one more on that side.
+        let search_box = Bounds3::new(bbox.min - vec3(1, 1, 1), bbox.max + vec3(2, 2, 2));
+        search::reachable(
+            &search_box.min,
+            |air| neighbors(*air).filter(move |p| search_box.contains(*p) && !self.contains(*p)),
+            &mut (),
+        )
+    }
+
+    /// The voxels split into pieces that are joined face to face, largest
+    /// first.
+    pub fn components(&self) -> Vec<VoxelSet> {
+        let mut components: Vec<VoxelSet> = vec![];
+        let mut seen = HashSet::new();
+        for voxel in self.iter() {
+            if seen.contains(&voxel) {
+                continue;
+            }
+            let component = search::reachable(
+                &voxel,
+                |p| neighbors(*p).filter(|p| self.contains(*p)),
+                &mut (),
+            );
+            seen.extend(component.iter().copied());
+            components.push(component.into_iter().collect());
+        }
+        // Sets iterate in no particular order, so break ties in size by
+        // where the pieces are to keep the order the same from run to run.
+        components.sort_by_key(|c| {
+            let min = c.iter().map(|p| (p.x, p.y, p.z)).min();
+            (std::cmp::Reverse(c.len()), min)
+        });
+        components
+    }
+
+    /// Pockets of air sealed inside the voxels, each as a set of its own,
+    /// largest first.
+    pub fn air_pockets(&self) -> Vec<VoxelSet> {
+        let Some(bbox) = self.bounding_box() else {
+            return vec![];
+        };
+        let outside = self.outside_air();
+        let inside: VoxelSet = (bbox.min.x..=bbox.max.x)
+            .flat_map(|x| (bbox.min.y..=bbox.max.y).map(move |y| (x, y)))
+            .flat_map(|(x, y)| (bbox.min.z..=bbox.max.z).map(move |z| point3(x, y, z)))
+            .filter(|p| !self.contains(*p) && !outside.contains(p))
+            .collect();
+        inside.components()
+    }
+}
+
+impl FromIterator<Point> for VoxelSet {
+    fn from_iter<I: IntoIterator<Item = Point>>(iter: I) -> Self {
+        Self {
+            voxels: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_point() {
+        assert_eq!(parse_point("1,-2,3").unwrap(), point3(1, -2, 3));
+        for bad in ["1,2", "1,2,3,4", "1,x,3", ""] {
+            assert!(parse_point(bad).is_err(), "{bad}");
+        }
+        let error = VoxelSet::parse("1,1,1\n2,2").unwrap_err();
+        assert!(format!("{error:#}").starts_with("line 2"));
+    }
+
+    #[test]
+    fn test_taxicab_distance() {
+        assert_eq!(taxicab_distance(point3(1, 2, 3), point3(1, 2, 3)), 0);
+        assert_eq!(taxicab_distance(point3(0, 0, 0), point3(0, 0, 5)), 5);
+        assert_eq!(taxicab_distance(point3(1, -2, 3), point3(-1, 2, -3)), 12);
+    }
+
+    #[test]
+    fn test_voxel_set() {
+        let pair: VoxelSet = [point3(1, 1, 1), point3(2, 1, 1)].into_iter().collect();
+        assert_eq!(pair.surface_area(), 10);
+        assert_eq!(pair.exterior_surface_area(), 10);
+        assert_eq!(
+            pair.bounding_box(),
+            Some(Bounds3::new(point3(1, 1, 1), point3(2, 1, 1)))
+        );
+
+        // A 3x3x3 cube with its middle missing has an inside face for each
+        // of the six cubes around the hole.
+        let hollow: VoxelSet = (0..27)
+            .map(|i| point3(i % 3, i / 3 % 3, i / 9))
+            .filter(|p| *p != point3(1, 1, 1))
+            .collect();
+        assert_eq!(hollow.surface_area(), 54 + 6);
+        assert_eq!(hollow.exterior_surface_area(), 54);
+
+        assert_eq!(VoxelSet::new().bounding_box(), None);
+        assert_eq!(VoxelSet::new().exterior_surface_area(), 0);
+    }
+
+    #[test]
+    fn test_exterior_faces() {
+        let cube: VoxelSet = [point3(1, 2, 3)].into_iter().collect();
+        let mut faces = cube.exterior_faces();
+        faces.sort_by_key(|(_, v)| FACES.iter().position(|f| f == v));
+        assert_eq!(faces, FACES.map(|v| (point3(1, 2, 3), v)),);
+    }
+
+    #[test]
+    fn test_components() {
+        // Two cubes touching along an edge aren't joined, one touching a
+        // face is.
+        let set: VoxelSet = [point3(0, 0, 0), point3(1, 1, 0), point3(1, 1, 1)]
+            .into_iter()
+            .collect();
+        let components = set.components();
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[0].len(), 2);
+        assert!(components[1].contains(point3(0, 0, 0)));
+        assert!(set.air_pockets().is_empty());
+        assert!(VoxelSet::new().components().is_empty());
+
+        // Two hollow cubes side by side, one with a bigger hole.
+        let hollow = |offset: Coord, size: Coord| {
+            (0..size.pow(3))
+                .map(move |i| point3(i % size, i / size % size, i / size / size))
+                .filter(move |p| [p.x, p.y, p.z].iter().any(|c| *c == 0 || *c == size - 1))
+                .map(move |p| p + vec3(offset, 0, 0))
+        };
+        let set: VoxelSet = hollow(0, 3).chain(hollow(10, 4)).collect();
+        assert_eq!(set.components().len(), 2);
+        let pockets = set.air_pockets();
+        assert_eq!(
+            pockets.iter().map(VoxelSet::len).collect::<Vec<_>>(),
+            [8, 1]
+        );
+        assert!(pockets[1].contains(point3(1, 1, 1)));
+        assert_eq!(
+            set.surface_area() - set.exterior_surface_area(),
+            pockets.iter().map(VoxelSet::surface_area).sum::<usize>()
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! Sets of integers stored as sorted, disjoint inclusive intervals.
+
+use std::{
+    fmt::Debug,
+    ops::{Add, RangeInclusive, Sub},
+};
+
+/// Integer types an [`IntervalSet`] can hold.
+pub trait Discrete: Copy + Ord + Debug + Add<Output = Self> + Sub<Output = Self> {
+    const ONE: Self;
+    const ZERO: Self;
+}
+
+macro_rules! impl_discrete {
+    ($($t:ty),*) => {
+        $(impl Discrete for $t {
+            const ONE: Self = 1;
+            const ZERO: Self = 0;
+        })*
+    };
+}
+
+impl_discrete!(i32, i64, i128, isize, u32, u64, usize);
+
+/// A set of integers, kept as the fewest inclusive intervals that cover it.
+/// Overlapping and adjacent intervals are merged as they're inserted, so
+/// `1..=3` and `4..=6` become `1..=6`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    intervals: Vec<RangeInclusive<T>>,
+}
+
+impl<T: Discrete> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self { intervals: vec![] }
+    }
+
+    /// The intervals in ascending order.
+    pub fn intervals(&self) -> &[RangeInclusive<T>] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Number of integers in the set.
+    pub fn len(&self) -> T {
+        self.intervals
+            .iter()
+            .fold(T::ZERO, |len, r| len + (*r.end() - *r.start()) + T::ONE)
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.intervals.iter().any(|r| r.contains(&value))
+    }
+
+    /// Add every integer in `range`. Empty ranges are ignored.
+    pub fn insert(&mut self, range: RangeInclusive<T>) {
+        if range.is_empty() {
+            return;
+        }
+        let (mut start, mut end) = range.into_inner();
+
+        // Intervals entirely before the new one, not even touching it, stay
+        // put; so do those entirely after it. Everything in between merges.
+        let first = self
+            .intervals
+            .partition_point(|r| *r.end() < start && *r.end() + T::ONE < start);
+        let last = self
+            .intervals
+            .partition_point(|r| *r.start() <= end || *r.start() - T::ONE <= end);
+        if first < last {
+            start = start.min(*self.intervals[first].start());
+            end = end.max(*self.intervals[last - 1].end());
+        }
+        self.intervals.splice(first..last, [start..=end]);
+    }
+
+    /// The union of this set and `other`.
+    pub fn merge(&self, other: &Self) -> Self {
+        let mut merged = self.clone();
+        for range in &other.intervals {
+            merged.insert(range.clone());
+        }
+        merged
+    }
+
+    /// The integers in both this set and `other`.
+    pub fn intersect(&self, other: &Self) -> Self {
+        let mut intervals = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a, b) = (&self.intervals[i], &other.intervals[j]);
+            let start = *a.start().max(b.start());
+            let end = *a.end().min(b.end());
+            if start <= end {
+                intervals.push(start..=end);
+            }
+            if a.end() < b.end() {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self { intervals }
+    }
+
+    /// The integers in `within` that aren't in this set.
+    pub fn complement(&self, within: RangeInclusive<T>) -> Self {
+        let mut intervals = vec![];
+        if within.is_empty() {
+            return Self { intervals };
+        }
+        let (mut next, end) = within.into_inner();
+        for range in &self.intervals {
+            if *range.end() < next {
+                continue;
+            }
+            if *range.start() > end {
+                break;
+            }
+            if *range.start() > next {
+                intervals.push(next..=*range.start() - T::ONE);
+            }
+            if *range.end() >= end {
+                return Self { intervals };
+            }
+            next = *range.end() + T::ONE;
+        }
+        intervals.push(next..=end);
+        Self { intervals }
+    }
+}
+
+impl<T: Discrete> FromIterator<RangeInclusive<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for range in iter {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+impl<T: Discrete> From<RangeInclusive<T>> for IntervalSet<T> {
+    fn from(range: RangeInclusive<T>) -> Self {
+        [range].into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn set(ranges: &[RangeInclusive<i64>]) -> IntervalSet<i64> {
+        ranges.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_insert_overlapping() {
+        let s = set(&[1..=5, 3..=8, 20..=25, 10..=12]);
+        assert_eq!(s.intervals(), &[1..=8, 10..=12, 20..=25]);
+        assert_eq!(s.len(), 8 + 3 + 6);
+    }
+
+    #[test]
+    fn test_insert_adjacent() {
+        assert_eq!(set(&[1..=3, 4..=6]).intervals(), &[1..=6]);
+        assert_eq!(set(&[4..=6, 1..=3]).intervals(), &[1..=6]);
+        assert_eq!(set(&[1..=3, 5..=6]).intervals(), &[1..=3, 5..=6]);
+        assert_eq!(set(&[1..=3, 5..=6, 4..=4]).intervals(), &[1..=6]);
+    }
+
+    #[test]
+    fn test_insert_spanning() {
+        let s = set(&[1..=2, 4..=5, 7..=8, 10..=11, 0..=8]);
+        assert_eq!(s.intervals(), &[0..=8, 10..=11]);
+    }
+
+    #[test]
+    fn test_single_points() {
+        let s = set(&[5..=5, 7..=7]);
+        assert_eq!(s.len(), 2);
+        assert!(s.contains(5));
+        assert!(!s.contains(6));
+        assert_eq!(s.complement(5..=7).intervals(), &[6..=6]);
+        assert_eq!(set(&[5..=5, 6..=6]).intervals(), &[5..=6]);
+    }
+
+    #[test]
+    fn test_empty() {
+        #[allow(clippy::reversed_empty_ranges)]
+        let s = set(&[3..=1]);
+        assert!(s.is_empty());
+        assert_eq!(s.len(), 0);
+        assert_eq!(s.complement(0..=4).intervals(), &[0..=4]);
+    }
+
+    #[test]
+    fn test_intersect() {
+        let a = set(&[0..=10, 20..=30]);
+        let b = set(&[5..=25, 30..=40]);
+        assert_eq!(a.intersect(&b).intervals(), &[5..=10, 20..=25, 30..=30]);
+        assert_eq!(a.intersect(&IntervalSet::new()), IntervalSet::new());
+    }
+
+    #[test]
+    fn test_merge() {
+        let a = set(&[0..=10, 20..=30]);
+        let b = set(&[11..=15, 31..=31]);
+        assert_eq!(a.merge(&b).intervals(), &[0..=15, 20..=31]);
+    }
+
+    #[test]
+    fn test_complement() {
+        let s = set(&[-5..=2, 5..=8, 12..=30]);
+        assert_eq!(s.complement(0..=20).intervals(), &[3..=4, 9..=11]);
+        assert_eq!(
+            s.complement(-10..=40).intervals(),
+            &[-10..=-6, 3..=4, 9..=11, 31..=40]
+        );
+        assert!(s.complement(5..=8).is_empty());
+    }
+
+    #[test]
+    fn test_unsigned_bounds() {
+        let s: IntervalSet<u64> = [0..=0, 2..=3].into_iter().collect();
+        assert_eq!(s.complement(0..=3).intervals(), &[1..=1]);
+        assert_eq!(s.len(), 3);
+    }
+}
@@ -0,0 +1,154 @@
+//! How much memory a run used. Allocations are only counted when built
+//! with the `profiling` feature, which installs a counting allocator for
+//! every binary; without it there's nothing to report.
+
+use serde::Serialize;
+use std::fmt;
+
+/// Memory used by the process so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Usage {
+    /// The most bytes allocated at any one time.
+    pub peak_bytes: usize,
+    pub allocations: usize,
+    /// The resident set size's high water mark, where the platform says.
+    pub peak_rss_kb: Option<u64>,
+}
+
+impl fmt::Display for Usage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mib = |bytes: f64| bytes / (1024.0 * 1024.0);
+        write!(
+            f,
+            "{:.1}MiB peak in {} allocations",
+            mib(self.peak_bytes as f64),
+            self.allocations
+        )?;
+        if let Some(kb) = self.peak_rss_kb {
+            write!(f, ", {:.1}MiB peak rss", mib(kb as f64 * 1024.0))?;
+        }
+        Ok(())
+    }
+}
+
+/// Usage so far, if allocations are being counted.
+pub fn usage() -> Option<Usage> {
+    counting::totals().map(|(peak_bytes, allocations)| Usage {
+        peak_bytes,
+        allocations,
+        peak_rss_kb: peak_rss_kb(),
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn peak_rss_kb() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn peak_rss_kb() -> Option<u64> {
+    None
+}
+
+#[cfg(feature = "profiling")]
+mod counting {
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    static CURRENT: AtomicUsize = AtomicUsize::new(0);
+    static PEAK: AtomicUsize = AtomicUsize::new(0);
+    static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+    /// The system allocator, keeping count of what's allocated.
+    struct Counting;
+
+    impl Counting {
+        fn grow(&self, bytes: usize) {
+            let current = CURRENT.fetch_add(bytes, Ordering::Relaxed) + bytes;
+            PEAK.fetch_max(current, Ordering::Relaxed);
+        }
+    }
+
+    // SAFETY: every call is passed straight on to the system allocator.
+    unsafe impl GlobalAlloc for Counting {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc(layout);
+            if !ptr.is_null() {
+                ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+                self.grow(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = System.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+                CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+                self.grow(new_size);
+            }
+            new_ptr
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: Counting = Counting;
+
+    /// Peak bytes and number of allocations.
+    pub fn totals() -> Option<(usize, usize)> {
+        Some((
+            PEAK.load(Ordering::Relaxed),
+            ALLOCATIONS.load(Ordering::Relaxed),
+        ))
+    }
+}
+
+#[cfg(not(feature = "profiling"))]
+mod counting {
+    pub fn totals() -> Option<(usize, usize)> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "profiling")]
+    #[test]
+    fn test_usage() {
+        let before = usage().unwrap();
+        let big = vec![0u8; 64 << 20];
+        let after = usage().unwrap();
+        assert!(after.peak_bytes >= big.len());
+        assert!(after.allocations > before.allocations);
+    }
+
+    #[cfg(not(feature = "profiling"))]
+    #[test]
+    fn test_usage() {
+        assert_eq!(usage(), None);
+    }
+
+    #[test]
+    fn test_display() {
+        let usage = Usage {
+            peak_bytes: 3 << 20,
+            allocations: 12,
+            peak_rss_kb: Some(5 * 1024),
+        };
+        assert_eq!(
+            usage.to_string(),
+            "3.0MiB peak in 12 allocations, 5.0MiB peak rss"
+        );
+    }
+}
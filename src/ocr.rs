@@ -0,0 +1,170 @@
+//! Reading the block letters some puzzles draw on a screen instead of
+//! printing an answer.
+
+/// Every glyph in the font is this many pixels tall.
+pub const GLYPH_HEIGHT: usize = 6;
+/// A glyph within this many pixels of a letter still counts as that letter.
+const TOLERANCE: usize = 2;
+
+/// The AoC screen font. Most letters are four pixels wide, but `I` and `Y`
+/// aren't, so glyphs are found by the blank columns between them rather than
+/// a fixed stride.
+const GLYPHS: &[(char, [&str; GLYPH_HEIGHT])] = &[
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', ["###", ".#.", ".#.", ".#.", ".#.", "###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', ["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// Anything other than '.' or a space is a lit pixel, so screens drawn with
+/// '█' or 'o' read the same as ones drawn with '#'.
+fn is_lit(c: char) -> bool {
+    !matches!(c, '.' | ' ')
+}
+
+type Pixels = Vec<Vec<bool>>;
+
+fn lit_at(pixels: &Pixels, row: usize, column: usize) -> bool {
+    pixels[row].get(column).copied().unwrap_or(false)
+}
+
+/// Number of pixels that differ between the glyph at `columns` of the screen
+/// and a letter of the font.
+fn distance(pixels: &Pixels, columns: (usize, usize), glyph: &[&str; GLYPH_HEIGHT]) -> usize {
+    let (start, width) = columns;
+    let glyph_width = glyph[0].len();
+    (0..GLYPH_HEIGHT)
+        .flat_map(|row| (0..width.max(glyph_width)).map(move |column| (row, column)))
+        .filter(|&(row, column)| {
+            let screen = column < width && lit_at(pixels, row, start + column);
+            let letter = matches!(glyph[row].chars().nth(column), Some(c) if is_lit(c));
+            screen != letter
+        })
+        .count()
+}
+
+/// Decode a screen of rows into letters, using '?' for any glyph that isn't
+/// close to a letter of the font. Blank rows above and below the letters are
+/// ignored.
+pub fn recognize(lines: &[String]) -> String {
+    let pixels: Pixels = lines
+        .iter()
+        .skip_while(|line| !line.chars().any(is_lit))
+        .take(GLYPH_HEIGHT)
+        .map(|line| line.chars().map(is_lit).collect())
+        .chain(std::iter::repeat(vec![]))
+        .take(GLYPH_HEIGHT)
+        .collect();
+
+    let width = pixels.iter().map(Vec::len).max().unwrap_or(0);
+    let blank_column = |column| (0..GLYPH_HEIGHT).all(|row| !lit_at(&pixels, row, column));
+
+    let mut glyphs = vec![];
+    let mut start = None;
+    for column in 0..=width {
+        match (start, column == width || blank_column(column)) {
+            (None, false) => start = Some(column),
+            (Some(first), true) => {
+                glyphs.push((first, column - first));
+                start = None;
+            }
+            _ => (),
+        }
+    }
+
+    glyphs
+        .into_iter()
+        .map(|columns| {
+            GLYPHS
+                .iter()
+                .map(|(letter, glyph)| (distance(&pixels, columns, glyph), *letter))
+                .min()
+                .filter(|(distance, _)| *distance <= TOLERANCE)
+                .map_or('?', |(_, letter)| letter)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn screen(rows: &[&str]) -> Vec<String> {
+        rows.iter().map(|row| row.to_string()).collect()
+    }
+
+    #[test]
+    fn test_alphabet() {
+        let alphabet = screen(&[
+            ".##..###...##..####.####..##..#..#.###...##.#..#.#.....##..###..###...###.#..#.#...#.####",
+            "#..#.#..#.#..#.#....#....#..#.#..#..#.....#.#.#..#....#..#.#..#.#..#.#....#..#.#...#....#",
+            "#..#.###..#....###..###..#....####..#.....#.##...#....#..#.#..#.#..#.#....#..#..#.#....#.",
+            "####.#..#.#....#....#....#.##.#..#..#.....#.#.#..#....#..#.###..###...##..#..#...#....#..",
+            "#..#.#..#.#..#.#....#....#..#.#..#..#..#..#.#.#..#....#..#.#....#.#.....#.#..#...#...#...",
+            "#..#.###...##..####.#.....###.#..#.###..##..#..#.####..##..#....#..#.###...##....#...####",
+        ]);
+        assert_eq!(recognize(&alphabet), "ABCEFGHIJKLOPRSUYZ");
+    }
+
+    #[test]
+    fn test_day10_screen() {
+        let crt = screen(&[
+            "###...##..###..#..#.###..####..##..###..",
+            "#..#.#..#.#..#.#..#.#..#.#....#..#.#..#.",
+            "#..#.#....#..#.####.###..###..#..#.###..",
+            "###..#.##.###..#..#.#..#.#....####.#..#.",
+            "#....#..#.#....#..#.#..#.#....#..#.#..#.",
+            "#.....###.#....#..#.###..####.#..#.###..",
+        ]);
+        assert_eq!(recognize(&crt), "PGPHBEAB");
+    }
+
+    #[test]
+    fn test_other_pixel_chars() {
+        let crt = screen(&[
+            "",
+            "████ █  █",
+            "█    █  █",
+            "███  ████",
+            "█    █  █",
+            "█    █  █",
+            "█    █  █",
+        ]);
+        assert_eq!(recognize(&crt), "FH");
+    }
+
+    #[test]
+    fn test_noise() {
+        let crt = screen(&[
+            ".##..###.",
+            "#..#.#..#",
+            "#..#.##..",
+            "###..#..#",
+            "#..#.#..#",
+            "#..#.###.",
+        ]);
+        assert_eq!(recognize(&crt), "AB");
+    }
+
+    #[test]
+    fn test_unknown_glyph() {
+        let crt = screen(&[
+            "#.#.####", ".#..#..#", "#.#.#..#", "....#..#", "....#..#", "....####",
+        ]);
+        assert_eq!(recognize(&crt), "??");
+    }
+}
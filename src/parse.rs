@@ -0,0 +1,208 @@
+//! Helpers for turning puzzle input into values, with errors that say
+//! where the input went wrong.
+
+use anyhow::{anyhow, ensure, Context, Error};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use std::str::FromStr;
+
+/// Parse each non-blank line of `s` with `parse`. Errors name the line
+/// number and quote the line.
+pub fn parse_lines_with<T>(
+    s: &str,
+    mut parse: impl FnMut(&str) -> Result<T, Error>,
+) -> Result<Vec<T>, Error> {
+    s.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| parse(line).with_context(|| format!("line {}: {line:?}", i + 1)))
+        .collect()
+}
+
+/// Parse each non-blank line of `s` as a `T`.
+pub fn parse_lines<T>(s: &str) -> Result<Vec<T>, Error>
+where
+    T: FromStr,
+    T::Err: Into<Error>,
+{
+    parse_lines_with(s, |line| line.parse().map_err(Into::into))
+}
+
+/// Every integer in `s`, with its sign, ignoring whatever is around them.
+pub fn ints(s: &str) -> Vec<i64> {
+    static INT_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"-?\d+").expect("regex"));
+    INT_RE
+        .find_iter(s)
+        .filter_map(|m| m.as_str().parse().ok())
+        .collect()
+}
+
+/// The parts of `s` between blank lines, without their trailing newlines.
+/// Runs of blank lines, and ones at either end, don't make empty blocks.
+pub fn blocks(s: &str) -> Vec<&str> {
+    let mut blocks = vec![];
+    let mut start = None;
+    let mut end = 0;
+    let mut offset = 0;
+    for line in s.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            if let Some(start) = start.take() {
+                blocks.push(&s[start..end]);
+            }
+        } else {
+            start.get_or_insert(offset);
+            end = offset + line.trim_end_matches(['\n', '\r']).len();
+        }
+        offset += line.len();
+    }
+    if let Some(start) = start {
+        blocks.push(&s[start..end]);
+    }
+    blocks
+}
+
+/// `re`'s captures in `s`, which must have a group for each of `fields`.
+/// Used by [`regex_struct!`](crate::regex_struct).
+#[doc(hidden)]
+pub fn captures<'a>(re: &Regex, s: &'a str, fields: usize) -> Result<Captures<'a>, Error> {
+    ensure!(
+        re.captures_len() == fields + 1,
+        "/{re}/ has {} groups for {fields} fields",
+        re.captures_len() - 1
+    );
+    re.captures(s.trim())
+        .ok_or_else(|| anyhow!("doesn't match /{re}/"))
+}
+
+/// Capture group `group` parsed as the `T` for the field `name`.
+#[doc(hidden)]
+pub fn capture<T>(captures: &Captures, group: usize, name: &str) -> Result<T, Error>
+where
+    T: FromStr,
+    T::Err: Into<Error>,
+{
+    let text = captures
+        .get(group)
+        .ok_or_else(|| anyhow!("no {name}"))?
+        .as_str();
+    text.parse()
+        .map_err(Into::into)
+        .with_context(|| format!("{name} {text:?}"))
+}
+
+/// A struct whose fields are read from a regex's capture groups, in order,
+/// each with its own `FromStr`. The regex is compiled once.
+///
+/// ```
+/// advent_of_code_2022::regex_struct! {
+///     struct Move = r"move (\d+) from (\d+) to (\d+)" {
+///         count: usize,
+///         from: usize,
+///         to: usize,
+///     }
+/// }
+///
+/// let step: Move = "move 3 from 1 to 2".parse().unwrap();
+/// assert_eq!((step.count, step.from, step.to), (3, 1, 2));
+/// ```
+#[macro_export]
+macro_rules! regex_struct {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident = $re:literal {
+            $($(#[$field_meta:meta])* $field_vis:vis $field:ident: $ty:ty),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $($(#[$field_meta])* $field_vis $field: $ty),+
+        }
+
+        impl ::std::str::FromStr for $name {
+            type Err = ::anyhow::Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                static RE: ::once_cell::sync::Lazy<::regex::Regex> =
+                    ::once_cell::sync::Lazy::new(|| ::regex::Regex::new($re).expect("regex"));
+                let fields = [$(stringify!($field)),+];
+                let captures = $crate::parse::captures(&RE, s, fields.len())?;
+                let mut group = 0;
+                $(
+                    group += 1;
+                    let $field = $crate::parse::capture(&captures, group, stringify!($field))?;
+                )+
+                Ok(Self { $($field),+ })
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_lines() {
+        assert_eq!(parse_lines::<i32>("1\n\n-2\n").unwrap(), [1, -2]);
+        let error = parse_lines::<i32>("1\n2\nx3").unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "line 3: \"x3\": invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn test_ints() {
+        assert_eq!(
+            ints("Sensor at x=2, y=-18: closest beacon is at x=-2, y=15"),
+            [2, -18, -2, 15]
+        );
+        assert_eq!(ints("a-b 7-9"), [7, -9]);
+        assert!(ints("no numbers").is_empty());
+    }
+
+    #[test]
+    fn test_blocks() {
+        assert_eq!(blocks("1\n2\n\n3\n"), ["1\n2", "3"]);
+        assert_eq!(blocks("\n\n1\n  \n\n\n2"), ["1", "2"]);
+        assert_eq!(blocks("1\r\n\r\n2\r\n"), ["1", "2"]);
+        assert!(blocks("\n \n").is_empty());
+    }
+
+    regex_struct! {
+        #[derive(Debug, PartialEq)]
+        struct Reading = r"^(\w+) at (-?\d+)$" {
+            name: String,
+            value: i32,
+        }
+    }
+
+    regex_struct! {
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        struct TooFew = r"(\d+) and (\d+)" {
+            first: u8,
+        }
+    }
+
+    #[test]
+    fn test_regex_struct() {
+        assert_eq!(
+            " gauge at -4\n".parse::<Reading>().unwrap(),
+            Reading {
+                name: "gauge".to_string(),
+                value: -4,
+            }
+        );
+        let error = "gauge at 4000000000".parse::<Reading>().unwrap_err();
+        assert!(format!("{error:#}").starts_with("value \"4000000000\": "));
+        let error = "gauge is 4".parse::<Reading>().unwrap_err();
+        assert_eq!(format!("{error:#}"), r"doesn't match /^(\w+) at (-?\d+)$/");
+        let error = "1 and 2".parse::<TooFew>().unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            r"/(\d+) and (\d+)/ has 2 groups for 1 fields"
+        );
+        assert!(parse_lines::<Reading>("a at 1\n\nb at 2\n").is_ok());
+    }
+}
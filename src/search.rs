@@ -0,0 +1,589 @@
+//! Breadth-first, Dijkstra and A* searches that report each node they
+//! expand to an [`Observer`], so a slow search can be watched instead of
+//! guessed at. Pass `&mut ()` to search without watching.
+
+use crate::grid::{chebyshev_distance, taxicab_distance};
+use anyhow::{bail, Error};
+use std::{
+    cmp::Ordering,
+    collections::{hash_map::Entry, BinaryHeap, HashMap, HashSet, VecDeque},
+    fmt::Write,
+    hash::Hash,
+    ops::{Add, Sub},
+    str::FromStr,
+};
+use tracing::debug;
+
+/// Where a search is when it expands `node`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress<'a, N, C> {
+    pub node: &'a N,
+    /// Cost of the cheapest known path to `node`.
+    pub cost: C,
+    /// `cost` plus the heuristic; the lowest total any path through the
+    /// frontier can still have. Equal to `cost` for BFS and Dijkstra.
+    pub best: C,
+    /// Nodes expanded so far, including this one.
+    pub expanded: usize,
+    /// Nodes waiting to be expanded.
+    pub frontier: usize,
+}
+
+/// Watches a search. Closures taking a [`Progress`] are observers, `()` is
+/// one that ignores everything, and `None` lets the caller decide at run
+/// time whether to watch.
+pub trait Observer<N, C> {
+    fn expand(&mut self, progress: &Progress<N, C>);
+}
+
+impl<N, C> Observer<N, C> for () {
+    fn expand(&mut self, _progress: &Progress<N, C>) {}
+}
+
+impl<N, C, O: Observer<N, C>> Observer<N, C> for Option<O> {
+    fn expand(&mut self, progress: &Progress<N, C>) {
+        if let Some(observer) = self {
+            observer.expand(progress);
+        }
+    }
+}
+
+impl<N, C, F: FnMut(&Progress<N, C>)> Observer<N, C> for F {
+    fn expand(&mut self, progress: &Progress<N, C>) {
+        self(progress)
+    }
+}
+
+fn path_to<N: Eq + Hash + Clone>(parents: &HashMap<N, Option<N>>, end: &N) -> Vec<N> {
+    let mut path = vec![end.clone()];
+    while let Some(Some(parent)) = parents.get(path.last().expect("path")) {
+        path.push(parent.clone());
+    }
+    path.reverse();
+    path
+}
+
+/// Shortest path, counted in steps, from `start` to a node where `success`
+/// is true. The path includes both ends.
+pub fn bfs<N, FN, IN, FS>(
+    start: &N,
+    mut successors: FN,
+    mut success: FS,
+    observer: &mut impl Observer<N, usize>,
+) -> Option<Vec<N>>
+where
+    N: Eq + Hash + Clone,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+    FS: FnMut(&N) -> bool,
+{
+    let mut parents = HashMap::from([(start.clone(), None)]);
+    let mut queue = VecDeque::from([(start.clone(), 0)]);
+    let mut expanded = 0;
+    while let Some((node, steps)) = queue.pop_front() {
+        expanded += 1;
+        observer.expand(&Progress {
+            node: &node,
+            cost: steps,
+            best: steps,
+            expanded,
+            frontier: queue.len(),
+        });
+        if success(&node) {
+            return Some(path_to(&parents, &node));
+        }
+        for next in successors(&node) {
+            if let Entry::Vacant(entry) = parents.entry(next.clone()) {
+                entry.insert(Some(node.clone()));
+                queue.push_back((next, steps + 1));
+            }
+        }
+    }
+    None
+}
+
+/// Every node reachable from `start`, including `start`.
+pub fn reachable<N, FN, IN>(
+    start: &N,
+    successors: FN,
+    observer: &mut impl Observer<N, usize>,
+) -> HashSet<N>
+where
+    N: Eq + Hash + Clone,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+{
+    let mut seen = HashSet::new();
+    bfs(
+        start,
+        successors,
+        |node| {
+            seen.insert(node.clone());
+            false
+        },
+        observer,
+    );
+    seen
+}
+
+/// A distance on a grid to estimate how far a search still has to go.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Heuristic {
+    /// Steps along the axes, for moves up, down, left and right.
+    #[default]
+    Taxicab,
+    /// The longer of the two axes, for when diagonal moves are allowed. It
+    /// never overestimates a taxicab distance either, just guides less.
+    Chebyshev,
+    /// No estimate, which makes A* Dijkstra.
+    Zero,
+}
+
+impl Heuristic {
+    pub fn estimate<T, U>(&self, from: euclid::Point2D<T, U>, to: euclid::Point2D<T, U>) -> T
+    where
+        T: Copy + Default + PartialOrd + Add<Output = T> + Sub<Output = T>,
+    {
+        match self {
+            Self::Taxicab => taxicab_distance(from, to),
+            Self::Chebyshev => chebyshev_distance(from, to),
+            Self::Zero => T::default(),
+        }
+    }
+}
+
+impl FromStr for Heuristic {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "taxicab" => Self::Taxicab,
+            "chebyshev" => Self::Chebyshev,
+            "zero" => Self::Zero,
+            _ => bail!("unknown heuristic {s:?}, expected taxicab, chebyshev or zero"),
+        })
+    }
+}
+
+/// Which of the nodes with the lowest estimate A* expands first. It can't
+/// change the cost found, only which of several equally cheap paths, and
+/// how many nodes it takes to get there.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TieBreak {
+    /// The one with the most cost behind it, so least still to go.
+    #[default]
+    Deepest,
+    /// The one with the least cost behind it.
+    Shallowest,
+    /// The one found first.
+    Oldest,
+}
+
+impl FromStr for TieBreak {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "deepest" => Self::Deepest,
+            "shallowest" => Self::Shallowest,
+            "oldest" => Self::Oldest,
+            _ => bail!("unknown tie break {s:?}, expected deepest, shallowest or oldest"),
+        })
+    }
+}
+
+/// An entry in the A* open set, ordered so the heap pops the lowest
+/// estimate first and, among equals, the one `tie_break` prefers.
+struct Open<C> {
+    estimate: C,
+    cost: C,
+    index: usize,
+    tie_break: TieBreak,
+}
+
+impl<C: Ord> PartialEq for Open<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<C: Ord> Eq for Open<C> {}
+
+impl<C: Ord> PartialOrd for Open<C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C: Ord> Ord for Open<C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then_with(|| match self.tie_break {
+                TieBreak::Deepest => self.cost.cmp(&other.cost),
+                TieBreak::Shallowest => other.cost.cmp(&self.cost),
+                TieBreak::Oldest => other.index.cmp(&self.index),
+            })
+    }
+}
+
+/// Cheapest path from `start` to a node where `success` is true, and its
+/// cost. `heuristic` must never overestimate the remaining cost. Costs
+/// start from `C::default()`, which is zero for the integer types.
+pub fn astar<N, C, FN, IN, FH, FS>(
+    start: &N,
+    successors: FN,
+    heuristic: FH,
+    success: FS,
+    observer: &mut impl Observer<N, C>,
+) -> Option<(Vec<N>, C)>
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FH: FnMut(&N) -> C,
+    FS: FnMut(&N) -> bool,
+{
+    astar_with(
+        start,
+        successors,
+        heuristic,
+        success,
+        TieBreak::default(),
+        observer,
+    )
+}
+
+/// [`astar`], breaking ties between equal estimates by `tie_break`.
+///
+/// Debug builds check the heuristic against each node on the path found,
+/// whose true remaining cost is known by then, and panic if it
+/// overestimated.
+pub fn astar_with<N, C, FN, IN, FH, FS>(
+    start: &N,
+    mut successors: FN,
+    mut heuristic: FH,
+    mut success: FS,
+    tie_break: TieBreak,
+    observer: &mut impl Observer<N, C>,
+) -> Option<(Vec<N>, C)>
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FH: FnMut(&N) -> C,
+    FS: FnMut(&N) -> bool,
+{
+    // Nodes are numbered in the order they're found; `nodes` holds each
+    // one's parent and cheapest known cost.
+    let mut index: HashMap<N, usize> = HashMap::from([(start.clone(), 0)]);
+    let mut nodes: Vec<(N, Option<usize>, C)> = vec![(start.clone(), None, C::default())];
+    let mut open = BinaryHeap::from([Open {
+        estimate: heuristic(start),
+        cost: C::default(),
+        index: 0,
+        tie_break,
+    }]);
+    let mut expanded = 0;
+    while let Some(Open {
+        estimate,
+        cost,
+        index: current,
+        ..
+    }) = open.pop()
+    {
+        if cost > nodes[current].2 {
+            // Superseded by a cheaper path found after this was queued.
+            continue;
+        }
+        let node = nodes[current].0.clone();
+        expanded += 1;
+        observer.expand(&Progress {
+            node: &node,
+            cost,
+            best: estimate,
+            expanded,
+            frontier: open.len(),
+        });
+        if success(&node) {
+            let mut path = vec![];
+            let mut at = Some(current);
+            while let Some(i) = at {
+                let (node, parent, so_far) = &nodes[i];
+                debug_assert!(
+                    *so_far + heuristic(node) <= cost,
+                    "the heuristic overestimated the cost from a node on the path"
+                );
+                path.push(node.clone());
+                at = *parent;
+            }
+            path.reverse();
+            return Some((path, cost));
+        }
+        for (next, step) in successors(&node) {
+            let next_cost = cost + step;
+            let next_index = match index.entry(next) {
+                Entry::Vacant(entry) => {
+                    let h = heuristic(entry.key());
+                    nodes.push((entry.key().clone(), Some(current), next_cost));
+                    entry.insert(nodes.len() - 1);
+                    open.push(Open {
+                        estimate: next_cost + h,
+                        cost: next_cost,
+                        index: nodes.len() - 1,
+                        tie_break,
+                    });
+                    continue;
+                }
+                Entry::Occupied(entry) => *entry.get(),
+            };
+            if next_cost < nodes[next_index].2 {
+                nodes[next_index].1 = Some(current);
+                nodes[next_index].2 = next_cost;
+                let h = heuristic(&nodes[next_index].0);
+                open.push(Open {
+                    estimate: next_cost + h,
+                    cost: next_cost,
+                    index: next_index,
+                    tie_break,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// [`astar`] without a heuristic.
+pub fn dijkstra<N, C, FN, IN, FS>(
+    start: &N,
+    successors: FN,
+    success: FS,
+    observer: &mut impl Observer<N, C>,
+) -> Option<(Vec<N>, C)>
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FS: FnMut(&N) -> bool,
+{
+    astar(start, successors, |_| C::default(), success, observer)
+}
+
+/// An observer for searches over a grid. It counts how often each cell is
+/// expanded and logs a heat map of them at debug level every `every`
+/// expansions, so the search can be watched spreading with `-v`.
+pub struct GridTrace<N> {
+    width: usize,
+    height: usize,
+    cell: fn(&N) -> (usize, usize),
+    every: usize,
+    visits: Vec<usize>,
+    current: Option<(usize, usize)>,
+}
+
+impl<N> GridTrace<N> {
+    /// Watch a search over a `width` by `height` grid, where `cell` gives
+    /// the column and row each node is in.
+    pub fn new(width: usize, height: usize, cell: fn(&N) -> (usize, usize), every: usize) -> Self {
+        Self {
+            width,
+            height,
+            cell,
+            every: every.max(1),
+            visits: vec![0; width * height],
+            current: None,
+        }
+    }
+
+    /// How many times the node at column `x`, row `y` has been expanded.
+    pub fn visits(&self, x: usize, y: usize) -> usize {
+        self.visits[y * self.width + x]
+    }
+
+    /// The grid with `.` for cells never expanded, `1` to `9` for how many
+    /// times the others were, `*` for more than that, and `@` where the
+    /// search is now.
+    pub fn render(&self) -> String {
+        let mut s = String::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let c = match self.visits(x, y) {
+                    _ if self.current == Some((x, y)) => '@',
+                    0 => '.',
+                    n @ 1..=9 => char::from_digit(n as u32, 10).expect("digit"),
+                    _ => '*',
+                };
+                s.push(c);
+            }
+            s.push('\n');
+        }
+        s
+    }
+}
+
+impl<N, C: std::fmt::Debug> Observer<N, C> for GridTrace<N> {
+    fn expand(&mut self, progress: &Progress<N, C>) {
+        let (x, y) = (self.cell)(progress.node);
+        if x < self.width && y < self.height {
+            self.visits[y * self.width + x] += 1;
+        }
+        self.current = Some((x, y));
+        if progress.expanded.is_multiple_of(self.every) {
+            let mut frame = String::new();
+            writeln!(
+                frame,
+                "expanded {} frontier {} cost {:?} best {:?}",
+                progress.expanded, progress.frontier, progress.cost, progress.best
+            )
+            .expect("write to string");
+            frame.push_str(&self.render());
+            debug!("\n{frame}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Steps on a 5x5 grid with a wall down column 2 that's open at row 4.
+    fn grid_successors(&(x, y): &(usize, usize)) -> Vec<(usize, usize)> {
+        let mut next = vec![];
+        if x > 0 {
+            next.push((x - 1, y));
+        }
+        if x < 4 {
+            next.push((x + 1, y));
+        }
+        if y > 0 {
+            next.push((x, y - 1));
+        }
+        if y < 4 {
+            next.push((x, y + 1));
+        }
+        next.retain(|&(x, y)| x != 2 || y == 4);
+        next
+    }
+
+    #[test]
+    fn test_bfs() {
+        let path = bfs(&(0, 0), grid_successors, |p| *p == (4, 0), &mut ()).unwrap();
+        assert_eq!(path.len() - 1, 12);
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&(4, 0)));
+        assert!(path.contains(&(2, 4)));
+        assert_eq!(bfs(&(0, 0), grid_successors, |_| false, &mut ()), None);
+    }
+
+    #[test]
+    fn test_reachable() {
+        assert_eq!(reachable(&(0, 0), grid_successors, &mut ()).len(), 21);
+        let island = reachable(&0, |n: &i32| [(n + 1) % 3], &mut ());
+        assert_eq!(island, HashSet::from([0, 1, 2]));
+    }
+
+    #[test]
+    fn test_astar() {
+        let step = |p: &(usize, usize)| grid_successors(p).into_iter().map(|n| (n, 1));
+        let distance = |&(x, y): &(usize, usize)| 4 - x + y;
+        let mut expansions = vec![];
+        let (path, cost) = astar(
+            &(0, 0),
+            step,
+            distance,
+            |p| *p == (4, 0),
+            &mut |progress: &Progress<_, usize>| {
+                assert!(progress.best >= progress.cost);
+                expansions.push(progress.best);
+            },
+        )
+        .unwrap();
+        assert_eq!(cost, 12);
+        assert_eq!(path.len(), 13);
+        // A consistent heuristic never lets the estimate go down.
+        assert!(expansions.windows(2).all(|w| w[0] <= w[1]));
+
+        let (dijkstra_path, dijkstra_cost) =
+            dijkstra(&(0, 0), step, |p| *p == (4, 0), &mut ()).unwrap();
+        assert_eq!(dijkstra_cost, cost);
+        assert_eq!(dijkstra_path.len(), path.len());
+    }
+
+    #[test]
+    fn test_tie_break() {
+        let step = |p: &(usize, usize)| grid_successors(p).into_iter().map(|n| (n, 1));
+        let mut expanded = vec![];
+        for tie_break in [TieBreak::Deepest, TieBreak::Shallowest, TieBreak::Oldest] {
+            let mut count = 0;
+            let (path, cost) = astar_with(
+                &(0, 0),
+                step,
+                |&(x, y): &(usize, usize)| 4 - x + y,
+                |p| *p == (4, 0),
+                tie_break,
+                &mut |_: &Progress<_, usize>| count += 1,
+            )
+            .unwrap();
+            assert_eq!(cost, 12, "{tie_break:?}");
+            assert_eq!(path.len(), 13, "{tie_break:?}");
+            expanded.push(count);
+        }
+        // Going deepest first follows one path to the goal rather than
+        // widening every path with the same estimate.
+        assert!(expanded[0] < expanded[1], "{expanded:?}");
+        assert_eq!("oldest".parse::<TieBreak>().unwrap(), TieBreak::Oldest);
+        assert!("newest".parse::<TieBreak>().is_err());
+    }
+
+    #[test]
+    fn test_heuristic() {
+        let (p, q) = (
+            euclid::default::Point2D::new(1, 5),
+            euclid::default::Point2D::new(4, 1),
+        );
+        assert_eq!(Heuristic::Taxicab.estimate(p, q), 7);
+        assert_eq!(Heuristic::Chebyshev.estimate(p, q), 4);
+        assert_eq!(Heuristic::Zero.estimate(p, q), 0);
+        assert_eq!(
+            "chebyshev".parse::<Heuristic>().unwrap(),
+            Heuristic::Chebyshev
+        );
+        assert!("euclid".parse::<Heuristic>().is_err());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "heuristic overestimated")]
+    fn test_inadmissible() {
+        let step = |p: &(usize, usize)| grid_successors(p).into_iter().map(|n| (n, 1));
+        astar(&(0, 0), step, |_| 100, |p| *p == (4, 0), &mut ());
+    }
+
+    #[test]
+    fn test_astar_cheaper_later() {
+        // The direct edge to 2 is found first but the detour is cheaper.
+        let successors = |n: &u8| match n {
+            0 => vec![(2, 10), (1, 1)],
+            1 => vec![(2, 1)],
+            _ => vec![],
+        };
+        let (path, cost) = dijkstra(&0, successors, |n| *n == 2, &mut ()).unwrap();
+        assert_eq!(path, [0, 1, 2]);
+        assert_eq!(cost, 2);
+    }
+
+    #[test]
+    fn test_grid_trace() {
+        let mut trace = GridTrace::new(5, 5, |p: &(usize, usize)| *p, 1000);
+        bfs(&(0, 0), grid_successors, |p| *p == (1, 1), &mut trace);
+        assert_eq!(trace.visits(0, 0), 1);
+        assert_eq!(trace.visits(4, 4), 0);
+        let render = trace.render();
+        assert_eq!(render.lines().count(), 5);
+        assert!(render.starts_with("11...\n1@...\n"));
+    }
+}
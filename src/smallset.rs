@@ -0,0 +1,331 @@
+//! Sets of small IDs kept as the bits of one integer, for days where
+//! everything worth tracking can be numbered from zero: valves, robot
+//! types, directions. [`Ids`] does the numbering.
+
+use anyhow::{bail, Error};
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug},
+    hash::Hash,
+    marker::PhantomData,
+    ops::{BitAnd, BitOr, Not},
+};
+
+/// An integer an [`IdSet`] can keep its bits in.
+pub trait Word:
+    Copy + Default + Eq + Hash + Ord + BitAnd<Output = Self> + BitOr<Output = Self> + Not<Output = Self>
+{
+    /// The most IDs a set can hold.
+    const CAPACITY: usize;
+
+    /// Just the bit for `id`.
+    fn bit(id: usize) -> Self;
+
+    /// The bits as an index into a table with an entry for every set.
+    fn index(self) -> usize;
+
+    fn count(self) -> usize;
+
+    /// The lowest ID in a non-empty set.
+    fn lowest(self) -> usize;
+}
+
+macro_rules! impl_word {
+    ($($t:ty),*) => {
+        $(
+            impl Word for $t {
+                const CAPACITY: usize = <$t>::BITS as usize;
+
+                fn bit(id: usize) -> Self {
+                    1 << id
+                }
+
+                fn index(self) -> usize {
+                    usize::try_from(self).expect("set too big to index")
+                }
+
+                fn count(self) -> usize {
+                    self.count_ones() as usize
+                }
+
+                fn lowest(self) -> usize {
+                    self.trailing_zeros() as usize
+                }
+            }
+        )*
+    };
+}
+
+impl_word!(u64, u128);
+
+/// A set of IDs below `W::CAPACITY`. Inserting or looking for a bigger ID
+/// panics, since it means whatever handed out the IDs has outgrown the
+/// set.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct IdSet<W: Word = u64>(W);
+
+impl<W: Word> IdSet<W> {
+    /// The most IDs a set can hold.
+    pub const CAPACITY: usize = W::CAPACITY;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn bit(id: usize) -> W {
+        assert!(
+            id < W::CAPACITY,
+            "ID {id} doesn't fit a set of {}",
+            W::CAPACITY
+        );
+        W::bit(id)
+    }
+
+    /// Add `id`, returning whether it's new.
+    pub fn insert(&mut self, id: usize) -> bool {
+        let added = !self.contains(id);
+        self.0 = self.0 | Self::bit(id);
+        added
+    }
+
+    /// This set with `id` in it too.
+    pub fn with(mut self, id: usize) -> Self {
+        self.insert(id);
+        self
+    }
+
+    /// Take out `id`, returning whether it was there.
+    pub fn remove(&mut self, id: usize) -> bool {
+        let removed = self.contains(id);
+        self.0 = self.0 & !Self::bit(id);
+        removed
+    }
+
+    pub fn contains(&self, id: usize) -> bool {
+        self.0 & Self::bit(id) != W::default()
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    pub fn is_disjoint(self, other: Self) -> bool {
+        self.intersection(other).is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == W::default()
+    }
+
+    /// The IDs in increasing order.
+    pub fn iter(&self) -> Iter<W> {
+        Iter(self.0)
+    }
+
+    /// Where this set goes in a table with an entry for each set, in which
+    /// a set comes after all of its subsets.
+    pub fn index(&self) -> usize {
+        self.0.index()
+    }
+}
+
+impl<W: Word> BitOr for IdSet<W> {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        self.union(other)
+    }
+}
+
+impl<W: Word> FromIterator<usize> for IdSet<W> {
+    fn from_iter<I: IntoIterator<Item = usize>>(ids: I) -> Self {
+        let mut set = Self::new();
+        set.extend(ids);
+        set
+    }
+}
+
+impl<W: Word> Extend<usize> for IdSet<W> {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, ids: I) {
+        for id in ids {
+            self.insert(id);
+        }
+    }
+}
+
+impl<W: Word> IntoIterator for IdSet<W> {
+    type Item = usize;
+    type IntoIter = Iter<W>;
+
+    fn into_iter(self) -> Iter<W> {
+        self.iter()
+    }
+}
+
+impl<W: Word> Debug for IdSet<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// The IDs in an [`IdSet`], lowest first.
+#[derive(Debug, Clone)]
+pub struct Iter<W>(W);
+
+impl<W: Word> Iterator for Iter<W> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.0 == W::default() {
+            return None;
+        }
+        let id = self.0.lowest();
+        self.0 = self.0 & !W::bit(id);
+        Some(id)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.count();
+        (len, Some(len))
+    }
+}
+
+impl<W: Word> ExactSizeIterator for Iter<W> {}
+
+/// Numbers keys from zero in the order they're first seen, so sets of them
+/// can be `IdSet<W>`s. It won't number more keys than those sets can hold.
+#[derive(Debug, Clone)]
+pub struct Ids<K, W: Word = u64> {
+    ids: HashMap<K, usize>,
+    keys: Vec<K>,
+    word: PhantomData<W>,
+}
+
+impl<K, W: Word> Default for Ids<K, W> {
+    fn default() -> Self {
+        Self {
+            ids: HashMap::new(),
+            keys: Vec::new(),
+            word: PhantomData,
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash, W: Word> Ids<K, W> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number each of `keys`.
+    pub fn from_keys(keys: impl IntoIterator<Item = K>) -> Result<Self, Error> {
+        let mut ids = Self::new();
+        for key in keys {
+            ids.intern(key)?;
+        }
+        Ok(ids)
+    }
+
+    /// The ID for `key`, giving it the next one if it hasn't got one yet,
+    /// unless the sets are already full.
+    pub fn intern(&mut self, key: K) -> Result<usize, Error> {
+        if let Some(&id) = self.ids.get(&key) {
+            return Ok(id);
+        }
+        let id = self.keys.len();
+        if id == W::CAPACITY {
+            bail!("a set can only hold {} IDs", W::CAPACITY);
+        }
+        self.ids.insert(key.clone(), id);
+        self.keys.push(key);
+        Ok(id)
+    }
+
+    pub fn get(&self, key: &K) -> Option<usize> {
+        self.ids.get(key).copied()
+    }
+
+    /// The key numbered `id`.
+    pub fn key(&self, id: usize) -> &K {
+        &self.keys[id]
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// The keys in ID order.
+    pub fn keys(&self) -> &[K] {
+        &self.keys
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_id_set() {
+        let mut set = IdSet::<u64>::new();
+        assert!(set.is_empty());
+        assert!(set.insert(3));
+        assert!(!set.insert(3));
+        assert!(set.insert(63));
+        assert!(set.insert(0));
+        assert_eq!(set.len(), 3);
+        assert!(set.contains(63) && !set.contains(62));
+        assert_eq!(set.iter().collect::<Vec<_>>(), [0, 3, 63]);
+        assert_eq!(format!("{set:?}"), "{0, 3, 63}");
+        assert!(set.remove(3));
+        assert!(!set.remove(3));
+        assert_eq!(set.with(5).len(), 3);
+
+        let other: IdSet = [1, 63].into_iter().collect();
+        assert_eq!(set | other, [0, 1, 63].into_iter().collect());
+        assert_eq!(set.intersection(other).iter().collect::<Vec<_>>(), [63]);
+        assert!(!set.is_disjoint(other));
+
+        assert_eq!(IdSet::<u64>::new().index(), 0);
+        assert_eq!(other.index(), (1 << 1) + (1 << 63));
+
+        let mut wide = IdSet::<u128>::new();
+        wide.insert(100);
+        assert_eq!(wide.into_iter().collect::<Vec<_>>(), [100]);
+        assert_eq!(IdSet::<u128>::CAPACITY, 128);
+    }
+
+    #[test]
+    #[should_panic(expected = "ID 64 doesn't fit a set of 64")]
+    fn test_too_big() {
+        IdSet::<u64>::new().insert(64);
+    }
+
+    #[test]
+    fn test_ids() {
+        let mut ids: Ids<&str> = Ids::from_keys(["AA", "BB"]).unwrap();
+        assert_eq!(ids.intern("CC").unwrap(), 2);
+        assert_eq!(ids.intern("AA").unwrap(), 0);
+        assert_eq!(ids.get(&"BB"), Some(1));
+        assert_eq!(ids.get(&"DD"), None);
+        assert_eq!(ids.key(2), &"CC");
+        assert_eq!(ids.keys(), ["AA", "BB", "CC"]);
+        assert_eq!(ids.len(), 3);
+
+        let mut full = Ids::<usize>::from_keys(0..64).unwrap();
+        assert_eq!(full.intern(63).unwrap(), 63);
+        let error = full.intern(64).unwrap_err();
+        assert_eq!(error.to_string(), "a set can only hold 64 IDs");
+        assert!(Ids::<usize, u128>::from_keys(0..128).is_ok());
+    }
+}
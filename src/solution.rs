@@ -0,0 +1,584 @@
+//! A common shape for the days' solvers. Solvers return their answers
+//! instead of printing them, and [`run`] reports them as text or JSON.
+
+use crate::{
+    cache::{Cache, Key},
+    cancel::Cancel,
+    cli::{CommonOpts, OutputFormat},
+    memory::{self, Usage},
+    submit,
+};
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Borrow,
+    fmt,
+    time::{Duration, Instant},
+};
+use tracing::warn;
+
+/// The answer to one part of a puzzle. Most are numbers, a few are letters.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Answer {
+    Number(i64),
+    Text(String),
+    /// A part that hasn't been solved yet; `null` in JSON.
+    Unsolved,
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(n) => write!(f, "{n}"),
+            Self::Text(s) => write!(f, "{s}"),
+            Self::Unsolved => write!(f, "unsolved"),
+        }
+    }
+}
+
+macro_rules! impl_from_integer {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            /// Numbers too big for JSON's integers are kept as text.
+            fn from(n: $t) -> Self {
+                i64::try_from(n).map_or_else(|_| Self::Text(n.to_string()), Self::Number)
+            }
+        })*
+    };
+}
+
+impl_from_integer!(i32, i64, i128, isize, u32, u64, u128, usize);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Self::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Self::Text(s.to_string())
+    }
+}
+
+/// A day's puzzle, parsed and ready to solve.
+pub trait Solution {
+    /// Day of December the puzzle is for.
+    const DAY: u8;
+
+    fn part_1(&self) -> Result<Answer, Error>;
+
+    /// Days that haven't solved part 2 leave this out, and are reported as
+    /// unsolved rather than failing.
+    fn part_2(&self) -> Result<Answer, Error> {
+        Err(crate::Error::UnsupportedPart {
+            day: Self::DAY,
+            part: 2,
+        }
+        .into())
+    }
+    /// Solve `part`, stopping early if `cancel` is cancelled. Days with
+    /// searches long enough to want a time limit check `cancel` as they go;
+    /// the rest ignore it.
+    fn solve_part(&self, part: u8, cancel: &Cancel) -> Result<Answer, Error> {
+        let _ = cancel;
+        match part {
+            1 => self.part_1(),
+            _ => self.part_2(),
+        }
+    }
+}
+
+/// Both parts' answers, as a day's `solve` gives them to callers using it
+/// as a library.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Answers {
+    pub part1: Answer,
+    pub part2: Answer,
+}
+
+/// Build a solution with `parse` and solve both parts, without printing
+/// anything.
+pub fn answers<S: Solution>(
+    parse: impl FnOnce() -> Result<S, Error>,
+) -> Result<Answers, crate::Error> {
+    let solution = parse().map_err(|e| crate::Error::parsing(S::DAY, e))?;
+    let cancel = Cancel::default();
+    Ok(Answers {
+        part1: answer_part(&solution, 1, &cancel)?,
+        part2: answer_part(&solution, 2, &cancel)?,
+    })
+}
+
+/// Solve one part, counting a part the day doesn't solve as unsolved.
+fn answer_part<S: Solution>(
+    solution: &S,
+    part: u8,
+    cancel: &Cancel,
+) -> Result<Answer, crate::Error> {
+    let answer = solution.solve_part(part, cancel);
+    match answer.map_err(|e| crate::Error::solving(S::DAY, part, e)) {
+        Err(crate::Error::UnsupportedPart { .. }) => Ok(Answer::Unsolved),
+        answer => answer,
+    }
+}
+
+/// CPU time used so far by the whole process, counting every thread, on
+/// platforms that can say.
+#[cfg(unix)]
+fn cpu_time() -> Option<Duration> {
+    let mut time = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: `time` is a valid timespec for the call to fill in.
+    let result = unsafe { libc::clock_gettime(libc::CLOCK_PROCESS_CPUTIME_ID, &mut time) };
+    (result == 0).then(|| Duration::new(time.tv_sec as u64, time.tv_nsec as u32))
+}
+
+#[cfg(not(unix))]
+fn cpu_time() -> Option<Duration> {
+    None
+}
+
+/// How long one stage of a run took.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Timing {
+    pub wall_ms: f64,
+    /// Missing where the platform doesn't report CPU time.
+    pub cpu_ms: Option<f64>,
+}
+
+impl Timing {
+    /// Call `f`, timing it.
+    pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Self) {
+        let (start, start_cpu) = (Instant::now(), cpu_time());
+        let result = f();
+        let cpu_ms = start_cpu
+            .zip(cpu_time())
+            .map(|(start, end)| end.saturating_sub(start).as_secs_f64() * 1000.0);
+        let wall_ms = start.elapsed().as_secs_f64() * 1000.0;
+        (result, Self { wall_ms, cpu_ms })
+    }
+}
+
+impl fmt::Display for Timing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.3}ms wall", self.wall_ms)?;
+        if let Some(cpu_ms) = self.cpu_ms {
+            write!(f, ", {cpu_ms:.3}ms cpu")?;
+        }
+        Ok(())
+    }
+}
+
+/// Timings for each stage of a run, for `--time`. Stages that didn't run,
+/// like parsing for days that parse before calling [`run`], are left out.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Timings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parse: Option<Timing>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part1: Option<Timing>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part2: Option<Timing>,
+}
+
+impl Timings {
+    /// Each stage that ran, by name.
+    pub fn stages(&self) -> impl Iterator<Item = (&'static str, Timing)> {
+        [
+            ("parse", self.parse),
+            ("part 1", self.part1),
+            ("part 2", self.part2),
+        ]
+        .into_iter()
+        .filter_map(|(name, timing)| Some((name, timing?)))
+    }
+}
+
+/// What one run of a day found.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    pub day: u8,
+    pub part1: Option<Answer>,
+    pub part2: Option<Answer>,
+    /// Time spent parsing, when [`parse_and_run`] does it, and solving.
+    pub elapsed_ms: f64,
+    /// Whether any part's answer came from the cache.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub cached: bool,
+    /// Stage by stage timings, with `--time`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timings: Option<Timings>,
+    /// Peak memory, when built with the `profiling` feature.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory: Option<Usage>,
+}
+
+impl Report {
+    /// The solved answers and their parts, in part order, for submitting.
+    pub fn answers(&self) -> Vec<(u8, String)> {
+        (1..)
+            .zip([&self.part1, &self.part2])
+            .filter_map(|(part, answer)| Some((part, answer.as_ref()?)))
+            .filter(|(_, answer)| **answer != Answer::Unsolved)
+            .map(|(part, answer)| (part, answer.to_string()))
+            .collect()
+    }
+}
+
+/// Solve the parts `common` asks for and report them in its output format.
+/// Text answers are printed as each part finishes, so a part 2 that panics
+/// doesn't hide part 1. Parts whose answers are cached for the puzzle input
+/// are taken from the cache instead. `--force` solves them anyway, only
+/// warning if an answer differs from the cached one, and leaves the cache
+/// as it was. With
+/// `--submit`, the answers are then posted to the site.
+pub fn run<S: Solution>(solution: &S, common: &CommonOpts) -> Result<Report, crate::Error> {
+    solve::<S, _>(common, || Ok(solution), false, open_cache(common, S::DAY))
+}
+
+/// Build the solution with `parse`, then solve it as [`run`] does. With
+/// `--time`, parsing is timed as a stage of its own. Parsing is skipped if
+/// every part's answer is cached.
+pub fn parse_and_run<S: Solution>(
+    common: &CommonOpts,
+    parse: impl FnOnce() -> Result<S, Error>,
+) -> Result<Report, crate::Error> {
+    solve::<S, _>(common, parse, true, open_cache(common, S::DAY))
+}
+
+/// The cache for `day`, if `common` read an input to key it with. A cache
+/// that can't be read is only worth a warning.
+fn open_cache(common: &CommonOpts, day: u8) -> Option<(Cache, Key)> {
+    let key = common.cache_key()?;
+    match Cache::open(&Cache::dir(), day) {
+        Ok(cache) => Some((cache, key)),
+        Err(e) => {
+            warn!("not caching: {e:#}");
+            None
+        }
+    }
+}
+
+fn solve<S: Solution, B: Borrow<S>>(
+    common: &CommonOpts,
+    parse: impl FnOnce() -> Result<B, Error>,
+    time_parse: bool,
+    mut cache: Option<(Cache, Key)>,
+) -> Result<Report, crate::Error> {
+    let start = Instant::now();
+    let cancel = common.cancel();
+    let mut parse = Some(parse);
+    let mut solution = None;
+    let mut parse_timing = None;
+    let mut parts = [(None, None), (None, None)];
+    let mut cached = false;
+    for (part, (answer, timing)) in (1..).zip(&mut parts) {
+        if !common.part(part) {
+            continue;
+        }
+        let hit = cache
+            .as_ref()
+            .filter(|_| !common.force)
+            .and_then(|(cache, key)| cache.get(part, key));
+        if let Some(hit) = hit {
+            if common.output == OutputFormat::Text {
+                println!("part {part} = {} (cached)", hit.answer);
+            }
+            *answer = Some(hit.answer.clone());
+            cached = true;
+            continue;
+        }
+
+        if solution.is_none() {
+            let parse = parse.take().expect("parsed once");
+            let (parsed, timing) = Timing::measure(parse);
+            parse_timing = time_parse.then_some(timing);
+            solution = Some(parsed.map_err(|e| crate::Error::parsing(S::DAY, e))?);
+        }
+        let solution: &S = solution.as_ref().expect("parsed").borrow();
+        let (solved, solve_timing) = Timing::measure(|| answer_part(solution, part, &cancel));
+        let solved = solved?;
+        if common.output == OutputFormat::Text {
+            println!("part {part} = {solved}");
+        }
+        if let Some((cache, key)) = &mut cache {
+            if solved != Answer::Unsolved {
+                let previous = if common.force {
+                    let cached = cache.get(part, key).map(|hit| hit.answer.clone());
+                    cached.filter(|cached| *cached != solved)
+                } else {
+                    cache.insert(part, key, solved.clone(), solve_timing.wall_ms)
+                };
+                if let Some(previous) = previous {
+                    warn!(day = S::DAY, part, %previous, now = %solved, "answer changed");
+                }
+            }
+        }
+        *answer = Some(solved);
+        *timing = Some(solve_timing);
+    }
+    if let Some((cache, _)) = cache.as_ref().filter(|_| !common.force) {
+        if let Err(e) = cache.save() {
+            warn!("not caching: {e:#}");
+        }
+    }
+
+    let [(part1, part1_timing), (part2, part2_timing)] = parts;
+    let timings = common.time.then_some(Timings {
+        parse: parse_timing,
+        part1: part1_timing,
+        part2: part2_timing,
+    });
+    let report = Report {
+        day: S::DAY,
+        part1,
+        part2,
+        elapsed_ms: start.elapsed().as_secs_f64() * 1000.0,
+        cached,
+        timings,
+        memory: memory::usage(),
+    };
+    if common.output == OutputFormat::Text {
+        for (stage, timing) in report.timings.iter().flat_map(Timings::stages) {
+            println!("time {stage} = {timing}");
+        }
+        if let Some(memory) = &report.memory {
+            println!("memory = {memory}");
+        }
+    }
+    if common.output == OutputFormat::Json {
+        println!(
+            "{}",
+            serde_json::to_string(&report).expect("reports serialize")
+        );
+    }
+    if common.submit {
+        submit::submit_answers(S::DAY, &report.answers()).map_err(|e| crate::Error::Submit {
+            day: S::DAY,
+            source: e.into(),
+        })?;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use structopt::StructOpt;
+
+    struct Sums(Vec<u32>);
+
+    impl Solution for Sums {
+        const DAY: u8 = 1;
+
+        fn part_1(&self) -> Result<Answer, Error> {
+            Ok(self.0.iter().sum::<u32>().into())
+        }
+    }
+
+    struct Broken;
+
+    impl Solution for Broken {
+        const DAY: u8 = 3;
+
+        fn part_1(&self) -> Result<Answer, Error> {
+            anyhow::bail!("no rucksacks")
+        }
+    }
+
+    /// Checks its cancel before answering.
+    struct Patient;
+
+    impl Solution for Patient {
+        const DAY: u8 = 24;
+
+        fn part_1(&self) -> Result<Answer, Error> {
+            Ok(18.into())
+        }
+
+        fn solve_part(&self, part: u8, cancel: &Cancel) -> Result<Answer, Error> {
+            cancel.check()?;
+            match part {
+                1 => self.part_1(),
+                _ => self.part_2(),
+            }
+        }
+    }
+
+    fn common(args: &[&str]) -> CommonOpts {
+        CommonOpts::from_iter(std::iter::once("day").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn test_answer_from() {
+        assert_eq!(Answer::from(42usize), Answer::Number(42));
+        assert_eq!(Answer::from(-3i32), Answer::Number(-3));
+        assert_eq!(
+            Answer::from(u64::MAX),
+            Answer::Text("18446744073709551615".to_string())
+        );
+        assert_eq!(Answer::from("PGPHBEAB").to_string(), "PGPHBEAB");
+    }
+
+    #[test]
+    fn test_run() {
+        let report = run(&Sums(vec![1, 2, 3]), &common(&["--output", "json"])).unwrap();
+        assert_eq!(report.day, 1);
+        assert_eq!(report.part1, Some(Answer::Number(6)));
+        assert_eq!(report.part2, Some(Answer::Unsolved));
+        assert_eq!(report.answers(), [(1, "6".to_string())]);
+
+        let report = run(&Sums(vec![1]), &common(&["--part", "2"])).unwrap();
+        assert_eq!(report.part1, None);
+        assert_eq!(report.answers(), []);
+    }
+
+    #[test]
+    fn test_answers() {
+        assert_eq!(
+            answers(|| Ok(Sums(vec![4, 5]))).unwrap(),
+            Answers {
+                part1: Answer::Number(9),
+                part2: Answer::Unsolved,
+            }
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = answers(|| Ok(Broken)).unwrap_err();
+        assert!(matches!(
+            error,
+            crate::Error::Solver {
+                day: 3,
+                part: 1,
+                ..
+            }
+        ));
+        assert_eq!(
+            format!("{:#}", anyhow::Error::from(error)),
+            "day 3 part 1 failed: no rucksacks"
+        );
+        let error = answers::<Sums>(|| anyhow::bail!("not a number")).unwrap_err();
+        assert!(matches!(error, crate::Error::Parse { day: 1, .. }));
+
+        // Part 2 isn't solved, which isn't a failure.
+        let report = run(&Broken, &common(&["--part", "2"])).unwrap();
+        assert_eq!(report.part2, Some(Answer::Unsolved));
+        assert!(matches!(
+            run(&Broken, &common(&[])),
+            Err(crate::Error::Solver { part: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_timeout() {
+        let report = run(&Patient, &common(&["--timeout", "60"])).unwrap();
+        assert_eq!(report.part1, Some(Answer::Number(18)));
+        assert!(matches!(
+            run(&Patient, &common(&["--timeout", "0"])),
+            Err(crate::Error::SolverTimeout { day: 24, part: 1, limit }) if limit.is_zero()
+        ));
+    }
+
+    #[test]
+    fn test_timings() {
+        let report = run(&Sums(vec![1]), &common(&[])).unwrap();
+        assert_eq!(report.timings, None);
+
+        let report =
+            parse_and_run(&common(&["--time", "--part", "1"]), || Ok(Sums(vec![1, 2]))).unwrap();
+        assert_eq!(report.part1, Some(Answer::Number(3)));
+        let timings = report.timings.unwrap();
+        let stages: Vec<_> = timings.stages().map(|(stage, _)| stage).collect();
+        assert_eq!(stages, ["parse", "part 1"]);
+        assert!(timings.parse.unwrap().wall_ms >= 0.0);
+        #[cfg(unix)]
+        assert!(timings.part1.unwrap().cpu_ms.is_some());
+
+        assert!(parse_and_run::<Sums>(&common(&[]), || anyhow::bail!("bad input")).is_err());
+    }
+
+    #[test]
+    fn test_cache() {
+        let dir = std::env::temp_dir().join(format!("solution-cache-{}", std::process::id()));
+        let key = Key {
+            input: crate::cache::digest("1\n2\n3\n"),
+            settings: vec![],
+        };
+        let open = || Some((Cache::open(&dir, 1).unwrap(), key.clone()));
+
+        let report =
+            solve::<Sums, Sums>(&common(&[]), || Ok(Sums(vec![1, 2, 3])), true, open()).unwrap();
+        assert!(!report.cached);
+
+        // Part 1 is cached now, so the input isn't even parsed.
+        let part1 = common(&["--part", "1", "--time"]);
+        let report = solve::<Sums, Sums>(&part1, || anyhow::bail!("parsed"), true, open()).unwrap();
+        assert!(report.cached);
+        assert_eq!(report.part1, Some(Answer::Number(6)));
+        assert_eq!(report.timings.unwrap().stages().count(), 0);
+
+        // Part 2 is unsolved, which isn't worth caching.
+        assert!(
+            solve::<Sums, Sums>(&common(&[]), || anyhow::bail!("parsed"), true, open()).is_err()
+        );
+
+        let forced = common(&["--part", "1", "--force"]);
+        let report =
+            solve::<Sums, Sums>(&forced, || Ok(Sums(vec![1, 2, 4])), true, open()).unwrap();
+        assert!(!report.cached);
+        assert_eq!(report.part1, Some(Answer::Number(7)));
+        let (cache, key) = open().unwrap();
+        assert_eq!(cache.get(1, &key).unwrap().answer, Answer::Number(6));
+
+        // Nothing is written when forcing, not even a new cache.
+        std::fs::remove_dir_all(&dir).unwrap();
+        solve::<Sums, Sums>(&forced, || Ok(Sums(vec![1])), true, open()).unwrap();
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_timing_json() {
+        let timings = Timings {
+            parse: Some(Timing {
+                wall_ms: 2.0,
+                cpu_ms: None,
+            }),
+            ..Timings::default()
+        };
+        assert_eq!(
+            serde_json::to_string(&timings).unwrap(),
+            r#"{"parse":{"wall_ms":2.0,"cpu_ms":null}}"#
+        );
+        assert_eq!(timings.parse.unwrap().to_string(), "2.000ms wall");
+    }
+
+    #[test]
+    fn test_json() {
+        let report = Report {
+            day: 19,
+            part1: Some(Answer::Number(2301)),
+            part2: Some(Answer::Text("PGPHBEAB".to_string())),
+            elapsed_ms: 1.5,
+            cached: false,
+            timings: None,
+            memory: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&report).unwrap(),
+            r#"{"day":19,"part1":2301,"part2":"PGPHBEAB","elapsed_ms":1.5}"#
+        );
+        let unsolved = Report {
+            part2: Some(Answer::Unsolved),
+            ..report
+        };
+        assert!(serde_json::to_string(&unsolved)
+            .unwrap()
+            .contains(r#""part2":null"#));
+    }
+}
@@ -0,0 +1,324 @@
+//! Submitting answers to adventofcode.com and remembering past guesses.
+
+use anyhow::{anyhow, bail, Context, Error};
+use std::{
+    fmt, fs,
+    io::Write,
+    process::{Command, Stdio},
+    str::FromStr,
+};
+
+const YEAR: u32 = 2022;
+const GUESS_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/data/guesses.txt");
+const SESSION_VAR: &str = "AOC_SESSION";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, without a hint as to which way
+    Incorrect,
+    /// Submitted too soon after a wrong answer
+    Wait(String),
+    /// The part was already solved
+    AlreadySolved,
+    Unknown(String),
+}
+
+impl Verdict {
+    /// Whether this verdict is worth recording as a guess.
+    fn is_final(&self) -> bool {
+        matches!(
+            self,
+            Self::Correct | Self::TooHigh | Self::TooLow | Self::Incorrect
+        )
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Correct => write!(f, "correct"),
+            Self::TooHigh => write!(f, "too high"),
+            Self::TooLow => write!(f, "too low"),
+            Self::Incorrect => write!(f, "incorrect"),
+            Self::Wait(time) => write!(f, "wait {time}"),
+            Self::AlreadySolved => write!(f, "already solved"),
+            Self::Unknown(s) => write!(f, "unknown: {s}"),
+        }
+    }
+}
+
+impl FromStr for Verdict {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "correct" => Self::Correct,
+            "too high" => Self::TooHigh,
+            "too low" => Self::TooLow,
+            "incorrect" => Self::Incorrect,
+            _ => bail!("unknown verdict {s:?}"),
+        })
+    }
+}
+
+/// Work out the verdict from the HTML the site returns for a submission.
+pub fn parse_response(html: &str) -> Verdict {
+    if html.contains("That's the right answer") {
+        Verdict::Correct
+    } else if html.contains("your answer is too high") {
+        Verdict::TooHigh
+    } else if html.contains("your answer is too low") {
+        Verdict::TooLow
+    } else if html.contains("That's not the right answer") {
+        Verdict::Incorrect
+    } else if html.contains("You gave an answer too recently") {
+        let wait = html
+            .split("You have ")
+            .nth(1)
+            .and_then(|s| s.split(" left to wait").next())
+            .unwrap_or("a while");
+        Verdict::Wait(wait.to_string())
+    } else if html.contains("Did you already complete it?") {
+        Verdict::AlreadySolved
+    } else {
+        let text = html.split("<article>").nth(1).unwrap_or(html);
+        Verdict::Unknown(text.chars().take(200).collect())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Guess {
+    pub day: u32,
+    pub part: u32,
+    pub answer: String,
+    pub verdict: Verdict,
+}
+
+impl fmt::Display for Guess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}\t{}",
+            self.day, self.part, self.answer, self.verdict
+        )
+    }
+}
+
+impl FromStr for Guess {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<_> = s.split('\t').collect();
+        if fields.len() != 4 {
+            bail!("expected day, part, answer and verdict in {s:?}");
+        }
+        Ok(Self {
+            day: fields[0].parse().context("day")?,
+            part: fields[1].parse().context("part")?,
+            answer: fields[2].to_string(),
+            verdict: fields[3].parse()?,
+        })
+    }
+}
+
+/// Every answer submitted so far, one tab-separated guess per line.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GuessLog {
+    pub guesses: Vec<Guess>,
+}
+
+impl GuessLog {
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        let guesses = s
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+            .map(|(index, line)| {
+                line.parse()
+                    .with_context(|| format!("{GUESS_FILE} line {}", index + 1))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { guesses })
+    }
+
+    pub fn load() -> Result<Self, Error> {
+        match fs::read_to_string(GUESS_FILE) {
+            Ok(s) => Self::parse(&s),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).context(GUESS_FILE),
+        }
+    }
+
+    pub fn record(&mut self, guess: Guess) -> Result<(), Error> {
+        self.guesses.push(guess);
+        let contents: String = self.guesses.iter().map(|g| format!("{g}\n")).collect();
+        fs::write(GUESS_FILE, contents).context(GUESS_FILE)
+    }
+
+    /// What the log already says about `answer`, without asking the site.
+    ///
+    /// An answer guessed before gets its old verdict, and a numeric answer
+    /// on the wrong side of an earlier "too high" or "too low" is rejected.
+    pub fn known_verdict(&self, day: u32, part: u32, answer: &str) -> Option<Verdict> {
+        let previous = self
+            .guesses
+            .iter()
+            .filter(|g| g.day == day && g.part == part);
+        let value = answer.parse::<i128>().ok();
+        for guess in previous {
+            if guess.answer == answer {
+                return Some(guess.verdict.clone());
+            }
+            match (value, guess.answer.parse::<i128>(), &guess.verdict) {
+                (Some(value), Ok(bound), Verdict::TooHigh) if value >= bound => {
+                    return Some(Verdict::TooHigh)
+                }
+                (Some(value), Ok(bound), Verdict::TooLow) if value <= bound => {
+                    return Some(Verdict::TooLow)
+                }
+                _ => (),
+            }
+        }
+        None
+    }
+}
+
+/// A curl config file that sends the session cookie. It goes to curl on
+/// stdin, so the token never shows up in its command line for `ps` to see.
+fn curl_config(session: &str) -> String {
+    let escaped = session.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("cookie = \"session={escaped}\"\n")
+}
+
+fn post_answer(day: u32, part: u32, answer: &str) -> Result<String, Error> {
+    let session = std::env::var(SESSION_VAR)
+        .map_err(|_| anyhow!("set {SESSION_VAR} to your adventofcode.com session cookie"))?;
+    let mut curl = Command::new("curl")
+        .arg("--silent")
+        .arg("--fail")
+        .args(["--config", "-"])
+        .args(["--user-agent", "github.com/rtsuk/advent_of_code_2022"])
+        .args(["--data", &format!("level={part}")])
+        .args(["--data-urlencode", &format!("answer={answer}")])
+        .arg(format!("https://adventofcode.com/{YEAR}/day/{day}/answer"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("running curl")?;
+    curl.stdin
+        .take()
+        .expect("piped stdin")
+        .write_all(curl_config(&session).as_bytes())
+        .context("sending curl the session cookie")?;
+    let output = curl.wait_with_output().context("running curl")?;
+    if !output.status.success() {
+        bail!(
+            "submission failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Submit `answer` for `day` and `part`, unless the guess log already
+/// knows it is wrong, and record the verdict.
+pub fn submit(day: u32, part: u32, answer: impl ToString) -> Result<Verdict, Error> {
+    let answer = answer.to_string();
+    let mut log = GuessLog::load()?;
+    if let Some(verdict) = log.known_verdict(day, part, &answer) {
+        return Ok(verdict);
+    }
+
+    let verdict = parse_response(&post_answer(day, part, &answer)?);
+    if verdict.is_final() {
+        log.record(Guess {
+            day,
+            part,
+            answer,
+            verdict: verdict.clone(),
+        })?;
+    }
+    Ok(verdict)
+}
+
+/// Submit each part's answer in order, stopping at the first part that
+/// isn't accepted, and print the verdicts.
+pub fn submit_answers(day: u8, answers: &[(u8, String)]) -> Result<(), Error> {
+    for (part, answer) in answers {
+        let verdict = submit(day.into(), (*part).into(), answer)?;
+        println!("day {day} part {part} answer {answer}: {verdict}");
+        if !matches!(verdict, Verdict::Correct | Verdict::AlreadySolved) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_response() {
+        assert_eq!(
+            parse_response("<article><p>That's the right answer!</p></article>"),
+            Verdict::Correct
+        );
+        assert_eq!(
+            parse_response(
+                "<p>That's not the right answer; your answer is too low. (You guessed 591.)</p>"
+            ),
+            Verdict::TooLow
+        );
+        assert_eq!(
+            parse_response("That's not the right answer; your answer is too high."),
+            Verdict::TooHigh
+        );
+        assert_eq!(
+            parse_response("That's not the right answer. If you're stuck"),
+            Verdict::Incorrect
+        );
+        assert_eq!(
+            parse_response("You gave an answer too recently. You have 34s left to wait."),
+            Verdict::Wait("34s".to_string())
+        );
+        assert_eq!(
+            parse_response(
+                "You don't seem to be solving the right level.  Did you already complete it?"
+            ),
+            Verdict::AlreadySolved
+        );
+    }
+
+    #[test]
+    fn test_curl_config() {
+        assert_eq!(curl_config("53616c74"), "cookie = \"session=53616c74\"\n");
+        assert_eq!(
+            curl_config(r#"a"b\c"#),
+            "cookie = \"session=a\\\"b\\\\c\"\n"
+        );
+    }
+
+    #[test]
+    fn test_guess_log() {
+        let log = GuessLog::parse(
+            "# day\tpart\tanswer\tverdict\n20\t1\t8920\ttoo high\n17\t1\t3171\ttoo low\n",
+        )
+        .expect("parse");
+        assert_eq!(log.guesses.len(), 2);
+        assert_eq!(log.guesses[0].to_string(), "20\t1\t8920\ttoo high");
+
+        assert_eq!(log.known_verdict(20, 1, "8920"), Some(Verdict::TooHigh));
+        assert_eq!(log.known_verdict(20, 1, "9000"), Some(Verdict::TooHigh));
+        assert_eq!(log.known_verdict(20, 1, "7584"), None);
+        assert_eq!(log.known_verdict(20, 2, "9000"), None);
+        assert_eq!(log.known_verdict(17, 1, "2000"), Some(Verdict::TooLow));
+
+        assert!(GuessLog::parse("20\t1\t8920\n").is_err());
+        assert!(GuessLog::parse("20\t1\t8920\tmaybe\n").is_err());
+    }
+}
@@ -0,0 +1,55 @@
+//! Inputs for tests, read from `data/` at run time. Each day's sample is
+//! kept in `data/dayNN_sample.txt` next to its puzzle input in
+//! `data/dayNN.txt`, so the days and the tests share one copy of each.
+
+use anyhow::{Context, Error};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+fn data_dir() -> &'static Path {
+    Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/data"))
+}
+
+pub fn sample_path(day: u8) -> PathBuf {
+    data_dir().join(format!("day{day:02}_sample.txt"))
+}
+
+pub fn input_path(day: u8) -> PathBuf {
+    data_dir().join(format!("day{day:02}.txt"))
+}
+
+/// The example from the puzzle's description.
+pub fn sample(day: u8) -> Result<String, Error> {
+    let path = sample_path(day);
+    fs::read_to_string(&path).with_context(|| format!("reading {path:?}"))
+}
+
+/// The puzzle input, unless it hasn't been saved. An empty file, as
+/// `new-day` leaves, counts as not saved.
+pub fn input(day: u8) -> Result<Option<String>, Error> {
+    let path = input_path(day);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let input = fs::read_to_string(&path).with_context(|| format!("reading {path:?}"))?;
+    Ok((!input.is_empty()).then_some(input))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sample() {
+        assert!(sample(1).unwrap().starts_with("1000\n2000\n"));
+        assert!(sample(0).is_err());
+    }
+
+    #[test]
+    fn test_input() {
+        assert!(input(1).unwrap().is_some());
+        assert_eq!(input(0).unwrap(), None);
+    }
+}
@@ -0,0 +1,192 @@
+//! Animations in the terminal, colour, and pictures saved as PNGs. These
+//! need `console` and `png`, which are only built with the `viz` feature;
+//! without it, [`Terminal::stdout`], [`write_gray_png`] and the days'
+//! pictures fail with an error saying how to get them, and everything else
+//! works the same.
+//!
+//! Each day's drawing code lives in a module of its own here.
+
+pub mod day09;
+pub mod day10;
+pub mod day12;
+pub mod day14;
+pub mod day17;
+pub mod day23;
+pub mod day24;
+
+use anyhow::{anyhow, bail, Error};
+use std::{path::Path, thread, time::Duration};
+
+/// The error for asking for `what` from a build without the `viz` feature.
+pub fn disabled(what: &str) -> Error {
+    anyhow!("{what} needs the viz feature; build with `--features viz`")
+}
+
+/// How long to show each frame of an animation playing `fps` frames a
+/// second.
+pub fn frame_delay(fps: f64) -> Result<Duration, Error> {
+    if fps.is_nan() || fps <= 0.0 {
+        bail!("can't animate at {fps} frames a second");
+    }
+    Ok(Duration::try_from_secs_f64(1.0 / fps)?)
+}
+
+/// The terminal, for animating a day or stepping through it a key at a
+/// time.
+pub struct Terminal(backend::Term);
+
+impl Terminal {
+    /// The terminal on standard output, for `what`, which fails without
+    /// the `viz` feature.
+    pub fn stdout(what: &str) -> Result<Self, Error> {
+        backend::stdout(what).map(Self)
+    }
+
+    /// Clear the screen and draw `frame`.
+    pub fn show(&self, frame: &str) -> Result<(), Error> {
+        backend::show(&self.0, frame)
+    }
+
+    pub fn wait_for_key(&self) -> Result<(), Error> {
+        backend::wait_for_key(&self.0)
+    }
+
+    /// Show each of `frames` in turn, `fps` a second.
+    pub fn play<S: AsRef<str>>(
+        &self,
+        frames: impl IntoIterator<Item = S>,
+        fps: f64,
+    ) -> Result<(), Error> {
+        let frame_delay = frame_delay(fps)?;
+        for frame in frames {
+            self.show(frame.as_ref())?;
+            thread::sleep(frame_delay);
+        }
+        Ok(())
+    }
+}
+
+/// Save `pixels`, a row at a time from the top, as an 8 bit grayscale PNG.
+pub fn write_gray_png(
+    path: &Path,
+    width: usize,
+    height: usize,
+    pixels: &[u8],
+) -> Result<(), Error> {
+    assert_eq!(pixels.len(), width * height, "pixels for {width}x{height}");
+    backend::write_gray_png(path, width, height, pixels)
+}
+
+#[cfg(feature = "viz")]
+mod backend {
+    use anyhow::{Context, Error};
+    use std::{fs::File, io::BufWriter, path::Path};
+
+    pub type Term = console::Term;
+
+    pub fn stdout(_what: &str) -> Result<Term, Error> {
+        Ok(Term::stdout())
+    }
+
+    pub fn show(term: &Term, frame: &str) -> Result<(), Error> {
+        term.clear_screen()?;
+        term.write_str(frame)?;
+        Ok(())
+    }
+
+    pub fn wait_for_key(term: &Term) -> Result<(), Error> {
+        term.read_char()?;
+        Ok(())
+    }
+
+    pub fn write_gray_png(
+        path: &Path,
+        width: usize,
+        height: usize,
+        pixels: &[u8],
+    ) -> Result<(), Error> {
+        let file =
+            BufWriter::new(File::create(path).with_context(|| format!("creating {path:?}"))?);
+        let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(pixels)?;
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "viz"))]
+mod backend {
+    use super::disabled;
+    use anyhow::Error;
+    use std::{convert::Infallible, path::Path};
+
+    /// There's no terminal to have without the feature.
+    pub type Term = Infallible;
+
+    pub fn stdout(what: &str) -> Result<Term, Error> {
+        Err(disabled(what))
+    }
+
+    pub fn show(term: &Term, _frame: &str) -> Result<(), Error> {
+        match *term {}
+    }
+
+    pub fn wait_for_key(term: &Term) -> Result<(), Error> {
+        match *term {}
+    }
+
+    pub fn write_gray_png(
+        _path: &Path,
+        _width: usize,
+        _height: usize,
+        _pixels: &[u8],
+    ) -> Result<(), Error> {
+        Err(disabled("PNG output"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_frame_delay() {
+        assert_eq!(frame_delay(4.0).unwrap(), Duration::from_millis(250));
+        assert_eq!(frame_delay(f64::INFINITY).unwrap(), Duration::ZERO);
+        for fps in [0.0, -2.0, f64::NAN] {
+            let error = frame_delay(fps).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!("can't animate at {fps} frames a second")
+            );
+        }
+    }
+
+    #[cfg(not(feature = "viz"))]
+    #[test]
+    fn test_disabled() {
+        let error = Terminal::stdout("--animate").err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "--animate needs the viz feature; build with `--features viz`"
+        );
+        let path = std::env::temp_dir().join("viz-disabled.png");
+        assert!(write_gray_png(&path, 1, 1, &[0]).is_err());
+        assert!(!path.exists());
+    }
+
+    #[cfg(feature = "viz")]
+    #[test]
+    fn test_write_gray_png() {
+        let path = std::env::temp_dir().join(format!("viz-{}.png", std::process::id()));
+        write_gray_png(&path, 3, 2, &[0, 0xff, 0, 0xff, 0, 0xff]).unwrap();
+        let decoder = png::Decoder::new(std::fs::File::open(&path).unwrap());
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((reader.info().width, reader.info().height), (3, 2));
+        assert_eq!(pixels, [0, 0xff, 0, 0xff, 0, 0xff]);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
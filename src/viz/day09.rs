@@ -0,0 +1,16 @@
+//! Day 9's rope, animated.
+
+use super::Terminal;
+use crate::day09::{steps, MoveList, Rope};
+use anyhow::Error;
+
+/// Move `rope` through `moves`, drawing it after every step, `fps` steps a
+/// second.
+pub fn animate(rope: &mut Rope, moves: &MoveList, fps: f64) -> Result<(), Error> {
+    let term = Terminal::stdout("--fps")?;
+    let frames = steps(moves).map(|step| {
+        rope.step(step);
+        rope.render()
+    });
+    term.play(frames, fps)
+}
@@ -0,0 +1,29 @@
+//! Day 10's screen as a picture.
+
+use super::write_gray_png;
+use anyhow::Error;
+use std::{iter, path::Path};
+
+/// Size of a CRT pixel in the exported image.
+const PNG_SCALE: usize = 8;
+
+/// Save the screen as a PNG, lit pixels white, each one `PNG_SCALE` image
+/// pixels square.
+pub fn write_png(screen: &[String], path: &Path) -> Result<(), Error> {
+    let width = screen.iter().map(String::len).max().unwrap_or(0);
+    let height = screen.len();
+
+    let mut pixels = Vec::with_capacity(width * height * PNG_SCALE * PNG_SCALE);
+    for line in screen {
+        let row: Vec<u8> = line
+            .bytes()
+            .chain(iter::repeat(b'.'))
+            .take(width)
+            .flat_map(|b| [if b == b'#' { 0xff } else { 0x00 }; PNG_SCALE])
+            .collect();
+        for _ in 0..PNG_SCALE {
+            pixels.extend_from_slice(&row);
+        }
+    }
+    write_gray_png(path, width * PNG_SCALE, height * PNG_SCALE, &pixels)
+}
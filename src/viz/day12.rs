@@ -0,0 +1,74 @@
+//! Day 12's paths in colour.
+
+use crate::{
+    day12::{Map, PathCell},
+    grid::Point,
+};
+use anyhow::Error;
+
+/// The map in colour: each cell's elevation as a shade of gray from black
+/// at a to white at z, `path` drawn over it in bright arrows, and the cells
+/// the path starts and ends at picked out in green and red. Falls back to
+/// plain text where the terminal has no colour.
+pub fn render_colored(map: &Map, path: &[Point]) -> Result<String, Error> {
+    let mut s = String::new();
+    for row in map.path_cells(path) {
+        for cell in row {
+            s += &paint(cell)?;
+        }
+        s.push('\n');
+    }
+    Ok(s)
+}
+
+#[cfg(feature = "viz")]
+fn paint(cell: PathCell) -> Result<String, Error> {
+    use crate::day12::PathMark;
+    use console::style;
+
+    // The 24 shades of gray at the end of the 256 colours.
+    let shade = 232 + (cell.elevation * 23 / 25) as u8;
+    let styled = match cell.mark {
+        PathMark::First => style(cell.c).black().on_green(),
+        PathMark::Last => style(cell.c).black().on_red(),
+        PathMark::Step => style(cell.c).yellow().bright().bold().on_color256(shade),
+        PathMark::Off => style(cell.c)
+            .color256(if shade < 244 { 250 } else { 238 })
+            .on_color256(shade),
+    };
+    Ok(styled.to_string())
+}
+
+#[cfg(not(feature = "viz"))]
+fn paint(_cell: PathCell) -> Result<String, Error> {
+    Err(super::disabled("--render"))
+}
+
+#[cfg(all(test, feature = "viz"))]
+mod test {
+    use super::*;
+    use crate::day12::{find_path_reverse, parse, SAMPLE};
+
+    #[test]
+    fn test_render_colored() {
+        let map = parse(SAMPLE).unwrap();
+        let (trail, _) = find_path_reverse(&map, &mut ()).unwrap();
+        console::set_colors_enabled(true);
+        let colored = render_colored(&map, &trail).unwrap();
+        assert!(colored.contains('\x1b'));
+
+        // Without the colours it's the map with the path drawn over it.
+        let plain = console::strip_ansi_codes(&colored);
+        let expected: String = map
+            .path_cells(&trail)
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.c)
+                    .chain(['\n'])
+                    .collect::<String>()
+            })
+            .collect();
+        assert_eq!(plain, expected);
+    }
+}
@@ -0,0 +1,21 @@
+//! Day 14's sand, animated.
+
+use super::{frame_delay, Terminal};
+use crate::day14::RockFall;
+use anyhow::Error;
+use std::thread;
+
+/// Drop sand until it stops, drawing the cave `fps` times a second with
+/// `scale` cells to a character. Returns how many units came to rest.
+pub fn animate(rockfall: &mut RockFall, scale: usize, fps: f64) -> Result<usize, Error> {
+    let frame_delay = frame_delay(fps)?;
+    let term = Terminal::stdout("animating (--headless solves without it)")?;
+    loop {
+        let done = rockfall.step();
+        term.show(&rockfall.render(scale))?;
+        if let Some(units) = done {
+            return Ok(units);
+        }
+        thread::sleep(frame_delay);
+    }
+}
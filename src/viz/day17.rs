@@ -0,0 +1,15 @@
+//! Day 17's rocks, a move at a time.
+
+use super::Terminal;
+use crate::day17::{render, watch_rocks, Chamber, Jets};
+use anyhow::Error;
+
+/// Drop `count` rocks, drawing the chamber after every move and waiting for
+/// a key before the next.
+pub fn step_through(jets: &Jets, count: usize) -> Result<Chamber, Error> {
+    let term = Terminal::stdout("--interactive")?;
+    watch_rocks(jets, count, &mut |chamber, shape| {
+        render(chamber, shape);
+        term.wait_for_key()
+    })
+}
@@ -0,0 +1,96 @@
+//! Day 23's elves spreading out, in the terminal and as pictures.
+
+use super::{frame_delay, write_gray_png, Terminal};
+use crate::day23::{direction_list, field_text, watch, World};
+use anyhow::Error;
+use euclid::vec2;
+use std::{path::Path, thread};
+
+/// Draw the elves as white pixels on black, one pixel per cell of their
+/// bounding box.
+pub fn write_png(world: &World, path: &Path) -> Result<(), Error> {
+    let bbox = world.bounding_box();
+    let size = (bbox.max - bbox.min).to_usize() + vec2(1, 1);
+    let mut pixels = vec![0u8; size.x * size.y];
+    for elf in &world.elves {
+        let p = (elf.position - bbox.min).to_usize();
+        pixels[p.y * size.x + p.x] = 0xff;
+    }
+    write_gray_png(path, size.x, size.y, &pixels)
+}
+
+/// Run rounds until the elves settle, as [`watch`] does. With an `fps`,
+/// draw each round in the terminal that many times a second, and with
+/// `snapshots` of `(every, dir)`, save a PNG of every `every`th round, and
+/// the last, in `dir`. Returns the number of rounds.
+pub fn show_rounds(
+    world: &mut World,
+    fps: Option<f64>,
+    snapshots: Option<(usize, &Path)>,
+) -> Result<usize, Error> {
+    let frame_delay = fps.map(frame_delay).transpose()?;
+    let term = fps.map(|_| Terminal::stdout("--animate")).transpose()?;
+    watch(world, |world, proposals| {
+        let settled = proposals.iter().all(Option::is_none);
+        if let Some((every, dir)) = snapshots {
+            if world.time.is_multiple_of(every) || settled {
+                write_png(world, &dir.join(format!("round-{:05}.png", world.time)))?;
+            }
+        }
+        if let Some((term, frame_delay)) = term.as_ref().zip(frame_delay) {
+            term.show(&format!(
+                "round {} {}\n{}",
+                world.time + 1,
+                direction_list(world.time),
+                field_text(&world.elves, proposals)
+            ))?;
+            thread::sleep(frame_delay);
+        }
+        Ok(())
+    })
+}
+
+#[cfg(all(test, feature = "viz"))]
+mod test {
+    use super::*;
+    use crate::day23::{parse, SAMPLE};
+    use std::fs::{self, File};
+
+    #[test]
+    fn test_write_png() {
+        let world = parse(SAMPLE);
+        let path = std::env::temp_dir().join(format!("day23-{}.png", std::process::id()));
+        write_png(&world, &path).unwrap();
+        let decoder = png::Decoder::new(File::open(&path).unwrap());
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((reader.info().width, reader.info().height), (7, 7));
+        assert_eq!(pixels.iter().filter(|p| **p == 0xff).count(), 22);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_snapshots() {
+        let dir = std::env::temp_dir().join(format!("day23-snapshots-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let rounds = show_rounds(&mut parse(SAMPLE), None, Some((5, &dir))).unwrap();
+        assert_eq!(rounds, 20);
+        let mut saved: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        saved.sort();
+        assert_eq!(
+            saved,
+            [
+                "round-00000.png",
+                "round-00005.png",
+                "round-00010.png",
+                "round-00015.png",
+                "round-00019.png"
+            ]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,9 @@
+//! Day 24's expedition, replayed.
+
+use super::Terminal;
+use anyhow::Error;
+
+/// Show the replay's `frames`, `fps` minutes a second.
+pub fn animate(frames: &[String], fps: f64) -> Result<(), Error> {
+    Terminal::stdout("--animate")?.play(frames, fps)
+}
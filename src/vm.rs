@@ -0,0 +1,261 @@
+//! The handheld device's CPU from day 10, as a small virtual machine.
+//!
+//! The puzzle only needs `noop` and `addx`. Building with the
+//! `extended-isa` feature adds `jmp`, `mulx` and `nop N` for writing
+//! programs of our own.
+
+use crate::parse::parse_lines;
+use anyhow::{anyhow, bail, Context, Error};
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Noop,
+    AddX(isize),
+    /// Jump by this many instructions, relative to the jump.
+    #[cfg(feature = "extended-isa")]
+    Jmp(isize),
+    #[cfg(feature = "extended-isa")]
+    MulX(isize),
+    /// Do nothing for this many cycles.
+    #[cfg(feature = "extended-isa")]
+    Nop(usize),
+}
+
+impl Instruction {
+    pub fn cycles(&self) -> usize {
+        match self {
+            Self::Noop => 1,
+            Self::AddX(_) => 2,
+            #[cfg(feature = "extended-isa")]
+            Self::Jmp(_) => 1,
+            #[cfg(feature = "extended-isa")]
+            Self::MulX(_) => 2,
+            #[cfg(feature = "extended-isa")]
+            Self::Nop(cycles) => *cycles,
+        }
+    }
+}
+
+impl FromStr for Instruction {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        let mnemonic = parts.next().ok_or_else(|| anyhow!("missing mnemonic"))?;
+        let mut operand = || -> Result<isize, Error> {
+            let operand = parts
+                .next()
+                .ok_or_else(|| anyhow!("{mnemonic} needs an operand"))?;
+            operand
+                .parse()
+                .with_context(|| format!("bad operand {operand:?}"))
+        };
+        let instruction = match mnemonic {
+            "noop" => Self::Noop,
+            "addx" => Self::AddX(operand()?),
+            #[cfg(feature = "extended-isa")]
+            "jmp" => Self::Jmp(operand()?),
+            #[cfg(feature = "extended-isa")]
+            "mulx" => Self::MulX(operand()?),
+            #[cfg(feature = "extended-isa")]
+            "nop" => match usize::try_from(operand()?) {
+                Ok(cycles) if cycles > 0 => Self::Nop(cycles),
+                _ => bail!("nop needs a positive number of cycles"),
+            },
+            _ => bail!("illegal mnemonic {mnemonic:?}"),
+        };
+        if let Some(extra) = parts.next() {
+            bail!("unexpected {extra:?} after {mnemonic}");
+        }
+        Ok(instruction)
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Noop => write!(f, "noop"),
+            Self::AddX(value) => write!(f, "addx {value}"),
+            #[cfg(feature = "extended-isa")]
+            Self::Jmp(offset) => write!(f, "jmp {offset}"),
+            #[cfg(feature = "extended-isa")]
+            Self::MulX(value) => write!(f, "mulx {value}"),
+            #[cfg(feature = "extended-isa")]
+            Self::Nop(cycles) => write!(f, "nop {cycles}"),
+        }
+    }
+}
+
+pub type Program = Vec<Instruction>;
+
+/// One instruction per line; blank lines are skipped.
+pub fn parse(s: &str) -> Result<Program, Error> {
+    parse_lines(s)
+}
+
+/// A listing of `program` with each instruction's address, the cycle it
+/// starts on and how many cycles it takes. Start cycles assume the program
+/// runs straight through, so they're only a guide after a `jmp`.
+pub fn disassemble(program: &[Instruction]) -> String {
+    let mut listing = String::new();
+    let mut cycle = 1;
+    for (address, instruction) in program.iter().enumerate() {
+        let cycles = instruction.cycles();
+        let plural = if cycles == 1 { "" } else { "s" };
+        // Display ignores widths, so pad the text instead.
+        let text = instruction.to_string();
+        listing.push_str(&format!(
+            "{address:04}  {cycle:>5}  {text:<12}; {cycles} cycle{plural}\n"
+        ));
+        cycle += cycles;
+    }
+    listing
+}
+
+#[derive(Debug, Clone)]
+pub struct Cpu {
+    program: Program,
+    pub pc: usize,
+    pub cycle: usize,
+    remaining_cycles: usize,
+    pub x: isize,
+}
+
+impl Cpu {
+    pub fn new(program: Program) -> Self {
+        let remaining_cycles = program.first().map_or(0, Instruction::cycles);
+        Self {
+            program,
+            pc: 0,
+            cycle: 1,
+            remaining_cycles,
+            x: 1,
+        }
+    }
+
+    pub fn running(&self) -> bool {
+        self.pc < self.program.len()
+    }
+
+    /// The instruction being executed during the current cycle.
+    pub fn current(&self) -> Option<&Instruction> {
+        self.program.get(self.pc)
+    }
+
+    pub fn clock(&mut self) {
+        self.cycle += 1;
+        self.remaining_cycles -= 1;
+        if self.remaining_cycles == 0 {
+            let next = self.pc + 1;
+            self.pc = match self.program[self.pc] {
+                Instruction::AddX(value) => {
+                    self.x += value;
+                    next
+                }
+                Instruction::Noop => next,
+                // Jumping outside the program halts it.
+                #[cfg(feature = "extended-isa")]
+                Instruction::Jmp(offset) => self
+                    .pc
+                    .checked_add_signed(offset)
+                    .unwrap_or(self.program.len()),
+                #[cfg(feature = "extended-isa")]
+                Instruction::MulX(value) => {
+                    self.x *= value;
+                    next
+                }
+                #[cfg(feature = "extended-isa")]
+                Instruction::Nop(_) => next,
+            };
+            if self.running() {
+                self.remaining_cycles = self.program[self.pc].cycles();
+            }
+        }
+    }
+
+    /// Clock the CPU until it's in `cycle`, so that `x` is the value during
+    /// that cycle. Returns false if the program halts first.
+    pub fn run_until(&mut self, cycle: usize) -> bool {
+        while self.running() && self.cycle < cycle {
+            self.clock();
+        }
+        self.cycle == cycle
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SMALL: &str = "noop\naddx 3\naddx -5\n";
+
+    #[test]
+    fn test_parse() {
+        let program = parse(SMALL).unwrap();
+        assert_eq!(
+            program,
+            [
+                Instruction::Noop,
+                Instruction::AddX(3),
+                Instruction::AddX(-5)
+            ]
+        );
+        for bad in ["addx", "addx x", "noop 1", "halt"] {
+            assert!(parse(bad).is_err(), "{bad}");
+        }
+        let error = parse("noop\naddx").unwrap_err();
+        assert!(format!("{error:#}").starts_with("line 2"));
+    }
+
+    #[test]
+    fn test_run_until() {
+        // The puzzle's small example: X is 1, 1, 1, 4, 4 during cycles 1-5
+        // and the program halts at the start of cycle 6.
+        let mut cpu = Cpu::new(parse(SMALL).unwrap());
+        assert!(cpu.run_until(3));
+        assert_eq!(cpu.x, 1);
+        assert!(cpu.run_until(4));
+        assert_eq!(cpu.x, 4);
+        assert!(!cpu.run_until(10));
+        assert_eq!(cpu.x, -1);
+        assert!(!Cpu::new(vec![]).running());
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let listing = disassemble(&parse(SMALL).unwrap());
+        assert_eq!(
+            listing,
+            "0000      1  noop        ; 1 cycle\n\
+             0001      2  addx 3      ; 2 cycles\n\
+             0002      4  addx -5     ; 2 cycles\n"
+        );
+    }
+
+    #[cfg(feature = "extended-isa")]
+    #[test]
+    fn test_extended_isa() {
+        let program = parse("addx 1\nmulx 3\nnop 4\njmp -2\n").unwrap();
+        assert_eq!(program[2], Instruction::Nop(4));
+        assert_eq!(
+            disassemble(&program).lines().nth(3).unwrap(),
+            "0003      9  jmp -2      ; 1 cycle"
+        );
+        assert!(parse("nop 0").is_err());
+
+        // x = (1 + 1) * 3, then mulx and nop loop forever.
+        let mut cpu = Cpu::new(program);
+        assert!(cpu.run_until(5));
+        assert_eq!(cpu.x, 6);
+        assert!(cpu.run_until(10));
+        assert_eq!(cpu.pc, 1);
+        assert!(cpu.run_until(12));
+        assert_eq!(cpu.x, 18);
+
+        let mut cpu = Cpu::new(parse("jmp -1\naddx 5").unwrap());
+        cpu.clock();
+        assert!(!cpu.running());
+        assert_eq!(cpu.x, 1);
+    }
+}
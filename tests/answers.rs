@@ -0,0 +1,142 @@
+//! Every day's answers, checked by running its binary with `--output json`:
+//! for the sample in `data/dayNN_sample.txt`, and for the puzzle input,
+//! where it's been saved, against `answers.toml`.
+//!
+//! The puzzle inputs take a while without optimizations, so those tests
+//! are ignored by default; run them with
+//! `cargo test --release --test answers -- --ignored`.
+
+use advent_of_code_2022::testing;
+use serde_json::Value;
+use std::{collections::BTreeMap, process::Command};
+
+const ANSWERS: &str = include_str!("../answers.toml");
+
+/// Run a day's binary and return the report it printed last. Cached
+/// answers are ignored, since checking them is the point.
+fn report(bin: &str, args: &[&str]) -> Value {
+    let output = Command::new(bin)
+        .args(args)
+        .args(["--output", "json", "--force"])
+        .output()
+        .unwrap_or_else(|e| panic!("running {bin}: {e}"));
+    assert!(
+        output.status.success(),
+        "{bin} {args:?} exited with {}:\n{}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json = stdout
+        .lines()
+        .rfind(|line| line.starts_with('{'))
+        .unwrap_or_else(|| panic!("{bin} {args:?} printed no report:\n{stdout}"));
+    serde_json::from_str(json).unwrap()
+}
+
+/// One part's answer from a report, written the way `answers.toml` does.
+fn answer(report: &Value, part: u8) -> String {
+    match &report[format!("part{part}")] {
+        Value::String(s) => s.clone(),
+        Value::Null => "unsolved".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Check the answers to the sample, for the parts given.
+fn check_sample(day: u8, bin: &str, args: &[&str], expected: [Option<&str>; 2]) {
+    let path = testing::sample_path(day);
+    let mut args = args.to_vec();
+    args.extend(["--input", path.to_str().unwrap()]);
+    let report = report(bin, &args);
+    for (part, expected) in (1..).zip(expected) {
+        if let Some(expected) = expected {
+            assert_eq!(answer(&report, part), expected, "day {day} part {part}");
+        }
+    }
+}
+
+/// Check the answers recorded for the puzzle input, if there is one.
+fn check_puzzle(day: u8, bin: &str) {
+    if testing::input(day).unwrap().is_none() {
+        return;
+    }
+    let doc: toml::Value = ANSWERS.parse().unwrap();
+    // Parts that need the same arguments are answered by one run.
+    let mut runs: BTreeMap<Vec<&str>, Vec<(u8, &str)>> = BTreeMap::new();
+    for entry in doc["answer"].as_array().unwrap() {
+        if entry["day"].as_integer() != Some(day.into()) {
+            continue;
+        }
+        let args = entry
+            .get("args")
+            .and_then(toml::Value::as_array)
+            .map_or_else(Vec::new, |args| {
+                args.iter().filter_map(toml::Value::as_str).collect()
+            });
+        let part = entry["part"].as_integer().unwrap() as u8;
+        runs.entry(args)
+            .or_default()
+            .push((part, entry["value"].as_str().unwrap()));
+    }
+    for (args, parts) in runs {
+        let report = report(bin, &args);
+        for (part, expected) in parts {
+            assert_eq!(answer(&report, part), expected, "day {day} part {part}");
+        }
+    }
+}
+
+/// A module of tests for each day, named after its binary: the day, any
+/// arguments its sample needs, and the sample's answers, or `None` for
+/// parts not to check.
+macro_rules! days {
+    ($($bin:ident: $day:literal [$($arg:literal),*] $part1:expr, $part2:expr;)*) => {
+        $(mod $bin {
+            use super::*;
+
+            const BIN: &str = env!(concat!("CARGO_BIN_EXE_", stringify!($bin)));
+
+            #[test]
+            fn sample() {
+                check_sample($day, BIN, &[$($arg),*], [$part1, $part2]);
+            }
+
+            #[test]
+            #[ignore = "slow without --release"]
+            fn puzzle() {
+                check_puzzle($day, BIN);
+            }
+        })*
+    };
+}
+
+days! {
+    day01: 1 [] Some("24000"), Some("45000");
+    day02: 2 [] Some("15"), Some("12");
+    day03: 3 [] Some("157"), Some("70");
+    day04: 4 [] Some("2"), Some("4");
+    day05: 5 [] Some("CMZ"), Some("MCD");
+    day06: 6 [] Some("7"), Some("19");
+    day07: 7 [] Some("95437"), Some("24933642");
+    day08: 8 [] Some("21"), Some("8");
+    day09: 9 [] Some("13"), Some("1");
+    // The sample's screen doesn't spell anything.
+    day10: 10 [] Some("13140"), None;
+    day11: 11 [] Some("10605"), Some("2713310158");
+    day12: 12 [] Some("31"), Some("29");
+    day13: 13 [] Some("13"), Some("140");
+    day14: 14 ["--headless"] Some("24"), Some("93");
+    day15: 15 [] Some("26"), Some("56000011");
+    day16: 16 [] Some("1651"), Some("1707");
+    day17: 17 [] Some("3068"), None;
+    day18: 18 [] Some("64"), Some("58");
+    // Part 2 of the sample is much slower than of the puzzle input.
+    day19: 19 ["--part", "1"] Some("33"), None;
+    day20: 20 [] Some("3"), Some("1623178306");
+    day21: 21 [] Some("152"), Some("301");
+    day22: 22 [] Some("6032"), Some("5031");
+    day23: 23 [] Some("110"), Some("20");
+    day24: 24 [] Some("18"), Some("54");
+    day25: 25 [] Some("2=-1=0"), None;
+}
@@ -0,0 +1,70 @@
+//! Days called as library functions, the way other crates would, rather
+//! than through their binaries.
+
+use advent_of_code_2022::{
+    day01, day07, day13, day15, day16, day21, day22, day25,
+    solution::{Answer, Answers},
+    testing, Error,
+};
+
+fn answers(part1: impl Into<Answer>, part2: impl Into<Answer>) -> Answers {
+    Answers {
+        part1: part1.into(),
+        part2: part2.into(),
+    }
+}
+
+#[test]
+fn test_default_options() {
+    let sample = |day| testing::sample(day).unwrap();
+    assert_eq!(
+        day01::solve(&sample(1), &day01::Options).unwrap(),
+        answers(24000, 45000)
+    );
+    assert_eq!(
+        day07::solve(&sample(7), &day07::Options).unwrap(),
+        answers(95437, 24933642)
+    );
+    assert_eq!(
+        day15::solve(&sample(15), &day15::Options::default()).unwrap(),
+        answers(26, 56000011)
+    );
+    assert_eq!(
+        day16::solve(&sample(16), &day16::Options::default()).unwrap(),
+        answers(1651, 1707)
+    );
+    assert_eq!(
+        day22::solve(&sample(22), &day22::Options).unwrap().part1,
+        Answer::from(6032)
+    );
+    assert_eq!(
+        day25::solve(&sample(25), &day25::Options).unwrap(),
+        answers("2=-1=0", Answer::Unsolved)
+    );
+}
+
+#[test]
+fn test_options() {
+    let options = day13::Options {
+        parser: day13::Backend::Json,
+        sort: true,
+    };
+    assert_eq!(
+        day13::solve(&testing::sample(13).unwrap(), &options).unwrap(),
+        answers(13, 140)
+    );
+
+    let options = day21::Options {
+        solver: day21::Solver::Numeric,
+    };
+    assert_eq!(
+        day21::solve(&testing::sample(21).unwrap(), &options).unwrap(),
+        answers(152, 301)
+    );
+}
+
+#[test]
+fn test_bad_input() {
+    let error = day01::solve("10\n\nx", &day01::Options).unwrap_err();
+    assert!(matches!(error, Error::Parse { day: 1, .. }), "{error:?}");
+}